@@ -0,0 +1,6 @@
+pub mod connectivity;
+pub mod fluids;
+pub mod rules;
+pub mod safety;
+pub mod support;
+pub mod update_order;
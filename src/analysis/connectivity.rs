@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use crate::iter::BlockIteratorExt;
+use crate::structure::{BlockState, Coordinates, Region};
+
+const RAILS: [&str; 4] = ["rail", "powered_rail", "detector_rail", "activator_rail"];
+
+// north, east, south, west
+const HORIZONTAL: [(&str, i32, i32); 4] = [
+    ("north", 0, -1),
+    ("east", 1, 0),
+    ("south", 0, 1),
+    ("west", -1, 0),
+];
+
+/// An undirected graph of connected block positions inside of a `Region`, e.g. rails or redstone dust.
+///
+/// Positions are relative to the region origin. Created by `rail_graph()` and `dust_graph()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectivityGraph {
+    nodes: Vec<Coordinates>,
+    edges: HashMap<Coordinates, Vec<Coordinates>>,
+}
+
+impl ConnectivityGraph {
+    /// Returns an iterator over all positions of the graph, in YZX order.
+    pub fn nodes(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.nodes.iter().copied()
+    }
+
+    /// Returns the positions connected to `position`. The slice is empty if the position is not part of the graph.
+    pub fn neighbors(&self, position: impl Into<Coordinates>) -> &[Coordinates] {
+        self.edges
+            .get(&position.into())
+            .map_or(&[], |neighbors| neighbors.as_slice())
+    }
+
+    /// Checks whether the given position is part of the graph.
+    pub fn contains(&self, position: impl Into<Coordinates>) -> bool {
+        self.edges.contains_key(&position.into())
+    }
+
+    /// Returns the number of positions in the graph.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of connections in the graph. Every connection is only counted once.
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(Vec::len).sum::<usize>() / 2
+    }
+
+    /// Returns the connected components of the graph, e.g. the separate rail lines of a region.
+    ///
+    /// The components are ordered by their first position in YZX order, and so are the positions of every component.
+    pub fn components(&self) -> Vec<Vec<Coordinates>> {
+        let order = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, position)| (*position, index))
+            .collect::<HashMap<_, _>>();
+
+        let mut component_of: HashMap<Coordinates, usize> = HashMap::new();
+        let mut components: Vec<Vec<Coordinates>> = Vec::new();
+
+        for start in &self.nodes {
+            if component_of.contains_key(start) {
+                continue;
+            }
+
+            let index = components.len();
+            let mut component = vec![*start];
+            let mut stack = vec![*start];
+            component_of.insert(*start, index);
+
+            while let Some(position) = stack.pop() {
+                for neighbor in self.neighbors(position) {
+                    if !component_of.contains_key(neighbor) {
+                        component_of.insert(*neighbor, index);
+                        component.push(*neighbor);
+                        stack.push(*neighbor);
+                    }
+                }
+            }
+
+            component.sort_by_key(|position| order[position]);
+            components.push(component);
+        }
+
+        components
+    }
+
+    fn add_node(&mut self, position: Coordinates) {
+        self.nodes.push(position);
+        self.edges.insert(position, Vec::new());
+    }
+
+    fn add_edge(&mut self, a: Coordinates, b: Coordinates) {
+        for (from, to) in [(a, b), (b, a)] {
+            let neighbors = self.edges.get_mut(&from).expect("node exists");
+
+            if !neighbors.contains(&to) {
+                neighbors.push(to);
+            }
+        }
+    }
+}
+
+/// Builds the graph of rails in the region, connected the way the game connects them.
+///
+/// Every rail has two ends given by its `shape`. Two rails are connected if each of them has an end pointing at the other one,
+/// where an end may also point at a rail one block further down that is ascending towards it.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::analysis::connectivity::rail_graph;
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+/// let graph = rail_graph(file.get_region("test").unwrap());
+///
+/// println!("{} separate rail lines", graph.components().len());
+/// ```
+pub fn rail_graph(region: &Region) -> ConnectivityGraph {
+    let mut graph = ConnectivityGraph::default();
+    let mut ends: HashMap<Coordinates, [Coordinates; 2]> = HashMap::new();
+
+    let is_rail = |block: &BlockState| {
+        block.get_name().get_namespace() == "minecraft"
+            && RAILS.contains(&block.get_name().get_path())
+    };
+
+    for (position, block) in region.iter_blocks().only(&is_rail) {
+        let Some(rail_ends) = block
+            .get_properties()
+            .get("shape")
+            .and_then(|shape| rail_ends(position, shape))
+        else {
+            continue;
+        };
+
+        graph.add_node(position);
+        ends.insert(position, rail_ends);
+    }
+
+    // an end of `from` points at `to` if it is at `to` or directly above it
+    let points_at = |from: Coordinates, to: Coordinates| {
+        ends[&from]
+            .iter()
+            .any(|end| *end == to || *end == offset(to, 0, 1, 0))
+    };
+
+    for position in graph.nodes.clone() {
+        for end in ends[&position] {
+            for candidate in [end, offset(end, 0, -1, 0)] {
+                if candidate != position
+                    && ends.contains_key(&candidate)
+                    && points_at(position, candidate)
+                    && points_at(candidate, position)
+                {
+                    graph.add_edge(position, candidate);
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+/// Builds the graph of redstone dust in the region, following the `north`, `east`, `south` and `west` properties.
+///
+/// A side that is `up` connects to dust one block higher, a side that is `side` connects to dust on the same level
+/// or, if there is none, one block lower. Connections to other components like repeaters are not part of the graph.
+pub fn dust_graph(region: &Region) -> ConnectivityGraph {
+    let mut graph = ConnectivityGraph::default();
+
+    let dust = region
+        .iter_blocks()
+        .named("minecraft:redstone_wire")
+        .collect::<Vec<_>>();
+
+    for (position, _) in &dust {
+        graph.add_node(*position);
+    }
+
+    for (position, block) in &dust {
+        for (direction, dx, dz) in HORIZONTAL {
+            let neighbor = offset(*position, dx, 0, dz);
+
+            let target = match block.get_properties().get(direction).map(String::as_str) {
+                Some("up") => Some(offset(neighbor, 0, 1, 0)),
+                Some("side") if graph.contains(neighbor) => Some(neighbor),
+                Some("side") => Some(offset(neighbor, 0, -1, 0)),
+                _ => None,
+            };
+
+            if let Some(target) = target.filter(|target| graph.contains(*target)) {
+                graph.add_edge(*position, target);
+            }
+        }
+    }
+
+    graph
+}
+
+/// Returns the positions the two ends of a rail with the given `shape` point at, or `None` if the shape is unknown.
+fn rail_ends(position: Coordinates, shape: &str) -> Option<[Coordinates; 2]> {
+    let direction = |name: &str| {
+        HORIZONTAL
+            .iter()
+            .find(|(direction, _, _)| *direction == name)
+            .map(|(_, dx, dz)| (*dx, *dz))
+    };
+
+    if let Some(up) = shape.strip_prefix("ascending_") {
+        let (dx, dz) = direction(up)?;
+
+        return Some([offset(position, dx, 1, dz), offset(position, -dx, 0, -dz)]);
+    }
+
+    let (first, second) = shape.split_once('_')?;
+    let (first_x, first_z) = direction(first)?;
+    let (second_x, second_z) = direction(second)?;
+
+    Some([
+        offset(position, first_x, 0, first_z),
+        offset(position, second_x, 0, second_z),
+    ])
+}
+
+fn offset(position: Coordinates, dx: i32, dy: i32, dz: i32) -> Coordinates {
+    Coordinates {
+        x: position.x + dx,
+        y: position.y + dy,
+        z: position.z + dz,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    fn rail(shape: &str) -> BlockState {
+        BlockStateBuilder::new("rail")
+            .properties([("shape", shape)])
+            .build()
+    }
+
+    #[test]
+    fn rails_connect_along_shapes() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 4, y: 2, z: 4 },
+        );
+
+        // a straight line turning east, going up a slope
+        region.set_block((0, 0, 0), rail("north_south"));
+        region.set_block((0, 0, 1), rail("north_east"));
+        region.set_block((1, 0, 1), rail("ascending_east"));
+        region.set_block((2, 1, 1), rail("east_west"));
+
+        // not connected, since it points along the wrong axis
+        region.set_block((3, 1, 1), rail("north_south"));
+
+        let graph = rail_graph(&region);
+
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(
+            graph.neighbors((1, 0, 1)),
+            &[(0, 0, 1).into(), (2, 1, 1).into()]
+        );
+        assert_eq!(graph.components().len(), 2);
+    }
+
+    #[test]
+    fn dust_connects_up_and_down() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 3, y: 2, z: 1 },
+        );
+
+        let dust = |east: &str, west: &str| {
+            BlockStateBuilder::new("redstone_wire")
+                .properties([
+                    ("east", east),
+                    ("west", west),
+                    ("north", "none"),
+                    ("south", "none"),
+                ])
+                .build()
+        };
+
+        region.set_block((0, 0, 0), dust("up", "none"));
+        region.set_block((1, 1, 0), dust("side", "side"));
+        region.set_block((2, 1, 0), dust("none", "side"));
+
+        let graph = dust_graph(&region);
+
+        assert_eq!(graph.edge_count(), 2);
+        assert!(graph.neighbors((1, 1, 0)).contains(&(0, 0, 0).into()));
+        assert_eq!(graph.components().len(), 1);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// Blocks that are always filled with water, even though they have no `waterlogged` property.
+const ALWAYS_WATER: [&str; 5] = [
+    "bubble_column",
+    "kelp",
+    "kelp_plant",
+    "seagrass",
+    "tall_seagrass",
+];
+
+/// The offsets of the neighbors that a fluid can flow into: below and the four horizontal sides.
+const FLOW_OFFSETS: [(i32, i32, i32); 5] =
+    [(0, -1, 0), (0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0)];
+
+/// A fluid that a block contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+/// A kind of problem found by `audit_fluids()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FluidIssue {
+    /// A water or lava block that is not a source. It disappears or spreads after pasting, because nothing feeds it.
+    Flowing { fluid: Fluid, level: u8 },
+
+    /// A fluid source or waterlogged block next to air that it will flow into after pasting.
+    Spreading { fluid: Fluid },
+
+    /// A waterlogged block without any water next to it, which was probably waterlogged by accident.
+    UnexpectedWaterlogged,
+
+    /// A block that can be waterlogged, but isn't, even though it is surrounded by water on every side it could flow in from.
+    MissingWaterlogged,
+}
+
+/// A single problem found by `audit_fluids()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FluidFinding {
+    /// The position of the block relative to `min_corner()`.
+    pub position: Coordinates,
+
+    pub issue: FluidIssue,
+}
+
+/// Finds fluids that will flood or drain when the region is pasted, and blocks that look accidentally (un)waterlogged.
+///
+/// Neighbors outside of the region are unknown, so they are never treated as air or water. Findings are returned in
+/// YZX order, and a block has at most one finding.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::analysis::fluids::audit_fluids;
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+///
+/// for finding in audit_fluids(file.get_region("test").unwrap()) {
+///     println!("{:?}: {:?}", finding.position, finding.issue);
+/// }
+/// ```
+pub fn audit_fluids(region: &Region) -> Vec<FluidFinding> {
+    let mut findings = Vec::new();
+
+    for (position, block) in region.iter_blocks() {
+        let neighbors = FLOW_OFFSETS
+            .iter()
+            .filter_map(|(x, y, z)| {
+                region.get(Coordinates {
+                    x: position.x + x,
+                    y: position.y + y,
+                    z: position.z + z,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let above = region.get(Coordinates {
+            x: position.x,
+            y: position.y + 1,
+            z: position.z,
+        });
+
+        let issue = match fluid(block) {
+            Some(fluid) => {
+                let level = block
+                    .get_properties()
+                    .get("level")
+                    .and_then(|level| level.parse::<u8>().ok())
+                    .unwrap_or(0);
+
+                if level != 0 {
+                    Some(FluidIssue::Flowing { fluid, level })
+                } else if neighbors.iter().any(|neighbor| neighbor.is_air()) {
+                    Some(FluidIssue::Spreading { fluid })
+                } else if is_waterlogged(block)
+                    && !neighbors.iter().copied().chain(above).any(is_water)
+                {
+                    Some(FluidIssue::UnexpectedWaterlogged)
+                } else {
+                    None
+                }
+            }
+            None => {
+                let surrounded = neighbors.len() == FLOW_OFFSETS.len()
+                    && above.is_some_and(is_water)
+                    && neighbors
+                        .iter()
+                        .skip(1) // water doesn't flow up, so the block below doesn't matter
+                        .all(|neighbor| is_water(neighbor));
+
+                let waterloggable = block.get_properties().contains_key("waterlogged");
+
+                (waterloggable && surrounded).then_some(FluidIssue::MissingWaterlogged)
+            }
+        };
+
+        if let Some(issue) = issue {
+            findings.push(FluidFinding { position, issue });
+        }
+    }
+
+    findings
+}
+
+/// Returns the fluid the block contains, if any.
+pub(crate) fn fluid(block: &BlockState) -> Option<Fluid> {
+    if block.get_name().get_namespace() != "minecraft" {
+        return None;
+    }
+
+    match block.get_name().get_path() {
+        "lava" => Some(Fluid::Lava),
+        "water" => Some(Fluid::Water),
+        path if ALWAYS_WATER.contains(&path) || is_waterlogged(block) => Some(Fluid::Water),
+        _ => None,
+    }
+}
+
+fn is_water(block: &BlockState) -> bool {
+    fluid(block) == Some(Fluid::Water)
+}
+
+fn is_waterlogged(block: &BlockState) -> bool {
+    block
+        .get_properties()
+        .get("waterlogged")
+        .map(String::as_str)
+        == Some("true")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn finds_fluid_issues() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let water = BlockStateBuilder::new("water")
+            .properties([("level", "0")])
+            .build();
+        let flowing_lava = BlockStateBuilder::new("lava")
+            .properties([("level", "3")])
+            .build();
+        let slab = |waterlogged: &str| {
+            BlockStateBuilder::new("stone_slab")
+                .properties([("type", "bottom"), ("waterlogged", waterlogged)])
+                .build()
+        };
+
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 7, y: 3, z: 3 },
+        );
+
+        for (position, _) in region.clone().iter_blocks() {
+            region.set_block(position, stone.clone());
+        }
+
+        // a pool of water around a slab that isn't waterlogged
+        for x in 1..=3 {
+            for z in 0..3 {
+                region.set_block((x, 1, z), water.clone());
+            }
+        }
+        region.set_block((2, 1, 1), slab("false"));
+        region.set_block((2, 2, 1), water.clone());
+
+        // a waterlogged slab next to air, and one buried in stone
+        region.set_block((5, 1, 1), slab("true"));
+        region.set_block((5, 2, 1), BlockStateBuilder::new("air").build());
+        region.set_block((5, 1, 0), BlockStateBuilder::new("air").build());
+        region.set_block((6, 0, 1), slab("true"));
+
+        region.set_block((0, 2, 0), flowing_lava);
+
+        let findings = audit_fluids(&region);
+        let issue = |position: (i32, i32, i32)| {
+            findings
+                .iter()
+                .find(|finding| finding.position == position.into())
+                .map(|finding| finding.issue.clone())
+        };
+
+        assert_eq!(issue((2, 1, 1)), Some(FluidIssue::MissingWaterlogged));
+        assert_eq!(
+            issue((5, 1, 1)),
+            Some(FluidIssue::Spreading {
+                fluid: Fluid::Water
+            })
+        );
+        assert_eq!(issue((6, 0, 1)), Some(FluidIssue::UnexpectedWaterlogged));
+        assert_eq!(
+            issue((0, 2, 0)),
+            Some(FluidIssue::Flowing {
+                fluid: Fluid::Lava,
+                level: 3
+            })
+        );
+        assert_eq!(issue((1, 1, 1)), None);
+        assert_eq!(findings.len(), 4);
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use crate::resource_location::ResourceLocation;
+use crate::structure::{BlockState, Coordinates, LitematicaFile};
+use crate::trace;
+
+/// A set of upload policies, e.g. for a creative server, that a `LitematicaFile` can be checked against.
+///
+/// An empty `Rules` allows everything. Every call adds a policy, so they can be chained.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::analysis::rules::Rules;
+///
+/// let rules = Rules::new()
+///     .deny_blocks(["command_block", "barrier", "minecraft:bedrock"])
+///     .max_dimensions((256, 128, 256))
+///     .deny_entities(["wither", "ender_dragon"]);
+///
+/// let file = LitematicaFile::read("upload.litematic").unwrap();
+///
+/// for violation in rules.check(&file) {
+///     println!("{:?}", violation);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    denied_blocks: Vec<ResourceLocation>,
+    max_dimensions: Option<Coordinates>,
+    denied_entities: Vec<ResourceLocation>,
+}
+
+/// A single rule broken by a schematic, found by `Rules::check()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// A region contains a denied block. Reported once per block state, with the number of blocks using it.
+    DeniedBlock {
+        region: String,
+        block: BlockState,
+        count: u64,
+    },
+
+    /// The box enclosing all regions is larger than the maximum dimensions along at least one axis.
+    TooLarge { size: Coordinates, max: Coordinates },
+
+    /// A region contains a denied entity, at the given block position relative to the region origin.
+    DeniedEntity {
+        region: String,
+        id: String,
+        position: Coordinates,
+    },
+}
+
+impl Rules {
+    /// Creates a new `Rules` that allows everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Denies the blocks with the given names, regardless of their properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - The names of the blocks as `ResourceLocation`s or `String`s in the format `namespace:name`. If no namespace is provided, `minecraft` is assumed.
+    pub fn deny_blocks(
+        mut self,
+        blocks: impl IntoIterator<Item = impl Into<ResourceLocation>>,
+    ) -> Self {
+        self.denied_blocks
+            .extend(blocks.into_iter().map(Into::into));
+        self
+    }
+
+    /// Limits the size of the box enclosing all regions. Every axis is checked on its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum size along the x, y and z axis.
+    pub fn max_dimensions(mut self, max: impl Into<Coordinates>) -> Self {
+        self.max_dimensions = Some(max.into());
+        self
+    }
+
+    /// Denies the entities with the given ids, e.g. `minecraft:wither`.
+    ///
+    /// # Arguments
+    ///
+    /// * `entities` - The ids of the entities as `ResourceLocation`s or `String`s in the format `namespace:name`. If no namespace is provided, `minecraft` is assumed.
+    pub fn deny_entities(
+        mut self,
+        entities: impl IntoIterator<Item = impl Into<ResourceLocation>>,
+    ) -> Self {
+        self.denied_entities
+            .extend(entities.into_iter().map(Into::into));
+        self
+    }
+
+    /// Checks the file against all rules and returns every violation.
+    ///
+    /// The size is checked first, then the regions in alphabetical order, with their blocks before their entities.
+    /// An empty list means the file may be uploaded.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(regions = file.regions.len(), violations, elapsed_ms))
+    )]
+    pub fn check(&self, file: &LitematicaFile) -> Vec<Violation> {
+        let _timer = trace::Timer::start();
+
+        let mut violations = Vec::new();
+
+        if let Some(max) = self.max_dimensions {
+            let (min, far) = file.enclosing_box();
+            let size = Coordinates {
+                x: far.x - min.x,
+                y: far.y - min.y,
+                z: far.z - min.z,
+            };
+
+            if size.x > max.x || size.y > max.y || size.z > max.z {
+                violations.push(Violation::TooLarge { size, max });
+            }
+        }
+
+        let mut names = file.regions.keys().collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            let region = &file.regions[name];
+
+            if !self.denied_blocks.is_empty() {
+                let mut counts: HashMap<&BlockState, u64> = HashMap::new();
+
+                for (_, block) in region.iter_blocks() {
+                    if self.denied_blocks.contains(block.get_name()) {
+                        *counts.entry(block).or_default() += 1;
+                    }
+                }
+
+                let mut counts = counts.into_iter().collect::<Vec<_>>();
+                counts.sort_by_cached_key(|(block, _)| block.to_string());
+
+                violations.extend(counts.into_iter().map(|(block, count)| {
+                    Violation::DeniedBlock {
+                        region: name.clone(),
+                        block: block.clone(),
+                        count,
+                    }
+                }));
+            }
+
+            for entity in &region.entities {
+                let denied = ResourceLocation::parse(&entity.id)
+                    .is_ok_and(|id| self.denied_entities.contains(&id));
+
+                if denied {
+                    violations.push(Violation::DeniedEntity {
+                        region: name.clone(),
+                        id: entity.id.clone(),
+                        position: entity.pos.block_position(),
+                    });
+                }
+            }
+        }
+
+        trace::record("violations", violations.len() as u64);
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::Entity;
+    use crate::vector::{Rotation2, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let command_block = BlockStateBuilder::new("command_block")
+            .properties([("conditional", "false"), ("facing", "up")])
+            .build();
+        region.set_block((0, 0, 0), command_block.clone());
+        region.set_block((1, 0, 0), command_block.clone());
+
+        assert!(Rules::new().check(&file).is_empty());
+
+        let rules = Rules::new()
+            .deny_blocks(["minecraft:command_block", "bedrock"])
+            .max_dimensions((8, 256, 256));
+
+        let violations = rules.check(&file);
+
+        assert!(matches!(violations[0], Violation::TooLarge { max, .. } if max.x == 8));
+        assert_eq!(
+            violations[1],
+            Violation::DeniedBlock {
+                region: "test".to_string(),
+                block: command_block,
+                count: 2,
+            }
+        );
+        assert_eq!(violations.len(), 2);
+
+        let region = file.get_region_mut("test").unwrap();
+        region.entities.clear();
+        region.entities.push(Entity {
+            rotation: Rotation2::new(0.0, 0.0),
+            fire: -1,
+            pos: Vec3::new(2.5, 4.0, 2.5),
+            motion: Vec3::new(0.0, 0.0, 0.0),
+            air: 300,
+            fall_distance: 0.0,
+            on_ground: true,
+            id: "minecraft:wither".to_string(),
+            portal_cooldown: 0,
+            uuid: vec![1, 2, 3, 4],
+            invulnerable: false,
+            facing: None,
+        });
+
+        let violations = Rules::new().deny_entities(["wither"]).check(&file);
+
+        assert_eq!(
+            violations,
+            [Violation::DeniedEntity {
+                region: "test".to_string(),
+                id: "minecraft:wither".to_string(),
+                position: Coordinates { x: 2, y: 4, z: 2 },
+            }]
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use nbt::Value;
+
+use crate::region::{value_get, value_position};
+use crate::structure::{Coordinates, LitematicaFile};
+use crate::trace;
+
+/// The maximum levels of the vanilla enchantments.
+const MAX_ENCHANTMENT_LEVELS: [(&str, i32); 42] = [
+    ("protection", 4),
+    ("fire_protection", 4),
+    ("feather_falling", 4),
+    ("blast_protection", 4),
+    ("projectile_protection", 4),
+    ("respiration", 3),
+    ("aqua_affinity", 1),
+    ("thorns", 3),
+    ("depth_strider", 3),
+    ("frost_walker", 2),
+    ("binding_curse", 1),
+    ("soul_speed", 3),
+    ("swift_sneak", 3),
+    ("sharpness", 5),
+    ("smite", 5),
+    ("bane_of_arthropods", 5),
+    ("knockback", 2),
+    ("fire_aspect", 2),
+    ("looting", 3),
+    ("sweeping", 3),
+    ("sweeping_edge", 3),
+    ("efficiency", 5),
+    ("silk_touch", 1),
+    ("unbreaking", 3),
+    ("fortune", 3),
+    ("power", 5),
+    ("punch", 2),
+    ("flame", 1),
+    ("infinity", 1),
+    ("luck_of_the_sea", 3),
+    ("lure", 3),
+    ("loyalty", 3),
+    ("impaling", 5),
+    ("riptide", 3),
+    ("channeling", 1),
+    ("multishot", 1),
+    ("quick_charge", 3),
+    ("piercing", 4),
+    ("mending", 1),
+    ("vanishing_curse", 1),
+    ("density", 5),
+    ("breach", 4),
+];
+
+/// Items that can only be stacked up to 16.
+const STACKS_TO_16: [&str; 7] = [
+    "ender_pearl",
+    "snowball",
+    "egg",
+    "bucket",
+    "honey_bottle",
+    "armor_stand",
+    "written_book",
+];
+
+/// Suffixes and names of items that can't be stacked at all.
+const UNSTACKABLE_SUFFIXES: [&str; 14] = [
+    "_sword",
+    "_pickaxe",
+    "_axe",
+    "_shovel",
+    "_hoe",
+    "_helmet",
+    "_chestplate",
+    "_leggings",
+    "_boots",
+    "_bucket",
+    "_boat",
+    "_minecart",
+    "shulker_box",
+    "potion",
+];
+
+const UNSTACKABLE: [&str; 17] = [
+    "minecart",
+    "saddle",
+    "elytra",
+    "shield",
+    "bow",
+    "crossbow",
+    "trident",
+    "fishing_rod",
+    "flint_and_steel",
+    "shears",
+    "totem_of_undying",
+    "enchanted_book",
+    "writable_book",
+    "mace",
+    "cake",
+    "spyglass",
+    "bundle",
+];
+
+/// Options that control what `scan_with_options()` considers suspicious.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// The maximum size of the NBT data of a single tile entity in bytes. Defaults to 32 KiB.
+    pub max_nbt_bytes: usize,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_nbt_bytes: 32 * 1024,
+        }
+    }
+}
+
+/// A kind of potentially malicious content found by `scan()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The NBT data of a tile entity is larger than `ScanOptions::max_nbt_bytes`, e.g. a book ban.
+    OversizedNbt { bytes: usize },
+
+    /// An item has an enchantment with a level above the vanilla maximum or below 1.
+    IllegalEnchantment {
+        item: String,
+        enchantment: String,
+        level: i32,
+    },
+
+    /// An item stack is larger than the item can be stacked in vanilla.
+    Overstacked { item: String, count: i32, max: i32 },
+
+    /// A command block or an item containing one has a command.
+    Command { command: String },
+}
+
+/// A single issue found by `scan()` inside of a tile entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The name of the region containing the tile entity.
+    pub region: String,
+
+    /// The position of the tile entity relative to the region origin, if it has one.
+    pub position: Option<Coordinates>,
+
+    pub issue: Issue,
+}
+
+/// Scans the tile entities of all regions for content that is commonly used for exploits, like oversized NBT,
+/// illegal enchantments, overstacked items and command blocks.
+///
+/// Items are also checked inside of nested containers like shulker boxes. Regions are scanned in alphabetical order.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::analysis::safety::scan;
+///
+/// let file = LitematicaFile::read("upload.litematic").unwrap();
+///
+/// if !scan(&file).is_empty() {
+///     println!("Rejected upload");
+/// }
+/// ```
+pub fn scan(file: &LitematicaFile) -> Vec<Finding> {
+    scan_with_options(file, &ScanOptions::default())
+}
+
+/// Scans the tile entities of all regions like `scan()`, using the given `ScanOptions`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(regions = file.regions.len(), findings, elapsed_ms))
+)]
+pub fn scan_with_options(file: &LitematicaFile, options: &ScanOptions) -> Vec<Finding> {
+    let _timer = trace::Timer::start();
+
+    let mut names = file.regions.keys().collect::<Vec<_>>();
+    names.sort();
+
+    let mut findings = Vec::new();
+
+    for name in names {
+        for tile_entity in &file.regions[name].tile_entities {
+            let mut issues = Vec::new();
+
+            let bytes = nbt_size(tile_entity);
+            if bytes > options.max_nbt_bytes {
+                issues.push(Issue::OversizedNbt { bytes });
+            }
+
+            scan_value(tile_entity, &mut issues);
+
+            findings.extend(issues.into_iter().map(|issue| Finding {
+                region: name.clone(),
+                position: value_position(tile_entity),
+                issue,
+            }));
+        }
+    }
+
+    trace::record("findings", findings.len() as u64);
+
+    findings
+}
+
+/// Recursively looks for items and commands inside of `value`.
+fn scan_value(value: &Value, issues: &mut Vec<Issue>) {
+    match value {
+        Value::Compound(compound) => {
+            if let Some(Value::String(command)) = compound.get("Command") {
+                if !command.trim().is_empty() {
+                    issues.push(Issue::Command {
+                        command: command.clone(),
+                    });
+                }
+            }
+
+            if let Some(Value::String(item)) = compound.get("id") {
+                let count = compound.get("Count").or_else(|| compound.get("count"));
+
+                if let Some(count) = count.and_then(as_int) {
+                    scan_item(item, count, value, issues);
+                }
+            }
+
+            for child in compound.values() {
+                scan_value(child, issues);
+            }
+        }
+        Value::List(list) => {
+            for child in list {
+                scan_value(child, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn scan_item(item: &str, count: i32, value: &Value, issues: &mut Vec<Issue>) {
+    let max = max_stack_size(item);
+
+    if count > max {
+        issues.push(Issue::Overstacked {
+            item: item.to_string(),
+            count,
+            max,
+        });
+    }
+
+    for (enchantment, level) in enchantments(value) {
+        let name = enchantment
+            .strip_prefix("minecraft:")
+            .unwrap_or(&enchantment);
+
+        let maximum = MAX_ENCHANTMENT_LEVELS
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, maximum)| *maximum);
+
+        // enchantments of other namespaces are unknown, so only their lower bound is checked
+        if level < 1 || maximum.is_some_and(|maximum| level > maximum) {
+            issues.push(Issue::IllegalEnchantment {
+                item: item.to_string(),
+                enchantment,
+                level,
+            });
+        }
+    }
+}
+
+/// Returns the enchantments of an item, both in the old `tag` format and in the item component format.
+fn enchantments(item: &Value) -> Vec<(String, i32)> {
+    let mut enchantments = Vec::new();
+
+    if let Some(Value::Compound(tag)) = value_get(item, "tag") {
+        for key in ["Enchantments", "StoredEnchantments"] {
+            if let Some(Value::List(list)) = tag.get(key) {
+                for enchantment in list {
+                    if let (Some(Value::String(id)), Some(level)) = (
+                        value_get(enchantment, "id"),
+                        value_get(enchantment, "lvl").and_then(as_int),
+                    ) {
+                        enchantments.push((id.clone(), level));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(Value::Compound(components)) = value_get(item, "components") {
+        for key in ["minecraft:enchantments", "minecraft:stored_enchantments"] {
+            let levels = components
+                .get(key)
+                .and_then(|component| value_get(component, "levels"));
+
+            if let Some(Value::Compound(levels)) = levels {
+                for (id, level) in levels {
+                    if let Some(level) = as_int(level) {
+                        enchantments.push((id.clone(), level));
+                    }
+                }
+            }
+        }
+    }
+
+    enchantments
+}
+
+fn max_stack_size(item: &str) -> i32 {
+    let name = item.strip_prefix("minecraft:").unwrap_or(item);
+
+    if UNSTACKABLE.contains(&name)
+        || UNSTACKABLE_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+    {
+        1
+    } else if STACKS_TO_16.contains(&name) || name.ends_with("_sign") || name.ends_with("_banner") {
+        16
+    } else {
+        64
+    }
+}
+
+fn as_int(value: &Value) -> Option<i32> {
+    match value {
+        Value::Byte(value) => Some(*value as i32),
+        Value::Short(value) => Some(*value as i32),
+        Value::Int(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Returns the size of the binary NBT payload of `value` in bytes.
+fn nbt_size(value: &Value) -> usize {
+    match value {
+        Value::Byte(_) => 1,
+        Value::Short(_) => 2,
+        Value::Int(_) | Value::Float(_) => 4,
+        Value::Long(_) | Value::Double(_) => 8,
+        Value::ByteArray(array) => 4 + array.len(),
+        Value::String(string) => 2 + string.len(),
+        Value::List(list) => 5 + list.iter().map(nbt_size).sum::<usize>(),
+        Value::Compound(compound) => {
+            1 + compound
+                .iter()
+                .map(|(key, value)| 3 + key.len() + nbt_size(value))
+                .sum::<usize>()
+        }
+        Value::IntArray(array) => 4 + 4 * array.len(),
+        Value::LongArray(array) => 4 + 8 * array.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn compound(entries: impl IntoIterator<Item = (&'static str, Value)>) -> Value {
+        Value::Compound(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    fn chest(items: Vec<Value>) -> Value {
+        compound([
+            ("id", Value::String("minecraft:chest".to_string())),
+            ("x", Value::Int(1)),
+            ("y", Value::Int(2)),
+            ("z", Value::Int(3)),
+            ("Items", Value::List(items)),
+        ])
+    }
+
+    #[test]
+    fn finds_exploits() {
+        let sword = compound([
+            ("id", Value::String("minecraft:diamond_sword".to_string())),
+            ("Count", Value::Byte(2)),
+            (
+                "tag",
+                compound([(
+                    "Enchantments",
+                    Value::List(vec![compound([
+                        ("id", Value::String("minecraft:sharpness".to_string())),
+                        ("lvl", Value::Short(32767)),
+                    ])]),
+                )]),
+            ),
+        ]);
+
+        let command_block = compound([
+            ("id", Value::String("minecraft:command_block".to_string())),
+            ("Command", Value::String("op Notch".to_string())),
+        ]);
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+        region.tile_entities = vec![chest(vec![sword]), command_block];
+
+        let findings = scan(&file);
+
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].position, Some(Coordinates { x: 1, y: 2, z: 3 }));
+        assert!(findings.iter().any(|finding| finding.issue
+            == Issue::Overstacked {
+                item: "minecraft:diamond_sword".to_string(),
+                count: 2,
+                max: 1
+            }));
+        assert!(findings.iter().any(|finding| matches!(
+            &finding.issue,
+            Issue::IllegalEnchantment { level: 32767, .. }
+        )));
+        assert!(findings.iter().any(|finding| finding.issue
+            == Issue::Command {
+                command: "op Notch".to_string()
+            }));
+    }
+
+    #[test]
+    fn oversized_nbt() {
+        let book_page = Value::String("x".repeat(100));
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+        region.tile_entities = vec![chest(vec![book_page; 10])];
+
+        let options = ScanOptions { max_nbt_bytes: 512 };
+
+        assert!(scan(&file).is_empty());
+        assert!(matches!(
+            scan_with_options(&file, &options)[0].issue,
+            Issue::OversizedNbt { .. }
+        ));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::block::BlockStateBuilder;
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// Blocks that fall when the block below them is free, apart from concrete powder.
+const GRAVITY_BLOCKS: [&str; 8] = [
+    "sand",
+    "red_sand",
+    "gravel",
+    "suspicious_sand",
+    "suspicious_gravel",
+    "anvil",
+    "chipped_anvil",
+    "damaged_anvil",
+];
+
+/// Blocks that are replaced by falling blocks and can't hold attached blocks.
+const REPLACEABLE: [&str; 14] = [
+    "air",
+    "cave_air",
+    "void_air",
+    "water",
+    "lava",
+    "fire",
+    "soul_fire",
+    "short_grass",
+    "grass",
+    "tall_grass",
+    "fern",
+    "large_fern",
+    "dead_bush",
+    "structure_void",
+];
+
+/// Blocks that drop as items when the block below them is removed.
+const FLOOR_ATTACHED: [&str; 10] = [
+    "torch",
+    "soul_torch",
+    "redstone_torch",
+    "rail",
+    "powered_rail",
+    "detector_rail",
+    "activator_rail",
+    "redstone_wire",
+    "repeater",
+    "comparator",
+];
+
+/// Blocks that hang on the side of the block opposite to their `facing`.
+const WALL_ATTACHED: [&str; 4] = [
+    "wall_torch",
+    "soul_wall_torch",
+    "redstone_wall_torch",
+    "ladder",
+];
+
+/// A kind of problem found by `check_support()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupportIssue {
+    /// A block affected by gravity, like sand, gravel or concrete powder, above a block it falls through.
+    Falling,
+
+    /// An attached block, like a torch, rail or button, whose anchor block is missing.
+    MissingAnchor {
+        /// The position of the missing anchor relative to `min_corner()`.
+        anchor: Coordinates,
+    },
+}
+
+/// A single problem found by `check_support()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportFinding {
+    /// The position of the block relative to `min_corner()`.
+    pub position: Coordinates,
+
+    pub issue: SupportIssue,
+}
+
+/// Finds blocks that will fall or break when the region is pasted, because the block they rest on or hang from is missing.
+///
+/// A block supports a falling block unless it is air, a fluid, fire or a replaceable plant. Attached blocks need any block
+/// that is not replaceable as their anchor, so a torch on glass is accepted even though the game would drop it. Anchors
+/// outside of the region are unknown and never reported. Findings are returned in YZX order.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::analysis::support::check_support;
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+///
+/// for finding in check_support(file.get_region("test").unwrap()) {
+///     println!("{:?}: {:?}", finding.position, finding.issue);
+/// }
+/// ```
+pub fn check_support(region: &Region) -> Vec<SupportFinding> {
+    let mut findings = Vec::new();
+
+    for (position, block) in region.iter_blocks() {
+        if block.get_name().get_namespace() != "minecraft" {
+            continue;
+        }
+
+        let below = offset(position, (0, -1, 0));
+
+        let issue = if is_gravity_block(block) {
+            region
+                .get(below)
+                .is_some_and(is_replaceable)
+                .then_some(SupportIssue::Falling)
+        } else {
+            anchor(block)
+                .map(|direction| offset(position, direction))
+                .filter(|anchor| region.get(*anchor).is_some_and(is_replaceable))
+                .map(|anchor| SupportIssue::MissingAnchor { anchor })
+        };
+
+        if let Some(issue) = issue {
+            findings.push(SupportFinding { position, issue });
+        }
+    }
+
+    findings
+}
+
+/// Options that control how `Region::plan_supports_with_options()` supports blocks.
+#[derive(Debug, Clone)]
+pub struct SupportOptions {
+    /// The temporary block placed under falling blocks and behind attached blocks. Defaults to `dirt`.
+    pub block: BlockState,
+}
+
+impl Default for SupportOptions {
+    fn default() -> Self {
+        Self {
+            block: BlockStateBuilder::new("dirt").build(),
+        }
+    }
+}
+
+/// A copy of a region with temporary support blocks, created by `Region::plan_supports()`.
+#[derive(Debug, Clone)]
+pub struct SupportPlan {
+    /// The region with the support blocks inserted.
+    pub region: Region,
+
+    /// The positions of the support blocks relative to `min_corner()`, in YZX order, which have to be removed again
+    /// once the blocks they hold are placed.
+    pub temporary: Vec<Coordinates>,
+}
+
+impl Region {
+    /// Inserts temporary `dirt` blocks under falling blocks and behind attached blocks that would otherwise break
+    /// while the region is built in survival, see `check_support()`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let plan = file.get_region("test").unwrap().plan_supports();
+    ///
+    /// for position in &plan.temporary {
+    ///     println!("remove the support at {:?} afterwards", position);
+    /// }
+    /// ```
+    pub fn plan_supports(&self) -> SupportPlan {
+        self.plan_supports_with_options(&SupportOptions::default())
+    }
+
+    /// Inserts temporary support blocks using the given `SupportOptions`.
+    pub fn plan_supports_with_options(&self, options: &SupportOptions) -> SupportPlan {
+        let mut temporary = check_support(self)
+            .into_iter()
+            .map(|finding| match finding.issue {
+                SupportIssue::Falling => offset(finding.position, (0, -1, 0)),
+                SupportIssue::MissingAnchor { anchor } => anchor,
+            })
+            .collect::<Vec<_>>();
+
+        // two blocks can share an anchor, e.g. a torch and a rail on either side of it
+        temporary.sort_by_key(|position| (position.y, position.z, position.x));
+        temporary.dedup();
+
+        let mut region = self.clone();
+
+        for position in &temporary {
+            region.set_block(*position, options.block.clone());
+        }
+
+        SupportPlan { region, temporary }
+    }
+}
+
+fn is_gravity_block(block: &BlockState) -> bool {
+    let path = block.get_name().get_path();
+
+    GRAVITY_BLOCKS.contains(&path) || path.ends_with("_concrete_powder")
+}
+
+fn is_replaceable(block: &BlockState) -> bool {
+    block.get_name().get_namespace() == "minecraft"
+        && REPLACEABLE.contains(&block.get_name().get_path())
+}
+
+/// Returns the offset from an attached block to the block it is attached to, or `None` if it isn't attached.
+fn anchor(block: &BlockState) -> Option<(i32, i32, i32)> {
+    let path = block.get_name().get_path();
+    let properties = block.get_properties();
+
+    let behind = || match properties.get("facing").map(String::as_str) {
+        Some("north") => Some((0, 0, 1)),
+        Some("south") => Some((0, 0, -1)),
+        Some("west") => Some((1, 0, 0)),
+        Some("east") => Some((-1, 0, 0)),
+        _ => None,
+    };
+
+    if FLOOR_ATTACHED.contains(&path)
+        || path.ends_with("_pressure_plate")
+        || path.ends_with("_carpet")
+    {
+        Some((0, -1, 0))
+    } else if WALL_ATTACHED.contains(&path) {
+        behind()
+    } else if path == "lever" || path.ends_with("_button") {
+        match properties.get("face").map(String::as_str) {
+            Some("floor") => Some((0, -1, 0)),
+            Some("ceiling") => Some((0, 1, 0)),
+            _ => behind(),
+        }
+    } else {
+        None
+    }
+}
+
+fn offset(position: Coordinates, (x, y, z): (i32, i32, i32)) -> Coordinates {
+    Coordinates {
+        x: position.x + x,
+        y: position.y + y,
+        z: position.z + z,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn finds_unsupported_blocks() {
+        let stone = BlockStateBuilder::new("stone").build();
+
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 4, y: 3, z: 3 },
+        );
+
+        region.set_block((0, 0, 0), stone.clone());
+        region.set_block((0, 1, 0), BlockStateBuilder::new("sand").build());
+        region.set_block(
+            (1, 1, 0),
+            BlockStateBuilder::new("red_concrete_powder").build(),
+        );
+        region.set_block((2, 0, 0), stone);
+        region.set_block((2, 1, 0), BlockStateBuilder::new("torch").build());
+        region.set_block((3, 1, 0), BlockStateBuilder::new("rail").build());
+        region.set_block(
+            (1, 1, 1),
+            BlockStateBuilder::new("wall_torch")
+                .properties([("facing", "north")])
+                .build(),
+        );
+        region.set_block(
+            (2, 1, 2),
+            BlockStateBuilder::new("stone_button")
+                .properties([("face", "ceiling"), ("facing", "east")])
+                .build(),
+        );
+
+        // gravel at the bottom of the region may rest on anything
+        region.set_block((3, 0, 2), BlockStateBuilder::new("gravel").build());
+
+        let findings = check_support(&region);
+        let plan = region.plan_supports();
+
+        assert_eq!(
+            plan.temporary,
+            vec![
+                Coordinates { x: 1, y: 0, z: 0 },
+                Coordinates { x: 3, y: 0, z: 0 },
+                Coordinates { x: 1, y: 1, z: 2 },
+                Coordinates { x: 2, y: 2, z: 2 },
+            ]
+        );
+        assert!(check_support(&plan.region).is_empty());
+        assert_eq!(
+            plan.region.get_block((1, 0, 0)).get_name().get_path(),
+            "dirt"
+        );
+
+        assert_eq!(
+            findings,
+            vec![
+                SupportFinding {
+                    position: Coordinates { x: 1, y: 1, z: 0 },
+                    issue: SupportIssue::Falling,
+                },
+                SupportFinding {
+                    position: Coordinates { x: 3, y: 1, z: 0 },
+                    issue: SupportIssue::MissingAnchor {
+                        anchor: Coordinates { x: 3, y: 0, z: 0 },
+                    },
+                },
+                SupportFinding {
+                    position: Coordinates { x: 1, y: 1, z: 1 },
+                    issue: SupportIssue::MissingAnchor {
+                        anchor: Coordinates { x: 1, y: 1, z: 2 },
+                    },
+                },
+                SupportFinding {
+                    position: Coordinates { x: 2, y: 1, z: 2 },
+                    issue: SupportIssue::MissingAnchor {
+                        anchor: Coordinates { x: 2, y: 2, z: 2 },
+                    },
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::placement::{Mirror, Rotation};
+use crate::structure::Coordinates;
+
+/// One of the six directions of the block grid, with north pointing to negative z.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+/// The order in which a block that changes sends block updates to its neighbors, e.g. to redstone components.
+pub const NEIGHBOR_UPDATE_ORDER: [Direction; 6] = [
+    Direction::West,
+    Direction::East,
+    Direction::Down,
+    Direction::Up,
+    Direction::North,
+    Direction::South,
+];
+
+/// The order in which a block that changes sends shape updates to its neighbors, e.g. to fences, walls and dust.
+pub const SHAPE_UPDATE_ORDER: [Direction; 6] = [
+    Direction::West,
+    Direction::East,
+    Direction::North,
+    Direction::South,
+    Direction::Down,
+    Direction::Up,
+];
+
+// clockwise, starting in the north
+const HORIZONTAL: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+impl Direction {
+    /// Returns the name of the direction, like in block state properties.
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Down => "down",
+            Direction::Up => "up",
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::West => "west",
+            Direction::East => "east",
+        }
+    }
+
+    /// Returns the offset to the neighboring block in this direction.
+    pub fn offset(self) -> Coordinates {
+        let (x, y, z) = match self {
+            Direction::Down => (0, -1, 0),
+            Direction::Up => (0, 1, 0),
+            Direction::North => (0, 0, -1),
+            Direction::South => (0, 0, 1),
+            Direction::West => (-1, 0, 0),
+            Direction::East => (1, 0, 0),
+        };
+
+        Coordinates { x, y, z }
+    }
+
+    /// Returns the direction after mirroring and then rotating it, like Litematica does when pasting a placement.
+    pub fn transform(self, mirror: Mirror, rotation: Rotation) -> Direction {
+        let Some(index) = HORIZONTAL.iter().position(|d| *d == self) else {
+            return self;
+        };
+
+        let mirrored = match mirror {
+            Mirror::LeftRight if index.is_multiple_of(2) => (index + 2) % 4,
+            Mirror::FrontBack if !index.is_multiple_of(2) => (index + 2) % 4,
+            _ => index,
+        };
+
+        HORIZONTAL[(mirrored + rotation.quarter_turns()) % 4]
+    }
+}
+
+/// Returns the neighbors of `position` in the order in which they receive block updates when the block at `position` changes.
+///
+/// # Examples
+/// ```
+/// use ritematica::analysis::update_order::neighbor_updates;
+///
+/// let order = neighbor_updates((0, 0, 0));
+///
+/// assert_eq!((order[0].x, order[0].y, order[0].z), (-1, 0, 0)); // west first
+/// ```
+pub fn neighbor_updates(position: impl Into<Coordinates>) -> [Coordinates; 6] {
+    neighbors(position.into(), NEIGHBOR_UPDATE_ORDER)
+}
+
+/// Returns the neighbors of `position` in the order in which they receive shape updates when the block at `position` changes.
+pub fn shape_updates(position: impl Into<Coordinates>) -> [Coordinates; 6] {
+    neighbors(position.into(), SHAPE_UPDATE_ORDER)
+}
+
+/// Returns the directions of `order` as seen in the coordinate frame of the schematic, once it is pasted with the
+/// given mirror and rotation.
+///
+/// The game always updates in world directions, so a location-dependent machine that relies on e.g. its west side
+/// being updated before its east side may break when pasted rotated or mirrored. Comparing the result with `order`
+/// shows which parts of the update order change.
+///
+/// # Examples
+/// ```
+/// use ritematica::analysis::update_order::{self, Direction, NEIGHBOR_UPDATE_ORDER};
+/// use ritematica::placement::{Mirror, Rotation};
+///
+/// let order = update_order::in_schematic_frame(NEIGHBOR_UPDATE_ORDER, Mirror::None, Rotation::Clockwise180);
+///
+/// // the schematic's east side is now in the west, so it is updated first
+/// assert_eq!(order[0], Direction::East);
+/// ```
+pub fn in_schematic_frame(
+    order: [Direction; 6],
+    mirror: Mirror,
+    rotation: Rotation,
+) -> [Direction; 6] {
+    order.map(|world| {
+        // the transformation is a bijection, so exactly one direction maps onto every world direction
+        HORIZONTAL
+            .into_iter()
+            .chain([Direction::Down, Direction::Up])
+            .find(|direction| direction.transform(mirror, rotation) == world)
+            .expect("every direction has a preimage")
+    })
+}
+
+/// Checks whether pasting with the given mirror and rotation changes the neighbor update order of the schematic,
+/// i.e. whether location-dependent machines may behave differently.
+pub fn changes_update_order(mirror: Mirror, rotation: Rotation) -> bool {
+    in_schematic_frame(NEIGHBOR_UPDATE_ORDER, mirror, rotation) != NEIGHBOR_UPDATE_ORDER
+}
+
+/// Sorts `positions` by the order in which they receive block updates from the block at `source`, once the schematic
+/// is pasted with the given mirror and rotation.
+///
+/// Positions are relative to the same origin as `source`. Positions that are not direct neighbors of `source`
+/// don't receive updates from it and are moved to the end, keeping their relative order.
+pub fn sort_by_update_order(
+    source: impl Into<Coordinates>,
+    positions: &mut [Coordinates],
+    mirror: Mirror,
+    rotation: Rotation,
+) {
+    let order = neighbors(
+        source.into(),
+        in_schematic_frame(NEIGHBOR_UPDATE_ORDER, mirror, rotation),
+    );
+
+    positions.sort_by_key(|position| {
+        order
+            .iter()
+            .position(|neighbor| neighbor == position)
+            .unwrap_or(order.len())
+    });
+}
+
+fn neighbors(position: Coordinates, order: [Direction; 6]) -> [Coordinates; 6] {
+    order.map(|direction| {
+        let offset = direction.offset();
+
+        Coordinates {
+            x: position.x + offset.x,
+            y: position.y + offset.y,
+            z: position.z + offset.z,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schematic_frame_order() {
+        assert!(!changes_update_order(Mirror::None, Rotation::None));
+        assert!(changes_update_order(Mirror::FrontBack, Rotation::None));
+        assert!(changes_update_order(Mirror::None, Rotation::Clockwise90));
+
+        // mirroring north and south doesn't swap east and west, but it swaps the last two updates
+        let order = in_schematic_frame(NEIGHBOR_UPDATE_ORDER, Mirror::LeftRight, Rotation::None);
+        assert_eq!(order[..4], NEIGHBOR_UPDATE_ORDER[..4]);
+        assert_eq!(order[4], Direction::South);
+
+        let order = in_schematic_frame(NEIGHBOR_UPDATE_ORDER, Mirror::None, Rotation::Clockwise90);
+        assert_eq!(order[0], Direction::South);
+        assert_eq!(order[1], Direction::North);
+    }
+
+    #[test]
+    fn sorts_neighbors() {
+        let mut positions = [
+            Coordinates { x: 5, y: 5, z: 5 },
+            Coordinates { x: 0, y: 0, z: 1 },
+            Coordinates { x: 1, y: 0, z: 0 },
+            Coordinates { x: -1, y: 0, z: 0 },
+        ];
+
+        sort_by_update_order((0, 0, 0), &mut positions, Mirror::None, Rotation::None);
+
+        assert_eq!(positions[0], Coordinates { x: -1, y: 0, z: 0 });
+        assert_eq!(positions[1], Coordinates { x: 1, y: 0, z: 0 });
+        assert_eq!(positions[2], Coordinates { x: 0, y: 0, z: 1 });
+        assert_eq!(positions[3], Coordinates { x: 5, y: 5, z: 5 });
+
+        sort_by_update_order((0, 0, 0), &mut positions, Mirror::FrontBack, Rotation::None);
+
+        assert_eq!(positions[0], Coordinates { x: 1, y: 0, z: 0 });
+    }
+}
@@ -0,0 +1,168 @@
+use crate::region::index_3d;
+use crate::structure::{Coordinates, LitematicaFile, Region};
+
+impl Region {
+    /// Names a block position of the region, e.g. `"input_chest"`, replacing any existing anchor with the same name.
+    ///
+    /// Anchors are stored inside of the region, so they survive writing and reading the file, and they move along with
+    /// their block when the region is transformed, extracted or merged. Litematica itself ignores them.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the anchor.
+    /// * `position` - The position of the block relative to the region origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// region.set_anchor("input_chest", (2, 4, 2));
+    ///
+    /// assert_eq!(region.get_anchor("input_chest"), Some((2, 4, 2).into()));
+    /// ```
+    pub fn set_anchor(
+        &mut self,
+        name: impl Into<String>,
+        position: impl Into<Coordinates>,
+    ) -> Option<Coordinates> {
+        let position = position.into();
+
+        // only used to check the bounds
+        index_3d(self.size, position);
+
+        self.extensions.anchors.insert(name.into(), position)
+    }
+
+    /// Returns the position of the anchor with the given name relative to the region origin, if it exists.
+    pub fn get_anchor(&self, name: &str) -> Option<Coordinates> {
+        self.extensions.anchors.get(name).copied()
+    }
+
+    /// Removes the anchor with the given name and returns its position. The block is not changed.
+    pub fn remove_anchor(&mut self, name: &str) -> Option<Coordinates> {
+        self.extensions.anchors.remove(name)
+    }
+
+    /// Returns an iterator over the names and positions of all anchors, sorted by name.
+    pub fn anchors(&self) -> impl Iterator<Item = (&str, Coordinates)> {
+        self.extensions
+            .anchors
+            .iter()
+            .map(|(name, position)| (name.as_str(), *position))
+    }
+}
+
+impl LitematicaFile {
+    /// Finds the anchor with the given name in any region and returns the name of the region and the position of the
+    /// anchor relative to the schematic origin.
+    ///
+    /// If several regions have an anchor with this name, the region that comes first by name is used.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("sorter.litematic").unwrap();
+    ///
+    /// if let Some((region, position)) = file.find_anchor("input_chest") {
+    ///     println!("the input chest is at {:?} in {}", position, region);
+    /// }
+    /// ```
+    pub fn find_anchor(&self, name: &str) -> Option<(&str, Coordinates)> {
+        self.regions
+            .iter()
+            .filter_map(|(region_name, region)| {
+                let local = region.get_anchor(name)?;
+                let corner = region.min_corner();
+
+                Some((
+                    region_name.as_str(),
+                    Coordinates {
+                        x: corner.x + local.x,
+                        y: corner.y + local.y,
+                        z: corner.z + local.z,
+                    },
+                ))
+            })
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_round_trip() {
+        let path = std::env::temp_dir().join("ritematica_anchors_round_trip.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.set_anchor("input_chest", (2, 4, 2));
+        region.set_anchor("output", (0, 0, 0));
+        assert_eq!(
+            region.set_anchor("output", (1, 0, 0)),
+            Some(Coordinates { x: 0, y: 0, z: 0 })
+        );
+
+        file.write(&path).unwrap();
+
+        let file = LitematicaFile::read(&path).unwrap();
+        let region = file.get_region("test").unwrap();
+        let corner = region.min_corner();
+
+        assert_eq!(
+            region.anchors().collect::<Vec<_>>(),
+            vec![
+                ("input_chest", Coordinates { x: 2, y: 4, z: 2 }),
+                ("output", Coordinates { x: 1, y: 0, z: 0 }),
+            ]
+        );
+        assert_eq!(
+            file.find_anchor("input_chest"),
+            Some((
+                "test",
+                Coordinates {
+                    x: corner.x + 2,
+                    y: corner.y + 4,
+                    z: corner.z + 2,
+                }
+            ))
+        );
+        assert_eq!(file.find_anchor("missing"), None);
+    }
+
+    #[test]
+    fn anchors_follow_extract() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.set_anchor("inside", (2, 4, 2));
+        region.set_anchor("outside", (0, 0, 0));
+
+        let part = region.extract((1, 3, 1), (3, 2, 3));
+
+        assert_eq!(
+            part.get_anchor("inside"),
+            Some(Coordinates { x: 1, y: 1, z: 1 })
+        );
+        assert_eq!(part.get_anchor("outside"), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn anchor_out_of_bounds() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.set_anchor("nowhere", (-1, 0, 0));
+    }
+}
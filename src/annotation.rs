@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use nbt::Value;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::region::index_3d;
+use crate::structure::{Coordinates, Region};
+
+/// A map of user data attached to block positions of a `Region`, e.g. colors, notes or group ids.
+///
+/// Annotations are stored as arbitrary NBT values inside of the region, so they survive writing and reading the file.
+/// Litematica itself ignores them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    entries: HashMap<Coordinates, Value>,
+}
+
+impl Annotations {
+    /// Returns the annotation at the given position, if there is one.
+    pub fn get(&self, position: impl Into<Coordinates>) -> Option<&Value> {
+        self.entries.get(&position.into())
+    }
+
+    /// Returns a mutable reference to the annotation at the given position, if there is one.
+    pub fn get_mut(&mut self, position: impl Into<Coordinates>) -> Option<&mut Value> {
+        self.entries.get_mut(&position.into())
+    }
+
+    /// Removes the annotation at the given position and returns it, if there was one.
+    pub fn remove(&mut self, position: impl Into<Coordinates>) -> Option<Value> {
+        self.entries.remove(&position.into())
+    }
+
+    /// Removes all annotations.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns an iterator over all annotated positions and their annotations, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinates, &Value)> {
+        self.entries
+            .iter()
+            .map(|(position, value)| (*position, value))
+    }
+
+    /// Returns the number of annotated positions.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether there are no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, position: Coordinates, value: Value) -> Option<Value> {
+        self.entries.insert(position, value)
+    }
+
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(Coordinates, &mut Value) -> bool) {
+        self.entries
+            .retain(|position, value| keep(*position, value));
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AnnotationEntry {
+    #[serde(serialize_with = "nbt::i32_array")]
+    pos: Vec<i32>,
+    data: Value,
+}
+
+impl Serialize for Annotations {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|(position, value)| AnnotationEntry {
+                pos: vec![position.x, position.y, position.z],
+                data: value.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        // sorted so that writing the same annotations always produces the same output
+        entries.sort_by_key(|entry| (entry.pos[1], entry.pos[2], entry.pos[0]));
+
+        entries.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Annotations {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<AnnotationEntry>::deserialize(deserializer)?;
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| match entry.pos[..] {
+                [x, y, z] => Ok((Coordinates { x, y, z }, entry.data)),
+                _ => Err(serde::de::Error::invalid_length(
+                    entry.pos.len(),
+                    &"a position with 3 coordinates",
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { entries })
+    }
+}
+
+impl Region {
+    /// Returns a reference to the `Annotations` of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// assert!(region.annotations().is_empty());
+    /// ```
+    pub fn annotations(&self) -> &Annotations {
+        &self.extensions.annotations
+    }
+
+    /// Returns a mutable reference to the `Annotations` of the region.
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.extensions.annotations
+    }
+
+    /// Attaches user data to the block at the given position, replacing any previous annotation.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position of the block relative to the region origin.
+    /// * `data` - The annotation as an NBT value, e.g. `Value::String` for a note or `Value::Int` for a color.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use nbt::Value;
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// region.annotate((2, 4, 2), Value::String("input rail".to_string()));
+    ///
+    /// assert_eq!(region.annotations().get((2, 4, 2)), Some(&Value::String("input rail".to_string())));
+    /// ```
+    pub fn annotate(&mut self, position: impl Into<Coordinates>, data: Value) -> Option<Value> {
+        let position = position.into();
+
+        // only used to check the bounds
+        index_3d(self.size, position);
+
+        self.extensions.annotations.insert(position, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn annotations_round_trip() {
+        let path = std::env::temp_dir().join("ritematica_annotations_round_trip.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.annotate((2, 4, 2), Value::String("input".to_string()));
+        region.annotate((0, 0, 0), Value::Int(0xff0000));
+
+        file.write(&path).unwrap();
+
+        let file = LitematicaFile::read(&path).unwrap();
+        let annotations = file.get_region("test").unwrap().annotations();
+
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(
+            annotations.get((2, 4, 2)),
+            Some(&Value::String("input".to_string()))
+        );
+        assert_eq!(annotations.get((0, 0, 0)), Some(&Value::Int(0xff0000)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn annotate_out_of_bounds() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.annotate((-1, 0, 0), Value::Byte(1));
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::io::BufReader;
+#[cfg(feature = "tar")]
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::structure::LitematicaFile;
+use crate::trace;
+
+impl LitematicaFile {
+    /// Reads a `Litematica` file that is stored inside of an archive, without extracting it first.
+    ///
+    /// The archive format is determined by the extension of `path`:
+    /// `.zip` requires the `zip` feature, `.tar`, `.tar.gz` and `.tgz` require the `tar` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the archive.
+    /// * `inner_path` - The path of the `.litematic` file inside of the archive, e.g. `schematics/farm.litematic`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be opened, the archive format is not supported,
+    /// `inner_path` does not exist in the archive or the file cannot be deserialized.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read_from_archive("backup.zip", "schematics/test.litematic").unwrap();
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(path = %path.as_ref().display(), inner_path = %inner_path.as_ref().display(), elapsed_ms))
+    )]
+    pub fn read_from_archive(
+        path: impl AsRef<Path>,
+        inner_path: impl AsRef<Path>,
+    ) -> Result<LitematicaFile> {
+        let _timer = trace::Timer::start();
+
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        #[cfg(feature = "zip")]
+        if file_name.ends_with(".zip") {
+            return read_from_zip(path, inner_path.as_ref());
+        }
+
+        #[cfg(feature = "tar")]
+        if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            let file = File::open(path)?;
+            let decoder = flate2::read::GzDecoder::new(BufReader::new(file));
+            return read_from_tar(decoder, inner_path.as_ref());
+        }
+
+        #[cfg(feature = "tar")]
+        if file_name.ends_with(".tar") {
+            let file = File::open(path)?;
+            return read_from_tar(BufReader::new(file), inner_path.as_ref());
+        }
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Unsupported archive format: {}", file_name),
+        )
+        .into())
+    }
+}
+
+#[cfg(feature = "zip")]
+fn read_from_zip(path: &Path, inner_path: &Path) -> Result<LitematicaFile> {
+    let file = File::open(path)?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))?;
+
+    let inner_name = inner_path.to_string_lossy().replace('\\', "/");
+    let entry = archive.by_name(&inner_name)?;
+
+    LitematicaFile::read_from(BufReader::new(entry))
+}
+
+#[cfg(feature = "tar")]
+fn read_from_tar(reader: impl Read, inner_path: &Path) -> Result<LitematicaFile> {
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+
+        if entry.path()? == inner_path {
+            return LitematicaFile::read_from(BufReader::new(entry));
+        }
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("{} not found in archive", inner_path.display()),
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// A file whose block states are too short for its region.
+    fn corrupt_bytes() -> Vec<u8> {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.get_region_mut("test")
+            .unwrap()
+            .block_states
+            .truncate(3);
+
+        file.to_bytes().unwrap()
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn read_from_zip() {
+        let path = std::env::temp_dir().join("ritematica_read_from_zip.zip");
+        let bytes = std::fs::read("test.litematic").unwrap();
+
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        writer
+            .start_file("schematics/test.litematic", Default::default())
+            .unwrap();
+        writer.write_all(&bytes).unwrap();
+        writer
+            .start_file("corrupt.litematic", Default::default())
+            .unwrap();
+        writer.write_all(&corrupt_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let file = LitematicaFile::read_from_archive(&path, "schematics/test.litematic").unwrap();
+        assert!(file.get_region("test").is_some());
+
+        assert!(LitematicaFile::read_from_archive(&path, "missing.litematic").is_err());
+        assert!(matches!(
+            LitematicaFile::read_from_archive(&path, "corrupt.litematic"),
+            Err(crate::error::Error::Region(_))
+        ));
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn read_from_tar_gz() {
+        let path = std::env::temp_dir().join("ritematica_read_from_tar.tar.gz");
+        let bytes = std::fs::read("test.litematic").unwrap();
+
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "schematics/test.litematic", &bytes[..])
+            .unwrap();
+
+        let corrupt = corrupt_bytes();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(corrupt.len() as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "corrupt.litematic", &corrupt[..])
+            .unwrap();
+        builder
+            .into_inner()
+            .unwrap()
+            .finish()
+            .unwrap()
+            .flush()
+            .unwrap();
+
+        let file = LitematicaFile::read_from_archive(&path, "schematics/test.litematic").unwrap();
+        assert!(file.get_region("test").is_some());
+
+        assert!(LitematicaFile::read_from_archive(&path, "missing.litematic").is_err());
+        assert!(matches!(
+            LitematicaFile::read_from_archive(&path, "corrupt.litematic"),
+            Err(crate::error::Error::Region(_))
+        ));
+    }
+
+    #[test]
+    fn unsupported_archive() {
+        let result = LitematicaFile::read_from_archive("test.litematic", "test.litematic");
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,1114 @@
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{self, Read, Write};
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use nbt::Value;
+
+use crate::block::BlockStateBuilder;
+use crate::error::Result;
+use crate::file::DEFAULT_DATA_VERSION;
+use crate::region::value_get;
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+// guards against stack overflows caused by maliciously nested files
+const MAX_DEPTH: usize = 512;
+
+/// The block version written into the palette of exported structures.
+const BLOCK_VERSION: i32 = 18_090_528;
+
+const COLOR_NAMES: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+/// Bedrock block names that differ from their Java counterpart.
+const RENAMED: [(&str, &str); 40] = [
+    ("grass", "grass_block"),
+    ("snow_layer", "snow"),
+    ("snow", "snow_block"),
+    ("golden_rail", "powered_rail"),
+    ("noteblock", "note_block"),
+    ("slime", "slime_block"),
+    ("web", "cobweb"),
+    ("waterlily", "lily_pad"),
+    ("reeds", "sugar_cane"),
+    ("deadbush", "dead_bush"),
+    ("yellow_flower", "dandelion"),
+    ("brick_block", "bricks"),
+    ("nether_brick", "nether_bricks"),
+    ("red_nether_brick", "red_nether_bricks"),
+    ("end_bricks", "end_stone_bricks"),
+    ("magma", "magma_block"),
+    ("hardened_clay", "terracotta"),
+    ("quartz_ore", "nether_quartz_ore"),
+    ("lit_pumpkin", "jack_o_lantern"),
+    ("melon_block", "melon"),
+    ("mob_spawner", "spawner"),
+    ("portal", "nether_portal"),
+    ("flowing_water", "water"),
+    ("flowing_lava", "lava"),
+    ("wooden_pressure_plate", "oak_pressure_plate"),
+    ("wooden_button", "oak_button"),
+    ("wooden_door", "oak_door"),
+    ("trapdoor", "oak_trapdoor"),
+    ("fence_gate", "oak_fence_gate"),
+    ("standing_sign", "oak_sign"),
+    ("wall_sign", "oak_wall_sign"),
+    ("movingBlock", "moving_piston"),
+    ("pistonArmCollision", "piston_head"),
+    ("stickyPistonArmCollision", "piston_head"),
+    ("unpowered_repeater", "repeater"),
+    ("powered_repeater", "repeater"),
+    ("unpowered_comparator", "comparator"),
+    ("powered_comparator", "comparator"),
+    ("lit_redstone_lamp", "redstone_lamp"),
+    ("lit_furnace", "furnace"),
+];
+
+/// Bedrock blocks that store their color in a `color` state, and the Java name they get after the color prefix.
+const COLORED: [(&str, &str); 9] = [
+    ("wool", "wool"),
+    ("carpet", "carpet"),
+    ("concrete", "concrete"),
+    ("concrete_powder", "concrete_powder"),
+    ("concretePowder", "concrete_powder"),
+    ("stained_glass", "stained_glass"),
+    ("stained_glass_pane", "stained_glass_pane"),
+    ("stained_hardened_clay", "terracotta"),
+    ("shulker_box", "shulker_box"),
+];
+
+const FACING_DIRECTIONS: [&str; 6] = ["down", "up", "north", "south", "west", "east"];
+const HORIZONTAL_DIRECTIONS: [&str; 4] = ["south", "west", "north", "east"];
+const STAIR_DIRECTIONS: [&str; 4] = ["east", "west", "south", "north"];
+const RAIL_SHAPES: [&str; 10] = [
+    "north_south",
+    "east_west",
+    "ascending_east",
+    "ascending_west",
+    "ascending_north",
+    "ascending_south",
+    "south_east",
+    "south_west",
+    "north_west",
+    "north_east",
+];
+
+impl LitematicaFile {
+    /// Reads a Bedrock Edition `.mcstructure` file and converts it into a `Litematica` file with a single region,
+    /// named after the file.
+    ///
+    /// See `read_mcstructure_from()` for details on the conversion.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the `.mcstructure` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid structure.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read_mcstructure("farm.mcstructure").unwrap();
+    /// file.write("farm.litematic").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn read_mcstructure(path: impl AsRef<Path>) -> Result<LitematicaFile> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let file = File::open(path)?;
+        Self::read_mcstructure_from(BufReader::new(file), name)
+    }
+
+    /// Reads an uncompressed, little-endian Bedrock Edition structure and converts it into a `Litematica` file
+    /// with a single region called `name`.
+    ///
+    /// Block states are mapped to Java with `bedrock_to_java()`. Blocks whose secondary layer is water are made
+    /// waterlogged, and structure voids become air. Entities and block entities are not imported, since their
+    /// data is stored in a different format on Bedrock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be read or is not a valid structure.
+    pub fn read_mcstructure_from(
+        reader: impl Read,
+        name: impl Into<String>,
+    ) -> Result<LitematicaFile> {
+        let root = read_root(reader)?;
+
+        let size = match value_get(&root, "size") {
+            Some(Value::List(size)) if size.len() == 3 => {
+                let component = |index: usize| match size[index] {
+                    Value::Int(value) if value > 0 => Ok(value),
+                    _ => Err(invalid_data("invalid structure size")),
+                };
+
+                Coordinates {
+                    x: component(0)?,
+                    y: component(1)?,
+                    z: component(2)?,
+                }
+            }
+            _ => return Err(invalid_data("missing structure size")),
+        };
+
+        let structure =
+            value_get(&root, "structure").ok_or_else(|| invalid_data("missing structure"))?;
+
+        let palette = match value_get(structure, "palette")
+            .and_then(|palette| value_get(palette, "default"))
+            .and_then(|palette| value_get(palette, "block_palette"))
+        {
+            Some(Value::List(palette)) => palette
+                .iter()
+                .map(|entry| {
+                    let Some(Value::String(name)) = value_get(entry, "name") else {
+                        return Err(invalid_data("palette entry without name"));
+                    };
+
+                    let states = match value_get(entry, "states") {
+                        Some(Value::Compound(states)) => states.clone(),
+                        _ => HashMap::new(),
+                    };
+
+                    Ok(bedrock_to_java(name, &states))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => return Err(invalid_data("missing block palette")),
+        };
+
+        let layers = match value_get(structure, "block_indices") {
+            Some(Value::List(layers)) if !layers.is_empty() => layers
+                .iter()
+                .map(|layer| match layer {
+                    Value::List(indices) => Ok(indices.as_slice()),
+                    _ => Err(invalid_data("invalid block indices")),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => return Err(invalid_data("missing block indices")),
+        };
+
+        let volume = size.x as u64 * size.y as u64 * size.z as u64;
+
+        if layers.iter().any(|layer| layer.len() as u64 != volume) {
+            return Err(invalid_data(
+                "block indices do not match the structure size",
+            ));
+        }
+
+        let palette_entry = |index: &Value| match index {
+            Value::Int(-1) => Ok(None),
+            Value::Int(index) => palette
+                .get(*index as usize)
+                .map(Some)
+                .ok_or_else(|| invalid_data("block index out of range")),
+            _ => Err(invalid_data("invalid block index")),
+        };
+
+        let mut region = Region::filled_with_air(Coordinates { x: 0, y: 0, z: 0 }, size);
+        let mut index = 0;
+
+        // bedrock stores blocks in XYZ order, with z changing the fastest
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    let block = palette_entry(&layers[0][index])?;
+                    let secondary = match layers.get(1) {
+                        Some(layer) => palette_entry(&layer[index])?,
+                        None => None,
+                    };
+
+                    index += 1;
+
+                    let Some(block) = block else {
+                        continue;
+                    };
+
+                    if block.is_air() {
+                        continue;
+                    }
+
+                    let waterlogged = secondary.is_some_and(|secondary| {
+                        secondary.get_name().get_path() == "water"
+                            && block.get_name().get_path() != "water"
+                    });
+
+                    if waterlogged {
+                        let mut block = block.clone();
+                        block.add_properties([("waterlogged", "true")]);
+                        region.set_block((x, y, z), block);
+                    } else {
+                        region.set_block((x, y, z), block.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(LitematicaFile::from_region(
+            name,
+            region,
+            DEFAULT_DATA_VERSION,
+        ))
+    }
+
+    /// Converts the file into a Bedrock Edition `.mcstructure` file.
+    ///
+    /// See `write_mcstructure_to()` for details on the conversion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("farm.litematic").unwrap();
+    /// file.write_mcstructure("farm.mcstructure").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn write_mcstructure(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let mut buf_writer = BufWriter::new(file);
+
+        self.write_mcstructure_to(&mut buf_writer)?;
+        buf_writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the file as an uncompressed, little-endian Bedrock Edition structure.
+    ///
+    /// Since a structure only holds a single box of blocks, all regions are combined into their enclosing box, where
+    /// the first non-air block by region name wins. Block states are mapped to Bedrock with `java_to_bedrock()`, and
+    /// waterlogged blocks get water in the secondary layer. Entities and block entities are not exported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be written.
+    pub fn write_mcstructure_to(&self, writer: &mut impl Write) -> Result<()> {
+        let region = self.combined_region();
+        let size = Coordinates {
+            x: region.size.x.abs(),
+            y: region.size.y.abs(),
+            z: region.size.z.abs(),
+        };
+
+        let mut palette = Vec::new();
+        let mut palette_indices = HashMap::new();
+        let mut palette_index = |name: String, states: HashMap<String, Value>| {
+            let key = (name.clone(), format!("{:?}", sorted(&states)));
+
+            *palette_indices.entry(key).or_insert_with(|| {
+                palette.push(Value::Compound(HashMap::from([
+                    ("name".to_string(), Value::String(name)),
+                    ("states".to_string(), Value::Compound(states)),
+                    ("version".to_string(), Value::Int(BLOCK_VERSION)),
+                ])));
+                palette.len() as i32 - 1
+            })
+        };
+
+        // every block state of the region is only converted once
+        let converted = region
+            .block_state_palette
+            .iter()
+            .map(|block| {
+                let (name, states) = java_to_bedrock(block);
+                let primary = palette_index(name, states);
+
+                let waterlogged = block
+                    .get_properties()
+                    .get("waterlogged")
+                    .is_some_and(|waterlogged| waterlogged == "true");
+                let secondary = waterlogged.then(|| {
+                    palette_index(
+                        "minecraft:water".to_string(),
+                        HashMap::from([("liquid_depth".to_string(), Value::Int(0))]),
+                    )
+                });
+
+                (primary, secondary.unwrap_or(-1))
+            })
+            .collect::<Vec<_>>();
+
+        let required_bits = Region::calc_required_bits(&region.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+        let volume = region.calc_volume() as usize;
+
+        let mut primary = Vec::with_capacity(volume);
+        let mut secondary = Vec::with_capacity(volume);
+
+        // bedrock stores blocks in XYZ order, with z changing the fastest
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    let index = region.get_3d_index((x, y, z));
+                    let palette_index =
+                        region.get_palette_index(index, required_bits, bitmask) as usize;
+                    let (block, water) = converted[palette_index];
+
+                    primary.push(Value::Int(block));
+                    secondary.push(Value::Int(water));
+                }
+            }
+        }
+
+        let coordinates = |coordinates: Coordinates| {
+            Value::List(vec![
+                Value::Int(coordinates.x),
+                Value::Int(coordinates.y),
+                Value::Int(coordinates.z),
+            ])
+        };
+
+        let root = Value::Compound(HashMap::from([
+            ("format_version".to_string(), Value::Int(1)),
+            ("size".to_string(), coordinates(size)),
+            (
+                "structure_world_origin".to_string(),
+                coordinates(region.min_corner()),
+            ),
+            (
+                "structure".to_string(),
+                Value::Compound(HashMap::from([
+                    (
+                        "block_indices".to_string(),
+                        Value::List(vec![Value::List(primary), Value::List(secondary)]),
+                    ),
+                    ("entities".to_string(), Value::List(Vec::new())),
+                    (
+                        "palette".to_string(),
+                        Value::Compound(HashMap::from([(
+                            "default".to_string(),
+                            Value::Compound(HashMap::from([
+                                ("block_palette".to_string(), Value::List(palette)),
+                                (
+                                    "block_position_data".to_string(),
+                                    Value::Compound(HashMap::new()),
+                                ),
+                            ])),
+                        )])),
+                    ),
+                ])),
+            ),
+        ]));
+
+        write_root(writer, &root)?;
+
+        Ok(())
+    }
+}
+
+/// Maps a Bedrock Edition block state to the equivalent Java Edition `BlockState`, as far as it is known.
+///
+/// Renamed blocks, blocks with a `color` state and the common states like `facing_direction`, `pillar_axis`,
+/// `upside_down_bit` or `rail_direction` are translated. Other states ending in `_bit` become boolean properties
+/// without the suffix, and all remaining states are kept as they are, so the result may need manual fixes for
+/// blocks that are very different between the editions.
+///
+/// # Arguments
+///
+/// * `name` - The Bedrock block name, e.g. `minecraft:wool`.
+/// * `states` - The block states of the Bedrock palette entry.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+/// use nbt::Value;
+/// use ritematica::bedrock::bedrock_to_java;
+///
+/// let states = HashMap::from([("color".to_string(), Value::String("silver".to_string()))]);
+///
+/// assert_eq!(bedrock_to_java("minecraft:wool", &states).to_string(), "minecraft:light_gray_wool");
+/// ```
+pub fn bedrock_to_java(name: &str, states: &HashMap<String, Value>) -> BlockState {
+    let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+
+    let mut properties = BTreeMap::new();
+    let mut java_path = RENAMED
+        .iter()
+        .find(|(bedrock, _)| *bedrock == path)
+        .map_or(path.to_string(), |(_, java)| java.to_string());
+
+    if let Some((_, java)) = COLORED.iter().find(|(bedrock, _)| *bedrock == path) {
+        if let Some(Value::String(color)) = states.get("color") {
+            let color = if color == "silver" {
+                "light_gray"
+            } else {
+                color
+            };
+            java_path = format!("{}_{}", color, java);
+        }
+    }
+
+    match path {
+        "unpowered_repeater" | "unpowered_comparator" | "redstone_lamp" | "furnace" => {
+            let key = if path.ends_with("comparator") || path.ends_with("repeater") {
+                "powered"
+            } else {
+                "lit"
+            };
+            properties.insert(key.to_string(), "false".to_string());
+        }
+        "powered_repeater" | "powered_comparator" => {
+            properties.insert("powered".to_string(), "true".to_string());
+        }
+        "lit_redstone_lamp" | "lit_furnace" => {
+            properties.insert("lit".to_string(), "true".to_string());
+        }
+        "redstone_torch" | "unlit_redstone_torch" => {
+            properties.insert("lit".to_string(), (path == "redstone_torch").to_string());
+        }
+        "stickyPistonArmCollision" => {
+            properties.insert("type".to_string(), "sticky".to_string());
+        }
+        _ => {}
+    }
+
+    for (key, value) in states {
+        let int = match value {
+            Value::Byte(value) => Some(*value as i32),
+            Value::Short(value) => Some(*value as i32),
+            Value::Int(value) => Some(*value),
+            _ => None,
+        };
+        let text = match value {
+            Value::String(value) => value.clone(),
+            _ => int.map_or_else(String::new, |int| int.to_string()),
+        };
+        let bit = int.map(|int| (int != 0).to_string());
+
+        let indexed = |names: &[&str]| {
+            int.and_then(|int| names.get(int as usize))
+                .map(|name| name.to_string())
+        };
+
+        let (key, value) = match key.as_str() {
+            "color" | "update_bit" => continue,
+            "facing_direction" => ("facing", indexed(&FACING_DIRECTIONS)),
+            "direction" => ("facing", indexed(&HORIZONTAL_DIRECTIONS)),
+            "weirdo_direction" => ("facing", indexed(&STAIR_DIRECTIONS)),
+            "minecraft:cardinal_direction" | "minecraft:facing_direction" => ("facing", Some(text)),
+            "pillar_axis" => ("axis", Some(text)),
+            "rail_direction" => ("shape", indexed(&RAIL_SHAPES)),
+            "rail_data_bit" | "button_pressed_bit" | "output_lit_bit" => ("powered", bit),
+            "redstone_signal" => ("power", Some(text)),
+            "repeater_delay" => ("delay", int.map(|delay| (delay + 1).to_string())),
+            "output_subtract_bit" => (
+                "mode",
+                int.map(|int| if int != 0 { "subtract" } else { "compare" }.to_string()),
+            ),
+            "upside_down_bit" => (
+                "half",
+                int.map(|int| if int != 0 { "top" } else { "bottom" }.to_string()),
+            ),
+            "upper_block_bit" => (
+                "half",
+                int.map(|int| if int != 0 { "upper" } else { "lower" }.to_string()),
+            ),
+            "door_hinge_bit" => (
+                "hinge",
+                int.map(|int| if int != 0 { "right" } else { "left" }.to_string()),
+            ),
+            "top_slot_bit" => (
+                "type",
+                int.map(|int| if int != 0 { "top" } else { "bottom" }.to_string()),
+            ),
+            "minecraft:vertical_half" => ("type", Some(text)),
+            "liquid_depth" => ("level", Some(text)),
+            "growth" => ("age", Some(text)),
+            "torch_facing_direction" => {
+                if text != "top" && text != "unknown" {
+                    java_path = java_path.replace("torch", "wall_torch");
+                    ("facing", Some(text))
+                } else {
+                    continue;
+                }
+            }
+            key => match key.strip_suffix("_bit") {
+                Some(stripped) => (stripped, bit),
+                None => (key, Some(text)),
+            },
+        };
+
+        if let Some(value) = value {
+            properties.insert(key.to_string(), value);
+        }
+    }
+
+    BlockStateBuilder::new(format!("{}:{}", namespace, java_path))
+        .properties(properties)
+        .build()
+}
+
+/// Maps a Java Edition `BlockState` to the equivalent Bedrock Edition block name and states, as far as it is known.
+///
+/// This is the inverse of `bedrock_to_java()`, so the names are the ones used by `read_mcstructure()`, e.g.
+/// `minecraft:wool` with a `color` state. Properties without a known Bedrock state are written as `<name>_bit` bytes
+/// for booleans, ints for numbers and strings otherwise, which `bedrock_to_java()` reads back as they were.
+/// `waterlogged` is left out, since Bedrock stores water in a separate layer.
+///
+/// # Examples
+/// ```
+/// use nbt::Value;
+/// use ritematica::BlockStateBuilder;
+/// use ritematica::bedrock::java_to_bedrock;
+///
+/// let (name, states) = java_to_bedrock(&BlockStateBuilder::new("light_gray_wool").build());
+///
+/// assert_eq!(name, "minecraft:wool");
+/// assert_eq!(states["color"], Value::String("silver".to_string()));
+/// ```
+pub fn java_to_bedrock(block: &BlockState) -> (String, HashMap<String, Value>) {
+    let name = block.get_name();
+    let path = name.get_path();
+    let properties = block.get_properties();
+    let property = |key: &str| properties.get(key).map(String::as_str);
+
+    let mut states = HashMap::new();
+    let mut bedrock_path = match path {
+        "repeater" | "comparator" => match property("powered") {
+            Some("true") => format!("powered_{}", path),
+            _ => format!("unpowered_{}", path),
+        },
+        "redstone_lamp" | "furnace" if property("lit") == Some("true") => format!("lit_{}", path),
+        "redstone_torch" | "redstone_wall_torch" if property("lit") == Some("false") => {
+            "unlit_redstone_torch".to_string()
+        }
+        "wall_torch" | "redstone_wall_torch" | "soul_wall_torch" => {
+            path.replace("wall_torch", "torch")
+        }
+        "water" | "lava" | "piston_head" => path.to_string(),
+        _ => RENAMED
+            .iter()
+            .find(|(_, java)| *java == path)
+            .map_or(path.to_string(), |(bedrock, _)| bedrock.to_string()),
+    };
+
+    // colored blocks share a single name with a color state, but only if the prefix is really a color
+    // reversed, so the first of two bedrock names for the same block wins
+    let colored = COLORED
+        .iter()
+        .rev()
+        .filter_map(|(bedrock, java)| {
+            let color = path.strip_suffix(java)?.strip_suffix('_')?;
+            COLOR_NAMES
+                .contains(&color)
+                .then_some((bedrock, java, color))
+        })
+        .max_by_key(|(_, java, _)| java.len());
+
+    if let Some((bedrock, _, color)) = colored {
+        bedrock_path = bedrock.to_string();
+        let color = if color == "light_gray" {
+            "silver"
+        } else {
+            color
+        };
+        states.insert("color".to_string(), Value::String(color.to_string()));
+    }
+
+    let int = |value: &str| value.parse::<i32>().ok();
+    let indexed = |names: &[&str], value: &str| {
+        names
+            .iter()
+            .position(|name| *name == value)
+            .map(|index| Value::Int(index as i32))
+    };
+    let bit = |value: bool| Value::Byte(value as i8);
+
+    for (key, value) in properties {
+        let value = value.as_str();
+
+        let state = match key.as_str() {
+            "waterlogged" => continue,
+            "powered" if matches!(path, "repeater" | "comparator") => continue,
+            "lit"
+                if matches!(
+                    path,
+                    "redstone_lamp" | "furnace" | "redstone_torch" | "redstone_wall_torch"
+                ) =>
+            {
+                continue
+            }
+            "facing" if path.ends_with("wall_torch") => {
+                Some(("torch_facing_direction", Value::String(value.to_string())))
+            }
+            "facing" if path.ends_with("_stairs") => {
+                indexed(&STAIR_DIRECTIONS, value).map(|value| ("weirdo_direction", value))
+            }
+            "facing" if uses_horizontal_direction(path) => {
+                indexed(&HORIZONTAL_DIRECTIONS, value).map(|value| ("direction", value))
+            }
+            "facing" => indexed(&FACING_DIRECTIONS, value).map(|value| ("facing_direction", value)),
+            "axis" => Some(("pillar_axis", Value::String(value.to_string()))),
+            "shape" if path.ends_with("rail") => {
+                indexed(&RAIL_SHAPES, value).map(|value| ("rail_direction", value))
+            }
+            "powered" if path.ends_with("rail") => Some(("rail_data_bit", bit(value == "true"))),
+            "powered" if path.ends_with("button") => {
+                Some(("button_pressed_bit", bit(value == "true")))
+            }
+            "power" => int(value).map(|value| ("redstone_signal", Value::Int(value))),
+            "delay" => int(value).map(|value| ("repeater_delay", Value::Int(value - 1))),
+            "mode" => Some(("output_subtract_bit", bit(value == "subtract"))),
+            "half" if value == "top" || value == "bottom" => {
+                Some(("upside_down_bit", bit(value == "top")))
+            }
+            "half" => Some(("upper_block_bit", bit(value == "upper"))),
+            "hinge" => Some(("door_hinge_bit", bit(value == "right"))),
+            "type" if value == "top" || value == "bottom" => {
+                Some(("top_slot_bit", bit(value == "top")))
+            }
+            "level" => int(value).map(|value| ("liquid_depth", Value::Int(value))),
+            "age" => int(value).map(|value| ("growth", Value::Int(value))),
+            _ => None,
+        };
+
+        let (key, value) = match state {
+            Some((key, value)) => (key.to_string(), value),
+            None => match value {
+                "true" | "false" => (format!("{}_bit", key), bit(value == "true")),
+                value => match int(value) {
+                    Some(int) => (key.clone(), Value::Int(int)),
+                    None => (key.clone(), Value::String(value.to_string())),
+                },
+            },
+        };
+
+        states.insert(key, value);
+    }
+
+    (format!("{}:{}", name.get_namespace(), bedrock_path), states)
+}
+
+/// Checks whether the block stores its `facing` as a horizontal `direction` on Bedrock.
+fn uses_horizontal_direction(path: &str) -> bool {
+    matches!(
+        path,
+        "repeater" | "comparator" | "tripwire_hook" | "cocoa" | "end_portal_frame" | "lever"
+    ) || path.ends_with("_door")
+        || path.ends_with("_bed")
+        || path.ends_with("_fence_gate")
+        || path.ends_with("anvil")
+}
+
+/// Reads the root compound of little-endian NBT data, as used by Bedrock Edition.
+fn read_root(mut reader: impl Read) -> Result<Value> {
+    if read_u8(&mut reader)? != TAG_COMPOUND {
+        return Err(invalid_data("root tag is not a compound"));
+    }
+
+    read_string(&mut reader)?;
+    read_payload(&mut reader, TAG_COMPOUND, 0)
+}
+
+fn read_payload(reader: &mut impl Read, tag: u8, depth: usize) -> Result<Value> {
+    if depth > MAX_DEPTH {
+        return Err(invalid_data("NBT nested too deeply"));
+    }
+
+    let value = match tag {
+        TAG_BYTE => Value::Byte(read_u8(reader)? as i8),
+        TAG_SHORT => Value::Short(i16::from_le_bytes(read_array(reader)?)),
+        TAG_INT => Value::Int(i32::from_le_bytes(read_array(reader)?)),
+        TAG_LONG => Value::Long(i64::from_le_bytes(read_array(reader)?)),
+        TAG_FLOAT => Value::Float(f32::from_le_bytes(read_array(reader)?)),
+        TAG_DOUBLE => Value::Double(f64::from_le_bytes(read_array(reader)?)),
+        TAG_BYTE_ARRAY => {
+            let len = read_len(reader)?;
+            let bytes = read_bytes(reader, len)?;
+            Value::ByteArray(bytes.into_iter().map(|byte| byte as i8).collect())
+        }
+        TAG_STRING => Value::String(read_string(reader)?),
+        TAG_LIST => {
+            let element_tag = read_u8(reader)?;
+            let len = read_len(reader)?;
+
+            let mut list = Vec::new();
+            for _ in 0..len {
+                list.push(read_payload(reader, element_tag, depth + 1)?);
+            }
+
+            Value::List(list)
+        }
+        TAG_COMPOUND => {
+            let mut compound = HashMap::new();
+
+            loop {
+                let child_tag = read_u8(reader)?;
+
+                if child_tag == TAG_END {
+                    break;
+                }
+
+                let name = read_string(reader)?;
+                compound.insert(name, read_payload(reader, child_tag, depth + 1)?);
+            }
+
+            Value::Compound(compound)
+        }
+        TAG_INT_ARRAY => {
+            let len = read_len(reader)?;
+
+            let mut array = Vec::new();
+            for _ in 0..len {
+                array.push(i32::from_le_bytes(read_array(reader)?));
+            }
+
+            Value::IntArray(array)
+        }
+        TAG_LONG_ARRAY => {
+            let len = read_len(reader)?;
+
+            let mut array = Vec::new();
+            for _ in 0..len {
+                array.push(i64::from_le_bytes(read_array(reader)?));
+            }
+
+            Value::LongArray(array)
+        }
+        _ => return Err(invalid_data(&format!("invalid tag type {}", tag))),
+    };
+
+    Ok(value)
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8> {
+    Ok(read_array::<1>(reader)?[0])
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N]> {
+    let mut buf = [0; N];
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf)
+}
+
+/// Reads a non-negative length prefix.
+fn read_len(reader: &mut impl Read) -> Result<usize> {
+    let len = i32::from_le_bytes(read_array(reader)?);
+
+    usize::try_from(len).map_err(|_| invalid_data("negative length"))
+}
+
+fn read_bytes(reader: &mut impl Read, len: usize) -> Result<Vec<u8>> {
+    // the buffer grows while reading, so a bogus length can't allocate huge amounts of memory
+    let mut buf = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buf)?;
+
+    if buf.len() != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(buf)
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String> {
+    let len = u16::from_le_bytes(read_array(reader)?) as usize;
+    let buf = read_bytes(reader, len)?;
+
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Writes `root` as the unnamed root compound of little-endian NBT data.
+fn write_root(writer: &mut impl Write, root: &Value) -> Result<()> {
+    let mut out = vec![TAG_COMPOUND, 0, 0];
+    write_payload(&mut out, root);
+    writer.write_all(&out)?;
+
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Byte(value) => out.push(*value as u8),
+        Value::Short(value) => out.extend_from_slice(&value.to_le_bytes()),
+        Value::Int(value) => out.extend_from_slice(&value.to_le_bytes()),
+        Value::Long(value) => out.extend_from_slice(&value.to_le_bytes()),
+        Value::Float(value) => out.extend_from_slice(&value.to_le_bytes()),
+        Value::Double(value) => out.extend_from_slice(&value.to_le_bytes()),
+        Value::ByteArray(array) => {
+            out.extend_from_slice(&(array.len() as i32).to_le_bytes());
+            out.extend(array.iter().map(|byte| *byte as u8));
+        }
+        Value::String(value) => {
+            out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            out.extend_from_slice(value.as_bytes());
+        }
+        Value::List(list) => {
+            out.push(list.first().map_or(TAG_END, tag_of));
+            out.extend_from_slice(&(list.len() as i32).to_le_bytes());
+
+            for value in list {
+                write_payload(out, value);
+            }
+        }
+        Value::Compound(compound) => {
+            // sorted, so the same structure is always written the same way
+            for (key, value) in sorted(compound) {
+                out.push(tag_of(value));
+                write_payload(out, &Value::String(key.clone()));
+                write_payload(out, value);
+            }
+
+            out.push(TAG_END);
+        }
+        Value::IntArray(array) => {
+            out.extend_from_slice(&(array.len() as i32).to_le_bytes());
+
+            for value in array {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        Value::LongArray(array) => {
+            out.extend_from_slice(&(array.len() as i32).to_le_bytes());
+
+            for value in array {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+}
+
+fn tag_of(value: &Value) -> u8 {
+    match value {
+        Value::Byte(_) => TAG_BYTE,
+        Value::Short(_) => TAG_SHORT,
+        Value::Int(_) => TAG_INT,
+        Value::Long(_) => TAG_LONG,
+        Value::Float(_) => TAG_FLOAT,
+        Value::Double(_) => TAG_DOUBLE,
+        Value::ByteArray(_) => TAG_BYTE_ARRAY,
+        Value::String(_) => TAG_STRING,
+        Value::List(_) => TAG_LIST,
+        Value::Compound(_) => TAG_COMPOUND,
+        Value::IntArray(_) => TAG_INT_ARRAY,
+        Value::LongArray(_) => TAG_LONG_ARRAY,
+    }
+}
+
+fn sorted(compound: &HashMap<String, Value>) -> BTreeMap<&String, &Value> {
+    compound.iter().collect()
+}
+
+fn invalid_data(message: &str) -> crate::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compound(entries: Vec<(&str, Value)>) -> Value {
+        Value::Compound(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn palette_entry(name: &str, states: Vec<(&str, Value)>) -> Value {
+        compound(vec![
+            ("name", Value::String(name.to_string())),
+            ("states", compound(states)),
+            ("version", Value::Int(18090528)),
+        ])
+    }
+
+    #[test]
+    fn read_structure() {
+        let palette = Value::List(vec![
+            palette_entry("minecraft:air", vec![]),
+            palette_entry(
+                "minecraft:wool",
+                vec![("color", Value::String("silver".to_string()))],
+            ),
+            palette_entry(
+                "minecraft:oak_stairs",
+                vec![
+                    ("weirdo_direction", Value::Int(3)),
+                    ("upside_down_bit", Value::Byte(1)),
+                ],
+            ),
+            palette_entry("minecraft:water", vec![("liquid_depth", Value::Int(0))]),
+        ]);
+
+        // 2x1x2, indexed with z changing the fastest
+        let primary = [1, -1, 2, 0].map(Value::Int).to_vec();
+        let secondary = [-1, -1, 3, -1].map(Value::Int).to_vec();
+
+        let root = compound(vec![
+            ("format_version", Value::Int(1)),
+            ("size", Value::List([2, 1, 2].map(Value::Int).to_vec())),
+            (
+                "structure",
+                compound(vec![
+                    (
+                        "block_indices",
+                        Value::List(vec![Value::List(primary), Value::List(secondary)]),
+                    ),
+                    ("entities", Value::List(vec![])),
+                    (
+                        "palette",
+                        compound(vec![(
+                            "default",
+                            compound(vec![("block_palette", palette)]),
+                        )]),
+                    ),
+                ]),
+            ),
+        ]);
+
+        let mut bytes = Vec::new();
+        write_root(&mut bytes, &root).unwrap();
+
+        let file = LitematicaFile::read_mcstructure_from(&bytes[..], "test").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        assert_eq!(region.size, Coordinates { x: 2, y: 1, z: 2 });
+        assert_eq!(
+            region.get_block((0, 0, 0)).to_string(),
+            "minecraft:light_gray_wool"
+        );
+        assert_eq!(region.get_block((0, 0, 1)).to_string(), "minecraft:air");
+        assert_eq!(
+            region.get_block((1, 0, 0)).to_string(),
+            "minecraft:oak_stairs[facing=north,half=top,waterlogged=true]"
+        );
+        assert_eq!(file.metadata.total_blocks, 2);
+    }
+
+    #[test]
+    fn map_redstone_components() {
+        let repeater = bedrock_to_java(
+            "minecraft:powered_repeater",
+            &HashMap::from([
+                ("direction".to_string(), Value::Int(2)),
+                ("repeater_delay".to_string(), Value::Int(3)),
+            ]),
+        );
+
+        assert_eq!(
+            repeater.to_string(),
+            "minecraft:repeater[delay=4,facing=north,powered=true]"
+        );
+
+        let rail = bedrock_to_java(
+            "minecraft:golden_rail",
+            &HashMap::from([
+                ("rail_direction".to_string(), Value::Int(1)),
+                ("rail_data_bit".to_string(), Value::Byte(0)),
+            ]),
+        );
+
+        assert_eq!(
+            rail.to_string(),
+            "minecraft:powered_rail[powered=false,shape=east_west]"
+        );
+    }
+
+    #[test]
+    fn map_java_to_bedrock() {
+        let blocks = [
+            BlockStateBuilder::new("light_gray_wool").build(),
+            BlockStateBuilder::new("stone").build(),
+            BlockStateBuilder::new("oak_stairs")
+                .properties([("facing", "east"), ("half", "bottom")])
+                .build(),
+            BlockStateBuilder::new("repeater")
+                .properties([("delay", "2"), ("facing", "south"), ("powered", "false")])
+                .build(),
+            BlockStateBuilder::new("powered_rail")
+                .properties([("powered", "true"), ("shape", "north_south")])
+                .build(),
+            BlockStateBuilder::new("oak_log")
+                .properties([("axis", "y")])
+                .build(),
+        ];
+
+        for block in blocks {
+            let (name, states) = java_to_bedrock(&block);
+            assert_eq!(bedrock_to_java(&name, &states), block, "{}", name);
+        }
+
+        let (name, states) = java_to_bedrock(&BlockStateBuilder::new("powered_rail").build());
+        assert_eq!(name, "minecraft:golden_rail");
+        assert!(states.is_empty());
+    }
+
+    #[test]
+    fn write_structure() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let stairs = BlockStateBuilder::new("oak_stairs")
+            .properties([("facing", "west"), ("half", "top"), ("waterlogged", "true")])
+            .build();
+        region.set_block((0, 0, 0), stairs.clone());
+        region.set_block((1, 0, 0), BlockStateBuilder::new("red_concrete").build());
+
+        let mut bytes = Vec::new();
+        file.write_mcstructure_to(&mut bytes).unwrap();
+
+        let read = LitematicaFile::read_mcstructure_from(&bytes[..], "test").unwrap();
+        let region = file.get_region("test").unwrap();
+        let read_region = read.get_region("test").unwrap();
+
+        assert_eq!(read_region.get_block((0, 0, 0)), &stairs);
+        assert_eq!(
+            read_region.get_block((1, 0, 0)).to_string(),
+            "minecraft:red_concrete"
+        );
+        assert_eq!(
+            read_region.size,
+            Coordinates {
+                x: region.size.x.abs(),
+                y: region.size.y.abs(),
+                z: region.size.z.abs(),
+            }
+        );
+    }
+}
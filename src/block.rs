@@ -1,5 +1,15 @@
-use crate::{resource_location::ResourceLocation, structure::BlockState};
-use std::collections::HashMap;
+use crate::error::ParseError;
+#[cfg(feature = "registry")]
+use crate::error::ValidationError;
+#[cfg(feature = "registry")]
+use crate::registry::Registry;
+use crate::resource_location::{ParseOptions, ResourceLocation};
+use crate::structure::{BlockState, BlockStateData};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter, Write};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
 
 /// A pattern that can be used to match block states.
 pub trait BlockStatePattern {
@@ -28,7 +38,7 @@ pub trait BlockStatePattern {
 #[derive(Debug)]
 pub struct BlockStateBuilder {
     name: ResourceLocation,
-    properties: HashMap<String, String>,
+    properties: BTreeMap<String, String>,
 }
 
 impl BlockStateBuilder {
@@ -50,7 +60,7 @@ impl BlockStateBuilder {
     pub fn new(name: impl Into<ResourceLocation>) -> Self {
         Self {
             name: name.into(),
-            properties: HashMap::new(),
+            properties: BTreeMap::new(),
         }
     }
 
@@ -91,10 +101,36 @@ impl BlockStateBuilder {
     ///    .build();
     /// ```
     pub fn build(self) -> BlockState {
-        BlockState {
-            name: self.name,
-            properties: self.properties,
-        }
+        BlockState::from_parts(self.name, self.properties)
+    }
+
+    /// Builds the `BlockState` and validates it against the property schema of `registry`, to catch typos in block
+    /// names, property names and values before they end up in a schematic.
+    ///
+    /// Properties that are not set are not an error, since Minecraft uses their default values. Blocks missing from a
+    /// partial registry (see `Registry::partial()`) are built without checks.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ValidationError` if the block is unknown, has an unknown property or a property has an invalid value.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::BlockStateBuilder;
+    /// use ritematica::registry::Registry;
+    ///
+    /// let result = BlockStateBuilder::new("piston")
+    ///    .properties([("facing", "sideways")])
+    ///    .build_checked(Registry::builtin());
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    #[cfg(feature = "registry")]
+    pub fn build_checked(self, registry: &Registry) -> Result<BlockState, ValidationError> {
+        let block_state = self.build();
+        registry.validate(&block_state)?;
+
+        Ok(block_state)
     }
 }
 
@@ -121,7 +157,9 @@ impl BlockState {
         &self.name
     }
 
-    /// Returns the properties of a `BlockState` as a reference to a `HashMap<String, String>`.
+    /// Returns the properties of a `BlockState` as a reference to a `BTreeMap<String, String>`.
+    ///
+    /// The properties are iterated in the order of their names, so output built from them is stable.
     ///
     /// # Examples
     /// ```
@@ -136,13 +174,30 @@ impl BlockState {
     ///     &[("facing".to_string(), "down".to_string())]
     ///         .iter()
     ///         .cloned()
-    ///         .collect::<HashMap<String, String>>()
+    ///         .collect::<BTreeMap<String, String>>()
     /// );
     /// ```
-    pub fn get_properties(&self) -> &HashMap<String, String> {
+    pub fn get_properties(&self) -> &BTreeMap<String, String> {
         &self.properties
     }
 
+    /// Checks whether the `BlockState` is any kind of air: `air`, `cave_air` or `void_air`.
+    ///
+    /// Schematics saved from a world often contain `cave_air`, which should be treated like regular air when blocks are
+    /// counted or skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::BlockStateBuilder;
+    ///
+    /// assert!(BlockStateBuilder::new("cave_air").build().is_air());
+    /// assert!(!BlockStateBuilder::new("glass").build().is_air());
+    /// ```
+    pub fn is_air(&self) -> bool {
+        self.name.get_namespace() == "minecraft"
+            && matches!(self.name.get_path(), "air" | "cave_air" | "void_air")
+    }
+
     /// Sets the name of a `BlockState`.
     ///
     /// # Arguments
@@ -169,6 +224,7 @@ impl BlockState {
     /// ```
     pub fn set_name(&mut self, name: impl Into<ResourceLocation>) {
         self.name = name.into();
+        self.update_canonical();
     }
 
     /// Sets the properties of a `BlockState`. Clears any existing properties before adding the new ones.
@@ -192,7 +248,7 @@ impl BlockState {
     ///     &[("facing".to_string(), "up".to_string())]
     ///         .iter()
     ///         .cloned()
-    ///         .collect::<HashMap<String, String>>()
+    ///         .collect::<BTreeMap<String, String>>()
     /// );
     /// ```
     pub fn set_properties<K, V>(&mut self, properties: impl IntoIterator<Item = (K, V)>)
@@ -200,11 +256,14 @@ impl BlockState {
         K: Into<String>,
         V: Into<String>,
     {
-        self.properties.clear();
+        let own = Arc::make_mut(&mut self.properties);
+        own.clear();
 
         for (key, value) in properties {
-            self.properties.insert(key.into(), value.into());
+            own.insert(key.into(), value.into());
         }
+
+        self.update_canonical();
     }
 
     /// Adds properties to a `BlockState`. If a property with the same name already exists, it will be overwritten.
@@ -228,7 +287,7 @@ impl BlockState {
     ///     &[("facing".to_string(), "down".to_string()), ("extended".to_string(), "true".to_string())]
     ///         .iter()
     ///         .cloned()
-    ///         .collect::<HashMap<String, String>>()
+    ///         .collect::<BTreeMap<String, String>>()
     /// );
     /// ```
     pub fn add_properties<K, V>(&mut self, properties: impl IntoIterator<Item = (K, V)>)
@@ -236,9 +295,13 @@ impl BlockState {
         K: Into<String>,
         V: Into<String>,
     {
+        let own = Arc::make_mut(&mut self.properties);
+
         for (key, value) in properties {
-            self.properties.insert(key.into(), value.into());
+            own.insert(key.into(), value.into());
         }
+
+        self.update_canonical();
     }
 
     /// Removes all properties from a `BlockState`.
@@ -256,7 +319,8 @@ impl BlockState {
     /// assert_eq!(blockstate.get_properties().len(), 0);
     /// ```
     pub fn clear_properties(&mut self) {
-        self.properties.clear();
+        Arc::make_mut(&mut self.properties).clear();
+        self.update_canonical();
     }
 
     /// Removes a property from a `BlockState` by name.
@@ -278,7 +342,173 @@ impl BlockState {
     /// assert_eq!(blockstate.get_properties().len(), 0);
     /// ```
     pub fn remove_property(&mut self, property: impl Into<String>) {
-        self.properties.remove(&property.into());
+        Arc::make_mut(&mut self.properties).remove(&property.into());
+        self.update_canonical();
+    }
+
+    /// Parses a `BlockState` in the format of Minecraft commands, e.g. `minecraft:piston[facing=down]`.
+    ///
+    /// This is the inverse of the `Display` implementation. If the namespace is omitted, `minecraft` is assumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the name is not a valid `ResourceLocation` or the properties are malformed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::BlockState;
+    ///
+    /// let block_state = BlockState::parse("piston[facing=down]").unwrap();
+    ///
+    /// assert_eq!(block_state.to_string(), "minecraft:piston[facing=down]");
+    /// assert!(BlockState::parse("piston[facing]").is_err());
+    /// ```
+    pub fn parse(input: impl AsRef<str>) -> Result<Self, ParseError> {
+        Self::parse_with(input.as_ref(), |name| ResourceLocation::parse(name))
+    }
+
+    /// Parses a `BlockState` in the format of Minecraft commands, reading its name with the given `ParseOptions`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the name is not a valid `ResourceLocation` or the properties are malformed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::BlockState;
+    /// use ritematica::resource_location::ParseOptions;
+    ///
+    /// let options = ParseOptions {
+    ///     default_namespace: "create".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let block_state = BlockState::parse_with_options("shaft[axis=y]", &options).unwrap();
+    ///
+    /// assert_eq!(block_state.to_string(), "create:shaft[axis=y]");
+    /// ```
+    pub fn parse_with_options(
+        input: impl AsRef<str>,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError> {
+        Self::parse_with(input.as_ref(), |name| {
+            ResourceLocation::parse_with_options(name, options)
+        })
+    }
+
+    fn parse_with(
+        input: &str,
+        parse_name: impl FnOnce(&str) -> Result<ResourceLocation, ParseError>,
+    ) -> Result<Self, ParseError> {
+        let input = input.trim();
+
+        let (name, properties) = match input.split_once('[') {
+            Some((name, rest)) => (name, Some(rest.strip_suffix(']').ok_or(ParseError)?)),
+            None => (input, None),
+        };
+
+        let name = parse_name(name.trim())?;
+        let mut parsed = BTreeMap::new();
+
+        if let Some(properties) = properties.filter(|properties| !properties.trim().is_empty()) {
+            for property in properties.split(',') {
+                let (key, value) = property.split_once('=').ok_or(ParseError)?;
+                let (key, value) = (key.trim(), value.trim());
+
+                if key.is_empty() || value.is_empty() {
+                    return Err(ParseError);
+                }
+
+                parsed.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        Ok(Self::from_parts(name, parsed))
+    }
+
+    /// Creates a `BlockState` from its name and properties.
+    pub(crate) fn from_parts(name: ResourceLocation, properties: BTreeMap<String, String>) -> Self {
+        let mut block_state = Self {
+            name,
+            properties: Arc::new(properties),
+            canonical: "".into(),
+        };
+
+        block_state.update_canonical();
+        block_state
+    }
+
+    /// Returns the canonical string of the `BlockState`, the same as its `Display` output.
+    ///
+    /// The string is precomputed on every mutation, so hashing and comparing palette entries in hot loops
+    /// doesn't walk the property map every time.
+    pub(crate) fn canonical(&self) -> &str {
+        &self.canonical
+    }
+
+    fn update_canonical(&mut self) {
+        let mut canonical = self.name.to_string();
+
+        if !self.properties.is_empty() {
+            canonical.push('[');
+
+            for (i, (key, value)) in self.properties.iter().enumerate() {
+                if i > 0 {
+                    canonical.push(',');
+                }
+
+                let _ = write!(canonical, "{}={}", key, value);
+            }
+
+            canonical.push(']');
+        }
+
+        self.canonical = canonical.into();
+    }
+}
+
+impl From<BlockStateData> for BlockState {
+    fn from(data: BlockStateData) -> Self {
+        Self::from_parts(data.name, data.properties)
+    }
+}
+
+impl FromStr for BlockState {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl PartialEq for BlockState {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.properties == other.properties
+    }
+}
+
+impl Eq for BlockState {}
+
+impl Hash for BlockState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical.hash(state);
+    }
+}
+
+impl Debug for BlockState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockState")
+            .field("name", &self.name)
+            .field("properties", &self.properties)
+            .finish()
+    }
+}
+
+impl Display for BlockState {
+    /// Formats the `BlockState` in the same way as Minecraft commands, e.g. `minecraft:piston[extended=false,facing=down]`.
+    ///
+    /// Properties are written in the order of their names, so the output is stable.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.canonical)
     }
 }
 
@@ -335,35 +565,78 @@ mod tests {
         assert_eq!(
             blockstate.name,
             ResourceLocation {
-                namespace: "minecraft".to_string(),
-                path: "piston".to_string(),
+                namespace: "minecraft".into(),
+                path: "piston".into(),
             }
         );
 
         assert_eq!(
-            blockstate.properties,
+            *blockstate.properties,
             [("facing".to_string(), "down".to_string())]
                 .iter()
                 .cloned()
-                .collect::<HashMap<String, String>>()
+                .collect::<BTreeMap<String, String>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "registry")]
+    fn builder_checked() {
+        let registry = Registry::builtin();
+
+        let piston = BlockStateBuilder::new("piston")
+            .properties([("facing", "down")])
+            .build_checked(registry)
+            .unwrap();
+        assert_eq!(piston.get_properties().get("facing").unwrap(), "down");
+
+        let error = BlockStateBuilder::new("piston")
+            .properties([("facin", "down")])
+            .build_checked(registry)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ValidationError::UnknownProperty {
+                block: ResourceLocation::from("piston"),
+                property: "facin".to_string(),
+            }
         );
+
+        let error = BlockStateBuilder::new("piston")
+            .properties([("facing", "sideways")])
+            .build_checked(registry)
+            .unwrap_err();
+        assert!(matches!(error, ValidationError::InvalidValue { .. }));
+
+        assert!(BlockStateBuilder::new("pistn")
+            .build_checked(registry)
+            .is_err());
+
+        for builder in [
+            BlockStateBuilder::new("blue_wool"),
+            BlockStateBuilder::new("short_grass"),
+            BlockStateBuilder::new("oak_fence").properties([("east", "true")]),
+            BlockStateBuilder::new("copper_bulb").properties([("lit", "true")]),
+        ] {
+            assert!(builder.build_checked(registry).is_ok());
+        }
     }
 
     #[test]
     fn blockstate() {
-        let blockstate = BlockState {
-            name: ResourceLocation {
-                namespace: "minecraft".to_string(),
-                path: "piston".to_string(),
+        let blockstate = BlockState::from_parts(
+            ResourceLocation {
+                namespace: "minecraft".into(),
+                path: "piston".into(),
             },
-            properties: [
+            [
                 ("facing".to_string(), "down".to_string()),
                 ("extended".to_string(), "true".to_string()),
             ]
             .iter()
             .cloned()
-            .collect::<HashMap<String, String>>(),
-        };
+            .collect::<BTreeMap<String, String>>(),
+        );
 
         // testing get_name()
         let get_name = blockstate.get_name();
@@ -371,8 +644,8 @@ mod tests {
         assert_eq!(
             get_name,
             &ResourceLocation {
-                namespace: "minecraft".to_string(),
-                path: "piston".to_string(),
+                namespace: "minecraft".into(),
+                path: "piston".into(),
             }
         );
 
@@ -387,7 +660,7 @@ mod tests {
             ]
             .iter()
             .cloned()
-            .collect::<HashMap<String, String>>()
+            .collect::<BTreeMap<String, String>>()
         );
 
         // testing set_name()
@@ -397,8 +670,8 @@ mod tests {
         assert_eq!(
             blockstate.name,
             ResourceLocation {
-                namespace: "minecraft".to_string(),
-                path: "sticky_piston".to_string(),
+                namespace: "minecraft".into(),
+                path: "sticky_piston".into(),
             }
         );
 
@@ -406,11 +679,11 @@ mod tests {
         blockstate.set_properties([("facing", "up")]);
 
         assert_eq!(
-            blockstate.properties,
+            *blockstate.properties,
             [("facing".to_string(), "up".to_string())]
                 .iter()
                 .cloned()
-                .collect::<HashMap<String, String>>()
+                .collect::<BTreeMap<String, String>>()
         );
     }
 
@@ -423,14 +696,14 @@ mod tests {
         blockstate.add_properties([("extended", "true")]);
 
         assert_eq!(
-            blockstate.properties,
+            *blockstate.properties,
             [
                 ("facing".to_string(), "down".to_string()),
                 ("extended".to_string(), "true".to_string()),
             ]
             .iter()
             .cloned()
-            .collect::<HashMap<String, String>>()
+            .collect::<BTreeMap<String, String>>()
         );
     }
 
@@ -442,7 +715,7 @@ mod tests {
 
         blockstate.clear_properties();
 
-        assert_eq!(blockstate.properties, HashMap::<String, String>::new());
+        assert_eq!(*blockstate.properties, BTreeMap::<String, String>::new());
     }
 
     #[test]
@@ -454,12 +727,101 @@ mod tests {
         blockstate.remove_property("extended");
 
         assert_eq!(
-            blockstate.properties,
+            *blockstate.properties,
             [("facing".to_string(), "down".to_string())]
                 .iter()
                 .cloned()
-                .collect::<HashMap<String, String>>()
+                .collect::<BTreeMap<String, String>>()
+        );
+    }
+
+    #[test]
+    fn blockstate_is_air() {
+        for name in ["air", "cave_air", "minecraft:void_air"] {
+            assert!(BlockStateBuilder::new(name).build().is_air());
+        }
+
+        assert!(!BlockStateBuilder::new("stone").build().is_air());
+        assert!(!BlockStateBuilder::new("mymod:air").build().is_air());
+    }
+
+    #[test]
+    fn blockstate_display() {
+        let blockstate = BlockStateBuilder::new("piston")
+            .properties([("facing", "down"), ("extended", "false")])
+            .build();
+
+        assert_eq!(
+            blockstate.to_string(),
+            "minecraft:piston[extended=false,facing=down]"
+        );
+        assert_eq!(
+            BlockStateBuilder::new("stone").build().to_string(),
+            "minecraft:stone"
+        );
+    }
+
+    #[test]
+    fn blockstate_properties_sorted() {
+        let blockstate = BlockStateBuilder::new("redstone_wire")
+            .properties([("west", "side"), ("power", "0"), ("east", "up")])
+            .build();
+
+        assert_eq!(
+            blockstate.get_properties().keys().collect::<Vec<_>>(),
+            ["east", "power", "west"]
+        );
+    }
+
+    #[test]
+    fn blockstate_canonical_string() {
+        let mut blockstate = BlockStateBuilder::new("piston")
+            .properties([("facing", "down")])
+            .build();
+
+        assert_eq!(blockstate.to_string(), "minecraft:piston[facing=down]");
+
+        blockstate.add_properties([("extended", "true")]);
+        assert_eq!(
+            blockstate.to_string(),
+            "minecraft:piston[extended=true,facing=down]"
+        );
+
+        blockstate.set_name("sticky_piston");
+        blockstate.clear_properties();
+        assert_eq!(blockstate.to_string(), "minecraft:sticky_piston");
+
+        let mut counts = std::collections::HashMap::new();
+        *counts.entry(blockstate.clone()).or_insert(0) += 1;
+        *counts
+            .entry(BlockStateBuilder::new("sticky_piston").build())
+            .or_insert(0) += 1;
+
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&blockstate], 2);
+    }
+
+    #[test]
+    fn blockstate_parse() {
+        let blockstate = BlockState::parse("piston[ facing=down,extended=true ]").unwrap();
+
+        assert_eq!(
+            blockstate,
+            BlockStateBuilder::new("piston")
+                .properties([("facing", "down"), ("extended", "true")])
+                .build()
+        );
+        assert_eq!(
+            BlockState::parse(blockstate.to_string()).unwrap(),
+            blockstate
+        );
+        assert_eq!(
+            "mod:machine[]".parse::<BlockState>().unwrap().to_string(),
+            "mod:machine"
         );
+        assert!(BlockState::parse("piston[facing=down").is_err());
+        assert!(BlockState::parse("piston[=down]").is_err());
+        assert!(BlockState::parse("piston!").is_err());
     }
 
     #[test]
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::error::Result;
+use crate::structure::{BlockState, LitematicaFile};
+
+/// A rough guess of the heap memory used by a single entity, tile entity or pending tick.
+const ESTIMATED_NBT_SIZE: usize = 256;
+
+/// A thread-safe least recently used cache of `Litematica` files, e.g. for bots that repeatedly answer queries
+/// about the same popular schematics.
+///
+/// Files are keyed by their path and modification time, so a file that changed on disk is read again.
+/// The memory usage of the cached files is estimated from their block data, palettes and NBT values,
+/// and the least recently used files are evicted when the budget is exceeded.
+///
+/// # Examples
+/// ```
+/// use ritematica::cache::SchematicCache;
+///
+/// let cache = SchematicCache::new(256 * 1024 * 1024);
+///
+/// let file = cache.get("test.litematic").unwrap();
+/// let again = cache.get("test.litematic").unwrap(); // not read again
+/// ```
+#[derive(Debug)]
+pub struct SchematicCache {
+    budget: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<PathBuf, Entry>,
+    memory_usage: usize,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    modified: SystemTime,
+    file: Arc<LitematicaFile>,
+    size: usize,
+    last_used: u64,
+}
+
+impl SchematicCache {
+    /// Creates an empty cache that holds at most `budget` bytes of files, as estimated by the cache.
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the file at `path`, reading it only if it is not cached or was modified since it was cached.
+    ///
+    /// A file that is larger than the whole budget is returned, but not cached.
+    /// The file is read without blocking other threads, so two threads asking for the same uncached file may both read it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the modification time of the file cannot be read or the file cannot be read or deserialized.
+    pub fn get(&self, path: impl AsRef<Path>) -> Result<Arc<LitematicaFile>> {
+        let path = path.as_ref();
+        let modified = path.metadata()?.modified()?;
+
+        {
+            let mut state = self.lock();
+            let tick = state.tick();
+
+            if let Some(entry) = state.entries.get_mut(path) {
+                if entry.modified == modified {
+                    entry.last_used = tick;
+                    return Ok(Arc::clone(&entry.file));
+                }
+            }
+        }
+
+        let file = Arc::new(LitematicaFile::read(path)?);
+        let size = estimated_size(&file);
+
+        let mut state = self.lock();
+        state.remove(path);
+
+        if size <= self.budget {
+            while state.memory_usage + size > self.budget {
+                state.evict_least_recently_used();
+            }
+
+            let last_used = state.tick();
+            state.memory_usage += size;
+            state.entries.insert(
+                path.to_path_buf(),
+                Entry {
+                    modified,
+                    file: Arc::clone(&file),
+                    size,
+                    last_used,
+                },
+            );
+        }
+
+        Ok(file)
+    }
+
+    /// Removes the file at `path` from the cache, if it is cached.
+    pub fn invalidate(&self, path: impl AsRef<Path>) {
+        self.lock().remove(path.as_ref());
+    }
+
+    /// Removes all files from the cache.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+
+        state.entries.clear();
+        state.memory_usage = 0;
+    }
+
+    /// Returns the number of cached files.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Checks whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the estimated memory usage of all cached files in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.lock().memory_usage
+    }
+
+    /// Returns the memory budget in bytes.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        // the state is consistent after every statement, so a panic in another thread doesn't corrupt it
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.memory_usage -= entry.size;
+        }
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(path, _)| path.clone());
+
+        if let Some(path) = oldest {
+            self.remove(&path);
+        }
+    }
+}
+
+/// Estimates the heap memory used by a file.
+fn estimated_size(file: &LitematicaFile) -> usize {
+    let regions = file.regions.iter().map(|(name, region)| {
+        let palette = region
+            .block_state_palette
+            .iter()
+            .map(|block| mem::size_of::<BlockState>() + 2 * block.canonical().len())
+            .sum::<usize>();
+
+        let nbt = region.entities.len()
+            + region.tile_entities.len()
+            + region.pending_block_ticks.len()
+            + region.pending_fluid_ticks.len();
+
+        name.len()
+            + region.block_states.len() * mem::size_of::<i64>()
+            + palette
+            + nbt * ESTIMATED_NBT_SIZE
+    });
+
+    mem::size_of::<LitematicaFile>() + regions.sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caches_and_evicts() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let size = estimated_size(&file);
+
+        let copy = std::env::temp_dir().join("ritematica_cache_copy.litematic");
+        std::fs::copy("test.litematic", &copy).unwrap();
+
+        let cache = SchematicCache::new(size + size / 2);
+
+        let first = cache.get("test.litematic").unwrap();
+        let second = cache.get("test.litematic").unwrap();
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.memory_usage(), size);
+
+        // only one file fits, so the first one is evicted
+        cache.get(&copy).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let third = cache.get("test.litematic").unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+
+        cache.invalidate("test.litematic");
+        assert!(cache.is_empty());
+        assert_eq!(cache.memory_usage(), 0);
+
+        let tiny = SchematicCache::new(1);
+        tiny.get("test.litematic").unwrap();
+        assert!(tiny.is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+use std::io::{self, Write};
+
+use crate::block::BlockStateBuilder;
+use crate::region::{index_3d, read_palette_index};
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// The number of blocks along each axis of a chunk section.
+pub const SECTION_SIZE: i32 = 16;
+
+const SECTION_VOLUME: usize = (SECTION_SIZE * SECTION_SIZE * SECTION_SIZE) as usize;
+
+/// A 16x16x16 section of blocks in the layout that Minecraft uses for chunks in the network protocol and in region files.
+///
+/// Created by `Region::to_chunk_sections()`. Blocks are indexed in YZX order and packed into `data` with
+/// `bits_per_entry` bits each, where entries never span two longs. A section with a single palette entry uses
+/// 0 bits and has no data, otherwise at least 4 bits are used, like in the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkSection {
+    /// The section coordinates, i.e. the block coordinates of the section's lowest corner divided by 16.
+    pub position: Coordinates,
+
+    /// The number of blocks in the section that are not air.
+    pub block_count: i16,
+
+    /// The block states used in the section.
+    pub palette: Vec<BlockState>,
+
+    pub bits_per_entry: u8,
+
+    /// The packed palette indices of all 4096 blocks.
+    pub data: Vec<i64>,
+}
+
+impl ChunkSection {
+    /// Returns the `BlockState` at the given position inside of the section.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a component of `position` is not in `0..16`.
+    pub fn get_block(&self, position: impl Into<Coordinates>) -> &BlockState {
+        let size = Coordinates {
+            x: SECTION_SIZE,
+            y: SECTION_SIZE,
+            z: SECTION_SIZE,
+        };
+
+        let index = index_3d(size, position.into()) as usize;
+
+        &self.palette[self.palette_index(index)]
+    }
+
+    /// Writes the block count and the block states container of the section in the format of the chunk data packet
+    /// (1.18 to 1.21.4), where every number is big-endian and lengths are prefixed as VarInts.
+    ///
+    /// The biome container is not written. Sections with more than 256 palette entries are written with direct
+    /// palettes, as the game expects.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer the section is written to.
+    /// * `global_bits` - The number of bits used for direct palettes, i.e. `ceil(log2(block state count))` of the game version.
+    /// * `global_id` - A closure returning the numeric id of a block state in the game version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_network(
+        &self,
+        writer: &mut impl Write,
+        global_bits: u8,
+        mut global_id: impl FnMut(&BlockState) -> u32,
+    ) -> io::Result<()> {
+        writer.write_all(&self.block_count.to_be_bytes())?;
+
+        let ids = self.palette.iter().map(&mut global_id).collect::<Vec<_>>();
+
+        let (bits, data) = if self.bits_per_entry > 8 {
+            let indices = (0..SECTION_VOLUME).map(|index| ids[self.palette_index(index)]);
+
+            (global_bits, pack(indices, global_bits))
+        } else {
+            (self.bits_per_entry, self.data.clone())
+        };
+
+        writer.write_all(&[bits])?;
+
+        if bits == 0 {
+            write_varint(writer, ids[0])?;
+        } else if bits <= 8 {
+            write_varint(writer, ids.len() as u32)?;
+
+            for id in &ids {
+                write_varint(writer, *id)?;
+            }
+        }
+
+        write_varint(writer, data.len() as u32)?;
+
+        for word in data {
+            writer.write_all(&word.to_be_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn palette_index(&self, index: usize) -> usize {
+        if self.bits_per_entry == 0 {
+            return 0;
+        }
+
+        let bits = self.bits_per_entry as usize;
+        let per_long = 64 / bits;
+        let word = self.data[index / per_long] as u64;
+
+        ((word >> ((index % per_long) * bits)) & ((1 << bits) - 1)) as usize
+    }
+}
+
+impl Region {
+    /// Splits the region into the chunk sections it overlaps, assuming the schematic origin is at `(0, 0, 0)`.
+    ///
+    /// See `to_chunk_sections_at()` for details.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for section in region.to_chunk_sections() {
+    ///     println!("{:?}: {} blocks", section.position, section.block_count);
+    /// }
+    /// ```
+    pub fn to_chunk_sections(&self) -> Vec<ChunkSection> {
+        self.to_chunk_sections_at(Coordinates { x: 0, y: 0, z: 0 })
+    }
+
+    /// Splits the region into the chunk sections it overlaps when the schematic origin is placed at `origin`,
+    /// e.g. to stream a preview of the schematic into a client as fake chunks.
+    ///
+    /// Parts of the sections outside of the region are filled with air, and sections that only contain air are skipped.
+    /// The sections are sorted by position in YZX order.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The world position of the schematic origin, which determines how the region is aligned to the sections.
+    pub fn to_chunk_sections_at(&self, origin: impl Into<Coordinates>) -> Vec<ChunkSection> {
+        let origin = origin.into();
+        let min_corner = self.min_corner();
+
+        let min = Coordinates {
+            x: origin.x + min_corner.x,
+            y: origin.y + min_corner.y,
+            z: origin.z + min_corner.z,
+        };
+        let size = Coordinates {
+            x: self.size.x.abs(),
+            y: self.size.y.abs(),
+            z: self.size.z.abs(),
+        };
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let air = BlockStateBuilder::new("air").build();
+        let section_range = |min: i32, size: i32| {
+            min.div_euclid(SECTION_SIZE)..=(min + size - 1).div_euclid(SECTION_SIZE)
+        };
+
+        let mut sections = Vec::new();
+
+        for section_y in section_range(min.y, size.y) {
+            for section_z in section_range(min.z, size.z) {
+                for section_x in section_range(min.x, size.x) {
+                    let mut palette: Vec<BlockState> = Vec::new();
+                    let mut remap = vec![None; self.block_state_palette.len()];
+                    let mut indices = Vec::with_capacity(SECTION_VOLUME);
+                    let mut block_count = 0;
+
+                    let mut palette_index =
+                        |block: &BlockState| match palette.iter().position(|b| b == block) {
+                            Some(index) => index as u32,
+                            None => {
+                                palette.push(block.clone());
+                                palette.len() as u32 - 1
+                            }
+                        };
+
+                    for y in 0..SECTION_SIZE {
+                        for z in 0..SECTION_SIZE {
+                            for x in 0..SECTION_SIZE {
+                                let position = Coordinates {
+                                    x: section_x * SECTION_SIZE + x - min.x,
+                                    y: section_y * SECTION_SIZE + y - min.y,
+                                    z: section_z * SECTION_SIZE + z - min.z,
+                                };
+
+                                if !self.contains(position) {
+                                    indices.push(palette_index(&air));
+                                    continue;
+                                }
+
+                                let region_index = read_palette_index(
+                                    &self.block_states,
+                                    index_3d(self.size, position),
+                                    required_bits,
+                                    bitmask,
+                                ) as usize;
+
+                                let block = &self.block_state_palette[region_index];
+
+                                if !block.is_air() {
+                                    block_count += 1;
+                                }
+
+                                let index = *remap[region_index]
+                                    .get_or_insert_with(|| palette_index(block));
+                                indices.push(index);
+                            }
+                        }
+                    }
+
+                    if block_count == 0 {
+                        continue;
+                    }
+
+                    let bits_per_entry = if palette.len() == 1 {
+                        0
+                    } else {
+                        (palette.len().next_power_of_two().trailing_zeros() as u8).max(4)
+                    };
+
+                    sections.push(ChunkSection {
+                        position: Coordinates {
+                            x: section_x,
+                            y: section_y,
+                            z: section_z,
+                        },
+                        block_count,
+                        palette,
+                        bits_per_entry,
+                        data: pack(indices.into_iter(), bits_per_entry),
+                    });
+                }
+            }
+        }
+
+        sections
+    }
+}
+
+/// Packs `values` with `bits` bits each into longs, without spanning entries across two longs.
+fn pack(values: impl Iterator<Item = u32>, bits: u8) -> Vec<i64> {
+    if bits == 0 {
+        return Vec::new();
+    }
+
+    let bits = bits as usize;
+    let per_long = 64 / bits;
+    let mut data = vec![0u64; SECTION_VOLUME.div_ceil(per_long)];
+
+    for (index, value) in values.enumerate() {
+        data[index / per_long] |= (value as u64) << ((index % per_long) * bits);
+    }
+
+    data.into_iter().map(|word| word as i64).collect()
+}
+
+fn write_varint(writer: &mut impl Write, mut value: u32) -> io::Result<()> {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value != 0 {
+            byte |= 0x80;
+        }
+
+        writer.write_all(&[byte])?;
+
+        if value == 0 {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn sections_match_region() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap(); // region size: 31x9x29
+
+        let origin = Coordinates { x: 5, y: -3, z: 0 };
+        let sections = region.to_chunk_sections_at(origin);
+        let min_corner = region.min_corner();
+
+        assert!(!sections.is_empty());
+
+        for section in &sections {
+            assert!(section.bits_per_entry == 0 || section.bits_per_entry >= 4);
+
+            for y in 0..SECTION_SIZE {
+                for z in 0..SECTION_SIZE {
+                    for x in 0..SECTION_SIZE {
+                        let position = Coordinates {
+                            x: section.position.x * SECTION_SIZE + x - origin.x - min_corner.x,
+                            y: section.position.y * SECTION_SIZE + y - origin.y - min_corner.y,
+                            z: section.position.z * SECTION_SIZE + z - origin.z - min_corner.z,
+                        };
+
+                        let expected = region
+                            .get(position)
+                            .map_or("minecraft:air".to_string(), BlockState::to_string);
+
+                        assert_eq!(section.get_block((x, y, z)).to_string(), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn write_single_valued_section() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates {
+                x: 16,
+                y: 16,
+                z: 16,
+            },
+        );
+
+        let stone = BlockStateBuilder::new("stone").build();
+        for (position, _) in region.clone().iter_blocks() {
+            region.set_block(position, stone.clone());
+        }
+
+        let sections = region.to_chunk_sections();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].block_count, 4096);
+        assert_eq!(sections[0].bits_per_entry, 0);
+
+        let mut bytes = Vec::new();
+        sections[0].write_network(&mut bytes, 15, |_| 1).unwrap();
+
+        // block count, bits per entry, palette id, data length
+        assert_eq!(bytes, [0x10, 0x00, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,269 @@
+use nbt::Value;
+
+use crate::region::{value_get, value_position};
+use crate::structure::{Coordinates, Region};
+
+/// How a command found by `Region::command_blocks()` is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandMode {
+    /// A `minecraft:command_block`, which runs once when powered.
+    Impulse,
+
+    /// A `minecraft:chain_command_block`, which runs after the command block pointing into it.
+    Chain,
+
+    /// A `minecraft:repeating_command_block`, which runs every tick while powered.
+    Repeating,
+
+    /// A sign with a `run_command` click event, which runs when a player clicks it.
+    Sign,
+}
+
+/// A command stored in a command block or a sign, created by `Region::command_blocks()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// The position of the block relative to the region origin.
+    pub position: Coordinates,
+
+    /// The command, without any leading `/`.
+    pub command: String,
+
+    pub mode: CommandMode,
+
+    /// Whether the command block only runs if the command block behind it succeeded. Always `false` for signs.
+    pub conditional: bool,
+
+    /// Whether the command block runs without redstone power. Always `false` for signs.
+    pub always_active: bool,
+}
+
+impl Region {
+    /// Returns all commands that can be run by the blocks of the region, so that they can be reviewed before pasting.
+    ///
+    /// This includes the commands of impulse, chain and repeating command blocks, and the `run_command` click events
+    /// on both sides of signs, in the text format of old and new versions. Empty command blocks are skipped.
+    /// The commands are sorted by position in YZX order.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for command_block in region.command_blocks() {
+    ///     println!("{:?} {:?}: {}", command_block.position, command_block.mode, command_block.command);
+    /// }
+    /// ```
+    pub fn command_blocks(&self) -> Vec<CommandBlock> {
+        let mut commands = Vec::new();
+
+        for tile_entity in &self.tile_entities {
+            let Some(position) = value_position(tile_entity) else {
+                continue;
+            };
+
+            if !self.contains(position) {
+                continue;
+            }
+
+            let block = self.get_block(position);
+
+            let mode = match block.get_name().get_path() {
+                "command_block" => CommandMode::Impulse,
+                "chain_command_block" => CommandMode::Chain,
+                "repeating_command_block" => CommandMode::Repeating,
+                _ => {
+                    commands.extend(sign_commands(tile_entity).into_iter().map(|command| {
+                        CommandBlock {
+                            position,
+                            command,
+                            mode: CommandMode::Sign,
+                            conditional: false,
+                            always_active: false,
+                        }
+                    }));
+
+                    continue;
+                }
+            };
+
+            let Some(Value::String(command)) = value_get(tile_entity, "Command") else {
+                continue;
+            };
+
+            if command.trim().is_empty() {
+                continue;
+            }
+
+            commands.push(CommandBlock {
+                position,
+                command: command.trim_start_matches('/').to_string(),
+                mode,
+                conditional: block
+                    .get_properties()
+                    .get("conditional")
+                    .map(String::as_str)
+                    == Some("true"),
+                always_active: matches!(value_get(tile_entity, "auto"), Some(Value::Byte(1))),
+            });
+        }
+
+        commands
+            .sort_by_key(|command| (command.position.y, command.position.z, command.position.x));
+
+        commands
+    }
+}
+
+/// Returns the commands of all `run_command` click events in the text of a sign.
+fn sign_commands(tile_entity: &Value) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    // signs before 1.20 store one JSON text per line
+    for key in ["Text1", "Text2", "Text3", "Text4"] {
+        if let Some(text) = value_get(tile_entity, key) {
+            messages.push(text);
+        }
+    }
+
+    for side in ["front_text", "back_text"] {
+        if let Some(Value::List(lines)) =
+            value_get(tile_entity, side).and_then(|text| value_get(text, "messages"))
+        {
+            messages.extend(lines);
+        }
+    }
+
+    let mut commands = Vec::new();
+
+    for message in messages {
+        // texts are stored as JSON strings in older versions and as NBT in newer ones
+        let text = match message {
+            Value::String(json) => serde_json::from_str(json).ok(),
+            other => serde_json::to_value(other).ok(),
+        };
+
+        if let Some(text) = text {
+            find_click_commands(&text, &mut commands);
+        }
+    }
+
+    commands
+}
+
+fn find_click_commands(text: &serde_json::Value, commands: &mut Vec<String>) {
+    match text {
+        serde_json::Value::Object(object) => {
+            for key in ["clickEvent", "click_event"] {
+                let Some(event) = object.get(key) else {
+                    continue;
+                };
+
+                if event.get("action").and_then(|action| action.as_str()) != Some("run_command") {
+                    continue;
+                }
+
+                let command = event
+                    .get("value")
+                    .or_else(|| event.get("command"))
+                    .and_then(|command| command.as_str());
+
+                if let Some(command) = command {
+                    commands.push(command.trim_start_matches('/').to_string());
+                }
+            }
+
+            for value in object.values() {
+                find_click_commands(value, commands);
+            }
+        }
+        serde_json::Value::Array(array) => {
+            for value in array {
+                find_click_commands(value, commands);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    fn tile_entity(position: (i32, i32, i32), entries: Vec<(&str, Value)>) -> Value {
+        let mut compound = entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<HashMap<_, _>>();
+
+        compound.insert("x".to_string(), Value::Int(position.0));
+        compound.insert("y".to_string(), Value::Int(position.1));
+        compound.insert("z".to_string(), Value::Int(position.2));
+
+        Value::Compound(compound)
+    }
+
+    #[test]
+    fn command_blocks_and_signs() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 3, y: 1, z: 1 },
+        );
+
+        region.set_block(
+            (0, 0, 0),
+            BlockStateBuilder::new("chain_command_block")
+                .properties([("conditional", "true"), ("facing", "east")])
+                .build(),
+        );
+        region.set_block((1, 0, 0), BlockStateBuilder::new("oak_sign").build());
+        region.set_block((2, 0, 0), BlockStateBuilder::new("command_block").build());
+
+        let messages = Value::List(vec![
+            Value::String(
+                r#"{"text":"Click me","clickEvent":{"action":"run_command","value":"/op @p"}}"#
+                    .to_string(),
+            ),
+            Value::String(r#"{"text":""}"#.to_string()),
+        ]);
+
+        region.tile_entities = vec![
+            tile_entity((2, 0, 0), vec![("Command", Value::String(String::new()))]),
+            tile_entity(
+                (1, 0, 0),
+                vec![(
+                    "front_text",
+                    Value::Compound([("messages".to_string(), messages)].into_iter().collect()),
+                )],
+            ),
+            tile_entity(
+                (0, 0, 0),
+                vec![
+                    ("Command", Value::String("say hi".to_string())),
+                    ("auto", Value::Byte(1)),
+                ],
+            ),
+        ];
+
+        let commands = region.command_blocks();
+
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[0],
+            CommandBlock {
+                position: Coordinates { x: 0, y: 0, z: 0 },
+                command: "say hi".to_string(),
+                mode: CommandMode::Chain,
+                conditional: true,
+                always_active: true,
+            }
+        );
+        assert_eq!(commands[1].mode, CommandMode::Sign);
+        assert_eq!(commands[1].command, "op @p");
+    }
+}
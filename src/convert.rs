@@ -0,0 +1,237 @@
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+#[cfg(feature = "fs")]
+use crate::stream;
+use crate::stream::Header;
+use crate::structure::Region;
+#[cfg(feature = "fs")]
+use crate::{sponge, trace};
+
+/// A target format that regions are converted into, one region at a time.
+pub trait RegionSink {
+    /// Writes a single region of the converted file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the region in the source file.
+    /// * `region` - The region data. It is dropped after this call returns.
+    /// * `header` - The top-level values of the source file.
+    fn write_region(&mut self, name: &str, region: &Region, header: &Header) -> Result<()>;
+}
+
+/// A `RegionSink` that writes every region into its own Sponge `.schem` (version 2) file inside of a directory.
+///
+/// The files are named `<stem>_<region name>.schem`, where characters that are not allowed in file names are replaced by `_`.
+#[cfg(feature = "fs")]
+#[derive(Debug)]
+pub struct SchemDirectory {
+    dir: PathBuf,
+    stem: String,
+    written: Vec<PathBuf>,
+}
+
+#[cfg(feature = "fs")]
+impl SchemDirectory {
+    /// Creates a new `SchemDirectory` that writes into `dir`. The directory must already exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory the `.schem` files are written to.
+    /// * `stem` - The prefix of every file name, usually the name of the source file.
+    pub fn new(dir: impl Into<PathBuf>, stem: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            stem: stem.into(),
+            written: Vec::new(),
+        }
+    }
+
+    /// Returns the paths of all files written so far.
+    pub fn written(&self) -> &[PathBuf] {
+        &self.written
+    }
+}
+
+#[cfg(feature = "fs")]
+impl RegionSink for SchemDirectory {
+    fn write_region(&mut self, name: &str, region: &Region, header: &Header) -> Result<()> {
+        let data_version = header.minecraft_data_version.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Missing MinecraftDataVersion",
+            )
+        })?;
+
+        let file_name = format!("{}_{}.schem", self.stem, sanitize_file_name(name));
+        let path = self.dir.join(file_name);
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        sponge::write_sponge(&mut writer, region, 2, data_version)?;
+        writer.flush()?;
+
+        self.written.push(path);
+
+        Ok(())
+    }
+}
+
+/// Converts a `Litematica` file region by region, passing every region to `sink`.
+///
+/// Only a single region is held in memory at any time, which makes this suitable for converting arbitrarily large files.
+/// The file is read twice: once for the top-level values and once for the regions.
+///
+/// # Arguments
+///
+/// * `path` - The path to the `.litematic` file.
+/// * `sink` - The `RegionSink` that receives the regions.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or if the sink returns an error.
+///
+/// # Examples
+/// ```
+/// use ritematica::convert::{self, SchemDirectory};
+///
+/// let mut sink = SchemDirectory::new("out", "test");
+/// convert::convert_file("test.litematic", &mut sink).unwrap();
+///
+/// assert_eq!(sink.written().len(), 1);
+/// ```
+#[cfg(feature = "fs")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(path = %path.as_ref().display(), regions, volume, elapsed_ms))
+)]
+pub fn convert_file(path: impl AsRef<Path>, sink: &mut impl RegionSink) -> Result<Header> {
+    let _timer = trace::Timer::start();
+
+    let path = path.as_ref();
+
+    let header = stream::read_header(File::open(path)?)?;
+
+    let mut regions = 0;
+    let mut volume = 0;
+
+    stream::for_each_region(File::open(path)?, |name, region| {
+        regions += 1;
+        volume += region.calc_volume();
+
+        sink.write_region(&name, &region, &header)
+    })?;
+
+    trace::record("regions", regions);
+    trace::record("volume", volume);
+
+    Ok(header)
+}
+
+/// Converts a `Litematica` file into one Sponge `.schem` file per region, with bounded memory usage.
+///
+/// # Arguments
+///
+/// * `path` - The path to the `.litematic` file.
+/// * `output_dir` - The existing directory the `.schem` files are written to.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the output cannot be written.
+///
+/// # Examples
+/// ```
+/// use ritematica::convert;
+///
+/// let written = convert::litematic_to_schem("test.litematic", "out").unwrap();
+/// ```
+#[cfg(feature = "fs")]
+pub fn litematic_to_schem(
+    path: impl AsRef<Path>,
+    output_dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut sink = SchemDirectory::new(output_dir.as_ref(), stem);
+    convert_file(path, &mut sink)?;
+
+    Ok(sink.written)
+}
+
+#[cfg(feature = "fs")]
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct ReadSpongeV2 {
+        version: i32,
+        data_version: i32,
+        width: i16,
+        height: i16,
+        length: i16,
+        palette: HashMap<String, i32>,
+        block_data: Vec<i8>,
+    }
+
+    #[test]
+    fn litematic_to_schem() {
+        let dir = std::env::temp_dir().join("ritematica_litematic_to_schem");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let written = super::litematic_to_schem("test.litematic", &dir).unwrap();
+        assert_eq!(written, vec![dir.join("test_test.schem")]);
+
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let schem: ReadSpongeV2 = nbt::from_gzip_reader(File::open(&written[0]).unwrap()).unwrap();
+
+        assert_eq!(schem.version, 2);
+        assert_eq!(schem.data_version, file.minecraft_data_version);
+        assert_eq!(schem.width as i32, region.size.x.abs());
+        assert_eq!(schem.height as i32, region.size.y.abs());
+        assert_eq!(schem.length as i32, region.size.z.abs());
+
+        // every palette index of the test file fits into a single varint byte
+        assert_eq!(schem.block_data.len() as u64, region.calc_volume());
+
+        let index = region.get_3d_index((2, 4, 2)) as usize;
+        let id = schem.block_data[index] as i32;
+        let name = schem
+            .palette
+            .iter()
+            .find(|(_, palette_id)| **palette_id == id)
+            .map(|(name, _)| name.clone())
+            .unwrap();
+
+        assert_eq!(name, region.get_block((2, 4, 2)).to_string());
+    }
+}
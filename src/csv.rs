@@ -0,0 +1,253 @@
+use std::io::{self, BufRead, Write};
+
+use crate::error::Result;
+use crate::structure::{BlockState, Coordinates, Region};
+
+const HEADER: [&str; 5] = ["x", "y", "z", "name", "properties"];
+
+impl Region {
+    /// Writes every block of the region that is not air as a CSV row with the columns `x,y,z,name,properties`,
+    /// e.g. for spreadsheets or quick scripts.
+    ///
+    /// Positions are relative to `min_corner()` and rows are written in YZX order after a header row.
+    /// The properties are written like in commands without the brackets, e.g. `extended=false,facing=down`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// region.to_csv(File::create("test.csv").unwrap()).unwrap();
+    /// ```
+    pub fn to_csv(&self, writer: impl Write) -> Result<()> {
+        self.write_table(writer, ',')
+    }
+
+    /// Writes the blocks of the region like `to_csv()`, but separated by tabs.
+    pub fn to_tsv(&self, writer: impl Write) -> Result<()> {
+        self.write_table(writer, '\t')
+    }
+
+    /// Creates a region of the given `size` at the schematic origin from CSV rows written by `to_csv()`.
+    ///
+    /// All positions without a row are air. The header row and the `properties` column are optional,
+    /// and empty lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, a row is malformed or a position is outside of `size`.
+    /// The message contains the line number of the invalid row.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::Region;
+    ///
+    /// let csv = "x,y,z,name,properties\n0,0,0,minecraft:piston,facing=up\n";
+    /// let region = Region::from_csv(csv.as_bytes(), (1, 2, 1)).unwrap();
+    ///
+    /// assert_eq!(region.get_block((0, 0, 0)).to_string(), "minecraft:piston[facing=up]");
+    /// ```
+    pub fn from_csv(reader: impl BufRead, size: impl Into<Coordinates>) -> Result<Region> {
+        Self::read_table(reader, size.into(), ',')
+    }
+
+    /// Creates a region from tab separated rows like `from_csv()`.
+    pub fn from_tsv(reader: impl BufRead, size: impl Into<Coordinates>) -> Result<Region> {
+        Self::read_table(reader, size.into(), '\t')
+    }
+
+    fn write_table(&self, mut writer: impl Write, separator: char) -> Result<()> {
+        writeln!(writer, "{}", HEADER.join(&separator.to_string()))?;
+
+        for (position, block) in self.iter_blocks() {
+            if block.is_air() {
+                continue;
+            }
+
+            let properties = block
+                .get_properties()
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let fields = [
+                position.x.to_string(),
+                position.y.to_string(),
+                position.z.to_string(),
+                block.get_name().to_string(),
+                properties,
+            ];
+
+            let row = fields
+                .iter()
+                .map(|field| escape_field(field, separator))
+                .collect::<Vec<_>>()
+                .join(&separator.to_string());
+
+            writeln!(writer, "{}", row)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_table(reader: impl BufRead, size: Coordinates, separator: char) -> Result<Region> {
+        let mut region = Region::filled_with_air(Coordinates { x: 0, y: 0, z: 0 }, size);
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = split_record(&line, separator);
+
+            if number == 0 && fields.iter().map(String::as_str).eq(HEADER) {
+                continue;
+            }
+
+            let (position, block) = parse_row(&fields, size).map_err(|message| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", number + 1, message),
+                )
+            })?;
+
+            region.set_block(position, block);
+        }
+
+        Ok(region)
+    }
+}
+
+fn parse_row(
+    fields: &[String],
+    size: Coordinates,
+) -> std::result::Result<(Coordinates, BlockState), String> {
+    if !(4..=5).contains(&fields.len()) {
+        return Err(format!("expected 4 or 5 columns, found {}", fields.len()));
+    }
+
+    let coordinate = |index: usize| {
+        fields[index]
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| format!("invalid coordinate {}", fields[index]))
+    };
+
+    let position = Coordinates {
+        x: coordinate(0)?,
+        y: coordinate(1)?,
+        z: coordinate(2)?,
+    };
+
+    let inside = (0..size.x).contains(&position.x)
+        && (0..size.y).contains(&position.y)
+        && (0..size.z).contains(&position.z);
+
+    if !inside {
+        return Err(format!("position {:?} is outside of the region", position));
+    }
+
+    let properties = fields.get(4).map_or("", |properties| properties.trim());
+    let block = format!("{}[{}]", fields[3].trim(), properties);
+
+    let block = BlockState::parse(&block).map_err(|_| format!("invalid block {}", block))?;
+
+    Ok((position, block))
+}
+
+/// Quotes a CSV field if it contains the separator, a quote or a line break.
+pub(crate) fn escape_field(field: &str, separator: char) -> String {
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits a single line into its fields, removing the quotes added by `escape_field()`.
+fn split_record(line: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn csv_round_trip() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let mut csv = Vec::new();
+        region.to_csv(&mut csv).unwrap();
+
+        let text = String::from_utf8(csv.clone()).unwrap();
+        assert!(text.starts_with("x,y,z,name,properties\n"));
+        assert!(text.contains("\"powered=true,shape=north_south,waterlogged=false\""));
+
+        let size = Coordinates {
+            x: region.size.x.abs(),
+            y: region.size.y.abs(),
+            z: region.size.z.abs(),
+        };
+        let parsed = Region::from_csv(&csv[..], size).unwrap();
+
+        assert!(region
+            .iter_blocks()
+            .all(|(position, block)| parsed.get_block(position) == block));
+    }
+
+    #[test]
+    fn tsv_round_trip() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let mut tsv = Vec::new();
+        region.to_tsv(&mut tsv).unwrap();
+
+        assert!(!String::from_utf8(tsv.clone()).unwrap().contains('"'));
+
+        let parsed = Region::from_tsv(&tsv[..], (31, 9, 29)).unwrap();
+        assert_eq!(parsed.get_block((2, 4, 2)), region.get_block((2, 4, 2)));
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let error = Region::from_csv("0,0,0,stone\n5,0,0,stone\n".as_bytes(), (2, 2, 2))
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("line 2"));
+        assert!(Region::from_csv("0,0,stone".as_bytes(), (2, 2, 2)).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::block::BlockStateBuilder;
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// The marker blocks that `Region::diff_overlay_with_options()` places at positions where two regions differ.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Marks positions where the expected region has a block, but the other region has air. Defaults to red concrete.
+    pub missing: BlockState,
+
+    /// Marks positions where the expected region has air, but the other region has a block. Defaults to lime concrete.
+    pub extra: BlockState,
+
+    /// Marks positions where both regions have different blocks. Defaults to yellow concrete.
+    pub wrong: BlockState,
+
+    /// Compares only the block names, so that e.g. a lever that is switched on doesn't count as wrong.
+    pub ignore_properties: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            missing: BlockStateBuilder::new("red_concrete").build(),
+            extra: BlockStateBuilder::new("lime_concrete").build(),
+            wrong: BlockStateBuilder::new("yellow_concrete").build(),
+            ignore_properties: false,
+        }
+    }
+}
+
+impl Region {
+    /// Creates a region that marks every position in which `other` differs from this region with a marker block,
+    /// and is air everywhere else, see `DiffOptions` for the default markers.
+    ///
+    /// Pasting the result in-game over a build shows exactly which blocks are missing, extra or wrong.
+    /// The regions are compared over the union of their bounding boxes, aligned at their corners with the lowest
+    /// coordinates like in `similarity()`, and positions outside of one of the regions count as air.
+    /// The result is placed at the lowest corner of this region and has no entities or tile entities.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let plan = LitematicaFile::read("plan.litematic").unwrap();
+    /// let mut build = LitematicaFile::read("build.litematic").unwrap();
+    ///
+    /// let diff = plan.get_region("farm").unwrap().diff_overlay(build.get_region("farm").unwrap());
+    /// build.get_regions_mut().insert("diff".to_string(), diff);
+    /// ```
+    pub fn diff_overlay(&self, other: &Region) -> Region {
+        self.diff_overlay_with_options(other, &DiffOptions::default())
+    }
+
+    /// Creates a region that marks the differences between the two regions with the marker blocks of the given
+    /// `DiffOptions`, see `diff_overlay()`.
+    pub fn diff_overlay_with_options(&self, other: &Region, options: &DiffOptions) -> Region {
+        let size = Coordinates {
+            x: self.size.x.abs().max(other.size.x.abs()),
+            y: self.size.y.abs().max(other.size.y.abs()),
+            z: self.size.z.abs().max(other.size.z.abs()),
+        };
+
+        let mut overlay = Region::filled_with_air(self.min_corner(), size);
+
+        for y in 0..size.y {
+            for z in 0..size.z {
+                for x in 0..size.x {
+                    let position = Coordinates { x, y, z };
+
+                    let expected = self.get(position).filter(|block| !block.is_air());
+                    let actual = other.get(position).filter(|block| !block.is_air());
+
+                    let marker = match (expected, actual) {
+                        (None, None) => continue,
+                        (Some(_), None) => &options.missing,
+                        (None, Some(_)) => &options.extra,
+                        (Some(expected), Some(actual)) if options.ignore_properties => {
+                            if expected.get_name() == actual.get_name() {
+                                continue;
+                            }
+
+                            &options.wrong
+                        }
+                        (Some(expected), Some(actual)) => {
+                            if expected == actual {
+                                continue;
+                            }
+
+                            &options.wrong
+                        }
+                    };
+
+                    overlay.set_block(position, marker.clone());
+                }
+            }
+        }
+
+        overlay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_differences() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let dirt = BlockStateBuilder::new("dirt").build();
+        let lever = BlockStateBuilder::new("lever")
+            .properties([("powered", "false")])
+            .build();
+        let powered_lever = BlockStateBuilder::new("lever")
+            .properties([("powered", "true")])
+            .build();
+
+        let mut plan = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 5, y: 1, z: 1 },
+        );
+        let mut build = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 6, y: 1, z: 1 },
+        );
+
+        plan.set_block((0, 0, 0), stone.clone());
+        build.set_block((0, 0, 0), stone.clone());
+        plan.set_block((1, 0, 0), stone.clone());
+        build.set_block((2, 0, 0), dirt.clone());
+        plan.set_block((3, 0, 0), stone);
+        build.set_block((3, 0, 0), dirt.clone());
+        plan.set_block((4, 0, 0), lever);
+        build.set_block((4, 0, 0), powered_lever);
+        build.set_block((5, 0, 0), dirt);
+
+        let options = DiffOptions::default();
+        let overlay = plan.diff_overlay(&build);
+
+        assert_eq!(overlay.size, Coordinates { x: 6, y: 1, z: 1 });
+        assert_eq!(overlay.get_block((0, 0, 0)).to_string(), "minecraft:air");
+        assert_eq!(overlay.get_block((1, 0, 0)), &options.missing);
+        assert_eq!(overlay.get_block((2, 0, 0)), &options.extra);
+        assert_eq!(overlay.get_block((3, 0, 0)), &options.wrong);
+        assert_eq!(overlay.get_block((4, 0, 0)), &options.wrong);
+        assert_eq!(overlay.get_block((5, 0, 0)), &options.extra);
+
+        let options = DiffOptions {
+            ignore_properties: true,
+            ..Default::default()
+        };
+        let overlay = plan.diff_overlay_with_options(&build, &options);
+
+        assert_eq!(overlay.get_block((4, 0, 0)).to_string(), "minecraft:air");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::region::read_palette_index;
+use crate::structure::Region;
+
+/// Statistics about how well the blocks of a `Region` compress, created by `Region::entropy_report()`.
+///
+/// The block data of a region is stored with a fixed number of bits per block that depends on the size of the palette,
+/// and gzip then shrinks long runs of the same block. Regions with many distinct blocks in short runs therefore dominate
+/// the size of a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntropyReport {
+    /// The number of blocks in the region.
+    pub volume: u64,
+
+    /// The number of entries in the palette, including unused ones.
+    pub palette_size: usize,
+
+    /// The number of block states that are actually used.
+    pub distinct_states: usize,
+
+    /// The number of bits stored per block with the current palette.
+    pub bits_per_block: u64,
+
+    /// The number of bits per block if the palette only contained the used block states.
+    pub minimal_bits_per_block: u64,
+
+    /// The number of runs of equal blocks in YZX order.
+    pub runs: u64,
+
+    /// The length of the longest run of equal blocks.
+    pub longest_run: u64,
+
+    /// The Shannon entropy of the block distribution in bits per block, a lower bound for any encoding that looks at
+    /// blocks one at a time.
+    pub entropy: f64,
+}
+
+impl EntropyReport {
+    /// Returns the average length of a run of equal blocks.
+    pub fn average_run_length(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.volume as f64 / self.runs as f64
+        }
+    }
+
+    /// Returns the size of the packed block data in bytes, before compression.
+    pub fn packed_bytes(&self) -> u64 {
+        packed_bytes(self.volume, self.bits_per_block)
+    }
+
+    /// Returns the size of the packed block data in bytes if unused palette entries were removed.
+    pub fn minimal_packed_bytes(&self) -> u64 {
+        packed_bytes(self.volume, self.minimal_bits_per_block)
+    }
+
+    /// Returns the number of bytes the block data would need at the entropy of the block distribution.
+    pub fn entropy_bytes(&self) -> u64 {
+        (self.entropy * self.volume as f64 / 8.0).ceil() as u64
+    }
+
+    /// Checks whether removing unused palette entries would make the packed block data smaller.
+    pub fn palette_can_shrink(&self) -> bool {
+        self.minimal_bits_per_block < self.bits_per_block
+    }
+}
+
+impl Region {
+    /// Estimates how well the blocks of the region compress, to find the regions that dominate the file size and check
+    /// whether optimizing the palette would help.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// for (name, region) in file.get_regions() {
+    ///     let report = region.entropy_report();
+    ///
+    ///     println!(
+    ///         "{}: {} bytes packed, {:.2} bits of entropy per block, {:.1} blocks per run",
+    ///         name,
+    ///         report.packed_bytes(),
+    ///         report.entropy,
+    ///         report.average_run_length()
+    ///     );
+    /// }
+    /// ```
+    pub fn entropy_report(&self) -> EntropyReport {
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+        let volume = self.calc_volume();
+
+        let mut counts = vec![0_u64; self.block_state_palette.len()];
+        let mut runs = 0;
+        let mut longest_run = 0;
+        let mut current_run = 0;
+        let mut previous = None;
+
+        for index in 0..volume {
+            let palette_index =
+                read_palette_index(&self.block_states, index, required_bits, bitmask) as usize;
+
+            counts[palette_index] += 1;
+
+            if previous == Some(palette_index) {
+                current_run += 1;
+            } else {
+                runs += 1;
+                current_run = 1;
+                previous = Some(palette_index);
+            }
+
+            longest_run = longest_run.max(current_run);
+        }
+
+        let distinct_states = counts.iter().filter(|count| **count > 0).count();
+
+        let entropy = counts
+            .iter()
+            .filter(|count| **count > 0)
+            .map(|count| {
+                let probability = *count as f64 / volume as f64;
+                -probability * probability.log2()
+            })
+            .sum::<f64>()
+            // avoids reporting -0 for regions made of a single block
+            .max(0.0);
+
+        EntropyReport {
+            volume,
+            palette_size: self.block_state_palette.len(),
+            distinct_states,
+            bits_per_block: required_bits,
+            minimal_bits_per_block: (distinct_states.max(1).next_power_of_two().trailing_zeros()
+                as u64)
+                .max(2),
+            runs,
+            longest_run,
+            entropy,
+        }
+    }
+}
+
+/// Returns the number of bytes of the long array storing `volume` blocks with `bits` bits each.
+fn packed_bytes(volume: u64, bits: u64) -> u64 {
+    (volume * bits).div_ceil(64) * 8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::{Coordinates, LitematicaFile};
+
+    use super::*;
+
+    #[test]
+    fn entropy_report() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let report = region.entropy_report();
+
+        assert_eq!(report.volume, region.calc_volume());
+        assert!(report.distinct_states <= report.palette_size);
+        assert!(report.runs >= report.distinct_states as u64);
+        assert!(report.entropy <= (report.distinct_states as f64).log2() + 1e-9);
+        assert!(report.entropy_bytes() <= report.packed_bytes());
+
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 4, y: 1, z: 1 },
+        );
+
+        let empty = region.entropy_report();
+        assert_eq!(empty.entropy, 0.0);
+        assert_eq!(empty.runs, 1);
+        assert_eq!(empty.longest_run, 4);
+
+        region.set_block((2, 0, 0), BlockStateBuilder::new("stone").build());
+        region.set_block((3, 0, 0), BlockStateBuilder::new("stone").build());
+
+        let half = region.entropy_report();
+        assert_eq!(half.distinct_states, 2);
+        assert_eq!(half.runs, 2);
+        assert_eq!(half.average_run_length(), 2.0);
+        assert!((half.entropy - 1.0).abs() < 1e-9);
+        assert_eq!(half.packed_bytes(), 8);
+    }
+}
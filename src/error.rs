@@ -1,9 +1,12 @@
 use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
+use crate::resource_location::ResourceLocation;
+use crate::structure::Coordinates;
+
 /// `Error` type for this crate.
 ///
-/// This is a simple `enum` that wraps the `std::io::Error` and `nbt::Error` types.
+/// This is a simple `enum` that wraps the `std::io::Error` and `nbt::Error` types, as well as the errors of optional dependencies.
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("IO error: {0}")]
@@ -11,6 +14,16 @@ pub enum Error {
 
     #[error("NBT error: {0}")]
     NBT(#[from] nbt::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "zip")]
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Region error: {0}")]
+    Region(#[from] RegionError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -24,3 +37,55 @@ impl Display for ParseError {
         write!(f, "Parse error")
     }
 }
+
+/// `Error` type for positions outside of a region and for block data that doesn't fit its region, e.g. in corrupt files.
+///
+/// Corrupt block data is rejected by `Region::validate()` whenever a file is read, so accessing blocks afterwards can't
+/// fail on it. Out of bounds positions are returned by the `try_*` accessors of `Region`, while `get_block()`,
+/// `get_block_mut()` and `set_block()` keep panicking on them like slice indexing does.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RegionError {
+    #[error("Position {coords:?} is outside of a region of size {size:?}")]
+    OutOfBounds {
+        coords: Coordinates,
+        size: Coordinates,
+    },
+
+    #[error("Block {block_index} uses palette index {palette_index}, but the palette only has {palette_len} entries")]
+    InvalidPaletteIndex {
+        block_index: u64,
+        palette_index: u32,
+        palette_len: usize,
+    },
+
+    /// The packed block states are shorter than the region size and palette require. Longer arrays are accepted.
+    #[error("The block states have {actual} longs, but {expected} are needed for the size and palette of the region")]
+    BlockStatesLength { expected: usize, actual: usize },
+
+    #[error("The palette is empty")]
+    EmptyPalette,
+
+    /// The volume or the packed block data of a region of this size doesn't fit into memory indices.
+    #[error("A region of size {size:?} is too large")]
+    SizeTooLarge { size: Coordinates },
+}
+
+/// `Error` type for validating a `BlockState` against a block property schema.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("Unknown block {0}")]
+    UnknownBlock(ResourceLocation),
+
+    #[error("Unknown property {property} for block {block}")]
+    UnknownProperty {
+        block: ResourceLocation,
+        property: String,
+    },
+
+    #[error("Invalid value {value} for property {property} of block {block}")]
+    InvalidValue {
+        block: ResourceLocation,
+        property: String,
+        value: String,
+    },
+}
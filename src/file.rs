@@ -1,12 +1,281 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "fs")]
 use std::fs::File;
 use std::hash::Hash;
+#[cfg(feature = "fs")]
 use std::io::{BufReader, BufWriter};
+use std::io::{Read, Write};
+#[cfg(feature = "fs")]
 use std::path::Path;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use nbt::Value;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::error::Result;
-use crate::structure::{LitematicaFile, Region};
+use crate::region::{set_value_position, value_position};
+use crate::structure::{Coordinates, Extensions, LitematicaFile, Metadata, Region};
+#[cfg(feature = "fs")]
+use crate::trace;
+
+/// Options that control how a `LitematicaFile` is written.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// Stores a checksum of the file contents, which can be checked with `LitematicaFile::verify_integrity()` after reading.
+    pub integrity: bool,
+
+    /// Leaves out all entities, e.g. item frames and armor stands.
+    pub strip_entities: bool,
+
+    /// Removes the stored items of tile entities, e.g. the contents of chests, jukeboxes and lecterns.
+    /// The tile entities themselves are kept.
+    pub strip_tile_entity_inventories: bool,
+
+    /// Leaves out all pending block and fluid ticks.
+    pub strip_pending_ticks: bool,
+
+    /// Recomputes the enclosing size, region count, total volume and total block count of the metadata and sets the
+    /// modification time to now, like `LitematicaFile::refresh_metadata()`. The file itself is left unchanged.
+    pub refresh_metadata: bool,
+}
+
+/// The version of the Litematica format written for new files.
+pub(crate) const LITEMATICA_VERSION: i32 = 6;
+
+/// The Minecraft data version assigned to files imported from formats that don't store one (1.21).
+pub(crate) const DEFAULT_DATA_VERSION: i32 = 3953;
+
+/// The keys under which tile entities store their items.
+const INVENTORY_KEYS: [&str; 4] = ["Items", "Item", "RecordItem", "Book"];
+
+impl WriteOptions {
+    fn strips_anything(&self) -> bool {
+        self.strip_entities || self.strip_tile_entity_inventories || self.strip_pending_ticks
+    }
+}
+
+/// The header of a `Litematica` file, read by `LitematicaFile::read_metadata()` without the regions.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FileMetadata {
+    pub metadata: Metadata,
+    pub minecraft_data_version: i32,
+    pub version: i32,
+}
+
+/// A borrowed version of `LitematicaFile` used for serializing with `WriteOptions` applied.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct FileView<'a> {
+    metadata: &'a Metadata,
+    minecraft_data_version: i32,
+    version: i32,
+    regions: OrderedRegions<'a>,
+
+    #[serde(rename = "Ritematica")]
+    #[serde(skip_serializing_if = "Extensions::is_empty")]
+    extensions: Extensions,
+}
+
+/// The regions of a file, serialized in the order of `LitematicaFile::regions_ordered()`.
+struct OrderedRegions<'a>(Vec<(&'a str, &'a Region)>);
+
+impl Serialize for OrderedRegions<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+
+        for (name, region) in &self.0 {
+            map.serialize_entry(name, region)?;
+        }
+
+        map.end()
+    }
+}
+
+/// A builder for creating the `Metadata` of a new file without setting every field by hand.
+///
+/// The creation and modification times default to now. The enclosing size, region count, total volume and total block
+/// count start at zero, since they are computed from the regions, e.g. by `LitematicaFile::refresh_metadata()`.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::file::MetadataBuilder;
+///
+/// let mut file = LitematicaFile::read("test.litematic").unwrap();
+///
+/// file.metadata = MetadataBuilder::new("My Farm")
+///     .author("defnot001")
+///     .description("A simple farm")
+///     .build();
+/// file.refresh_metadata();
+/// ```
+#[derive(Debug)]
+pub struct MetadataBuilder {
+    name: String,
+    author: String,
+    description: String,
+    time_created: Option<i64>,
+    time_modified: Option<i64>,
+}
+
+impl MetadataBuilder {
+    /// Creates a new `MetadataBuilder` for a schematic with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the schematic that is shown in Litematica.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            author: String::new(),
+            description: String::new(),
+            time_created: None,
+            time_modified: None,
+        }
+    }
+
+    /// Sets the author of the schematic.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Sets the description of the schematic.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the creation time in milliseconds since the Unix epoch. Defaults to now.
+    pub fn time_created(mut self, time_created: i64) -> Self {
+        self.time_created = Some(time_created);
+        self
+    }
+
+    /// Sets the modification time in milliseconds since the Unix epoch. Defaults to now.
+    pub fn time_modified(mut self, time_modified: i64) -> Self {
+        self.time_modified = Some(time_modified);
+        self
+    }
+
+    /// Builds the `Metadata`.
+    pub fn build(self) -> Metadata {
+        let now = now_millis();
+
+        Metadata {
+            author: self.author,
+            enclosing_size: Coordinates { x: 0, y: 0, z: 0 },
+            total_volume: 0,
+            region_count: 0,
+            description: self.description,
+            name: self.name,
+            time_modified: self.time_modified.unwrap_or(now),
+            total_blocks: 0,
+            time_created: self.time_created.unwrap_or(now),
+        }
+    }
+}
+
+/// A builder for creating a `LitematicaFile` from scratch instead of reading one from disk.
+///
+/// The enclosing size, region count, total volume and total block count of the metadata are computed from the regions
+/// when building, and the creation and modification times are set to the current time.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::file::LitematicaFileBuilder;
+///
+/// let template = LitematicaFile::read("test.litematic").unwrap();
+/// let region = template.get_region("test").unwrap().clone();
+///
+/// let file = LitematicaFileBuilder::new("My Farm")
+///     .author("defnot001")
+///     .description("A simple farm")
+///     .region("farm", region)
+///     .build();
+///
+/// file.write("farm.litematic").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct LitematicaFileBuilder {
+    name: String,
+    author: String,
+    description: String,
+    minecraft_data_version: i32,
+    regions: HashMap<String, Region>,
+}
+
+impl LitematicaFileBuilder {
+    /// Creates a new `LitematicaFileBuilder` for a schematic with the given name and no regions.
+    ///
+    /// The Minecraft data version defaults to the one of 1.21, see `minecraft_data_version()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the schematic that is shown in Litematica.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            author: String::new(),
+            description: String::new(),
+            minecraft_data_version: DEFAULT_DATA_VERSION,
+            regions: HashMap::new(),
+        }
+    }
+
+    /// Sets the author of the schematic.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Sets the description of the schematic.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the data version of the Minecraft release the blocks belong to, e.g. `3465` for 1.20.1.
+    pub fn minecraft_data_version(mut self, minecraft_data_version: i32) -> Self {
+        self.minecraft_data_version = minecraft_data_version;
+        self
+    }
+
+    /// Adds a region to the schematic. A region that was added with the same name before is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the region.
+    /// * `region` - The `Region`, whose `position` is relative to the schematic origin.
+    pub fn region(mut self, name: impl Into<String>, region: Region) -> Self {
+        self.regions.insert(name.into(), region);
+        self
+    }
+
+    /// Builds the `LitematicaFile` and computes its metadata from the regions.
+    pub fn build(self) -> LitematicaFile {
+        let mut file = LitematicaFile {
+            metadata: MetadataBuilder::new(self.name)
+                .author(self.author)
+                .description(self.description)
+                .build(),
+            minecraft_data_version: self.minecraft_data_version,
+            version: LITEMATICA_VERSION,
+            regions: self.regions,
+            extensions: Extensions::default(),
+            region_order: Vec::new(),
+        };
+
+        file.update_metadata();
+        file
+    }
+}
 
 impl LitematicaFile {
     /// Reads a `Litematica` file from the given path.
@@ -25,9 +294,113 @@ impl LitematicaFile {
     ///
     /// let file = LitematicaFile::read("test.litematic").unrwrap();
     ///```
+    #[cfg(feature = "fs")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(path = %path.as_ref().display(), regions, volume, elapsed_ms)
+        )
+    )]
     pub fn read(path: impl AsRef<Path>) -> Result<LitematicaFile> {
+        let _timer = trace::Timer::start();
+
+        let file = File::open(path)?;
+        let file = Self::read_from(BufReader::new(file))?;
+
+        trace::record("regions", file.regions.len() as u64);
+        trace::record("volume", file.metadata.total_volume as u64);
+
+        Ok(file)
+    }
+
+    /// Reads a `Litematica` file from a reader, e.g. a network stream, an archive entry or an in-memory buffer.
+    ///
+    /// The data is expected to be gzip-compressed, like on disk. The reader is not buffered, so wrap it in a
+    /// `BufReader` if it does many small reads.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read the file from.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be read or deserialized, or the block data of a region is corrupt.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::net::TcpStream;
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let stream = TcpStream::connect("127.0.0.1:25575").unwrap();
+    /// let file = LitematicaFile::read_from(stream).unwrap();
+    /// ```
+    pub fn read_from(reader: impl Read) -> Result<LitematicaFile> {
+        let file: LitematicaFile = nbt::from_gzip_reader(reader)?;
+
+        for region in file.regions.values() {
+            region.validate()?;
+        }
+
+        #[cfg(feature = "tracing")]
+        if !file.verify_enclosing_size() {
+            tracing::warn!(
+                stored = ?file.metadata.enclosing_size,
+                computed = ?file.compute_enclosing_size(),
+                "Stored enclosing size does not match the regions"
+            );
+        }
+
+        Ok(file)
+    }
+
+    /// Reads a `Litematica` file from its gzip-compressed bytes, e.g. an upload, without going through a temporary file.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The contents of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes cannot be deserialized.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let bytes = std::fs::read("test.litematic").unwrap();
+    /// let file = LitematicaFile::from_bytes(&bytes).unwrap();
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<LitematicaFile> {
+        Self::read_from(bytes)
+    }
+
+    /// Reads only the metadata and versions of a `Litematica` file from the given path.
+    ///
+    /// The regions are skipped while decoding instead of being deserialized, so this is much cheaper than `read()`
+    /// for tools that index many schematics. The file still has to be decompressed though.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or deserialized.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let header = LitematicaFile::read_metadata("test.litematic").unwrap();
+    ///
+    /// println!("{} by {}", header.metadata.name, header.metadata.author);
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn read_metadata(path: impl AsRef<Path>) -> Result<FileMetadata> {
         let file = File::open(path)?;
         let buf_reader = BufReader::new(file);
+
         Ok(nbt::from_gzip_reader(buf_reader)?)
     }
 
@@ -51,7 +424,47 @@ impl LitematicaFile {
     /// let file = LitematicaFile::read("test.litematic").unrwrap();
     /// file.write("test2.litematic").unrwrap();
     /// ```
+    #[cfg(feature = "fs")]
     pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.write_with_options(path, &WriteOptions::default())
+    }
+
+    /// Writes a `Litematica` file to the given path, using the given `WriteOptions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path where the file should be written to.
+    /// * `options` - The `WriteOptions` controlling what is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or serialized.
+    /// Also returns an error if the file extension is not `.litematic`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{LitematicaFile, WriteOptions};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let options = WriteOptions {
+    ///     integrity: true,
+    ///     strip_entities: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// file.write_with_options("test2.litematic", &options).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(path = %path.as_ref().display(), regions = self.regions.len(), volume = self.metadata.total_volume, elapsed_ms)
+        )
+    )]
+    pub fn write_with_options(&self, path: impl AsRef<Path>, options: &WriteOptions) -> Result<()> {
+        let _timer = trace::Timer::start();
+
         if let Some(ext) = path.as_ref().extension() {
             if ext != "litematic" {
                 return Err(std::io::Error::new(
@@ -64,11 +477,383 @@ impl LitematicaFile {
 
         let file = File::create(path)?;
         let mut buf_writer = BufWriter::new(file);
-        nbt::to_gzip_writer(&mut buf_writer, self, None)?;
+
+        self.write_with_options_to(&mut buf_writer, options)?;
+        buf_writer.flush()?;
 
         Ok(())
     }
 
+    /// Writes a `Litematica` file to a writer, e.g. a network stream, an archive entry or an in-memory buffer.
+    ///
+    /// The data is gzip-compressed, like on disk. The writer is not buffered or flushed, so wrap it in a `BufWriter`
+    /// if it does many small writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the file to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be written or serialized.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let mut buffer = Vec::new();
+    ///
+    /// file.write_to(&mut buffer).unwrap();
+    /// ```
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<()> {
+        self.write_with_options_to(writer, &WriteOptions::default())
+    }
+
+    /// Serializes a `Litematica` file into its gzip-compressed bytes, e.g. for a download, without going through a
+    /// temporary file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be serialized.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let bytes = file.to_bytes().unwrap();
+    ///
+    /// assert_eq!(LitematicaFile::from_bytes(&bytes).unwrap().metadata, file.metadata);
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Writes a `Litematica` file to a writer, using the given `WriteOptions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the file to.
+    /// * `options` - The `WriteOptions` controlling what is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be written or serialized.
+    pub fn write_with_options_to(
+        &self,
+        writer: &mut impl Write,
+        options: &WriteOptions,
+    ) -> Result<()> {
+        let stripped;
+        let regions = if options.strips_anything() {
+            stripped = self
+                .regions
+                .iter()
+                .map(|(name, region)| (name.clone(), strip_region(region, options)))
+                .collect();
+
+            &stripped
+        } else {
+            &self.regions
+        };
+
+        let refreshed;
+        let metadata = if options.refresh_metadata {
+            refreshed = Metadata {
+                time_modified: now_millis(),
+                ..self.computed_metadata()
+            };
+
+            &refreshed
+        } else {
+            &self.metadata
+        };
+
+        // a stored checksum would be stale if the file was modified after reading, so it is always recomputed or dropped
+        let mut extensions = self.extensions.clone();
+        extensions.checksum = options
+            .integrity
+            .then(|| self.checksum_with(metadata, regions) as i64);
+
+        let view = FileView {
+            metadata,
+            minecraft_data_version: self.minecraft_data_version,
+            version: self.version,
+            regions: OrderedRegions(
+                self.ordered_names()
+                    .into_iter()
+                    .map(|name| (name, &regions[name]))
+                    .collect(),
+            ),
+            extensions,
+        };
+
+        nbt::to_gzip_writer(writer, &view, None)?;
+
+        Ok(())
+    }
+
+    /// Creates a file containing a single region, with the metadata computed from it.
+    ///
+    /// Used when importing other formats, so the creation and modification times are set to the current time.
+    pub(crate) fn from_region(
+        name: impl Into<String>,
+        region: Region,
+        minecraft_data_version: i32,
+    ) -> Self {
+        let name = name.into();
+
+        LitematicaFileBuilder::new(name.clone())
+            .minecraft_data_version(minecraft_data_version)
+            .region(name, region)
+            .build()
+    }
+
+    /// Sets the name of the schematic that is shown in Litematica.
+    ///
+    /// Returns the file, so the setters can be chained.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// file.set_name("My Farm")
+    ///     .set_author("defnot001")
+    ///     .set_description("A simple farm");
+    /// ```
+    pub fn set_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.metadata.name = name.into();
+        self
+    }
+
+    /// Sets the author of the schematic. Returns the file, so the setters can be chained.
+    pub fn set_author(&mut self, author: impl Into<String>) -> &mut Self {
+        self.metadata.author = author.into();
+        self
+    }
+
+    /// Sets the description of the schematic. Returns the file, so the setters can be chained.
+    pub fn set_description(&mut self, description: impl Into<String>) -> &mut Self {
+        self.metadata.description = description.into();
+        self
+    }
+
+    /// Recomputes the enclosing size, region count, total volume and total block count of the metadata, and sets the
+    /// modification time to now.
+    ///
+    /// Edits through `get_region_mut()` don't update the metadata, so this should be called before writing an edited
+    /// file, or `WriteOptions::refresh_metadata` should be set. Otherwise Litematica shows the stale values.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    /// region.set_block((0, 0, 0), BlockStateBuilder::new("stone").build());
+    ///
+    /// file.refresh_metadata();
+    /// file.write("test2.litematic").unwrap();
+    /// ```
+    pub fn refresh_metadata(&mut self) {
+        self.update_metadata();
+        self.metadata.time_modified = now_millis();
+    }
+
+    /// Recomputes the enclosing size, region count, total volume and total block count of the metadata from the regions.
+    pub(crate) fn update_metadata(&mut self) {
+        self.metadata = self.computed_metadata();
+    }
+
+    /// Returns a copy of the metadata with the values that depend on the regions recomputed.
+    fn computed_metadata(&self) -> Metadata {
+        Metadata {
+            enclosing_size: self.compute_enclosing_size(),
+            region_count: self.regions.len() as i32,
+            total_volume: saturating_sum(self.regions.values().map(Region::calc_volume)),
+            total_blocks: saturating_sum(self.regions.values().map(|region| {
+                region
+                    .iter_blocks()
+                    .filter(|(_, block)| !block.is_air())
+                    .count() as u64
+            })),
+            ..self.metadata.clone()
+        }
+    }
+
+    /// Computes the size of the box enclosing all regions from their positions and sizes, which is what
+    /// `metadata.enclosing_size` should contain.
+    ///
+    /// Regions with negative sizes extend from their position towards the lower coordinates. A file without regions
+    /// has a size of 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// println!("{:?}", file.compute_enclosing_size());
+    /// ```
+    pub fn compute_enclosing_size(&self) -> Coordinates {
+        let (min, max) = self.enclosing_box();
+
+        Coordinates {
+            x: max.x - min.x,
+            y: max.y - min.y,
+            z: max.z - min.z,
+        }
+    }
+
+    /// Returns whether `metadata.enclosing_size` matches the size computed from the regions.
+    ///
+    /// A mismatch means the file was written by a tool that did not update its metadata. It can be fixed with
+    /// `refresh_metadata()`. With the `tracing` feature, reading such a file logs a warning.
+    pub fn verify_enclosing_size(&self) -> bool {
+        self.metadata.enclosing_size == self.compute_enclosing_size()
+    }
+
+    /// Returns the corner of the box enclosing all regions with the lowest coordinates and the corner just past the
+    /// highest coordinates, both relative to the schematic origin.
+    pub(crate) fn enclosing_box(&self) -> (Coordinates, Coordinates) {
+        let mut min = Coordinates { x: 0, y: 0, z: 0 };
+        let mut max = Coordinates { x: 0, y: 0, z: 0 };
+
+        for (index, region) in self.regions.values().enumerate() {
+            let corner = region.min_corner();
+            let far = Coordinates {
+                x: corner.x + region.size.x.abs(),
+                y: corner.y + region.size.y.abs(),
+                z: corner.z + region.size.z.abs(),
+            };
+
+            if index == 0 {
+                (min, max) = (corner, far);
+            } else {
+                min = Coordinates {
+                    x: min.x.min(corner.x),
+                    y: min.y.min(corner.y),
+                    z: min.z.min(corner.z),
+                };
+                max = Coordinates {
+                    x: max.x.max(far.x),
+                    y: max.y.max(far.y),
+                    z: max.z.max(far.z),
+                };
+            }
+        }
+
+        (min, max)
+    }
+
+    /// Copies the blocks and tile entities of all regions into a single region covering their enclosing box, for
+    /// formats that only hold one box of blocks. Entities are dropped.
+    ///
+    /// Where regions overlap, the first non-air block by region name is used.
+    pub(crate) fn combined_region(&self) -> Region {
+        let mut names = self.regions.keys().collect::<Vec<_>>();
+        names.sort();
+
+        if let [name] = names[..] {
+            let mut region = self.regions[name].clone();
+            region.entities.clear();
+            return region;
+        }
+
+        let (min, max) = self.enclosing_box();
+        let mut combined = Region::filled_with_air(
+            min,
+            Coordinates {
+                x: max.x - min.x,
+                y: max.y - min.y,
+                z: max.z - min.z,
+            },
+        );
+
+        for name in names {
+            let region = &self.regions[name];
+            let corner = region.min_corner();
+            let shift = |position: Coordinates| Coordinates {
+                x: position.x + corner.x - min.x,
+                y: position.y + corner.y - min.y,
+                z: position.z + corner.z - min.z,
+            };
+
+            let mut copied = HashSet::new();
+
+            for (position, block) in region.iter_blocks() {
+                let target = shift(position);
+
+                if !block.is_air() && combined.get_block(target).is_air() {
+                    combined.set_block(target, block.clone());
+                    copied.insert(position);
+                }
+            }
+
+            for tile_entity in &region.tile_entities {
+                let Some(position) = value_position(tile_entity) else {
+                    continue;
+                };
+
+                if copied.contains(&position) {
+                    let mut tile_entity = tile_entity.clone();
+                    set_value_position(&mut tile_entity, shift(position));
+                    combined.tile_entities.push(tile_entity);
+                }
+            }
+        }
+
+        combined
+    }
+
+    /// Finds the region containing a position of the enclosing box and returns its name, the region and the position
+    /// relative to the region origin.
+    ///
+    /// The position is relative to the corner of the enclosing box with the lowest coordinates, so `(0, 0, 0)` is the
+    /// first block of the schematic as shown in the Litematica placement. If several regions overlap at the position,
+    /// the region that comes first by name is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// let (name, region, local) = file.region_at((2, 4, 2)).unwrap();
+    ///
+    /// assert_eq!(name, "test");
+    /// println!("{}", region.get_block(local));
+    /// ```
+    pub fn region_at(
+        &self,
+        position: impl Into<Coordinates>,
+    ) -> Option<(&str, &Region, Coordinates)> {
+        let position = position.into();
+        let (min, _) = self.enclosing_box();
+
+        self.regions
+            .iter()
+            .filter_map(|(name, region)| {
+                let corner = region.min_corner();
+                let local = Coordinates {
+                    x: min.x + position.x - corner.x,
+                    y: min.y + position.y - corner.y,
+                    z: min.z + position.z - corner.z,
+                };
+
+                region
+                    .contains(local)
+                    .then_some((name.as_str(), region, local))
+            })
+            .min_by(|(a, _, _), (b, _, _)| a.cmp(b))
+    }
+
     /// Returns a reference to a `HashMap` containing all the `regions` in the file.
     ///
     /// The `HashMap` is keyed by the region's `name`. The value is the region `data`.
@@ -136,10 +921,10 @@ impl LitematicaFile {
     ///
     /// assert!(region.is_some());
     /// ```
-    pub fn get_region<Q: ?Sized>(&self, name: &Q) -> Option<&Region>
+    pub fn get_region<Q>(&self, name: &Q) -> Option<&Region>
     where
         String: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: Hash + Eq + ?Sized,
     {
         self.regions.get(name)
     }
@@ -160,14 +945,69 @@ impl LitematicaFile {
     ///
     /// assert!(region.is_some());
     /// ```
-    pub fn get_region_mut<Q: ?Sized>(&mut self, name: &Q) -> Option<&mut Region>
+    pub fn get_region_mut<Q>(&mut self, name: &Q) -> Option<&mut Region>
     where
         String: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: Hash + Eq + ?Sized,
     {
         self.regions.get_mut(name)
     }
 
+    /// Adds a `region` with the given `name` to the file and updates the metadata.
+    /// If a region with the same name already exists, it is replaced and returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the region.
+    /// * `region` - The `Region`, whose `position` is relative to the schematic origin.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::structure::Region;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// file.add_region("foundation", Region::new((32, 1, 32)));
+    ///
+    /// assert_eq!(file.metadata.region_count, 2);
+    /// ```
+    pub fn add_region(&mut self, name: impl Into<String>, region: Region) -> Option<Region> {
+        let replaced = self.regions.insert(name.into(), region);
+        self.update_metadata();
+
+        replaced
+    }
+
+    /// Removes the region with the given `name` from the file and updates the metadata.
+    /// Returns the removed region, or `None` if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the region to remove.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.remove_region("test").unwrap();
+    ///
+    /// assert_eq!(file.metadata.region_count, 0);
+    /// ```
+    pub fn remove_region<Q>(&mut self, name: &Q) -> Option<Region>
+    where
+        String: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.regions.remove(name)?;
+
+        self.region_order
+            .retain(|ordered| Borrow::<Q>::borrow(ordered) != name);
+        self.update_metadata();
+
+        Some(removed)
+    }
+
     /// Renames a `region` with the given `old_name` to the given `new_name`.
     /// If the region does not exist, nothing happens.
     ///
@@ -186,24 +1026,542 @@ impl LitematicaFile {
     /// assert!(file.get_region("test").is_none());
     /// assert!(file.get_region("test2").is_some());
     /// ```
-    pub fn rename_region<Q: ?Sized>(&mut self, old_name: &Q, new_name: impl Into<String>)
+    pub fn rename_region<Q>(&mut self, old_name: &Q, new_name: impl Into<String>)
     where
         String: Borrow<Q>,
-        Q: Hash + Eq,
+        Q: Hash + Eq + ?Sized,
     {
         let removed = self.regions.remove(old_name);
 
         if let Some(region) = removed {
-            self.regions.insert(new_name.into(), region);
+            let new_name = new_name.into();
+
+            if let Some(name) = self
+                .region_order
+                .iter_mut()
+                .find(|name| Borrow::<Q>::borrow(&**name) == old_name)
+            {
+                *name = new_name.clone();
+            }
+
+            self.regions.insert(new_name, region);
         }
     }
+
+    /// Returns an iterator over the names and regions of the file in a predictable order, which is also the order
+    /// in which they are written.
+    ///
+    /// Regions are sorted by name, unless they were reordered with `sort_regions_by()`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// for (name, region) in file.regions_ordered() {
+    ///     println!("{}: {:?}", name, region.size);
+    /// }
+    /// ```
+    pub fn regions_ordered(&self) -> impl Iterator<Item = (&str, &Region)> {
+        self.ordered_names()
+            .into_iter()
+            .map(|name| (name, &self.regions[name]))
+    }
+
+    /// Returns the names of the regions in the order of `regions_ordered()`.
+    pub fn region_names_ordered(&self) -> impl Iterator<Item = &str> {
+        self.ordered_names().into_iter()
+    }
+
+    /// Sorts the regions by name, undoing any order set with `sort_regions_by()`.
+    pub fn sort_regions(&mut self) {
+        self.region_order.clear();
+    }
+
+    /// Sorts the regions with a comparator function, e.g. by their height for a build order.
+    ///
+    /// The order is used by `regions_ordered()` and when writing the file. Regions added afterwards come last, sorted by name.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// file.sort_regions_by(|(_, a), (_, b)| a.min_corner().y.cmp(&b.min_corner().y));
+    /// ```
+    pub fn sort_regions_by(
+        &mut self,
+        mut compare: impl FnMut((&str, &Region), (&str, &Region)) -> Ordering,
+    ) {
+        let mut names = self.regions.keys().collect::<Vec<_>>();
+        names.sort();
+        names.sort_by(|a, b| compare((a, &self.regions[*a]), (b, &self.regions[*b])));
+
+        self.region_order = names.into_iter().cloned().collect();
+    }
+
+    /// Returns the names of the regions in `region_order` followed by the remaining ones sorted by name.
+    fn ordered_names(&self) -> Vec<&str> {
+        let mut names = self
+            .region_order
+            .iter()
+            .filter(|name| self.regions.contains_key(*name))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        let mut rest = self
+            .regions
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !names.contains(name))
+            .collect::<Vec<_>>();
+        rest.sort_unstable();
+
+        names.extend(rest);
+        names
+    }
+
+    /// Returns a copy of the file without any entities, e.g. to distribute the block structure of a build separately
+    /// from its decoration like item frames and armor stands.
+    ///
+    /// Tile entities and scheduled ticks are kept, since they belong to blocks.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// file.retain_blocks_only().write("blocks.litematic").unwrap();
+    /// file.retain_entities_only().write("decoration.litematic").unwrap();
+    /// ```
+    pub fn retain_blocks_only(&self) -> LitematicaFile {
+        self.map_regions(|region| {
+            let mut region = region.clone();
+            region.entities.clear();
+            region
+        })
+    }
+
+    /// Returns a copy of the file that only contains the entities, with every block replaced by air.
+    ///
+    /// The regions keep their positions and sizes, so the entities stay in place when both parts are pasted.
+    /// Tile entities and scheduled ticks are removed along with their blocks. See `retain_blocks_only()` for the counterpart.
+    pub fn retain_entities_only(&self) -> LitematicaFile {
+        self.map_regions(|region| {
+            let mut stripped = Region::filled_with_air(region.position, region.size);
+            stripped.entities = region.entities.clone();
+            stripped
+        })
+    }
+
+    /// Returns a copy of the file with `map` applied to every region and the metadata updated.
+    fn map_regions(&self, map: impl Fn(&Region) -> Region) -> LitematicaFile {
+        let mut extensions = self.extensions.clone();
+
+        // the contents change, so a stored checksum would be stale
+        extensions.checksum = None;
+
+        let mut file = LitematicaFile {
+            metadata: self.metadata.clone(),
+            minecraft_data_version: self.minecraft_data_version,
+            version: self.version,
+            regions: self
+                .regions
+                .iter()
+                .map(|(name, region)| (name.clone(), map(region)))
+                .collect(),
+            extensions,
+            region_order: self.region_order.clone(),
+        };
+
+        file.update_metadata();
+        file
+    }
 }
 
-#[cfg(test)]
+/// Returns the current time in milliseconds since the Unix epoch, as stored in the metadata.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as i64)
+}
+
+/// Returns 0, since there is no system clock on `wasm32-unknown-unknown` and `SystemTime::now()` panics there.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_millis() -> i64 {
+    0
+}
+
+/// Adds up block counts for an `i32` metadata field in `i64`, clamping to `i32::MAX` instead of wrapping.
+fn saturating_sum(counts: impl Iterator<Item = u64>) -> i32 {
+    let sum = counts.fold(0i64, |sum, count| {
+        sum.saturating_add(i64::try_from(count).unwrap_or(i64::MAX))
+    });
+
+    i32::try_from(sum).unwrap_or(i32::MAX)
+}
+
+/// Returns a copy of `region` without the data that `options` strip.
+fn strip_region(region: &Region, options: &WriteOptions) -> Region {
+    let mut region = region.clone();
+
+    if options.strip_entities {
+        region.entities.clear();
+    }
+
+    if options.strip_tile_entity_inventories {
+        for tile_entity in &mut region.tile_entities {
+            if let Value::Compound(compound) = tile_entity {
+                for key in INVENTORY_KEYS {
+                    compound.remove(key);
+                }
+            }
+        }
+    }
+
+    if options.strip_pending_ticks {
+        region.pending_block_ticks.clear();
+        region.pending_fluid_ticks.clear();
+    }
 
+    region
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn builder() {
+        let template = LitematicaFile::read("test.litematic").unwrap();
+        let mut region = template.get_region("test").unwrap().clone();
+        region.position = Coordinates { x: 10, y: 0, z: 0 };
+
+        let file = LitematicaFileBuilder::new("Farm")
+            .author("defnot001")
+            .description("A simple farm")
+            .region("a", template.get_region("test").unwrap().clone())
+            .region("b", region)
+            .build();
+
+        assert_eq!(file.metadata.name, "Farm");
+        assert_eq!(file.metadata.author, "defnot001");
+        assert_eq!(file.metadata.region_count, 2);
+        assert_eq!(
+            file.metadata.total_blocks,
+            2 * template.metadata.total_blocks
+        );
+        assert_eq!(file.metadata.time_created, file.metadata.time_modified);
+        assert_eq!(file.minecraft_data_version, DEFAULT_DATA_VERSION);
+        assert_eq!(file.version, LITEMATICA_VERSION);
+
+        let (min, max) = file.enclosing_box();
+        assert_eq!(file.metadata.enclosing_size.x, max.x - min.x);
+
+        let empty = LitematicaFileBuilder::new("Empty").build();
+        assert_eq!(empty.metadata.region_count, 0);
+        assert_eq!(empty.metadata.total_volume, 0);
+    }
+
+    #[test]
+    fn saturating_metadata_sum() {
+        assert_eq!(saturating_sum([64, 1].into_iter()), 65);
+        assert_eq!(saturating_sum([i32::MAX as u64, 1].into_iter()), i32::MAX);
+        assert_eq!(saturating_sum([u64::MAX, u64::MAX].into_iter()), i32::MAX);
+    }
+
+    #[test]
+    fn add_and_remove_region() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let volume = file.metadata.total_volume;
+
+        let mut region = Region::new((4, 4, 4));
+        region.position = Coordinates { x: -10, y: 0, z: 0 };
+
+        assert!(file.add_region("extra", region).is_none());
+        assert_eq!(file.metadata.region_count, 2);
+        assert_eq!(file.metadata.total_volume, volume + 64);
+
+        let (min, max) = file.enclosing_box();
+        assert_eq!(min.x, -10);
+        assert_eq!(file.metadata.enclosing_size.x, max.x - min.x);
+
+        assert!(file.add_region("extra", Region::new((1, 1, 1))).is_some());
+        assert_eq!(file.metadata.total_volume, volume + 1);
+
+        file.sort_regions_by(|(a, _), (b, _)| b.cmp(a));
+        assert!(file.remove_region("extra").is_some());
+        assert!(file.remove_region("extra").is_none());
+        assert_eq!(file.metadata.region_count, 1);
+        assert_eq!(file.metadata.total_volume, volume);
+        assert_eq!(file.region_order, ["test"]);
+    }
+
+    #[test]
+    fn read_metadata() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let header = LitematicaFile::read_metadata("test.litematic").unwrap();
+
+        assert_eq!(header.version, file.version);
+        assert_eq!(header.minecraft_data_version, file.minecraft_data_version);
+        assert_eq!(header.metadata.name, file.metadata.name);
+        assert_eq!(header.metadata.total_blocks, file.metadata.total_blocks);
+        assert_eq!(header.metadata.enclosing_size, file.metadata.enclosing_size);
+    }
+
+    #[test]
+    fn read_from_and_write_to() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+
+        let mut buffer = Vec::new();
+        file.write_to(&mut buffer).unwrap();
+
+        let read = LitematicaFile::read_from(&buffer[..]).unwrap();
+        assert_eq!(read.metadata.name, file.metadata.name);
+        assert_eq!(read.region_order, file.region_order);
+        assert_eq!(
+            read.get_region("test")
+                .unwrap()
+                .iter_blocks()
+                .collect::<Vec<_>>(),
+            file.get_region("test")
+                .unwrap()
+                .iter_blocks()
+                .collect::<Vec<_>>()
+        );
+
+        let options = WriteOptions {
+            strip_entities: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        file.write_with_options_to(&mut buffer, &options).unwrap();
+
+        let stripped = LitematicaFile::read_from(&buffer[..]).unwrap();
+        assert!(stripped.get_region("test").unwrap().entities.is_empty());
+
+        assert!(LitematicaFile::read_from(&b"not a schematic"[..]).is_err());
+    }
+
+    #[test]
+    fn from_and_to_bytes() {
+        let bytes = std::fs::read("test.litematic").unwrap();
+        let file = LitematicaFile::from_bytes(&bytes).unwrap();
+
+        let written = LitematicaFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        assert_eq!(written.metadata, file.metadata);
+        assert_eq!(
+            written
+                .get_region("test")
+                .unwrap()
+                .iter_blocks()
+                .collect::<Vec<_>>(),
+            file.get_region("test")
+                .unwrap()
+                .iter_blocks()
+                .collect::<Vec<_>>()
+        );
+
+        assert!(LitematicaFile::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+        assert!(LitematicaFile::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn compute_enclosing_size() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        assert!(file.verify_enclosing_size());
+
+        let size = file.get_region("test").unwrap().size;
+        assert_eq!(
+            file.compute_enclosing_size(),
+            Coordinates {
+                x: size.x.abs(),
+                y: size.y.abs(),
+                z: size.z.abs(),
+            }
+        );
+
+        // spans from x = -8 to x = -5 and overlaps the other region along y and z
+        let mut region = Region::new((-4, 1, 1));
+        region.position = Coordinates { x: -5, y: 0, z: 0 };
+        file.regions.insert("negative".to_string(), region);
+
+        assert!(!file.verify_enclosing_size());
+
+        let (min, max) = file.enclosing_box();
+        assert_eq!(min.x, -8);
+        assert_eq!(file.compute_enclosing_size().x, max.x + 8);
+
+        file.update_metadata();
+        assert!(file.verify_enclosing_size());
+    }
+
+    #[test]
+    fn metadata_builder() {
+        let metadata = MetadataBuilder::new("Farm")
+            .author("defnot001")
+            .time_created(1000)
+            .build();
+
+        assert_eq!(metadata.name, "Farm");
+        assert_eq!(metadata.author, "defnot001");
+        assert_eq!(metadata.description, "");
+        assert_eq!(metadata.time_created, 1000);
+        assert!(metadata.time_modified > 1000);
+        assert_eq!(metadata.region_count, 0);
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.set_name("Renamed")
+            .set_author("someone")
+            .set_description("described");
+
+        assert_eq!(file.metadata.name, "Renamed");
+        assert_eq!(file.metadata.author, "someone");
+        assert_eq!(file.metadata.description, "described");
+    }
+
+    #[test]
+    fn refresh_metadata() {
+        let path = std::env::temp_dir().join("ritematica_refresh_metadata.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let blocks = file.metadata.total_blocks;
+        let modified = file.metadata.time_modified;
+
+        let region = file.get_region_mut("test").unwrap();
+        let position = region
+            .iter_blocks()
+            .find(|(_, block)| block.is_air())
+            .map(|(position, _)| position)
+            .unwrap();
+        region.set_block(
+            position,
+            crate::block::BlockStateBuilder::new("stone").build(),
+        );
+
+        let options = WriteOptions {
+            integrity: true,
+            refresh_metadata: true,
+            ..Default::default()
+        };
+        file.write_with_options(&path, &options).unwrap();
+
+        // the file itself keeps its metadata
+        assert_eq!(file.metadata.total_blocks, blocks);
+
+        let written = LitematicaFile::read(&path).unwrap();
+        assert_eq!(written.metadata.total_blocks, blocks + 1);
+        assert!(written.metadata.time_modified > modified);
+        assert_eq!(written.verify_integrity(), Some(true));
+
+        file.refresh_metadata();
+        assert_eq!(file.metadata.total_blocks, blocks + 1);
+        assert_eq!(file.metadata.region_count, 1);
+    }
+
+    #[test]
+    fn blocks_and_entities_only() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.get_region_mut("test")
+            .unwrap()
+            .tile_entities
+            .push(Value::Compound(HashMap::new()));
+
+        let blocks = file.retain_blocks_only();
+        let entities = file.retain_entities_only();
+        let region = file.get_region("test").unwrap();
+
+        let blocks_region = blocks.get_region("test").unwrap();
+        assert!(blocks_region.entities.is_empty());
+        assert_eq!(
+            blocks_region.tile_entities.len(),
+            region.tile_entities.len()
+        );
+        assert_eq!(blocks.metadata.total_blocks, file.metadata.total_blocks);
+
+        let entities_region = entities.get_region("test").unwrap();
+        assert_eq!(entities_region.entities.len(), region.entities.len());
+        assert_eq!(entities_region.size, region.size);
+        assert!(entities_region.tile_entities.is_empty());
+        assert_eq!(entities.metadata.total_blocks, 0);
+    }
+
+    #[test]
+    fn region_at() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let rail = file
+            .get_region("test")
+            .unwrap()
+            .get_block((2, 4, 2))
+            .clone();
+
+        let (name, region, local) = file.region_at((2, 4, 2)).unwrap();
+        assert_eq!(name, "test");
+        assert_eq!(local, Coordinates { x: 2, y: 4, z: 2 });
+        assert_eq!(region.get_block(local), &rail);
+
+        // a second region below the first one moves the corner of the enclosing box
+        let corner = file.get_region("test").unwrap().min_corner();
+        let below = Region::filled_with_air(
+            Coordinates {
+                x: corner.x,
+                y: corner.y - 3,
+                z: corner.z,
+            },
+            Coordinates { x: 2, y: 3, z: 2 },
+        );
+        file.get_regions_mut().insert("below".to_string(), below);
+
+        let (name, _, local) = file.region_at((2, 7, 2)).unwrap();
+        assert_eq!(name, "test");
+        assert_eq!(local, Coordinates { x: 2, y: 4, z: 2 });
+
+        let (name, _, local) = file.region_at((1, 0, 1)).unwrap();
+        assert_eq!(name, "below");
+        assert_eq!(local, Coordinates { x: 1, y: 0, z: 1 });
+
+        assert!(file.region_at((5, 0, 5)).is_none());
+    }
+
+    #[test]
+    fn regions_ordered() {
+        let path = std::env::temp_dir().join("ritematica_regions_ordered.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+
+        for (name, y) in [("b", 0), ("a", 20), ("c", 10)] {
+            let region = Region::filled_with_air(
+                Coordinates { x: 0, y, z: 0 },
+                Coordinates { x: 1, y: 1, z: 1 },
+            );
+            file.get_regions_mut().insert(name.to_string(), region);
+        }
+
+        fn names(file: &LitematicaFile) -> Vec<&str> {
+            file.region_names_ordered().collect()
+        }
+
+        assert_eq!(names(&file), vec!["a", "b", "c", "test"]);
+
+        file.sort_regions_by(|(_, a), (_, b)| a.position.y.cmp(&b.position.y));
+        assert_eq!(names(&file), vec!["b", "test", "c", "a"]);
+
+        file.rename_region("c", "d");
+        file.get_regions_mut().remove("test");
+        assert_eq!(names(&file), vec!["b", "d", "a"]);
+
+        file.write(&path).unwrap();
+        let read = LitematicaFile::read(&path).unwrap();
+        assert_eq!(names(&read), vec!["a", "b", "d"]);
+
+        file.sort_regions();
+        assert_eq!(names(&file), vec!["a", "b", "d"]);
+    }
+
     #[test]
     fn get_regions() {
         let file = LitematicaFile::read("test.litematic").unwrap();
@@ -229,6 +1587,63 @@ mod tests {
         assert!(region.is_some());
     }
 
+    #[test]
+    fn write_with_strip_options() {
+        let path = std::env::temp_dir().join("ritematica_write_with_strip_options.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let chest = Value::Compound(
+            [
+                (
+                    "id".to_string(),
+                    Value::String("minecraft:chest".to_string()),
+                ),
+                ("x".to_string(), Value::Int(0)),
+                ("y".to_string(), Value::Int(0)),
+                ("z".to_string(), Value::Int(0)),
+                ("Items".to_string(), Value::List(Vec::new())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        region.tile_entities.push(chest);
+        region
+            .pending_block_ticks
+            .push(Value::Compound(HashMap::new()));
+
+        let options = WriteOptions {
+            integrity: true,
+            strip_entities: true,
+            strip_tile_entity_inventories: true,
+            strip_pending_ticks: true,
+            ..Default::default()
+        };
+        file.write_with_options(&path, &options).unwrap();
+
+        let written = LitematicaFile::read(&path).unwrap();
+        let region = written.get_region("test").unwrap();
+
+        assert!(region.entities.is_empty());
+        assert!(region.pending_block_ticks.is_empty());
+        assert!(region
+            .tile_entities
+            .iter()
+            .all(|tile_entity| match tile_entity {
+                Value::Compound(compound) => !compound.contains_key("Items"),
+                _ => true,
+            }));
+        assert_eq!(written.verify_integrity(), Some(true));
+
+        // the file itself is not modified
+        assert!(!file
+            .get_region("test")
+            .unwrap()
+            .pending_block_ticks
+            .is_empty());
+    }
+
     #[test]
     fn rename_region() {
         let mut file = LitematicaFile::read("test.litematic").unwrap();
@@ -0,0 +1,434 @@
+use crate::region::{index_3d, BIT_TO_LONG_SHIFT};
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// One of the three axes of a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    fn of(self, coordinates: Coordinates) -> i32 {
+        match self {
+            Axis::X => coordinates.x,
+            Axis::Y => coordinates.y,
+            Axis::Z => coordinates.z,
+        }
+    }
+}
+
+impl Region {
+    /// Sets every block of a box to `block`.
+    ///
+    /// Unlike calling `set_block()` for every position, the palette is only searched once, and whole longs of the
+    /// packed block data are written at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - One inclusive corner of the box relative to the region origin.
+    /// * `max` - The opposite inclusive corner of the box.
+    /// * `block` - The `BlockState` the box is filled with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box is not completely inside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// region.fill((0, 0, 0), (15, 0, 15), BlockStateBuilder::new("stone").build());
+    /// ```
+    pub fn fill(
+        &mut self,
+        min: impl Into<Coordinates>,
+        max: impl Into<Coordinates>,
+        block: BlockState,
+    ) {
+        let (min, max) = self.box_corners((min, max));
+        let (value, bits) = self.palette_index_or_insert(block);
+
+        // every row along the x axis is a run of consecutive indices, runs that touch are merged
+        let mut runs: Vec<(u64, u64)> = Vec::new();
+
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                let start = index_3d(self.size, Coordinates { x: min.x, y, z });
+                let end = start + (max.x - min.x) as u64;
+
+                match runs.last_mut() {
+                    Some((_, last_end)) if *last_end + 1 == start => *last_end = end,
+                    _ => runs.push((start, end)),
+                }
+            }
+        }
+
+        // the value repeated from bit 0, long enough for every phase of a long
+        let mut repeated = 0u128;
+        let mut shift = 0;
+        while shift < 64 + bits {
+            repeated |= (value as u128) << shift;
+            shift += bits;
+        }
+
+        for (start, end) in runs {
+            let first_bit = start * bits;
+            let last_bit = (end + 1) * bits - 1;
+
+            for word_index in (first_bit >> BIT_TO_LONG_SHIFT)..=(last_bit >> BIT_TO_LONG_SHIFT) {
+                let word_start = word_index << BIT_TO_LONG_SHIFT;
+                let low = first_bit.max(word_start) - word_start;
+                let high = last_bit.min(word_start + 63) - word_start;
+                let mask = (u64::MAX >> (63 - high)) & (u64::MAX << low);
+
+                let pattern = (repeated >> (word_start % bits)) as u64;
+                let word = &mut self.block_states[word_index as usize];
+
+                *word = ((*word as u64 & !mask) | (pattern & mask)) as i64;
+            }
+        }
+    }
+
+    /// Fills a box with a weighted random mix of blocks, like `50%stone,50%andesite` in WorldEdit.
+    ///
+    /// The block at every position only depends on the `seed` and the position, so filling the same box with the same seed
+    /// always produces the same result.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds` - The inclusive corners of the box relative to the region origin, e.g. the result of `BlockGroup::bounds()`.
+    /// * `blocks` - The `BlockState`s together with their relative weights.
+    /// * `seed` - The seed of the random mix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box is not completely inside of the region or if the weights add up to zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// let stone = BlockStateBuilder::new("stone").build();
+    /// let andesite = BlockStateBuilder::new("andesite").build();
+    ///
+    /// region.fill_weighted(((0, 0, 0), (4, 0, 4)), [(stone, 1), (andesite, 1)], 42);
+    /// ```
+    pub fn fill_weighted(
+        &mut self,
+        bounds: (impl Into<Coordinates>, impl Into<Coordinates>),
+        blocks: impl IntoIterator<Item = (BlockState, u32)>,
+        seed: u64,
+    ) {
+        let blocks = blocks
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect::<Vec<_>>();
+        let total = blocks.iter().map(|(_, weight)| *weight as u64).sum::<u64>();
+
+        assert!(
+            total > 0,
+            "The weights of the blocks must not add up to zero"
+        );
+
+        for position in self.box_positions(bounds) {
+            let mut roll = position_hash(seed, position) % total;
+
+            let (block, _) = blocks
+                .iter()
+                .find(|(_, weight)| {
+                    let found = roll < *weight as u64;
+                    roll = roll.saturating_sub(*weight as u64);
+                    found
+                })
+                .expect("roll is smaller than the total weight");
+
+            self.set_block(position, block.clone());
+        }
+    }
+
+    /// Fills a box with a gradient, splitting it into equally thick layers along `axis` that use the `blocks` in order.
+    ///
+    /// If the box is not evenly divisible, the layers differ in thickness by at most one block.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds` - The inclusive corners of the box relative to the region origin.
+    /// * `axis` - The `Axis` along which the gradient runs, from the lower to the higher coordinates.
+    /// * `blocks` - The `BlockState`s of the gradient, starting at the lower end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box is not completely inside of the region or if `blocks` is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    /// use ritematica::fill::Axis;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// let blocks = ["black_concrete", "gray_concrete", "white_concrete"]
+    ///     .map(|name| BlockStateBuilder::new(name).build());
+    ///
+    /// region.fill_gradient(((0, 0, 0), (5, 8, 0)), Axis::Y, &blocks);
+    /// ```
+    pub fn fill_gradient(
+        &mut self,
+        bounds: (impl Into<Coordinates>, impl Into<Coordinates>),
+        axis: Axis,
+        blocks: &[BlockState],
+    ) {
+        assert!(!blocks.is_empty(), "A gradient needs at least one block");
+
+        let positions = self.box_positions(bounds).collect::<Vec<_>>();
+
+        let start = positions.iter().map(|p| axis.of(*p)).min().unwrap_or(0);
+        let end = positions.iter().map(|p| axis.of(*p)).max().unwrap_or(0);
+        let length = (end - start + 1) as usize;
+
+        for position in positions {
+            let offset = (axis.of(position) - start) as usize;
+
+            self.set_block(position, blocks[offset * blocks.len() / length].clone());
+        }
+    }
+
+    /// Fills a box by repeating the blocks of a small `template` region in every direction.
+    ///
+    /// The template starts at the corner of the box with the lowest coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds` - The inclusive corners of the box relative to the region origin.
+    /// * `template` - The `Region` whose blocks are repeated. Its entities and tile entities are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box is not completely inside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let template = LitematicaFile::read("checkerboard.litematic").unwrap();
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// let template = template.get_region("checkerboard").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// region.fill_pattern(((0, 0, 0), (10, 0, 10)), template);
+    /// ```
+    pub fn fill_pattern(
+        &mut self,
+        bounds: (impl Into<Coordinates>, impl Into<Coordinates>),
+        template: &Region,
+    ) {
+        let positions = self.box_positions(bounds).collect::<Vec<_>>();
+        let Some(min) = positions.first().copied() else {
+            return;
+        };
+
+        for position in positions {
+            let source = Coordinates {
+                x: (position.x - min.x) % template.size.x.abs(),
+                y: (position.y - min.y) % template.size.y.abs(),
+                z: (position.z - min.z) % template.size.z.abs(),
+            };
+
+            self.set_block(position, template.get_block(source).clone());
+        }
+    }
+
+    /// Returns all positions of the box between the two inclusive corners in YZX order, after checking that it is inside of the region.
+    pub(crate) fn box_positions(
+        &self,
+        bounds: (impl Into<Coordinates>, impl Into<Coordinates>),
+    ) -> impl Iterator<Item = Coordinates> {
+        let (min, max) = self.box_corners(bounds);
+
+        (min.y..=max.y).flat_map(move |y| {
+            (min.z..=max.z).flat_map(move |z| (min.x..=max.x).map(move |x| Coordinates { x, y, z }))
+        })
+    }
+
+    /// Returns the lowest and highest corner of the box between the two inclusive corners, after checking that it is
+    /// inside of the region.
+    fn box_corners(
+        &self,
+        (first, second): (impl Into<Coordinates>, impl Into<Coordinates>),
+    ) -> (Coordinates, Coordinates) {
+        let (first, second) = (first.into(), second.into());
+
+        let min = Coordinates {
+            x: first.x.min(second.x),
+            y: first.y.min(second.y),
+            z: first.z.min(second.z),
+        };
+        let max = Coordinates {
+            x: first.x.max(second.x),
+            y: first.y.max(second.y),
+            z: first.z.max(second.z),
+        };
+
+        // only used to check the bounds
+        index_3d(self.size, min);
+        index_3d(self.size, max);
+
+        (min, max)
+    }
+}
+
+/// Mixes the seed and a position into a pseudo-random number, using the SplitMix64 finalizer for every coordinate.
+fn position_hash(seed: u64, position: Coordinates) -> u64 {
+    [position.x, position.y, position.z]
+        .into_iter()
+        .fold(seed, |hash, coordinate| {
+            split_mix(hash ^ coordinate as u32 as u64)
+        })
+}
+
+pub(crate) fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    fn region() -> Region {
+        Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 8, y: 8, z: 8 },
+        )
+    }
+
+    #[test]
+    fn fill() {
+        let blocks = ["stone", "dirt", "glass", "sand", "gravel"]
+            .map(|name| BlockStateBuilder::new(name).build());
+
+        let mut filled = region();
+        let mut expected = region();
+
+        // the fifth block grows the palette to 3 bits, so values cross the boundaries of longs
+        let boxes = [
+            ((0, 0, 0), (7, 7, 7)),
+            ((1, 2, 3), (6, 5, 3)),
+            ((7, 7, 0), (0, 0, 0)),
+            ((0, 3, 0), (7, 4, 7)),
+            ((2, 0, 5), (2, 7, 6)),
+        ];
+
+        for ((first, second), block) in boxes.into_iter().zip(blocks) {
+            filled.fill(first, second, block.clone());
+
+            for position in expected.box_positions((first, second)).collect::<Vec<_>>() {
+                expected.set_block(position, block.clone());
+            }
+
+            assert_eq!(filled.block_state_palette, expected.block_state_palette);
+            assert_eq!(filled.block_states, expected.block_states);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn fill_outside() {
+        let stone = BlockStateBuilder::new("stone").build();
+
+        region().fill((0, 0, 0), (8, 0, 0), stone);
+    }
+
+    #[test]
+    fn fill_weighted_is_deterministic() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let andesite = BlockStateBuilder::new("andesite").build();
+        let dirt = BlockStateBuilder::new("dirt").build();
+
+        let blocks = [(stone.clone(), 3), (andesite.clone(), 1), (dirt.clone(), 0)];
+
+        let mut first = region();
+        let mut second = region();
+        let mut other_seed = region();
+
+        first.fill_weighted(((0, 0, 0), (7, 7, 7)), blocks.clone(), 7);
+        second.fill_weighted(((7, 7, 7), (0, 0, 0)), blocks.clone(), 7);
+        other_seed.fill_weighted(((0, 0, 0), (7, 7, 7)), blocks, 8);
+
+        let blocks = |region: &Region| {
+            region
+                .iter_blocks()
+                .map(|(_, block)| block.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(blocks(&first), blocks(&second));
+        assert_ne!(blocks(&first), blocks(&other_seed));
+
+        let stones = blocks(&first).iter().filter(|b| **b == stone).count();
+        let andesites = blocks(&first).iter().filter(|b| **b == andesite).count();
+
+        assert_eq!(stones + andesites, 512);
+        assert!(stones > andesites);
+        assert!(!blocks(&first).contains(&dirt));
+    }
+
+    #[test]
+    fn fill_gradient_layers() {
+        let blocks = ["black_concrete", "gray_concrete", "white_concrete"]
+            .map(|name| BlockStateBuilder::new(name).build());
+
+        let mut region = region();
+        region.fill_gradient(((0, 0, 0), (7, 5, 0)), Axis::Y, &blocks);
+
+        assert_eq!(region.get_block((3, 0, 0)), &blocks[0]);
+        assert_eq!(region.get_block((3, 1, 0)), &blocks[0]);
+        assert_eq!(region.get_block((3, 2, 0)), &blocks[1]);
+        assert_eq!(region.get_block((3, 5, 0)), &blocks[2]);
+        assert_eq!(region.get_block((3, 6, 0)).get_name().get_path(), "air");
+    }
+
+    #[test]
+    fn fill_pattern_repeats_template() {
+        let stone = BlockStateBuilder::new("stone").build();
+
+        let mut template = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 2, y: 1, z: 2 },
+        );
+        template.set_block((0, 0, 0), stone.clone());
+        template.set_block((1, 0, 1), stone.clone());
+
+        let mut region = region();
+        region.fill_pattern(((1, 1, 1), (6, 1, 6)), &template);
+
+        for (position, block) in region.iter_blocks().filter(|(p, _)| p.y == 1) {
+            let inside = (1..=6).contains(&position.x) && (1..=6).contains(&position.z);
+            let checker = (position.x + position.z) % 2 == 0;
+
+            assert_eq!(block == &stone, inside && checker);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn fill_weighted_without_weights() {
+        let stone = BlockStateBuilder::new("stone").build();
+
+        region().fill_weighted(((0, 0, 0), (1, 1, 1)), [(stone, 0)], 0);
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::block::{BlockStateBuilder, BlockStatePattern};
+use crate::region::{index_3d, set_value_position, value_position};
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// A named set of block positions inside of a `Region`, e.g. all blocks belonging to the input bus of a machine.
+///
+/// Groups are stored inside of the region, so they survive writing and reading the file. Litematica itself ignores them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockGroup {
+    positions: Vec<Coordinates>,
+}
+
+impl BlockGroup {
+    /// Returns the positions of the group, in the order they were added.
+    pub fn positions(&self) -> &[Coordinates] {
+        &self.positions
+    }
+
+    /// Returns the number of positions in the group.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Checks whether the group does not contain any positions.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Checks whether the group contains the given position.
+    pub fn contains(&self, position: impl Into<Coordinates>) -> bool {
+        self.positions.contains(&position.into())
+    }
+
+    pub(crate) fn retain(&mut self, keep: impl FnMut(&Coordinates) -> bool) {
+        self.positions.retain(keep);
+    }
+
+    /// Returns the inclusive minimum and maximum corner of the group, or `None` if it is empty.
+    pub fn bounds(&self) -> Option<(Coordinates, Coordinates)> {
+        let first = *self.positions.first()?;
+
+        Some(
+            self.positions
+                .iter()
+                .fold((first, first), |(min, max), position| {
+                    (
+                        Coordinates {
+                            x: min.x.min(position.x),
+                            y: min.y.min(position.y),
+                            z: min.z.min(position.z),
+                        },
+                        Coordinates {
+                            x: max.x.max(position.x),
+                            y: max.y.max(position.y),
+                            z: max.z.max(position.z),
+                        },
+                    )
+                }),
+        )
+    }
+}
+
+// positions are stored as a flat int array of x, y, z triples
+impl Serialize for BlockGroup {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let flat = self
+            .positions
+            .iter()
+            .flat_map(|position| [position.x, position.y, position.z]);
+
+        nbt::i32_array(flat, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockGroup {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let flat = Vec::<i32>::deserialize(deserializer)?;
+
+        if flat.len() % 3 != 0 {
+            return Err(serde::de::Error::invalid_length(
+                flat.len(),
+                &"a multiple of 3 coordinates",
+            ));
+        }
+
+        let positions = flat
+            .chunks_exact(3)
+            .map(|chunk| Coordinates {
+                x: chunk[0],
+                y: chunk[1],
+                z: chunk[2],
+            })
+            .collect();
+
+        Ok(Self { positions })
+    }
+}
+
+impl Region {
+    /// Creates a named group of block positions, replacing any existing group with the same name.
+    ///
+    /// Duplicate positions are only stored once.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the group.
+    /// * `positions` - The positions of the blocks relative to the region origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any position is outside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// region.create_group("input_bus", [(0, 0, 0), (1, 0, 0)]);
+    ///
+    /// assert_eq!(region.get_group("input_bus").unwrap().len(), 2);
+    /// ```
+    pub fn create_group(
+        &mut self,
+        name: impl Into<String>,
+        positions: impl IntoIterator<Item = impl Into<Coordinates>>,
+    ) {
+        let mut group = BlockGroup::default();
+        let mut seen = HashSet::new();
+
+        for position in positions {
+            let position = position.into();
+
+            // only used to check the bounds
+            index_3d(self.size, position);
+
+            if seen.insert(position) {
+                group.positions.push(position);
+            }
+        }
+
+        self.extensions.groups.insert(name.into(), group);
+    }
+
+    /// Returns the group with the given name, if it exists.
+    pub fn get_group(&self, name: &str) -> Option<&BlockGroup> {
+        self.extensions.groups.get(name)
+    }
+
+    /// Returns an iterator over the names of all groups, sorted by name.
+    pub fn group_names(&self) -> impl Iterator<Item = &str> {
+        self.extensions.groups.keys().map(|s| s.as_str())
+    }
+
+    /// Removes the group with the given name and returns it. The blocks of the group are not changed.
+    pub fn remove_group(&mut self, name: &str) -> Option<BlockGroup> {
+        self.extensions.groups.remove(name)
+    }
+
+    /// Returns an iterator over the positions and `BlockState`s of the group with the given name.
+    ///
+    /// The iterator is empty if the group does not exist.
+    pub fn group_blocks<'a>(
+        &'a self,
+        name: &str,
+    ) -> impl Iterator<Item = (Coordinates, &'a BlockState)> + 'a {
+        self.extensions
+            .groups
+            .get(name)
+            .into_iter()
+            .flat_map(|group| group.positions.iter())
+            .map(|position| (*position, self.get_block(*position)))
+    }
+
+    /// Sets every block of the group with the given name to `block`.
+    ///
+    /// Returns `false` if the group does not exist.
+    pub fn fill_group(&mut self, name: &str, block: BlockState) -> bool {
+        let Some(group) = self.extensions.groups.get(name) else {
+            return false;
+        };
+
+        for position in group.positions.clone() {
+            self.set_block(position, block.clone());
+        }
+
+        true
+    }
+
+    /// Replaces every block of the group with the given name that matches `pattern` with `block`.
+    ///
+    /// Returns the number of replaced blocks.
+    pub fn replace_in_group(
+        &mut self,
+        name: &str,
+        pattern: &impl BlockStatePattern,
+        block: BlockState,
+    ) -> usize {
+        let matching = self
+            .group_blocks(name)
+            .filter(|(_, state)| pattern.matches(state))
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+
+        for position in &matching {
+            self.set_block(*position, block.clone());
+        }
+
+        matching.len()
+    }
+
+    /// Moves the blocks of the group with the given name by `offset`, leaving air behind, and updates the group positions.
+    ///
+    /// Tile entities, pending ticks, annotations and anchors at the moved blocks are moved along with them.
+    /// Blocks that are not part of the group are overwritten if they are in the way, together with their tile entities,
+    /// pending ticks and annotations.
+    /// Returns `false` if the group does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any moved block would end up outside of the region. The region is not modified in this case.
+    pub fn translate_group(&mut self, name: &str, offset: impl Into<Coordinates>) -> bool {
+        let offset = offset.into();
+
+        let Some(group) = self.extensions.groups.get(name) else {
+            return false;
+        };
+
+        let moved = group
+            .positions
+            .iter()
+            .map(|position| {
+                let target = Coordinates {
+                    x: position.x + offset.x,
+                    y: position.y + offset.y,
+                    z: position.z + offset.z,
+                };
+
+                // only used to check the bounds before anything is modified
+                index_3d(self.size, target);
+
+                (*position, target, self.get_block(*position).clone())
+            })
+            .collect::<Vec<_>>();
+
+        let air = BlockStateBuilder::new("air").build();
+
+        for (source, _, _) in &moved {
+            self.set_block(*source, air.clone());
+        }
+
+        for (_, target, block) in &moved {
+            self.set_block(*target, block.clone());
+        }
+
+        let moves = moved
+            .iter()
+            .map(|(source, target, _)| (*source, *target))
+            .collect::<HashMap<_, _>>();
+        // targets that are not part of the group themselves, whose data is overwritten
+        let overwritten = moves
+            .values()
+            .filter(|target| !moves.contains_key(target))
+            .copied()
+            .collect::<HashSet<_>>();
+
+        for values in [
+            &mut self.tile_entities,
+            &mut self.pending_block_ticks,
+            &mut self.pending_fluid_ticks,
+        ] {
+            values.retain(|value| !value_position(value).is_some_and(|p| overwritten.contains(&p)));
+
+            for value in values.iter_mut() {
+                if let Some(target) = value_position(value).and_then(|p| moves.get(&p)) {
+                    set_value_position(value, *target);
+                }
+            }
+        }
+
+        let annotations = &mut self.extensions.annotations;
+
+        for position in &overwritten {
+            annotations.remove(*position);
+        }
+
+        let moved_annotations = moves
+            .iter()
+            .filter_map(|(source, target)| Some((*target, annotations.remove(*source)?)))
+            .collect::<Vec<_>>();
+
+        for (target, data) in moved_annotations {
+            annotations.insert(target, data);
+        }
+
+        for position in self.extensions.anchors.values_mut() {
+            if let Some(target) = moves.get(position) {
+                *position = *target;
+            }
+        }
+
+        let group = self.extensions.groups.get_mut(name).expect("group exists");
+        group.positions = moved.into_iter().map(|(_, target, _)| target).collect();
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nbt::Value;
+
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn groups_round_trip() {
+        let path = std::env::temp_dir().join("ritematica_groups_round_trip.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.create_group("rails", [(2, 4, 2), (2, 4, 2), (0, 0, 0)]);
+        file.write(&path).unwrap();
+
+        let file = LitematicaFile::read(&path).unwrap();
+        let region = file.get_region("test").unwrap();
+        let group = region.get_group("rails").unwrap();
+
+        assert_eq!(region.group_names().collect::<Vec<_>>(), vec!["rails"]);
+        assert_eq!(
+            group.positions(),
+            &[
+                Coordinates { x: 2, y: 4, z: 2 },
+                Coordinates { x: 0, y: 0, z: 0 }
+            ]
+        );
+        assert_eq!(
+            group.bounds(),
+            Some((
+                Coordinates { x: 0, y: 0, z: 0 },
+                Coordinates { x: 2, y: 4, z: 2 }
+            ))
+        );
+    }
+
+    #[test]
+    fn translate_group() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+        let rail = region.get_block((2, 4, 2)).clone();
+
+        region.create_group("rail", [(2, 4, 2)]);
+        assert!(region.translate_group("rail", (1, 0, 0)));
+
+        assert_eq!(region.get_block((3, 4, 2)), &rail);
+        assert_eq!(region.get_block((2, 4, 2)).get_name().get_path(), "air");
+        assert!(region.get_group("rail").unwrap().contains((3, 4, 2)));
+    }
+
+    #[test]
+    fn translate_group_moves_block_data() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let tile_entity = |x: i32| {
+            Value::Compound(
+                [
+                    ("x".to_string(), Value::Int(x)),
+                    ("y".to_string(), Value::Int(0)),
+                    ("z".to_string(), Value::Int(0)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+
+        region.tile_entities = vec![tile_entity(0), tile_entity(2)];
+        region.pending_block_ticks = vec![tile_entity(0)];
+        region.annotate((0, 0, 0), Value::String("moved".to_string()));
+        region.annotate((2, 0, 0), Value::String("overwritten".to_string()));
+        region.set_anchor("start", (0, 0, 0));
+        region.set_anchor("other", (5, 0, 0));
+
+        region.create_group("pair", [(0, 0, 0), (1, 0, 0)]);
+        assert!(region.translate_group("pair", (1, 0, 0)));
+
+        let positions =
+            |values: &[Value]| values.iter().filter_map(value_position).collect::<Vec<_>>();
+
+        assert_eq!(
+            positions(&region.tile_entities),
+            vec![Coordinates { x: 1, y: 0, z: 0 }]
+        );
+        assert_eq!(
+            positions(&region.pending_block_ticks),
+            vec![Coordinates { x: 1, y: 0, z: 0 }]
+        );
+        assert_eq!(region.annotations().len(), 1);
+        assert_eq!(
+            region.annotations().get((1, 0, 0)),
+            Some(&Value::String("moved".to_string()))
+        );
+        assert_eq!(region.get_anchor("start"), Some((1, 0, 0).into()));
+        assert_eq!(region.get_anchor("other"), Some((5, 0, 0).into()));
+    }
+
+    #[test]
+    fn fill_and_replace_in_group() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.create_group("corner", [(0, 0, 0), (1, 0, 0)]);
+
+        let stone = BlockStateBuilder::new("stone").build();
+        let glass = BlockStateBuilder::new("glass").build();
+
+        assert!(region.fill_group("corner", stone.clone()));
+        assert_eq!(region.replace_in_group("corner", &stone, glass.clone()), 2);
+        assert!(region
+            .group_blocks("corner")
+            .all(|(_, block)| block == &glass));
+        assert!(!region.fill_group("missing", stone));
+    }
+}
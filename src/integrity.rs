@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use crc32fast::Hasher;
+use nbt::Value;
+
+use crate::structure::{Coordinates, LitematicaFile, Metadata, Region};
+
+impl LitematicaFile {
+    /// Computes a CRC32 checksum over the normalized contents of the file.
+    ///
+    /// The checksum covers the metadata, the versions, the provenance and every region (sorted by name) including its
+    /// palette, block data, entities, tile entities, pending ticks, annotations, groups and anchors.
+    /// It does not depend on the order in which the NBT compounds were written, so it is stable across round-trips.
+    /// The stored checksum and the checksum chain of the provenance are not covered, since they are made of checksums.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let checksum = file.compute_checksum();
+    /// ```
+    pub fn compute_checksum(&self) -> u32 {
+        self.checksum_with(&self.metadata, &self.regions)
+    }
+
+    /// Computes the checksum of the file as if it contained `metadata` and `regions` instead of its own.
+    pub(crate) fn checksum_with(
+        &self,
+        metadata: &Metadata,
+        regions: &HashMap<String, Region>,
+    ) -> u32 {
+        let mut hasher = Hasher::new();
+
+        hasher.update(&self.minecraft_data_version.to_le_bytes());
+        hasher.update(&self.version.to_le_bytes());
+
+        update_str(&mut hasher, &metadata.name);
+        update_str(&mut hasher, &metadata.author);
+        update_str(&mut hasher, &metadata.description);
+        update_coords(&mut hasher, metadata.enclosing_size);
+        hasher.update(&metadata.total_volume.to_le_bytes());
+        hasher.update(&metadata.total_blocks.to_le_bytes());
+        hasher.update(&metadata.region_count.to_le_bytes());
+        hasher.update(&metadata.time_created.to_le_bytes());
+        hasher.update(&metadata.time_modified.to_le_bytes());
+
+        let provenance = self.extensions.provenance.as_ref();
+        update_option(&mut hasher, provenance.and_then(|p| p.tooling.as_deref()));
+        update_option(
+            &mut hasher,
+            provenance.and_then(|p| p.source_url.as_deref()),
+        );
+        update_option(&mut hasher, provenance.and_then(|p| p.license.as_deref()));
+
+        let mut names = regions.keys().collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            let region = &regions[name];
+
+            update_str(&mut hasher, name);
+            update_coords(&mut hasher, region.position);
+            update_coords(&mut hasher, region.size);
+
+            hasher.update(&(region.block_state_palette.len() as u64).to_le_bytes());
+
+            for block_state in &region.block_state_palette {
+                update_str(&mut hasher, &block_state.name.to_string());
+
+                hasher.update(&(block_state.properties.len() as u64).to_le_bytes());
+
+                for (key, value) in block_state.properties.iter() {
+                    update_str(&mut hasher, key);
+                    update_str(&mut hasher, value);
+                }
+            }
+
+            hasher.update(&(region.block_states.len() as u64).to_le_bytes());
+
+            for word in &region.block_states {
+                hasher.update(&word.to_le_bytes());
+            }
+
+            hasher.update(&(region.entities.len() as u64).to_le_bytes());
+
+            for entity in &region.entities {
+                let mut bytes = Vec::new();
+                nbt::to_writer(&mut bytes, entity, None).expect("Entities always serialize to NBT");
+
+                hasher.update(&(bytes.len() as u64).to_le_bytes());
+                hasher.update(&bytes);
+            }
+
+            for values in [
+                &region.tile_entities,
+                &region.pending_block_ticks,
+                &region.pending_fluid_ticks,
+            ] {
+                hasher.update(&(values.len() as u64).to_le_bytes());
+
+                for value in values {
+                    update_value(&mut hasher, value);
+                }
+            }
+
+            let extensions = &region.extensions;
+
+            let mut annotations = extensions.annotations.iter().collect::<Vec<_>>();
+            annotations.sort_by_key(|(position, _)| (position.y, position.z, position.x));
+
+            hasher.update(&(annotations.len() as u64).to_le_bytes());
+
+            for (position, value) in annotations {
+                update_coords(&mut hasher, position);
+                update_value(&mut hasher, value);
+            }
+
+            hasher.update(&(extensions.groups.len() as u64).to_le_bytes());
+
+            for (name, group) in &extensions.groups {
+                update_str(&mut hasher, name);
+                hasher.update(&(group.len() as u64).to_le_bytes());
+
+                for position in group.positions() {
+                    update_coords(&mut hasher, *position);
+                }
+            }
+
+            hasher.update(&(extensions.anchors.len() as u64).to_le_bytes());
+
+            for (name, position) in &extensions.anchors {
+                update_str(&mut hasher, name);
+                update_coords(&mut hasher, *position);
+            }
+        }
+
+        hasher.finalize()
+    }
+
+    /// Returns the checksum stored in the file, if it was written with `WriteOptions::integrity` enabled.
+    pub fn get_stored_checksum(&self) -> Option<u32> {
+        self.extensions.checksum.map(|checksum| checksum as u32)
+    }
+
+    /// Verifies the contents of the file against the checksum stored when it was written.
+    ///
+    /// Returns `None` if the file does not contain a checksum, `Some(true)` if the contents match
+    /// and `Some(false)` if the file was modified or corrupted after the checksum was written.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{LitematicaFile, WriteOptions};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// file.write_with_options("test2.litematic", &WriteOptions { integrity: true, ..Default::default() }).unwrap();
+    ///
+    /// let file = LitematicaFile::read("test2.litematic").unwrap();
+    /// assert_eq!(file.verify_integrity(), Some(true));
+    /// ```
+    pub fn verify_integrity(&self) -> Option<bool> {
+        self.get_stored_checksum()
+            .map(|checksum| checksum == self.compute_checksum())
+    }
+}
+
+fn update_str(hasher: &mut Hasher, s: &str) {
+    hasher.update(&(s.len() as u64).to_le_bytes());
+    hasher.update(s.as_bytes());
+}
+
+fn update_option(hasher: &mut Hasher, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            hasher.update(&[1]);
+            update_str(hasher, s);
+        }
+        None => hasher.update(&[0]),
+    }
+}
+
+/// Hashes an NBT value, with the keys of compounds sorted so the result doesn't depend on the order of the
+/// underlying `HashMap`.
+///
+/// Numbers are hashed by value and arrays like lists, since deserializing an NBT value picks the smallest tag that
+/// fits, e.g. an `Int(0)` is read back as a `Byte(0)`.
+fn update_value(hasher: &mut Hasher, value: &Value) {
+    match value {
+        Value::Byte(v) => update_int(hasher, *v as i64),
+        Value::Short(v) => update_int(hasher, *v as i64),
+        Value::Int(v) => update_int(hasher, *v as i64),
+        Value::Long(v) => update_int(hasher, *v),
+        Value::Float(v) => update_float(hasher, *v as f64),
+        Value::Double(v) => update_float(hasher, *v),
+        Value::String(v) => {
+            hasher.update(b"s");
+            update_str(hasher, v);
+        }
+        Value::ByteArray(values) => {
+            update_list_len(hasher, values.len());
+            values.iter().for_each(|v| update_int(hasher, *v as i64));
+        }
+        Value::IntArray(values) => {
+            update_list_len(hasher, values.len());
+            values.iter().for_each(|v| update_int(hasher, *v as i64));
+        }
+        Value::LongArray(values) => {
+            update_list_len(hasher, values.len());
+            values.iter().for_each(|v| update_int(hasher, *v));
+        }
+        Value::List(values) => {
+            update_list_len(hasher, values.len());
+            values.iter().for_each(|v| update_value(hasher, v));
+        }
+        Value::Compound(entries) => {
+            let mut keys = entries.keys().collect::<Vec<_>>();
+            keys.sort();
+
+            hasher.update(b"c");
+            hasher.update(&(keys.len() as u64).to_le_bytes());
+
+            for key in keys {
+                update_str(hasher, key);
+                update_value(hasher, &entries[key]);
+            }
+        }
+    }
+}
+
+fn update_int(hasher: &mut Hasher, v: i64) {
+    hasher.update(b"i");
+    hasher.update(&v.to_le_bytes());
+}
+
+fn update_float(hasher: &mut Hasher, v: f64) {
+    hasher.update(b"f");
+    hasher.update(&v.to_le_bytes());
+}
+
+fn update_list_len(hasher: &mut Hasher, len: usize) {
+    hasher.update(b"l");
+    hasher.update(&(len as u64).to_le_bytes());
+}
+
+fn update_coords(hasher: &mut Hasher, coords: Coordinates) {
+    hasher.update(&coords.x.to_le_bytes());
+    hasher.update(&coords.y.to_le_bytes());
+    hasher.update(&coords.z.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file::WriteOptions;
+
+    use super::*;
+
+    #[test]
+    fn verify_integrity() {
+        let path = std::env::temp_dir().join("ritematica_verify_integrity.litematic");
+
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        assert_eq!(file.verify_integrity(), None);
+
+        let options = WriteOptions {
+            integrity: true,
+            ..Default::default()
+        };
+        file.write_with_options(&path, &options).unwrap();
+
+        let mut file = LitematicaFile::read(&path).unwrap();
+        assert_eq!(file.verify_integrity(), Some(true));
+
+        file.metadata.author.push('!');
+        assert_eq!(file.verify_integrity(), Some(false));
+    }
+
+    #[test]
+    fn checksum_covers_contents() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let mut checksum = file.compute_checksum();
+
+        let changes: [fn(&mut LitematicaFile); 4] = [
+            |file| {
+                let region = file.get_region_mut("test").unwrap();
+                region.tile_entities[0] = Value::Compound(HashMap::new());
+            },
+            |file| {
+                let region = file.get_region_mut("test").unwrap();
+                region.annotate((0, 0, 0), Value::String("entrance".to_string()));
+            },
+            |file| {
+                let region = file.get_region_mut("test").unwrap();
+                region.set_anchor("origin", (1, 1, 1));
+            },
+            |file| file.provenance_mut().license = Some("CC0-1.0".to_string()),
+        ];
+
+        for change in changes {
+            change(&mut file);
+
+            let changed = file.compute_checksum();
+            assert_ne!(changed, checksum);
+            checksum = changed;
+        }
+
+        // the checksum chain is not covered, so chaining doesn't change the checksum
+        assert_eq!(file.chain_checksum(), checksum);
+        assert_eq!(file.compute_checksum(), checksum);
+
+        let bytes = file.to_bytes().unwrap();
+        let file = LitematicaFile::from_bytes(&bytes).unwrap();
+        assert_eq!(file.compute_checksum(), checksum);
+    }
+}
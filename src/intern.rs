@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::structure::{BlockState, LitematicaFile, Region};
+
+/// A thread-safe interner that deduplicates identical `BlockState`s across many files, e.g. for servers that keep
+/// hundreds of schematics in memory at once.
+///
+/// Clones of a `BlockState` share its name, properties and canonical string. Interning replaces every palette entry
+/// with a clone of the first equal `BlockState` the registry has seen, so the same block state in different files
+/// or regions only takes up memory once. Changing an interned `BlockState` afterwards gives it its own copy again.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::intern::StateRegistry;
+///
+/// let registry = StateRegistry::new();
+/// let mut files = Vec::new();
+///
+/// for entry in std::fs::read_dir("schematics").unwrap() {
+///     let mut file = LitematicaFile::read(entry.unwrap().path()).unwrap();
+///     registry.intern_file(&mut file);
+///     files.push(file);
+/// }
+///
+/// println!("{} distinct block states", registry.len());
+/// ```
+#[derive(Debug, Default)]
+pub struct StateRegistry {
+    states: Mutex<HashSet<BlockState>>,
+}
+
+impl StateRegistry {
+    /// Creates an empty `StateRegistry`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a `BlockState` equal to `block_state` that shares its memory with every other one returned for it.
+    pub fn intern(&self, block_state: &BlockState) -> BlockState {
+        let mut states = self.lock();
+
+        if let Some(interned) = states.get(block_state) {
+            return interned.clone();
+        }
+
+        states.insert(block_state.clone());
+        block_state.clone()
+    }
+
+    /// Replaces every palette entry of `region` with its interned `BlockState`.
+    ///
+    /// Returns the number of palette entries that now share memory with an entry interned before.
+    pub fn intern_region(&self, region: &mut Region) -> usize {
+        let mut states = self.lock();
+        let mut shared = 0;
+
+        for block_state in &mut region.block_state_palette {
+            match states.get(block_state) {
+                Some(interned) => {
+                    if !Arc::ptr_eq(&interned.canonical, &block_state.canonical) {
+                        *block_state = interned.clone();
+                        shared += 1;
+                    }
+                }
+                None => {
+                    states.insert(block_state.clone());
+                }
+            }
+        }
+
+        shared
+    }
+
+    /// Replaces every palette entry of every region of `file` with its interned `BlockState`.
+    ///
+    /// Returns the number of palette entries that now share memory with an entry interned before.
+    pub fn intern_file(&self, file: &mut LitematicaFile) -> usize {
+        file.regions
+            .values_mut()
+            .map(|region| self.intern_region(region))
+            .sum()
+    }
+
+    /// Returns the number of distinct block states in the registry.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the registry contains no block states.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes every block state that is not used outside of the registry anymore, e.g. after files were dropped.
+    ///
+    /// Returns the number of removed block states.
+    pub fn purge(&self) -> usize {
+        let mut states = self.lock();
+        let before = states.len();
+
+        states.retain(|block_state| Arc::strong_count(&block_state.canonical) > 1);
+
+        before - states.len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<BlockState>> {
+        // the set is never left half-updated, so it is still usable after a panic in another thread
+        self.states
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let registry = StateRegistry::new();
+
+        let mut first = LitematicaFile::read("test.litematic").unwrap();
+        let mut second = LitematicaFile::read("test.litematic").unwrap();
+
+        assert_eq!(registry.intern_file(&mut first), 0);
+        let distinct = registry.len();
+        assert_eq!(
+            registry.intern_file(&mut second),
+            second.get_region("test").unwrap().block_state_palette.len()
+        );
+        assert_eq!(registry.len(), distinct);
+        assert_eq!(registry.intern_file(&mut second), 0);
+
+        let first_palette = &first.get_region("test").unwrap().block_state_palette;
+        let second_palette = &second.get_region("test").unwrap().block_state_palette;
+        assert!(Arc::ptr_eq(
+            &first_palette[1].properties,
+            &second_palette[1].properties
+        ));
+
+        // changing an interned block state doesn't change the others
+        let mut changed = registry.intern(&first_palette[1]);
+        changed.set_name("glass");
+        assert_ne!(changed, first_palette[1]);
+        assert_eq!(first_palette[1], second_palette[1]);
+
+        let stone = registry.intern(&BlockStateBuilder::new("stone").build());
+        assert_eq!(registry.purge(), 0);
+
+        drop(stone);
+        drop(first);
+        drop(second);
+        assert_eq!(registry.purge(), distinct + 1);
+        assert!(registry.is_empty());
+    }
+}
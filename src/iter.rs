@@ -0,0 +1,456 @@
+use nbt::Value;
+
+use crate::block::BlockStatePattern;
+use crate::region::{read_palette_index, value_position};
+use crate::resource_location::ResourceLocation;
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// An iterator over every position of a `Region` together with its `BlockState`, in YZX order.
+///
+/// Created by `Region::iter_blocks()`.
+#[derive(Debug, Clone)]
+pub struct Blocks<'a> {
+    region: &'a Region,
+    index: u64,
+    volume: u64,
+    required_bits: u64,
+    bitmask: u32,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = (Coordinates, &'a BlockState);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.volume {
+            return None;
+        }
+
+        let index = self.index;
+        self.index += 1;
+
+        let coords = coordinates_of(index, self.region.size);
+
+        let palette_index = read_palette_index(
+            &self.region.block_states,
+            index,
+            self.required_bits,
+            self.bitmask,
+        );
+
+        Some((
+            coords,
+            &self.region.block_state_palette[palette_index as usize],
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.volume - self.index) as usize;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Blocks<'_> {}
+
+/// Iterates over every position of the region together with its `BlockState`, like `Region::iter_blocks()`.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+/// let region = file.get_region("test").unwrap();
+///
+/// for (position, block) in region {
+///     println!("{:?}: {}", position, block);
+/// }
+/// ```
+impl<'a> IntoIterator for &'a Region {
+    type Item = (Coordinates, &'a BlockState);
+    type IntoIter = Blocks<'a>;
+
+    fn into_iter(self) -> Blocks<'a> {
+        self.iter_blocks()
+    }
+}
+
+/// An iterator over the positions of all blocks that use one palette entry of a `Region`, in YZX order.
+///
+/// Created by `Region::positions_by_palette()`.
+#[derive(Debug, Clone)]
+pub struct PalettePositions<'a> {
+    region: &'a Region,
+    palette_index: u32,
+    index: u64,
+    remaining: usize,
+    required_bits: u64,
+    bitmask: u32,
+}
+
+impl Iterator for PalettePositions<'_> {
+    type Item = Coordinates;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // stops right after the last match instead of scanning the rest of the region
+        if self.remaining == 0 {
+            return None;
+        }
+
+        loop {
+            let index = self.index;
+            self.index += 1;
+
+            let palette_index = read_palette_index(
+                &self.region.block_states,
+                index,
+                self.required_bits,
+                self.bitmask,
+            );
+
+            if palette_index == self.palette_index {
+                self.remaining -= 1;
+
+                return Some(coordinates_of(index, self.region.size));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for PalettePositions<'_> {}
+
+/// An iterator that only yields blocks matching a `BlockStatePattern`. Created by `BlockIteratorExt::only()`.
+#[derive(Debug, Clone)]
+pub struct Only<'p, I, P: ?Sized> {
+    inner: I,
+    pattern: &'p P,
+}
+
+impl<'a, I, P> Iterator for Only<'_, I, P>
+where
+    I: Iterator<Item = (Coordinates, &'a BlockState)>,
+    P: BlockStatePattern + ?Sized,
+{
+    type Item = (Coordinates, &'a BlockState);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pattern = self.pattern;
+
+        self.inner.find(|(_, block)| pattern.matches(block))
+    }
+}
+
+/// An iterator that only yields blocks with a specific name, ignoring their properties. Created by `BlockIteratorExt::named()`.
+#[derive(Debug, Clone)]
+pub struct Named<I> {
+    inner: I,
+    name: ResourceLocation,
+}
+
+impl<'a, I> Iterator for Named<I>
+where
+    I: Iterator<Item = (Coordinates, &'a BlockState)>,
+{
+    type Item = (Coordinates, &'a BlockState);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = &self.name;
+
+        self.inner.find(|(_, block)| block.get_name() == name)
+    }
+}
+
+/// An iterator that only yields the positions of blocks. Created by `BlockIteratorExt::positions()`.
+#[derive(Debug, Clone)]
+pub struct Positions<I> {
+    inner: I,
+}
+
+impl<'a, I> Iterator for Positions<I>
+where
+    I: Iterator<Item = (Coordinates, &'a BlockState)>,
+{
+    type Item = Coordinates;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(coords, _)| coords)
+    }
+}
+
+/// Adapters for iterators over `(Coordinates, &BlockState)` pairs, such as `Region::iter_blocks()`.
+///
+/// # Examples
+/// ```
+/// use ritematica::{BlockIteratorExt, LitematicaFile};
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+/// let region = file.get_region("test").unwrap();
+///
+/// let hoppers = region.iter_blocks().named("minecraft:hopper").positions().count();
+/// ```
+pub trait BlockIteratorExt<'a>: Iterator<Item = (Coordinates, &'a BlockState)> + Sized {
+    /// Only yields blocks that match the given `pattern`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A `BlockStatePattern`, e.g. a `BlockState` or a closure.
+    fn only<P: BlockStatePattern + ?Sized>(self, pattern: &P) -> Only<'_, Self, P> {
+        Only {
+            inner: self,
+            pattern,
+        }
+    }
+
+    /// Only yields blocks with the given name, regardless of their properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the block as a `ResourceLocation` or a `String` in the format `namespace:name`. If no namespace is provided, `minecraft` is assumed.
+    fn named(self, name: impl Into<ResourceLocation>) -> Named<Self> {
+        Named {
+            inner: self,
+            name: name.into(),
+        }
+    }
+
+    /// Only yields the positions of the blocks.
+    fn positions(self) -> Positions<Self> {
+        Positions { inner: self }
+    }
+}
+
+impl<'a, I> BlockIteratorExt<'a> for I where I: Iterator<Item = (Coordinates, &'a BlockState)> {}
+
+impl Region {
+    /// Returns an iterator over every position of the region together with its `BlockState`, in YZX order.
+    ///
+    /// The iterator can be narrowed down with the adapters of `BlockIteratorExt`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockIteratorExt, LitematicaFile};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for (position, block) in region.iter_blocks().named("minecraft:powered_rail") {
+    ///     println!("{:?}: {}", position, block);
+    /// }
+    /// ```
+    pub fn iter_blocks(&self) -> Blocks<'_> {
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+
+        Blocks {
+            region: self,
+            index: 0,
+            volume: self.calc_volume(),
+            required_bits,
+            bitmask: (1 << required_bits) - 1,
+        }
+    }
+
+    /// Returns every palette entry of the region together with an iterator over the positions of the blocks using it.
+    ///
+    /// The block data is read once up front to count the blocks of each entry. Each iterator then decodes the block
+    /// data on demand while iterating, without collecting the positions, and stops after its last block. Unused palette
+    /// entries get an empty iterator.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for (block, positions) in region.positions_by_palette() {
+    ///     println!("{}: {}", block, positions.len());
+    /// }
+    /// ```
+    pub fn positions_by_palette(&self) -> Vec<(&BlockState, PalettePositions<'_>)> {
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let mut counts = vec![0; self.block_state_palette.len()];
+
+        for index in 0..self.calc_volume() {
+            let palette_index =
+                read_palette_index(&self.block_states, index, required_bits, bitmask);
+            counts[palette_index as usize] += 1;
+        }
+
+        self.block_state_palette
+            .iter()
+            .zip(counts)
+            .enumerate()
+            .map(|(palette_index, (block, count))| {
+                let positions = PalettePositions {
+                    region: self,
+                    palette_index: palette_index as u32,
+                    index: 0,
+                    remaining: count,
+                    required_bits,
+                    bitmask,
+                };
+
+                (block, positions)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the tile entities of the region together with their positions, which are read from
+    /// their `x`, `y` and `z` fields. Tile entities without a valid position are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for (position, tile_entity) in region.iter_tile_entities() {
+    ///     println!("{:?}: {:?}", position, tile_entity);
+    /// }
+    /// ```
+    pub fn iter_tile_entities(&self) -> impl Iterator<Item = (Coordinates, &Value)> {
+        self.tile_entities
+            .iter()
+            .filter_map(|value| Some((value_position(value)?, value)))
+    }
+
+    /// Returns an iterator over the tile entities of the region together with their positions, which allows modifying
+    /// them. Changing the position fields of a tile entity moves it to another block.
+    pub fn iter_tile_entities_mut(&mut self) -> impl Iterator<Item = (Coordinates, &mut Value)> {
+        self.tile_entities
+            .iter_mut()
+            .filter_map(|value| Some((value_position(value)?, value)))
+    }
+}
+
+/// Converts an index into the block data of a region with the given size into a position relative to its origin.
+pub(crate) fn coordinates_of(index: u64, size: Coordinates) -> Coordinates {
+    let size_x = size.x.unsigned_abs() as u64;
+    let size_z = size.z.unsigned_abs() as u64;
+
+    Coordinates {
+        x: (index % size_x) as i32,
+        y: (index / (size_x * size_z)) as i32,
+        z: ((index / size_x) % size_z) as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn iter_blocks_matches_get_block() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        assert_eq!(region.iter_blocks().len() as u64, region.calc_volume());
+
+        for (position, block) in region.iter_blocks().step_by(97) {
+            assert_eq!(block, region.get_block(position));
+        }
+
+        let mut count = 0;
+        for ((position, block), expected) in region.into_iter().zip(region.iter_blocks()) {
+            assert_eq!((position, block), expected);
+            count += 1;
+        }
+        assert_eq!(count, region.calc_volume());
+    }
+
+    #[test]
+    fn positions_by_palette() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let groups = region.positions_by_palette();
+        let mut total = 0;
+
+        assert_eq!(groups.len(), region.block_state_palette.len());
+
+        for (block, positions) in groups {
+            let len = positions.len();
+            let mut count = 0;
+
+            for position in positions {
+                count += 1;
+                assert_eq!(region.get_block(position), block);
+                total += 1;
+            }
+
+            assert_eq!(count, len);
+        }
+
+        assert_eq!(total, region.calc_volume());
+    }
+
+    #[test]
+    fn iter_tile_entities() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let chest = Value::Compound(
+            [
+                (
+                    "id".to_string(),
+                    Value::String("minecraft:chest".to_string()),
+                ),
+                ("x".to_string(), Value::Int(1)),
+                ("y".to_string(), Value::Int(2)),
+                ("z".to_string(), Value::Int(3)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        region.tile_entities = vec![chest, Value::Compound(Default::default())];
+
+        for (_, tile_entity) in region.iter_tile_entities_mut() {
+            if let Value::Compound(compound) = tile_entity {
+                compound.insert("CustomName".to_string(), Value::String("loot".to_string()));
+            }
+        }
+
+        let tile_entities = region.iter_tile_entities().collect::<Vec<_>>();
+
+        assert_eq!(tile_entities.len(), 1);
+        assert_eq!(tile_entities[0].0, Coordinates { x: 1, y: 2, z: 3 });
+        assert!(matches!(
+            tile_entities[0].1,
+            Value::Compound(compound) if compound.contains_key("CustomName")
+        ));
+    }
+
+    #[test]
+    fn adapters() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let named = region
+            .iter_blocks()
+            .named("powered_rail")
+            .positions()
+            .collect::<Vec<_>>();
+
+        assert!(named.contains(&Coordinates { x: 2, y: 4, z: 2 }));
+
+        let pattern = region.get_block((2, 4, 2)).clone();
+        let only = region.iter_blocks().only(&pattern).positions().count();
+
+        assert!(only > 0 && only <= named.len());
+
+        let stone = BlockStateBuilder::new("stone").build();
+        let is_air = |block: &BlockState| block.get_name().get_path() == "air";
+
+        assert_eq!(region.iter_blocks().only(&stone).count(), 0);
+        assert!(region.iter_blocks().only(&is_air).count() > 0);
+    }
+}
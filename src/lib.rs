@@ -1,6 +1,57 @@
+pub mod analysis;
+pub mod anchor;
+pub mod annotation;
+#[cfg(any(feature = "zip", feature = "tar"))]
+pub mod archive;
+pub mod bedrock;
 pub mod block;
+#[cfg(feature = "fs")]
+pub mod cache;
+pub mod chunk;
+pub mod command;
+pub mod convert;
+pub mod csv;
+pub mod density;
+pub mod diff;
+pub mod entropy;
 pub mod error;
 pub mod file;
+pub mod fill;
+pub mod group;
+pub mod integrity;
+pub mod intern;
+pub mod iter;
+pub mod lod;
+pub mod mcedit;
+pub mod mcfunction;
+pub mod migrate;
+pub mod modded;
+pub mod overlap;
+pub mod palette;
+pub mod patch;
+pub mod placement;
+pub mod provenance;
 pub mod region;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod replace;
 pub mod resource_location;
+pub mod sample;
+pub mod schematic;
+pub mod similarity;
+pub mod snapshot;
+pub mod spatial;
+pub mod sponge;
+pub mod stats;
+pub mod stream;
 pub mod structure;
+pub mod subregion;
+pub mod symmetry;
+pub mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
+pub mod transform;
+pub mod vector;
+pub mod version;
+pub mod world;
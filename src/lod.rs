@@ -0,0 +1,170 @@
+use crate::region::read_palette_index;
+use crate::structure::{Coordinates, Region};
+
+impl Region {
+    /// Generates progressively downsampled copies of the region, e.g. for 3D viewers that render far-away schematics
+    /// with fewer blocks.
+    ///
+    /// Every level halves the size of the previous one along every axis, rounding up, so the first level has one block
+    /// for every 2×2×2 blocks of the region, the second one for every 4×4×4 blocks and so on. A block of a level is the
+    /// most common non-air block of the 8 blocks it covers, so thin walls and pillars don't disappear, and only air if
+    /// all of them are air. Ties are won by the first of them in YZX order.
+    ///
+    /// The levels have the same position as the region, and a copy of its palette. Entities, tile entities and
+    /// scheduled ticks are not included. Generation stops early once a level consists of a single block.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - The maximum number of levels to generate.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for (level, lod) in region.generate_lod(3).iter().enumerate() {
+    ///     println!("Level {}: {:?} at 1:{}", level + 1, lod.size, 2 << level);
+    /// }
+    /// ```
+    pub fn generate_lod(&self, levels: usize) -> Vec<Region> {
+        let mut lods: Vec<Region> = Vec::with_capacity(levels);
+
+        for _ in 0..levels {
+            let source = lods.last().unwrap_or(self);
+
+            if source.calc_volume() <= 1 {
+                break;
+            }
+
+            let lod = source.downsample();
+            lods.push(lod);
+        }
+
+        lods
+    }
+
+    /// Returns a copy of the region at half the size, see `generate_lod()`.
+    fn downsample(&self) -> Region {
+        let half = |size: i32| size.signum() * ((size.abs() + 1) / 2);
+        let size = Coordinates {
+            x: half(self.size.x),
+            y: half(self.size.y),
+            z: half(self.size.z),
+        };
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let mut lod = Region::filled_with_air(self.position, size);
+        lod.block_state_palette = self.block_state_palette.clone();
+        lod.block_states = vec![0; (lod.calc_volume() * required_bits).div_ceil(64) as usize];
+
+        let mut counts = vec![0u8; self.block_state_palette.len()];
+        let mut cell = Vec::with_capacity(8);
+        let mut index = 0;
+
+        for y in 0..size.y.abs() {
+            for z in 0..size.z.abs() {
+                for x in 0..size.x.abs() {
+                    cell.clear();
+
+                    for dy in 0..2 {
+                        for dz in 0..2 {
+                            for dx in 0..2 {
+                                let source = Coordinates {
+                                    x: x * 2 + dx,
+                                    y: y * 2 + dy,
+                                    z: z * 2 + dz,
+                                };
+
+                                if self.contains(source) {
+                                    cell.push(read_palette_index(
+                                        &self.block_states,
+                                        self.get_3d_index(source),
+                                        required_bits,
+                                        bitmask,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+
+                    let mut best: Option<u32> = None;
+
+                    for &palette_index in &cell {
+                        if self.block_state_palette[palette_index as usize].is_air() {
+                            continue;
+                        }
+
+                        counts[palette_index as usize] += 1;
+
+                        if best.is_none_or(|best| {
+                            counts[palette_index as usize] > counts[best as usize]
+                        }) {
+                            best = Some(palette_index);
+                        }
+                    }
+
+                    for &palette_index in &cell {
+                        counts[palette_index as usize] = 0;
+                    }
+
+                    Self::set_block_index(
+                        &mut lod.block_states,
+                        index,
+                        best.unwrap_or(cell[0]),
+                        required_bits,
+                        bitmask,
+                    );
+                    index += 1;
+                }
+            }
+        }
+
+        lod
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn generate_lod() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let glass = BlockStateBuilder::new("glass").build();
+        let wool = BlockStateBuilder::new("white_wool").build();
+
+        let mut region = Region::new((5, 4, -4));
+        region.fill((0, 0, 0), (3, 3, 3), stone.clone());
+        region.set_block((0, 0, 0), glass.clone());
+        region.set_block((3, 3, 3), glass.clone());
+        region.set_block((2, 2, 2), glass.clone());
+        region.set_block((4, 1, 1), wool.clone());
+
+        let lods = region.generate_lod(5);
+
+        // 5×4×4, 3×2×2, 2×1×1 and 1×1×1
+        assert_eq!(lods.len(), 3);
+        assert_eq!(lods[0].size, Coordinates { x: 3, y: 2, z: -2 });
+        assert_eq!(lods[2].size, Coordinates { x: 1, y: 1, z: -1 });
+
+        assert_eq!(lods[0].get_block((0, 0, 0)), &stone);
+        // two glass blocks against six stone blocks
+        assert_eq!(lods[0].get_block((1, 1, 1)), &stone);
+        // a single block beats seven air blocks
+        assert_eq!(lods[0].get_block((2, 0, 0)), &wool);
+        assert!(lods[0].get_block((2, 1, 1)).is_air());
+
+        assert_eq!(lods[1].get_block((0, 0, 0)), &stone);
+        assert_eq!(lods[1].get_block((1, 0, 0)), &wool);
+        assert_eq!(lods[2].get_block((0, 0, 0)), &stone);
+
+        assert!(region.generate_lod(0).is_empty());
+        assert!(lods[2].generate_lod(1).is_empty());
+    }
+}
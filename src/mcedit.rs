@@ -0,0 +1,809 @@
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+use std::io::{self, Read};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::block::BlockStateBuilder;
+use crate::error::Result;
+use crate::file::DEFAULT_DATA_VERSION;
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+/// The 16 dye colors in the order of their legacy data values.
+const COLORS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+/// The wood types in the order of their legacy data values.
+const WOODS: [&str; 6] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak"];
+
+/// Legacy block ids whose name doesn't depend on the data value.
+const LEGACY_NAMES: [(u16, &str); 169] = [
+    (0, "air"),
+    (2, "grass_block"),
+    (4, "cobblestone"),
+    (7, "bedrock"),
+    (8, "water"),
+    (9, "water"),
+    (10, "lava"),
+    (11, "lava"),
+    (13, "gravel"),
+    (14, "gold_ore"),
+    (15, "iron_ore"),
+    (16, "coal_ore"),
+    (20, "glass"),
+    (21, "lapis_ore"),
+    (22, "lapis_block"),
+    (23, "dispenser"),
+    (25, "note_block"),
+    (26, "red_bed"),
+    (27, "powered_rail"),
+    (28, "detector_rail"),
+    (29, "sticky_piston"),
+    (30, "cobweb"),
+    (32, "dead_bush"),
+    (33, "piston"),
+    (34, "piston_head"),
+    (36, "moving_piston"),
+    (37, "dandelion"),
+    (39, "brown_mushroom"),
+    (40, "red_mushroom"),
+    (41, "gold_block"),
+    (42, "iron_block"),
+    (45, "bricks"),
+    (46, "tnt"),
+    (47, "bookshelf"),
+    (48, "mossy_cobblestone"),
+    (49, "obsidian"),
+    (51, "fire"),
+    (52, "spawner"),
+    (53, "oak_stairs"),
+    (54, "chest"),
+    (55, "redstone_wire"),
+    (56, "diamond_ore"),
+    (57, "diamond_block"),
+    (58, "crafting_table"),
+    (59, "wheat"),
+    (60, "farmland"),
+    (61, "furnace"),
+    (62, "furnace"),
+    (63, "oak_sign"),
+    (64, "oak_door"),
+    (65, "ladder"),
+    (66, "rail"),
+    (67, "cobblestone_stairs"),
+    (68, "oak_wall_sign"),
+    (69, "lever"),
+    (70, "stone_pressure_plate"),
+    (71, "iron_door"),
+    (72, "oak_pressure_plate"),
+    (73, "redstone_ore"),
+    (74, "redstone_ore"),
+    (77, "stone_button"),
+    (78, "snow"),
+    (79, "ice"),
+    (80, "snow_block"),
+    (81, "cactus"),
+    (82, "clay"),
+    (83, "sugar_cane"),
+    (84, "jukebox"),
+    (85, "oak_fence"),
+    (86, "carved_pumpkin"),
+    (87, "netherrack"),
+    (88, "soul_sand"),
+    (89, "glowstone"),
+    (90, "nether_portal"),
+    (91, "jack_o_lantern"),
+    (92, "cake"),
+    (93, "repeater"),
+    (94, "repeater"),
+    (96, "oak_trapdoor"),
+    (99, "brown_mushroom_block"),
+    (100, "red_mushroom_block"),
+    (101, "iron_bars"),
+    (102, "glass_pane"),
+    (103, "melon"),
+    (104, "pumpkin_stem"),
+    (105, "melon_stem"),
+    (106, "vine"),
+    (107, "oak_fence_gate"),
+    (108, "brick_stairs"),
+    (109, "stone_brick_stairs"),
+    (110, "mycelium"),
+    (111, "lily_pad"),
+    (112, "nether_bricks"),
+    (113, "nether_brick_fence"),
+    (114, "nether_brick_stairs"),
+    (115, "nether_wart"),
+    (116, "enchanting_table"),
+    (117, "brewing_stand"),
+    (118, "cauldron"),
+    (119, "end_portal"),
+    (120, "end_portal_frame"),
+    (121, "end_stone"),
+    (122, "dragon_egg"),
+    (123, "redstone_lamp"),
+    (124, "redstone_lamp"),
+    (127, "cocoa"),
+    (128, "sandstone_stairs"),
+    (129, "emerald_ore"),
+    (130, "ender_chest"),
+    (131, "tripwire_hook"),
+    (132, "tripwire"),
+    (133, "emerald_block"),
+    (134, "spruce_stairs"),
+    (135, "birch_stairs"),
+    (136, "jungle_stairs"),
+    (137, "command_block"),
+    (138, "beacon"),
+    (140, "flower_pot"),
+    (141, "carrots"),
+    (142, "potatoes"),
+    (143, "oak_button"),
+    (144, "skeleton_skull"),
+    (145, "anvil"),
+    (146, "trapped_chest"),
+    (147, "light_weighted_pressure_plate"),
+    (148, "heavy_weighted_pressure_plate"),
+    (149, "comparator"),
+    (150, "comparator"),
+    (151, "daylight_detector"),
+    (152, "redstone_block"),
+    (153, "nether_quartz_ore"),
+    (154, "hopper"),
+    (156, "quartz_stairs"),
+    (157, "activator_rail"),
+    (158, "dropper"),
+    (163, "acacia_stairs"),
+    (164, "dark_oak_stairs"),
+    (165, "slime_block"),
+    (166, "barrier"),
+    (167, "iron_trapdoor"),
+    (169, "sea_lantern"),
+    (170, "hay_block"),
+    (172, "terracotta"),
+    (173, "coal_block"),
+    (174, "packed_ice"),
+    (176, "white_banner"),
+    (177, "white_wall_banner"),
+    (178, "daylight_detector"),
+    (180, "red_sandstone_stairs"),
+    (198, "end_rod"),
+    (199, "chorus_plant"),
+    (200, "chorus_flower"),
+    (201, "purpur_block"),
+    (202, "purpur_pillar"),
+    (203, "purpur_stairs"),
+    (206, "end_stone_bricks"),
+    (207, "beetroots"),
+    (208, "dirt_path"),
+    (209, "end_gateway"),
+    (210, "repeating_command_block"),
+    (211, "chain_command_block"),
+    (212, "frosted_ice"),
+    (213, "magma_block"),
+    (214, "nether_wart_block"),
+    (215, "red_nether_bricks"),
+    (216, "bone_block"),
+    (217, "structure_void"),
+    (218, "observer"),
+    (255, "structure_block"),
+];
+
+/// Legacy block ids whose data value (masked by the length of the list) selects the block.
+const LEGACY_VARIANTS: [(u16, &[&str]); 16] = [
+    (
+        1,
+        &[
+            "stone",
+            "granite",
+            "polished_granite",
+            "diorite",
+            "polished_diorite",
+            "andesite",
+            "polished_andesite",
+            "stone",
+        ],
+    ),
+    (3, &["dirt", "coarse_dirt", "podzol", "dirt"]),
+    (12, &["sand", "red_sand"]),
+    (19, &["sponge", "wet_sponge"]),
+    (
+        24,
+        &[
+            "sandstone",
+            "chiseled_sandstone",
+            "cut_sandstone",
+            "sandstone",
+        ],
+    ),
+    (31, &["dead_bush", "short_grass", "fern", "short_grass"]),
+    (
+        38,
+        &[
+            "poppy",
+            "blue_orchid",
+            "allium",
+            "azure_bluet",
+            "red_tulip",
+            "orange_tulip",
+            "white_tulip",
+            "pink_tulip",
+            "oxeye_daisy",
+            "poppy",
+            "poppy",
+            "poppy",
+            "poppy",
+            "poppy",
+            "poppy",
+            "poppy",
+        ],
+    ),
+    (
+        97,
+        &[
+            "infested_stone",
+            "infested_cobblestone",
+            "infested_stone_bricks",
+            "infested_mossy_stone_bricks",
+            "infested_cracked_stone_bricks",
+            "infested_chiseled_stone_bricks",
+            "infested_stone",
+            "infested_stone",
+        ],
+    ),
+    (
+        98,
+        &[
+            "stone_bricks",
+            "mossy_stone_bricks",
+            "cracked_stone_bricks",
+            "chiseled_stone_bricks",
+        ],
+    ),
+    (139, &["cobblestone_wall", "mossy_cobblestone_wall"]),
+    (
+        168,
+        &[
+            "prismarine",
+            "prismarine_bricks",
+            "dark_prismarine",
+            "prismarine",
+        ],
+    ),
+    (
+        179,
+        &[
+            "red_sandstone",
+            "chiseled_red_sandstone",
+            "cut_red_sandstone",
+            "red_sandstone",
+        ],
+    ),
+    (
+        175,
+        &[
+            "sunflower",
+            "lilac",
+            "tall_grass",
+            "large_fern",
+            "rose_bush",
+            "peony",
+            "sunflower",
+            "sunflower",
+        ],
+    ),
+    (
+        6,
+        &[
+            "oak_sapling",
+            "spruce_sapling",
+            "birch_sapling",
+            "jungle_sapling",
+            "acacia_sapling",
+            "dark_oak_sapling",
+            "oak_sapling",
+            "oak_sapling",
+        ],
+    ),
+    (
+        5,
+        &[
+            "oak_planks",
+            "spruce_planks",
+            "birch_planks",
+            "jungle_planks",
+            "acacia_planks",
+            "dark_oak_planks",
+            "oak_planks",
+            "oak_planks",
+        ],
+    ),
+    (
+        155,
+        &[
+            "quartz_block",
+            "chiseled_quartz_block",
+            "quartz_pillar",
+            "quartz_pillar",
+            "quartz_pillar",
+            "quartz_block",
+            "quartz_block",
+            "quartz_block",
+        ],
+    ),
+];
+
+/// The materials of the stone slabs with the legacy ids `43` and `44`.
+const STONE_SLABS: [&str; 8] = [
+    "smooth_stone",
+    "sandstone",
+    "petrified_oak",
+    "cobblestone",
+    "brick",
+    "stone_brick",
+    "nether_brick",
+    "quartz",
+];
+
+const HORIZONTAL_FACING: [&str; 6] = ["north", "north", "north", "south", "west", "east"];
+const FACING: [&str; 6] = ["down", "up", "north", "south", "west", "east"];
+const STAIR_FACING: [&str; 4] = ["east", "west", "south", "north"];
+const TORCH_FACING: [&str; 4] = ["east", "west", "south", "north"];
+const AXES: [&str; 3] = ["y", "x", "z"];
+
+impl LitematicaFile {
+    /// Reads a legacy MCEdit or WorldEdit `.schematic` file from before Minecraft 1.13 and converts it into a
+    /// `Litematica` file with a single region, named after the file.
+    ///
+    /// See `read_mcedit_from()` for details on the conversion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid schematic.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read_mcedit("old_castle.schematic").unwrap();
+    /// file.write("old_castle.litematic").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn read_mcedit(path: impl AsRef<Path>) -> Result<LitematicaFile> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let file = File::open(path)?;
+        Self::read_mcedit_from(BufReader::new(file), name)
+    }
+
+    /// Reads a gzip-compressed legacy `.schematic` and converts it into a `Litematica` file with a single region
+    /// called `name`.
+    ///
+    /// The numeric block ids and data values are mapped to block states with `legacy_to_modern()`, and unknown ids
+    /// become air. The region is placed at the WorldEdit offset if the file has one. Entities and tile entities are
+    /// not imported, since they use the pre-1.13 item and entity ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be read or is not a valid schematic of the `Alpha` format.
+    pub fn read_mcedit_from(reader: impl Read, name: impl Into<String>) -> Result<LitematicaFile> {
+        let schematic: McEditSchematic = nbt::from_gzip_reader(reader)?;
+
+        if !schematic.materials.is_empty() && schematic.materials != "Alpha" {
+            return Err(invalid_data(&format!(
+                "unsupported materials {}",
+                schematic.materials
+            )));
+        }
+
+        let size = Coordinates {
+            x: schematic.width as u16 as i32,
+            y: schematic.height as u16 as i32,
+            z: schematic.length as u16 as i32,
+        };
+        let volume = size.x as usize * size.y as usize * size.z as usize;
+
+        if schematic.blocks.len() != volume || schematic.data.len() != volume {
+            return Err(invalid_data("block data does not match the schematic size"));
+        }
+
+        let position = Coordinates {
+            x: schematic.offset_x,
+            y: schematic.offset_y,
+            z: schematic.offset_z,
+        };
+        let mut region = Region::filled_with_air(position, size);
+
+        // the ids and data values repeat a lot, so every combination is only converted once
+        let mut converted = std::collections::HashMap::new();
+        let mut index = 0;
+
+        // the same YZX order as litematica
+        for y in 0..size.y {
+            for z in 0..size.z {
+                for x in 0..size.x {
+                    let id = legacy_id(&schematic.blocks, &schematic.add_blocks, index);
+                    let data = schematic.data[index] as u8 & 0xf;
+
+                    index += 1;
+
+                    let block = converted
+                        .entry((id, data))
+                        .or_insert_with(|| legacy_to_modern(id, data));
+
+                    if let Some(block) = block.as_ref().filter(|block| !block.is_air()) {
+                        region.set_block((x, y, z), block.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(LitematicaFile::from_region(
+            name,
+            region,
+            DEFAULT_DATA_VERSION,
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct McEditSchematic {
+    width: i16,
+    height: i16,
+    length: i16,
+
+    #[serde(default)]
+    materials: String,
+
+    blocks: Vec<i8>,
+    data: Vec<i8>,
+
+    #[serde(default)]
+    add_blocks: Vec<i8>,
+
+    #[serde(rename = "WEOffsetX")]
+    #[serde(default)]
+    offset_x: i32,
+
+    #[serde(rename = "WEOffsetY")]
+    #[serde(default)]
+    offset_y: i32,
+
+    #[serde(rename = "WEOffsetZ")]
+    #[serde(default)]
+    offset_z: i32,
+}
+
+/// Maps a legacy block id and data value from before Minecraft 1.13 to the equivalent `BlockState`.
+///
+/// All vanilla blocks up to 1.12 are known. The data value selects the variant of blocks like wool, planks, logs or
+/// stone, and is translated into the common properties like `facing`, `axis`, `half`, `type`, `level`, `power` and
+/// `age`. Other properties are left at their defaults. Returns `None` for unknown ids, e.g. of mods.
+///
+/// # Arguments
+///
+/// * `id` - The numeric block id, including the bits stored in `AddBlocks`.
+/// * `data` - The 4 bit data value.
+///
+/// # Examples
+/// ```
+/// use ritematica::mcedit::legacy_to_modern;
+///
+/// assert_eq!(legacy_to_modern(35, 14).unwrap().to_string(), "minecraft:red_wool");
+/// assert_eq!(legacy_to_modern(17, 4).unwrap().to_string(), "minecraft:oak_log[axis=x]");
+/// ```
+pub fn legacy_to_modern(id: u16, data: u8) -> Option<BlockState> {
+    let data = data as usize & 0xf;
+
+    let block = match id {
+        // colored blocks
+        35 | 95 | 159 | 160 | 171 | 251 | 252 => {
+            let suffix = match id {
+                35 => "wool",
+                95 => "stained_glass",
+                159 => "terracotta",
+                160 => "stained_glass_pane",
+                171 => "carpet",
+                251 => "concrete",
+                _ => "concrete_powder",
+            };
+
+            BlockStateBuilder::new(format!("{}_{}", COLORS[data], suffix).as_str())
+        }
+        219..=234 => {
+            BlockStateBuilder::new(format!("{}_shulker_box", COLORS[id as usize - 219]).as_str())
+                .properties([("facing", FACING[(data & 7).min(5)])])
+        }
+        235..=250 => BlockStateBuilder::new(
+            format!("{}_glazed_terracotta", COLORS[id as usize - 235]).as_str(),
+        )
+        .properties([("facing", STAIR_FACING[[3, 0, 2, 1][data & 3]])]),
+
+        // wood
+        17 | 162 => {
+            let wood = wood(id == 162, data);
+
+            match data >> 2 {
+                3 => BlockStateBuilder::new(format!("{}_wood", wood).as_str())
+                    .properties([("axis", "y")]),
+                axis => BlockStateBuilder::new(format!("{}_log", wood).as_str())
+                    .properties([("axis", AXES[axis])]),
+            }
+        }
+        18 | 161 => {
+            let wood = wood(id == 161, data);
+
+            BlockStateBuilder::new(format!("{}_leaves", wood).as_str())
+                .properties([("persistent", (data & 4 != 0).to_string())])
+        }
+        125 | 126 => BlockStateBuilder::new(format!("{}_slab", WOODS[(data & 7).min(5)]).as_str())
+            .properties([("type", slab_type(id == 125, data))]),
+        43 | 44 => BlockStateBuilder::new(format!("{}_slab", STONE_SLABS[data & 7]).as_str())
+            .properties([("type", slab_type(id == 43, data))]),
+        181 | 182 => BlockStateBuilder::new("red_sandstone_slab")
+            .properties([("type", slab_type(id == 181, data))]),
+        204 | 205 => {
+            BlockStateBuilder::new("purpur_slab").properties([("type", slab_type(id == 204, data))])
+        }
+        183..=187 => BlockStateBuilder::new(
+            format!(
+                "{}_fence_gate",
+                ["spruce", "birch", "jungle", "dark_oak", "acacia"][id as usize - 183]
+            )
+            .as_str(),
+        ),
+        188..=192 => BlockStateBuilder::new(
+            format!(
+                "{}_fence",
+                ["spruce", "birch", "jungle", "dark_oak", "acacia"][id as usize - 188]
+            )
+            .as_str(),
+        ),
+        193..=197 => BlockStateBuilder::new(
+            format!(
+                "{}_door",
+                ["spruce", "birch", "jungle", "acacia", "dark_oak"][id as usize - 193]
+            )
+            .as_str(),
+        ),
+
+        // torches
+        50 | 75 | 76 => {
+            let (floor, wall) = match id {
+                50 => ("torch", "wall_torch"),
+                _ => ("redstone_torch", "redstone_wall_torch"),
+            };
+
+            let builder = match data {
+                1..=4 => {
+                    BlockStateBuilder::new(wall).properties([("facing", TORCH_FACING[data - 1])])
+                }
+                _ => BlockStateBuilder::new(floor),
+            };
+
+            if id == 50 {
+                builder
+            } else {
+                builder.properties([("lit", (id == 76).to_string())])
+            }
+        }
+
+        _ => {
+            let name = LEGACY_NAMES
+                .iter()
+                .find(|(legacy, _)| *legacy == id)
+                .map(|(_, name)| *name)
+                .or_else(|| {
+                    LEGACY_VARIANTS
+                        .iter()
+                        .find(|(legacy, _)| *legacy == id)
+                        .map(|(_, variants)| variants[data % variants.len()])
+                })?;
+
+            let builder = BlockStateBuilder::new(name);
+
+            let builder = match id {
+                8..=11 => builder.properties([("level", data.to_string())]),
+                55 => builder.properties([("power", data.to_string())]),
+                59 | 104 | 105 | 115 | 141 | 142 | 207 => builder.properties([(
+                    "age",
+                    match id {
+                        115 | 207 => (data & 3).to_string(),
+                        141 | 142 => (data & 7).to_string(),
+                        _ => data.min(7).to_string(),
+                    },
+                )]),
+                78 => builder.properties([("layers", ((data & 7) + 1).to_string())]),
+                53 | 67 | 108 | 109 | 114 | 128 | 134 | 135 | 136 | 156 | 163 | 164 | 180 | 203 => {
+                    builder.properties([
+                        ("facing", STAIR_FACING[data & 3]),
+                        ("half", if data & 4 != 0 { "top" } else { "bottom" }),
+                    ])
+                }
+                54 | 61 | 62 | 65 | 68 | 130 | 146 => {
+                    builder.properties([("facing", HORIZONTAL_FACING[(data & 7).min(5)])])
+                }
+                23 | 29 | 33 | 158 | 198 | 218 => {
+                    builder.properties([("facing", FACING[(data & 7).min(5)])])
+                }
+                155 if (2..=4).contains(&data) => builder.properties([("axis", AXES[data - 2])]),
+                170 | 202 | 216 => builder.properties([("axis", AXES[(data >> 2).min(2)])]),
+                175 if data & 8 != 0 => builder.properties([("half", "upper")]),
+                175 => builder.properties([("half", "lower")]),
+                _ => builder,
+            };
+
+            match id {
+                62 | 74 | 124 => builder.properties([("lit", "true")]),
+                61 | 73 | 123 => builder.properties([("lit", "false")]),
+                _ => builder,
+            }
+        }
+    };
+
+    Some(block.build())
+}
+
+/// Returns the wood type of a log or leaves block, where the ids added in 1.7 continue the list of `WOODS`.
+fn wood(newer: bool, data: usize) -> &'static str {
+    let offset = if newer { 4 } else { 0 };
+
+    WOODS.get((data & 3) + offset).copied().unwrap_or("oak")
+}
+
+/// Returns the `type` of a slab from its legacy id being a double slab and its data value.
+fn slab_type(double: bool, data: usize) -> &'static str {
+    if double {
+        "double"
+    } else if data & 8 != 0 {
+        "top"
+    } else {
+        "bottom"
+    }
+}
+
+/// Returns the block id at `index`, including the upper bits stored as nibbles in `add_blocks`.
+fn legacy_id(blocks: &[i8], add_blocks: &[i8], index: usize) -> u16 {
+    let id = blocks[index] as u8 as u16;
+
+    match add_blocks.get(index >> 1) {
+        Some(add) if index & 1 == 0 => id | ((*add as u8 as u16 & 0x0f) << 8),
+        Some(add) => id | ((*add as u8 as u16 & 0xf0) << 4),
+        None => id,
+    }
+}
+
+fn invalid_data(message: &str) -> crate::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct WriteSchematic {
+        width: i16,
+        height: i16,
+        length: i16,
+        materials: String,
+
+        #[serde(serialize_with = "nbt::i8_array")]
+        blocks: Vec<i8>,
+
+        #[serde(serialize_with = "nbt::i8_array")]
+        data: Vec<i8>,
+
+        #[serde(rename = "WEOffsetX")]
+        offset_x: i32,
+
+        #[serde(rename = "WEOffsetY")]
+        offset_y: i32,
+
+        #[serde(rename = "WEOffsetZ")]
+        offset_z: i32,
+    }
+
+    #[test]
+    fn legacy_block_states() {
+        let block = |id, data| legacy_to_modern(id, data).unwrap().to_string();
+
+        assert_eq!(block(1, 3), "minecraft:diorite");
+        assert_eq!(block(5, 5), "minecraft:dark_oak_planks");
+        assert_eq!(block(162, 9), "minecraft:dark_oak_log[axis=z]");
+        assert_eq!(block(17, 12), "minecraft:oak_wood[axis=y]");
+        assert_eq!(block(44, 12), "minecraft:brick_slab[type=top]");
+        assert_eq!(block(53, 6), "minecraft:oak_stairs[facing=south,half=top]");
+        assert_eq!(block(9, 0), "minecraft:water[level=0]");
+        assert_eq!(
+            block(75, 3),
+            "minecraft:redstone_wall_torch[facing=south,lit=false]"
+        );
+        assert_eq!(block(159, 8), "minecraft:light_gray_terracotta");
+        assert_eq!(block(234, 1), "minecraft:black_shulker_box[facing=up]");
+        assert_eq!(block(155, 4), "minecraft:quartz_pillar[axis=z]");
+        assert!(legacy_to_modern(4000, 0).is_none());
+
+        // every known id maps to a valid block name for every data value
+        let mut names = HashMap::new();
+        for id in 0..256 {
+            for data in 0..16 {
+                if let Some(block) = legacy_to_modern(id, data) {
+                    names.insert(block.get_name().to_string(), id);
+                }
+            }
+        }
+
+        assert!(names.contains_key("minecraft:observer"));
+        assert!(!names.contains_key("minecraft:stone_slab"));
+    }
+
+    #[test]
+    fn read_schematic() {
+        let schematic = WriteSchematic {
+            width: 2,
+            height: 1,
+            length: 2,
+            materials: "Alpha".to_string(),
+            blocks: vec![1, 35, 0, 17],
+            data: vec![0, 4, 0, 8],
+            offset_x: -1,
+            offset_y: 0,
+            offset_z: 3,
+        };
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        nbt::to_writer(&mut encoder, &schematic, Some("Schematic")).unwrap();
+        encoder.flush().unwrap();
+        let bytes = encoder.finish().unwrap();
+
+        let file = LitematicaFile::read_mcedit_from(&bytes[..], "old").unwrap();
+        let region = file.get_region("old").unwrap();
+
+        assert_eq!(region.position, Coordinates { x: -1, y: 0, z: 3 });
+        assert_eq!(region.get_block((0, 0, 0)).to_string(), "minecraft:stone");
+        assert_eq!(
+            region.get_block((1, 0, 0)).to_string(),
+            "minecraft:yellow_wool"
+        );
+        assert!(region.get_block((0, 0, 1)).is_air());
+        assert_eq!(
+            region.get_block((1, 0, 1)).to_string(),
+            "minecraft:oak_log[axis=z]"
+        );
+        assert_eq!(file.metadata.total_blocks, 3);
+    }
+}
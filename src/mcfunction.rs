@@ -0,0 +1,406 @@
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::block::BlockStateBuilder;
+use crate::error::Result;
+use crate::file::DEFAULT_DATA_VERSION;
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+/// The largest number of blocks the region created by `LitematicaFile::from_mcfunction()` may contain.
+pub const MAX_MCFUNCTION_VOLUME: u64 = 1 << 28;
+
+/// How a `fill` or `setblock` command treats the blocks it overwrites.
+#[derive(Debug, Clone)]
+enum Mode {
+    Replace(Option<BlockState>),
+    Keep,
+    Hollow,
+    Outline,
+}
+
+#[derive(Debug, Clone)]
+struct Command {
+    from: Coordinates,
+    to: Coordinates,
+    block: BlockState,
+    mode: Mode,
+}
+
+impl LitematicaFile {
+    /// Reads a `.mcfunction` file and reconstructs the blocks placed by its commands, see `from_mcfunction()`.
+    /// The region is named after the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or contains an invalid `setblock` or `fill` command.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read_mcfunction("build.mcfunction").unwrap();
+    /// file.write("build.litematic").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn read_mcfunction(path: impl AsRef<Path>) -> Result<LitematicaFile> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Self::from_mcfunction(&fs::read_to_string(path)?, name)
+    }
+
+    /// Runs the `setblock` and `fill` commands of a Minecraft function in order and returns the result as a
+    /// `Litematica` file with a single region called `name`, which is just large enough to contain every command.
+    ///
+    /// Coordinates may be absolute or relative with `~`, and both are treated as relative to the schematic origin.
+    /// Local coordinates with `^` are not supported. All modes of both commands are supported, including
+    /// `fill ... replace <filter>`, where properties missing from the filter match any value. Block entity data
+    /// in curly braces is skipped, and lines with other commands or comments are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The contents of the function, one command per line.
+    /// * `name` - The name of the region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a `setblock` or `fill` command is invalid, with the line number in the message, or if the
+    /// box containing every command has more than `MAX_MCFUNCTION_VOLUME` blocks.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::from_mcfunction("fill ~ ~ ~ ~4 ~ ~4 stone\nsetblock ~2 ~1 ~2 lever[face=floor]", "pad").unwrap();
+    /// ```
+    pub fn from_mcfunction(source: &str, name: impl Into<String>) -> Result<LitematicaFile> {
+        let mut commands = Vec::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let parsed = parse_line(line).map_err(|message| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", number + 1, message),
+                )
+            })?;
+
+            commands.extend(parsed);
+        }
+
+        let mut min = Coordinates { x: 0, y: 0, z: 0 };
+        let mut max = Coordinates { x: 0, y: 0, z: 0 };
+
+        for (index, command) in commands.iter().enumerate() {
+            if index == 0 {
+                (min, max) = (command.from, command.to);
+            } else {
+                min = Coordinates {
+                    x: min.x.min(command.from.x),
+                    y: min.y.min(command.from.y),
+                    z: min.z.min(command.from.z),
+                };
+                max = Coordinates {
+                    x: max.x.max(command.to.x),
+                    y: max.y.max(command.to.y),
+                    z: max.z.max(command.to.z),
+                };
+            }
+        }
+
+        let too_large = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the commands span from {:?} to {:?}, which is more than {} blocks",
+                    min, max, MAX_MCFUNCTION_VOLUME
+                ),
+            )
+        };
+        let length = |min: i32, max: i32| {
+            max.checked_sub(min)
+                .and_then(|length| length.checked_add(1))
+                .ok_or_else(too_large)
+        };
+
+        let size = Coordinates {
+            x: length(min.x, max.x)?,
+            y: length(min.y, max.y)?,
+            z: length(min.z, max.z)?,
+        };
+
+        if size.x as u64 * size.y as u64 * size.z as u64 > MAX_MCFUNCTION_VOLUME {
+            return Err(too_large().into());
+        }
+
+        let mut region = Region::filled_with_air(min, size);
+        let air = BlockStateBuilder::new("air").build();
+
+        for command in &commands {
+            for y in command.from.y..=command.to.y {
+                for z in command.from.z..=command.to.z {
+                    for x in command.from.x..=command.to.x {
+                        let position = Coordinates {
+                            x: x - min.x,
+                            y: y - min.y,
+                            z: z - min.z,
+                        };
+
+                        let on_edge = x == command.from.x
+                            || x == command.to.x
+                            || y == command.from.y
+                            || y == command.to.y
+                            || z == command.from.z
+                            || z == command.to.z;
+
+                        let current = region.get_block(position);
+
+                        let block = match &command.mode {
+                            Mode::Replace(None) => Some(&command.block),
+                            Mode::Replace(Some(filter)) => {
+                                matches_filter(current, filter).then_some(&command.block)
+                            }
+                            Mode::Keep => current.is_air().then_some(&command.block),
+                            Mode::Hollow if on_edge => Some(&command.block),
+                            Mode::Hollow => Some(&air),
+                            Mode::Outline => on_edge.then_some(&command.block),
+                        };
+
+                        if let Some(block) = block {
+                            region.set_block(position, block.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(LitematicaFile::from_region(
+            name,
+            region,
+            DEFAULT_DATA_VERSION,
+        ))
+    }
+}
+
+/// Parses a single line of a function, returning `None` for lines that don't place blocks.
+fn parse_line(line: &str) -> std::result::Result<Option<Command>, String> {
+    let line = line.trim();
+    let line = line.strip_prefix('/').unwrap_or(line);
+
+    let Some((command, arguments)) = line.split_once(char::is_whitespace) else {
+        return Ok(None);
+    };
+
+    let coordinate_count = match command {
+        "setblock" => 3,
+        "fill" => 6,
+        _ => return Ok(None),
+    };
+
+    let mut rest = arguments.trim_start();
+    let mut coordinates = Vec::with_capacity(coordinate_count);
+
+    for _ in 0..coordinate_count {
+        let (token, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+        coordinates.push(parse_coordinate(token)?);
+        rest = remaining.trim_start();
+    }
+
+    let (block, rest) = parse_block(rest)?;
+    let mut arguments = rest.split_whitespace();
+
+    let mode = match arguments.next() {
+        None | Some("destroy" | "strict") => Mode::Replace(None),
+        Some("replace") if command == "fill" => {
+            let filter = arguments.collect::<Vec<_>>().join(" ");
+
+            if filter.is_empty() {
+                Mode::Replace(None)
+            } else {
+                Mode::Replace(Some(parse_block(&filter)?.0))
+            }
+        }
+        Some("replace") => Mode::Replace(None),
+        Some("keep") => Mode::Keep,
+        Some("hollow") if command == "fill" => Mode::Hollow,
+        Some("outline") if command == "fill" => Mode::Outline,
+        Some(mode) => return Err(format!("unknown mode {}", mode)),
+    };
+
+    let corner = |offset: usize| Coordinates {
+        x: coordinates[offset],
+        y: coordinates[offset + 1],
+        z: coordinates[offset + 2],
+    };
+
+    let (first, second) = if command == "fill" {
+        (corner(0), corner(3))
+    } else {
+        (corner(0), corner(0))
+    };
+
+    Ok(Some(Command {
+        from: Coordinates {
+            x: first.x.min(second.x),
+            y: first.y.min(second.y),
+            z: first.z.min(second.z),
+        },
+        to: Coordinates {
+            x: first.x.max(second.x),
+            y: first.y.max(second.y),
+            z: first.z.max(second.z),
+        },
+        block,
+        mode,
+    }))
+}
+
+fn parse_coordinate(token: &str) -> std::result::Result<i32, String> {
+    if token.starts_with('^') {
+        return Err("local coordinates are not supported".to_string());
+    }
+
+    let number = match token.strip_prefix('~') {
+        Some("") => return Ok(0),
+        Some(offset) => offset,
+        None => token,
+    };
+
+    number
+        .parse::<f64>()
+        .map(|number| number.floor() as i32)
+        .map_err(|_| format!("invalid coordinate {}", token))
+}
+
+/// Parses a block argument with optional properties and block entity data, returning the rest of the input.
+fn parse_block(input: &str) -> std::result::Result<(BlockState, &str), String> {
+    let input = input.trim_start();
+
+    let mut end = input
+        .find(|c: char| c.is_whitespace() || c == '[' || c == '{')
+        .unwrap_or(input.len());
+
+    if input[end..].starts_with('[') {
+        end += input[end..]
+            .find(']')
+            .ok_or_else(|| "unclosed block properties".to_string())?
+            + 1;
+    }
+
+    let block =
+        BlockState::parse(&input[..end]).map_err(|_| format!("invalid block {}", &input[..end]))?;
+
+    let mut rest = &input[end..];
+
+    // skips the block entity data, which may contain spaces and braces inside of strings
+    if rest.starts_with('{') {
+        let mut depth = 0;
+        let mut quote = None;
+        let mut escaped = false;
+        let mut nbt_end = None;
+
+        for (index, c) in rest.char_indices() {
+            match (quote, c) {
+                (Some(_), _) if escaped => escaped = false,
+                (Some(_), '\\') => escaped = true,
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '{') => depth += 1,
+                (None, '}') => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        nbt_end = Some(index + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        rest = &rest[nbt_end.ok_or_else(|| "unclosed block entity data".to_string())?..];
+    }
+
+    Ok((block, rest))
+}
+
+fn matches_filter(block: &BlockState, filter: &BlockState) -> bool {
+    block.get_name() == filter.get_name()
+        && filter
+            .get_properties()
+            .iter()
+            .all(|(key, value)| block.get_properties().get(key) == Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setblock_and_fill() {
+        let source = r#"
+            # a small room
+            fill ~ ~ ~ ~4 ~2 ~4 stone_bricks hollow
+            fill 0 0 0 4 0 4 oak_planks replace stone_bricks
+            setblock ~2 ~1 ~2 chest[facing=north]{Items:[{id:"minecraft:stone", Count:1b, tag:{display:{Name:'"a } b"'}}}]} replace
+            /setblock ~2 ~1 ~2 stone keep
+            say done
+        "#;
+
+        let file = LitematicaFile::from_mcfunction(source, "room").unwrap();
+        let region = file.get_region("room").unwrap();
+
+        assert_eq!(region.size, Coordinates { x: 5, y: 3, z: 5 });
+        assert_eq!(
+            region.get_block((0, 2, 0)).to_string(),
+            "minecraft:stone_bricks"
+        );
+        assert_eq!(
+            region.get_block((1, 0, 1)).to_string(),
+            "minecraft:oak_planks"
+        );
+        assert_eq!(
+            region.get_block((2, 1, 2)).to_string(),
+            "minecraft:chest[facing=north]"
+        );
+        assert_eq!(region.get_block((1, 1, 1)).to_string(), "minecraft:air");
+    }
+
+    #[test]
+    fn invalid_commands() {
+        let error = LitematicaFile::from_mcfunction("say hi\nsetblock ^ ^ ^1 stone", "test")
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("line 2"));
+        assert!(LitematicaFile::from_mcfunction("fill 0 0 0 1 1 stone", "test").is_err());
+        assert!(LitematicaFile::from_mcfunction("setblock 0 0 0 stone sideways", "test").is_err());
+    }
+
+    #[test]
+    fn far_apart_commands() {
+        let far = "setblock 0 0 0 stone\nsetblock 100000 100000 100000 stone";
+        assert!(LitematicaFile::from_mcfunction(far, "test").is_err());
+
+        let overflow = "setblock -2147483648 0 0 stone\nsetblock 2147483647 0 0 stone";
+        assert!(LitematicaFile::from_mcfunction(overflow, "test").is_err());
+
+        let near = "setblock 0 0 0 stone\nsetblock 100 10 100 stone";
+        let file = LitematicaFile::from_mcfunction(near, "test").unwrap();
+        assert_eq!(
+            file.get_region("test").unwrap().size,
+            Coordinates {
+                x: 101,
+                y: 11,
+                z: 101
+            }
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::error::Result;
+use crate::palette::PaletteChange;
+use crate::resource_location::ResourceLocation;
+use crate::structure::LitematicaFile;
+
+/// Blocks that were renamed by a Minecraft release, as the data version of the release and the old and new name.
+const RENAMED_BLOCKS: [(i32, &str, &str); 4] = [
+    // 1.14
+    (1952, "sign", "oak_sign"),
+    (1952, "wall_sign", "oak_wall_sign"),
+    // 1.17
+    (2724, "grass_path", "dirt_path"),
+    // 1.20.3
+    (3698, "grass", "short_grass"),
+];
+
+impl LitematicaFile {
+    /// Migrates the blocks of the file to a newer Minecraft release and sets `minecraft_data_version` to it.
+    ///
+    /// Only blocks that were renamed between the releases are migrated, their properties are kept. Returns the
+    /// changed block states of every region that changed, by region name, so the migration can be shown to users.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_data_version` - The data version to migrate to, e.g. `3953` for 1.21. See `version::data_version()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `target_data_version` is older than the data version of the file, since downgrades are
+    /// not supported. The file is left unchanged in that case.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// for (region, changes) in file.migrate(3953).unwrap() {
+    ///     for change in changes {
+    ///         println!("{}: {}", region, change);
+    ///     }
+    /// }
+    /// ```
+    pub fn migrate(
+        &mut self,
+        target_data_version: i32,
+    ) -> Result<BTreeMap<String, Vec<PaletteChange>>> {
+        let current = self.minecraft_data_version;
+
+        if target_data_version < current {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot downgrade from data version {} to {}",
+                    current, target_data_version
+                ),
+            )
+            .into());
+        }
+
+        // applied in order, so blocks renamed twice end up with the newest name
+        let renames = RENAMED_BLOCKS
+            .iter()
+            .filter(|(version, _, _)| (current + 1..=target_data_version).contains(version))
+            .map(|(_, old, new)| (ResourceLocation::minecraft(*old), *new))
+            .collect::<Vec<_>>();
+
+        let mut changes = BTreeMap::new();
+
+        for (name, region) in &mut self.regions {
+            let renamed = region.remap_palette(|block| {
+                let mut name = block.get_name().clone();
+
+                for (old, new) in &renames {
+                    if name == *old {
+                        name = ResourceLocation::minecraft(*new);
+                    }
+                }
+
+                (name != *block.get_name()).then(|| {
+                    let mut block = block.clone();
+                    block.set_name(name);
+                    block
+                })
+            });
+
+            if !renamed.is_empty() {
+                changes.insert(name.clone(), renamed);
+            }
+        }
+
+        self.minecraft_data_version = target_data_version;
+
+        Ok(changes)
+    }
+
+    /// Upgrades a file on disk to a newer Minecraft release in one call, e.g. for maintaining a folder of schematics.
+    ///
+    /// The file is read, migrated with `migrate()`, and its metadata refreshed. A copy of the original is kept next to
+    /// it with `.bak` appended to the name. The upgraded file is first written to a temporary file in the same
+    /// directory and then renamed over the original, so the original is never left half-written.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the `.litematic` file.
+    /// * `target_data_version` - The data version to migrate to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, migrated or written, or the backup cannot be created.
+    /// The original file is left unchanged in that case.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// for entry in std::fs::read_dir("schematics").unwrap() {
+    ///     LitematicaFile::upgrade_file(entry.unwrap().path(), 3953).unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn upgrade_file(path: impl AsRef<Path>, target_data_version: i32) -> Result<()> {
+        let path = path.as_ref();
+
+        let mut file = LitematicaFile::read(path)?;
+        file.migrate(target_data_version)?;
+        file.refresh_metadata();
+
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?
+            .to_string_lossy();
+
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        // keeps the extension, since `write()` only accepts `.litematic` files
+        let temporary = path.with_file_name(format!(".{}.tmp.litematic", file_name));
+
+        file.write(&temporary)?;
+
+        if let Err(error) = fs::copy(path, &backup).and_then(|_| fs::rename(&temporary, path)) {
+            let _ = fs::remove_file(&temporary);
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn migrate() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.minecraft_data_version = 1631;
+
+        let region = file.get_region_mut("test").unwrap();
+        let sign = BlockStateBuilder::new("sign")
+            .properties([("rotation", "4"), ("waterlogged", "false")])
+            .build();
+        region.set_block((0, 0, 0), sign);
+        region.set_block((1, 0, 0), BlockStateBuilder::new("grass").build());
+
+        assert!(file.migrate(1000).is_err());
+        assert_eq!(file.minecraft_data_version, 1631);
+
+        let changes = file.migrate(3465).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes["test"].len(), 1);
+        assert_eq!(
+            changes["test"][0].to_string(),
+            "minecraft:sign[rotation=4,waterlogged=false] → \
+             minecraft:oak_sign[rotation=4,waterlogged=false] (1 block)"
+        );
+
+        let region = file.get_region("test").unwrap();
+        assert_eq!(
+            region.get_block((0, 0, 0)).to_string(),
+            "minecraft:oak_sign[rotation=4,waterlogged=false]"
+        );
+        assert_eq!(region.get_block((1, 0, 0)).to_string(), "minecraft:grass");
+
+        assert_eq!(file.migrate(3953).unwrap()["test"][0].count, 1);
+        assert!(file.migrate(3953).unwrap().is_empty());
+        assert_eq!(
+            file.get_region("test")
+                .unwrap()
+                .get_block((1, 0, 0))
+                .to_string(),
+            "minecraft:short_grass"
+        );
+        assert_eq!(file.minecraft_data_version, 3953);
+    }
+
+    #[test]
+    fn upgrade_file() {
+        let directory = std::env::temp_dir().join("ritematica_upgrade_file");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        let path = directory.join("farm.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.minecraft_data_version = 2586;
+        file.get_region_mut("test")
+            .unwrap()
+            .set_block((0, 0, 0), BlockStateBuilder::new("grass_path").build());
+        file.write(&path).unwrap();
+
+        assert!(LitematicaFile::upgrade_file(&path, 2000).is_err());
+        assert!(!directory.join("farm.litematic.bak").exists());
+
+        LitematicaFile::upgrade_file(&path, 3953).unwrap();
+
+        let upgraded = LitematicaFile::read(&path).unwrap();
+        let backup = LitematicaFile::read(directory.join("farm.litematic.bak")).unwrap();
+
+        assert_eq!(upgraded.minecraft_data_version, 3953);
+        assert_eq!(backup.minecraft_data_version, 2586);
+        assert_eq!(
+            upgraded
+                .get_region("test")
+                .unwrap()
+                .get_block((0, 0, 0))
+                .to_string(),
+            "minecraft:dirt_path"
+        );
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use crate::region::value_position;
+use crate::resource_location::ResourceLocation;
+use crate::structure::{BlockState, LitematicaFile};
+use crate::trace;
+
+/// The blocks that replace modded blocks in `LitematicaFile::strip_modded_with()`, configurable per namespace.
+///
+/// # Examples
+/// ```
+/// use ritematica::{BlockStateBuilder, ModdedFallbacks};
+///
+/// let fallbacks = ModdedFallbacks::new(BlockStateBuilder::new("stone").build())
+///     .namespace("create", BlockStateBuilder::new("andesite").build());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModdedFallbacks {
+    default: BlockState,
+    namespaces: HashMap<String, BlockState>,
+}
+
+impl ModdedFallbacks {
+    /// Creates `ModdedFallbacks` that replace blocks of every non-vanilla namespace with `default`.
+    pub fn new(default: BlockState) -> Self {
+        Self {
+            default,
+            namespaces: HashMap::new(),
+        }
+    }
+
+    /// Replaces blocks of the given `namespace` with `fallback` instead of the default.
+    /// If the namespace already has a fallback, it will be overwritten.
+    pub fn namespace(mut self, namespace: impl Into<String>, fallback: BlockState) -> Self {
+        self.namespaces.insert(namespace.into(), fallback);
+        self
+    }
+
+    /// Returns the fallback for a block with the given name, or `None` if it is a vanilla block.
+    pub fn fallback_for(&self, name: &ResourceLocation) -> Option<&BlockState> {
+        if name.get_namespace() == "minecraft" {
+            return None;
+        }
+
+        Some(
+            self.namespaces
+                .get(name.get_namespace())
+                .unwrap_or(&self.default),
+        )
+    }
+}
+
+impl LitematicaFile {
+    /// Replaces every block outside of the `minecraft` namespace with `fallback`, so that the schematic can at least be pasted
+    /// geometrically on a vanilla server.
+    ///
+    /// See `strip_modded_with()` for details.
+    pub fn strip_modded(&mut self, fallback: BlockState) -> usize {
+        self.strip_modded_with(&ModdedFallbacks::new(fallback))
+    }
+
+    /// Replaces every block outside of the `minecraft` namespace with its fallback from `fallbacks`.
+    ///
+    /// Tile entities of replaced blocks and entities outside of the `minecraft` namespace are removed,
+    /// since the game could not load them. Returns the number of replaced block states over all regions.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile, ModdedFallbacks};
+    ///
+    /// let mut file = LitematicaFile::read("modded.litematic").unwrap();
+    ///
+    /// let fallbacks = ModdedFallbacks::new(BlockStateBuilder::new("stone").build())
+    ///     .namespace("create", BlockStateBuilder::new("andesite").build());
+    ///
+    /// file.strip_modded_with(&fallbacks);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(regions = self.regions.len(), replaced, elapsed_ms))
+    )]
+    pub fn strip_modded_with(&mut self, fallbacks: &ModdedFallbacks) -> usize {
+        let _timer = trace::Timer::start();
+
+        let mut replaced = 0;
+
+        for region in self.regions.values_mut() {
+            let tile_entities = std::mem::take(&mut region.tile_entities);
+
+            region.tile_entities = tile_entities
+                .into_iter()
+                .filter(|tile_entity| {
+                    value_position(tile_entity).is_none_or(|position| {
+                        !region.contains(position)
+                            || fallbacks
+                                .fallback_for(region.get_block(position).get_name())
+                                .is_none()
+                    })
+                })
+                .collect();
+
+            region.entities.retain(|entity| {
+                entity
+                    .id
+                    .split_once(':')
+                    .is_none_or(|(namespace, _)| namespace == "minecraft")
+            });
+
+            replaced +=
+                region.replace_palette(|block| fallbacks.fallback_for(block.get_name()).cloned());
+        }
+
+        trace::record("replaced", replaced as u64);
+
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nbt::Value;
+
+    use crate::block::BlockStateBuilder;
+    use crate::structure::{Coordinates, Region};
+
+    use super::*;
+
+    #[test]
+    fn strip_modded_blocks() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.set_block((0, 0, 0), BlockStateBuilder::new("create:shaft").build());
+        region.set_block((1, 0, 0), BlockStateBuilder::new("create:cogwheel").build());
+        region.set_block((2, 0, 0), BlockStateBuilder::new("mekanism:pipe").build());
+
+        // tile entities outside of the region are kept instead of looking up a block there
+        let outside = Value::Compound(HashMap::from([
+            ("x".to_string(), Value::Int(-1)),
+            ("y".to_string(), Value::Int(0)),
+            ("z".to_string(), Value::Int(0)),
+        ]));
+        region.tile_entities.push(outside.clone());
+
+        let stone = BlockStateBuilder::new("stone").build();
+        let andesite = BlockStateBuilder::new("andesite").build();
+        let fallbacks = ModdedFallbacks::new(stone.clone()).namespace("create", andesite.clone());
+
+        assert_eq!(file.strip_modded_with(&fallbacks), 3);
+
+        let region = file.get_region("test").unwrap();
+
+        assert_eq!(region.get_block((0, 0, 0)), &andesite);
+        assert_eq!(region.get_block((1, 0, 0)), &andesite);
+        assert_eq!(region.get_block((2, 0, 0)), &stone);
+        assert!(region.tile_entities.contains(&outside));
+        assert!(region
+            .iter_blocks()
+            .all(|(_, block)| block.get_name().get_namespace() == "minecraft"));
+    }
+
+    #[test]
+    fn replace_palette_merges_entries() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 5, y: 1, z: 1 },
+        );
+
+        for x in 0..5 {
+            region.set_block(
+                (x, 0, 0),
+                BlockStateBuilder::new(format!("mod:block_{}", x)).build(),
+            );
+        }
+
+        let stone = BlockStateBuilder::new("stone").build();
+        region.replace_palette(|block| {
+            (block.get_name().get_namespace() == "mod").then(|| stone.clone())
+        });
+
+        assert_eq!(region.block_state_palette.len(), 2);
+        assert!(region.iter_blocks().all(|(_, block)| block == &stone));
+    }
+}
@@ -0,0 +1,455 @@
+use std::collections::BTreeMap;
+
+use crate::block::BlockStateBuilder;
+use crate::region::{set_value_position, value_position};
+use crate::structure::{Coordinates, LitematicaFile, Region};
+use crate::vector::Vec3;
+
+/// Two regions of a file whose bounding boxes intersect. Found by `LitematicaFile::overlapping_regions()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlap {
+    /// The name of the alphabetically first region.
+    pub first: String,
+
+    /// The name of the alphabetically second region.
+    pub second: String,
+
+    /// The corner of the intersection with the lowest coordinates, relative to the schematic origin.
+    pub min: Coordinates,
+
+    /// The size of the intersection, which is always positive.
+    pub size: Coordinates,
+}
+
+/// How `LitematicaFile::resolve_overlaps()` removes overlaps between regions.
+///
+/// In both cases, the alphabetically first region wins where two regions have a block at the same position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlapStrategy {
+    /// Merges every group of overlapping regions into a single region spanning all of them, named after the
+    /// alphabetically first one. Regions overlapped by the merged bounding box are merged into it as well. A block
+    /// that is air in the winning region is taken from the next region.
+    Merge,
+
+    /// Replaces the overlapping blocks of the alphabetically later region with air and removes its tile entities and
+    /// scheduled ticks there, keeping all regions and their sizes.
+    ///
+    /// The result is only unambiguous if the schematic is pasted without replacing blocks with air.
+    ClearLater,
+}
+
+impl LitematicaFile {
+    /// Returns every pair of regions whose bounding boxes intersect, sorted by the region names.
+    ///
+    /// Overlapping regions have no well-defined paste order, so the blocks in the intersection depend on the tool
+    /// that pastes them. Operations that combine regions should resolve overlaps first, see `resolve_overlaps()`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// for overlap in file.overlapping_regions() {
+    ///     println!("{} and {} overlap in {:?}", overlap.first, overlap.second, overlap.size);
+    /// }
+    /// ```
+    pub fn overlapping_regions(&self) -> Vec<Overlap> {
+        let mut names = self.regions.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut overlaps = Vec::new();
+
+        for (index, first) in names.iter().enumerate() {
+            for second in &names[index + 1..] {
+                let intersection = intersect(&self.regions[*first], &self.regions[*second]);
+
+                if let Some((min, size)) = intersection {
+                    overlaps.push(Overlap {
+                        first: first.to_string(),
+                        second: second.to_string(),
+                        min,
+                        size,
+                    });
+                }
+            }
+        }
+
+        overlaps
+    }
+
+    /// Removes all overlaps between regions using the given `OverlapStrategy`, and returns the number of overlaps
+    /// that were resolved. The metadata is updated if anything changed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::overlap::OverlapStrategy;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// file.resolve_overlaps(OverlapStrategy::Merge);
+    ///
+    /// assert!(file.overlapping_regions().is_empty());
+    /// ```
+    pub fn resolve_overlaps(&mut self, strategy: OverlapStrategy) -> usize {
+        let mut overlaps = self.overlapping_regions();
+
+        if overlaps.is_empty() {
+            return 0;
+        }
+
+        let mut resolved = 0;
+
+        match strategy {
+            OverlapStrategy::Merge => {
+                // a merged bounding box can overlap regions that touched none of its members, so merge again until
+                // nothing overlaps anymore
+                while !overlaps.is_empty() {
+                    self.merge_overlapping(&overlaps);
+                    resolved += overlaps.len();
+                    overlaps = self.overlapping_regions();
+                }
+            }
+            OverlapStrategy::ClearLater => {
+                for overlap in &overlaps {
+                    let region = self
+                        .regions
+                        .get_mut(&overlap.second)
+                        .expect("region exists");
+                    clear_box(region, overlap.min, overlap.size);
+                }
+
+                resolved = overlaps.len();
+            }
+        }
+
+        self.update_metadata();
+
+        resolved
+    }
+
+    fn merge_overlapping(&mut self, overlaps: &[Overlap]) {
+        // every region starts in its own group, named after the alphabetically first member
+        let mut group_of = self
+            .regions
+            .keys()
+            .map(|name| (name.clone(), name.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        for overlap in overlaps {
+            let first = group_of[&overlap.first].clone();
+            let second = group_of[&overlap.second].clone();
+            let (winner, loser) = if first <= second {
+                (first, second)
+            } else {
+                (second, first)
+            };
+
+            for group in group_of.values_mut() {
+                if *group == loser {
+                    *group = winner.clone();
+                }
+            }
+        }
+
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for (name, group) in group_of {
+            groups.entry(group).or_default().push(name);
+        }
+
+        for (name, members) in groups {
+            if members.len() < 2 {
+                continue;
+            }
+
+            let regions = members
+                .iter()
+                .map(|member| self.regions.remove(member).expect("region exists"))
+                .collect::<Vec<_>>();
+
+            self.regions.insert(name, merge(&regions));
+        }
+    }
+}
+
+/// Returns the corner and size of the intersection of the bounding boxes of two regions, if they intersect.
+fn intersect(a: &Region, b: &Region) -> Option<(Coordinates, Coordinates)> {
+    let (a_min, b_min) = (a.min_corner(), b.min_corner());
+
+    let axis = |a_min: i32, a_size: i32, b_min: i32, b_size: i32| {
+        let min = a_min.max(b_min);
+        let max = (a_min + a_size.abs()).min(b_min + b_size.abs());
+
+        (max > min).then_some((min, max - min))
+    };
+
+    let (x, size_x) = axis(a_min.x, a.size.x, b_min.x, b.size.x)?;
+    let (y, size_y) = axis(a_min.y, a.size.y, b_min.y, b.size.y)?;
+    let (z, size_z) = axis(a_min.z, a.size.z, b_min.z, b.size.z)?;
+
+    Some((
+        Coordinates { x, y, z },
+        Coordinates {
+            x: size_x,
+            y: size_y,
+            z: size_z,
+        },
+    ))
+}
+
+/// Merges `regions`, which are sorted by priority, into a single region spanning all of them.
+fn merge(regions: &[Region]) -> Region {
+    let mut min = regions[0].min_corner();
+    let mut max = min;
+
+    for region in regions {
+        let corner = region.min_corner();
+
+        min = Coordinates {
+            x: min.x.min(corner.x),
+            y: min.y.min(corner.y),
+            z: min.z.min(corner.z),
+        };
+        max = Coordinates {
+            x: max.x.max(corner.x + region.size.x.abs()),
+            y: max.y.max(corner.y + region.size.y.abs()),
+            z: max.z.max(corner.z + region.size.z.abs()),
+        };
+    }
+
+    let size = Coordinates {
+        x: max.x - min.x,
+        y: max.y - min.y,
+        z: max.z - min.z,
+    };
+
+    let mut merged = Region::filled_with_air(min, size);
+    let mut owner = vec![None; merged.calc_volume() as usize];
+    let mut groups: BTreeMap<String, Vec<Coordinates>> = BTreeMap::new();
+
+    for (index, region) in regions.iter().enumerate() {
+        let corner = region.min_corner();
+        let shift = |position: Coordinates| Coordinates {
+            x: position.x + corner.x - min.x,
+            y: position.y + corner.y - min.y,
+            z: position.z + corner.z - min.z,
+        };
+
+        for (position, block) in region.iter_blocks() {
+            let target = shift(position);
+            let slot = &mut owner[merged.get_3d_index(target) as usize];
+
+            if slot.is_none() && !block.is_air() {
+                *slot = Some(index);
+                merged.set_block(target, block.clone());
+            }
+        }
+
+        for entity in &region.entities {
+            let mut entity = entity.clone();
+            entity.pos += Vec3::from(corner) - min.into();
+
+            merged.entities.push(entity);
+        }
+
+        // tile entities and ticks only belong to the merged region if their block won
+        let owned = |values: &[nbt::Value]| {
+            values
+                .iter()
+                .filter_map(|value| {
+                    let target = shift(value_position(value)?);
+                    let inside = merged.contains(target)
+                        && owner[merged.get_3d_index(target) as usize] == Some(index);
+
+                    inside.then(|| {
+                        let mut value = value.clone();
+                        set_value_position(&mut value, target);
+                        value
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let tile_entities = owned(&region.tile_entities);
+        let block_ticks = owned(&region.pending_block_ticks);
+        let fluid_ticks = owned(&region.pending_fluid_ticks);
+
+        merged.tile_entities.extend(tile_entities);
+        merged.pending_block_ticks.extend(block_ticks);
+        merged.pending_fluid_ticks.extend(fluid_ticks);
+
+        for (position, data) in region.annotations().iter() {
+            let target = shift(position);
+
+            if merged.annotations().get(target).is_none() {
+                merged.extensions.annotations.insert(target, data.clone());
+            }
+        }
+
+        for name in region.group_names() {
+            let positions = region.get_group(name).expect("group exists").positions();
+
+            groups
+                .entry(name.to_string())
+                .or_default()
+                .extend(positions.iter().map(|position| shift(*position)));
+        }
+
+        for (name, position) in region.anchors() {
+            if merged.get_anchor(name).is_none() {
+                merged.set_anchor(name, shift(position));
+            }
+        }
+    }
+
+    for (name, positions) in groups {
+        merged.create_group(name, positions);
+    }
+
+    merged
+}
+
+/// Replaces the blocks of `region` inside of the box with air and removes the tile entities and ticks there.
+///
+/// `min` is relative to the schematic origin.
+fn clear_box(region: &mut Region, min: Coordinates, size: Coordinates) {
+    let corner = region.min_corner();
+    let air = BlockStateBuilder::new("air").build();
+
+    let local = Coordinates {
+        x: min.x - corner.x,
+        y: min.y - corner.y,
+        z: min.z - corner.z,
+    };
+
+    let inside = |position: Coordinates| {
+        (local.x..local.x + size.x).contains(&position.x)
+            && (local.y..local.y + size.y).contains(&position.y)
+            && (local.z..local.z + size.z).contains(&position.z)
+    };
+
+    for y in local.y..local.y + size.y {
+        for z in local.z..local.z + size.z {
+            for x in local.x..local.x + size.x {
+                region.set_block(Coordinates { x, y, z }, air.clone());
+            }
+        }
+    }
+
+    let outside = |value: &nbt::Value| value_position(value).is_none_or(|p| !inside(p));
+
+    region.tile_entities.retain(outside);
+    region.pending_block_ticks.retain(outside);
+    region.pending_fluid_ticks.retain(outside);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::file::DEFAULT_DATA_VERSION;
+
+    use super::*;
+
+    fn file_with_overlap() -> LitematicaFile {
+        let stone = BlockStateBuilder::new("stone").build();
+        let dirt = BlockStateBuilder::new("dirt").build();
+
+        let mut a = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 3, y: 1, z: 1 },
+        );
+        let mut b = Region::filled_with_air(
+            Coordinates { x: 4, y: 0, z: 0 },
+            Coordinates { x: -3, y: 1, z: 1 },
+        );
+        let far = Region::filled_with_air(
+            Coordinates { x: 10, y: 0, z: 0 },
+            Coordinates { x: 1, y: 1, z: 1 },
+        );
+
+        a.set_block((1, 0, 0), stone.clone());
+        b.set_block((0, 0, 0), dirt.clone()); // (2, 0, 0), under air in a
+        b.set_block((1, 0, 0), dirt.clone()); // (3, 0, 0), outside of a
+
+        let mut file = LitematicaFile::from_region("a", a, DEFAULT_DATA_VERSION);
+        file.regions.extend(HashMap::from([
+            ("b".to_string(), b),
+            ("far".to_string(), far),
+        ]));
+
+        file
+    }
+
+    #[test]
+    fn finds_overlaps() {
+        let file = file_with_overlap();
+
+        assert_eq!(
+            file.overlapping_regions(),
+            vec![Overlap {
+                first: "a".to_string(),
+                second: "b".to_string(),
+                min: Coordinates { x: 2, y: 0, z: 0 },
+                size: Coordinates { x: 1, y: 1, z: 1 },
+            }]
+        );
+    }
+
+    #[test]
+    fn merge_overlaps() {
+        let mut file = file_with_overlap();
+
+        assert_eq!(file.resolve_overlaps(OverlapStrategy::Merge), 1);
+        assert!(file.overlapping_regions().is_empty());
+        assert_eq!(file.regions.len(), 2);
+
+        let merged = file.get_region("a").unwrap();
+        assert_eq!(merged.size, Coordinates { x: 5, y: 1, z: 1 });
+        assert_eq!(merged.get_block((1, 0, 0)).to_string(), "minecraft:stone");
+        assert_eq!(merged.get_block((2, 0, 0)).to_string(), "minecraft:dirt");
+        assert_eq!(merged.get_block((3, 0, 0)).to_string(), "minecraft:dirt");
+    }
+
+    #[test]
+    fn merge_overlaps_of_merged_box() {
+        let region = |x, z, size_x, size_z| {
+            Region::filled_with_air(
+                Coordinates { x, y: 0, z },
+                Coordinates {
+                    x: size_x,
+                    y: 1,
+                    z: size_z,
+                },
+            )
+        };
+
+        // c only overlaps the bounding box of a and b, not a or b themselves
+        let mut file = LitematicaFile::from_region("a", region(0, 0, 10, 1), DEFAULT_DATA_VERSION);
+        file.regions.extend(HashMap::from([
+            ("b".to_string(), region(9, 0, 2, 10)),
+            ("c".to_string(), region(0, 5, 1, 1)),
+        ]));
+
+        assert_eq!(file.resolve_overlaps(OverlapStrategy::Merge), 2);
+        assert!(file.overlapping_regions().is_empty());
+        assert_eq!(file.regions.len(), 1);
+        assert_eq!(
+            file.get_region("a").unwrap().size,
+            Coordinates { x: 11, y: 1, z: 10 }
+        );
+    }
+
+    #[test]
+    fn clear_later_overlaps() {
+        let mut file = file_with_overlap();
+
+        assert_eq!(file.resolve_overlaps(OverlapStrategy::ClearLater), 1);
+        assert_eq!(file.regions.len(), 3);
+
+        let b = file.get_region("b").unwrap();
+        assert_eq!(b.get_block((0, 0, 0)).to_string(), "minecraft:air");
+        assert_eq!(b.get_block((1, 0, 0)).to_string(), "minecraft:dirt");
+    }
+}
@@ -0,0 +1,283 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::region::read_palette_index;
+use crate::structure::{BlockState, Region};
+
+/// The blocks of a `Region` as a table of block state strings and one index into it per position, created by
+/// `Region::export_palette_mapping()`.
+///
+/// This is the input most WebGL schematic renderers expect. Serialized to JSON it looks like
+/// `{"size":[x,y,z],"palette":["minecraft:air",...],"blocks":[0,0,1,...]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PaletteMapping {
+    /// The absolute size of the region.
+    pub size: [i32; 3],
+
+    /// The block states as strings, e.g. `minecraft:piston[extended=false,facing=down]`.
+    pub palette: Vec<String>,
+
+    /// The index into `palette` of every position, in YZX order.
+    pub blocks: Vec<u32>,
+}
+
+impl PaletteMapping {
+    /// Serializes the mapping as a compact JSON object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mapping cannot be serialized.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// A block state that was replaced by another one, e.g. by a migration, as reported by `Region::remap_palette()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteChange {
+    /// The block state before the change.
+    pub old: BlockState,
+
+    /// The block state after the change.
+    pub new: BlockState,
+
+    /// The number of blocks that were changed.
+    pub count: u64,
+}
+
+impl Display for PaletteChange {
+    /// Formats the change for users, e.g. `minecraft:grass → minecraft:short_grass (12 blocks)`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} → {} ({} block{})",
+            self.old,
+            self.new,
+            self.count,
+            if self.count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+impl Region {
+    /// Replaces every palette entry for which `remap` returns a different `BlockState`, and returns what changed.
+    ///
+    /// Only the palette is rewritten, entries that become equal are merged. The change log has one entry per replaced
+    /// block state that is actually used, in palette order, so tools can show exactly what a conversion did.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// let changes = region.remap_palette(|block| {
+    ///     (block.get_name().get_path() == "grass").then(|| BlockStateBuilder::new("short_grass").build())
+    /// });
+    ///
+    /// for change in changes {
+    ///     println!("{}", change);
+    /// }
+    /// ```
+    pub fn remap_palette(
+        &mut self,
+        mut remap: impl FnMut(&BlockState) -> Option<BlockState>,
+    ) -> Vec<PaletteChange> {
+        let replacements = self
+            .block_state_palette
+            .iter()
+            .map(|block| remap(block).filter(|new| new != block))
+            .collect::<Vec<_>>();
+
+        if replacements.iter().all(Option::is_none) {
+            return Vec::new();
+        }
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+        let mut counts = vec![0; self.block_state_palette.len()];
+
+        for index in 0..self.calc_volume() {
+            counts
+                [read_palette_index(&self.block_states, index, required_bits, bitmask) as usize] +=
+                1;
+        }
+
+        let mut changes: Vec<PaletteChange> = Vec::new();
+
+        for ((old, new), count) in self
+            .block_state_palette
+            .iter()
+            .zip(&replacements)
+            .zip(counts)
+        {
+            let Some(new) = new else {
+                continue;
+            };
+
+            // unused entries, e.g. reserved placeholders, didn't change any blocks
+            if count == 0 {
+                continue;
+            }
+
+            match changes
+                .iter_mut()
+                .find(|change| change.old == *old && change.new == *new)
+            {
+                Some(change) => change.count += count,
+                None => changes.push(PaletteChange {
+                    old: old.clone(),
+                    new: new.clone(),
+                    count,
+                }),
+            }
+        }
+
+        let mut replacements = replacements.into_iter();
+        self.replace_palette(|_| replacements.next().flatten());
+
+        changes
+    }
+
+    /// Exports the blocks of the region as a `PaletteMapping` for external renderers.
+    ///
+    /// The palette only contains the block states that are used, in the order they first appear in YZX order, so the
+    /// same blocks always produce the same mapping, no matter how the palette of the region was built up.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let mapping = file.get_region("test").unwrap().export_palette_mapping();
+    ///
+    /// std::fs::write("test.json", mapping.to_json().unwrap()).unwrap();
+    /// ```
+    pub fn export_palette_mapping(&self) -> PaletteMapping {
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+        let volume = self.calc_volume();
+
+        let mut remap: Vec<Option<u32>> = vec![None; self.block_state_palette.len()];
+        let mut palette = Vec::new();
+        let mut blocks = Vec::with_capacity(volume as usize);
+
+        for index in 0..volume {
+            let palette_index =
+                read_palette_index(&self.block_states, index, required_bits, bitmask) as usize;
+
+            let mapped = *remap[palette_index].get_or_insert_with(|| {
+                palette.push(self.block_state_palette[palette_index].to_string());
+                palette.len() as u32 - 1
+            });
+
+            blocks.push(mapped);
+        }
+
+        PaletteMapping {
+            size: [self.size.x.abs(), self.size.y.abs(), self.size.z.abs()],
+            palette,
+            blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::{Coordinates, LitematicaFile};
+
+    use super::*;
+
+    #[test]
+    fn remap_palette() {
+        let mut region = Region::new((4, 1, 1));
+
+        let grass = BlockStateBuilder::new("grass").build();
+        let short_grass = BlockStateBuilder::new("short_grass").build();
+        let stone = BlockStateBuilder::new("stone").build();
+
+        region.set_block((0, 0, 0), grass.clone());
+        region.set_block((1, 0, 0), grass.clone());
+        region.set_block((2, 0, 0), stone.clone());
+        region.reserve_palette(4);
+
+        let changes = region.remap_palette(|block| {
+            (block.get_name().get_path() != "air").then(|| short_grass.clone())
+        });
+
+        assert_eq!(
+            changes,
+            [
+                PaletteChange {
+                    old: grass,
+                    new: short_grass.clone(),
+                    count: 2,
+                },
+                PaletteChange {
+                    old: stone,
+                    new: short_grass.clone(),
+                    count: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "minecraft:grass → minecraft:short_grass (2 blocks)"
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "minecraft:stone → minecraft:short_grass (1 block)"
+        );
+        assert_eq!(region.get_block((2, 0, 0)), &short_grass);
+        assert!(region.get_block((3, 0, 0)).is_air());
+
+        assert_eq!(region.remap_palette(|_| Some(short_grass.clone())).len(), 1);
+        assert!(region.remap_palette(|block| Some(block.clone())).is_empty());
+    }
+
+    #[test]
+    fn export_palette_mapping() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let mapping = region.export_palette_mapping();
+
+        assert_eq!(mapping.blocks.len() as u64, region.calc_volume());
+        assert_eq!(mapping.blocks[0], 0);
+
+        for (index, (_, block)) in region.iter_blocks().enumerate().step_by(61) {
+            assert_eq!(
+                mapping.palette[mapping.blocks[index] as usize],
+                block.to_string()
+            );
+        }
+
+        // the same blocks placed in a different order produce the same mapping
+        let stone = BlockStateBuilder::new("stone").build();
+        let dirt = BlockStateBuilder::new("dirt").build();
+
+        let mut first = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 2, y: 1, z: 1 },
+        );
+        let mut second = first.clone();
+
+        first.set_block((0, 0, 0), stone.clone());
+        first.set_block((1, 0, 0), dirt.clone());
+        second.set_block((1, 0, 0), dirt);
+        second.set_block((0, 0, 0), stone);
+
+        assert_eq!(
+            first.export_palette_mapping(),
+            second.export_palette_mapping()
+        );
+        assert_eq!(
+            first.export_palette_mapping().to_json().unwrap(),
+            r#"{"size":[2,1,1],"palette":["minecraft:stone","minecraft:dirt"],"blocks":[0,1]}"#
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use nbt::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::structure::{BlockState, Coordinates, Entity, LitematicaFile, Region, RegionExtensions};
+
+/// The changes between two versions of a schematic, which can be applied to another copy of the older version.
+///
+/// Created by `LitematicaFile::diff()` and applied with `LitematicaFile::apply_patch()`. Only changed blocks are stored,
+/// so a patch of a small edit to a huge schematic stays small. The entities, tile entities, scheduled ticks, annotations,
+/// groups and anchors of a region are stored completely if any of them changed, and so are regions that were added or resized.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::patch::SchematicPatch;
+///
+/// let old = LitematicaFile::read("farm_v1.litematic").unwrap();
+/// let new = LitematicaFile::read("farm_v2.litematic").unwrap();
+///
+/// old.diff(&new).write("farm_v1_to_v2.litpatch").unwrap();
+///
+/// let mut copy = LitematicaFile::read("farm_v1.litematic").unwrap();
+/// copy.apply_patch(&SchematicPatch::read("farm_v1_to_v2.litpatch").unwrap()).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SchematicPatch {
+    base_checksum: i64,
+
+    #[serde(default)]
+    changed: BTreeMap<String, RegionPatch>,
+
+    #[serde(default)]
+    added: BTreeMap<String, Region>,
+
+    #[serde(default)]
+    removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RegionPatch {
+    palette: Vec<BlockState>,
+
+    /// The changed blocks as `x, y, z, palette index` quadruples.
+    #[serde(serialize_with = "nbt::i32_array")]
+    blocks: Vec<i32>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entities: Option<Vec<Entity>>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tile_entities: Option<Vec<Value>>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pending_block_ticks: Option<Vec<Value>>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pending_fluid_ticks: Option<Vec<Value>>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ritematica: Option<RegionExtensions>,
+}
+
+impl SchematicPatch {
+    /// Reads a patch written by `write()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid patch.
+    #[cfg(feature = "fs")]
+    pub fn read(path: impl AsRef<Path>) -> Result<SchematicPatch> {
+        let file = File::open(path)?;
+        let buf_reader = BufReader::new(file);
+
+        Ok(nbt::from_gzip_reader(buf_reader)?)
+    }
+
+    /// Writes the patch as gzip-compressed NBT, like a `Litematica` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or serialized.
+    #[cfg(feature = "fs")]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let mut buf_writer = BufWriter::new(file);
+
+        nbt::to_gzip_writer(&mut buf_writer, self, None)?;
+
+        Ok(())
+    }
+
+    /// Checks whether the patch changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns the number of changed blocks in regions that exist in both versions.
+    pub fn changed_blocks(&self) -> usize {
+        self.changed
+            .values()
+            .map(|region| region.blocks.len() / 4)
+            .sum()
+    }
+
+    /// Checks whether `file` is the exact version the patch was created from, see `LitematicaFile::compute_checksum()`.
+    ///
+    /// A patch can also be applied to a modified copy, as long as the changed regions still exist with the same sizes.
+    pub fn matches_base(&self, file: &LitematicaFile) -> bool {
+        file.compute_checksum() as i64 == self.base_checksum
+    }
+}
+
+impl LitematicaFile {
+    /// Creates a `SchematicPatch` containing the changes from this file to `newer`.
+    ///
+    /// Regions are matched by name. A region whose size changed is stored as removed and added again.
+    /// The metadata is not part of the patch and is recomputed when the patch is applied.
+    pub fn diff(&self, newer: &LitematicaFile) -> SchematicPatch {
+        let mut patch = SchematicPatch {
+            base_checksum: self.compute_checksum() as i64,
+            ..Default::default()
+        };
+
+        for (name, old) in &self.regions {
+            match newer.regions.get(name) {
+                Some(new) if new.position == old.position && new.size == old.size => {
+                    let region_patch = diff_region(old, new);
+
+                    if region_patch != RegionPatch::default() {
+                        patch.changed.insert(name.clone(), region_patch);
+                    }
+                }
+                Some(new) => {
+                    patch.removed.push(name.clone());
+                    patch.added.insert(name.clone(), new.clone());
+                }
+                None => patch.removed.push(name.clone()),
+            }
+        }
+
+        for (name, new) in &newer.regions {
+            if !self.regions.contains_key(name) {
+                patch.added.insert(name.clone(), new.clone());
+            }
+        }
+
+        patch.removed.sort();
+
+        patch
+    }
+
+    /// Applies a `SchematicPatch` created by `diff()` to the file and updates the metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a region changed by the patch doesn't exist or is too small for the changed blocks, or if the
+    /// patch itself is corrupt, e.g. it refers to palette entries it doesn't have or adds regions with invalid block
+    /// data. The file is left unchanged in that case. Positions and sizes aren't stored in the patch, so a region that
+    /// was moved or grew since the diff is patched anyway.
+    pub fn apply_patch(&mut self, patch: &SchematicPatch) -> Result<()> {
+        for (name, region_patch) in &patch.changed {
+            if patch.removed.contains(name) {
+                return Err(invalid_data(format!(
+                    "Region {} is both changed and removed by the patch",
+                    name
+                )));
+            }
+
+            let region = self
+                .regions
+                .get(name)
+                .ok_or_else(|| invalid_data(format!("Region {} does not exist", name)))?;
+
+            let palette_len = region_patch.palette.len();
+            let invalid_index = region_patch
+                .blocks
+                .chunks_exact(4)
+                .find(|block| !(0..palette_len as i64).contains(&(block[3] as i64)));
+
+            if let Some(block) = invalid_index {
+                return Err(invalid_data(format!(
+                    "Block of region {} uses palette index {}, but the patch palette only has {} entries",
+                    name, block[3], palette_len
+                )));
+            }
+
+            let in_bounds = region_patch
+                .blocks
+                .chunks_exact(4)
+                .all(|block| region.contains((block[0], block[1], block[2])));
+
+            if !in_bounds {
+                return Err(invalid_data(format!(
+                    "Region {} is smaller than in the patch",
+                    name
+                )));
+            }
+        }
+
+        for region in patch.added.values() {
+            region.validate()?;
+        }
+
+        for name in &patch.removed {
+            self.regions.remove(name);
+        }
+
+        for (name, region_patch) in &patch.changed {
+            let region = self.regions.get_mut(name).expect("region exists");
+
+            for block in region_patch.blocks.chunks_exact(4) {
+                let state = region_patch.palette[block[3] as usize].clone();
+                region.set_block((block[0], block[1], block[2]), state);
+            }
+
+            if let Some(entities) = &region_patch.entities {
+                region.entities = entities.clone();
+            }
+            if let Some(tile_entities) = &region_patch.tile_entities {
+                region.tile_entities = tile_entities.clone();
+            }
+            if let Some(ticks) = &region_patch.pending_block_ticks {
+                region.pending_block_ticks = ticks.clone();
+            }
+            if let Some(ticks) = &region_patch.pending_fluid_ticks {
+                region.pending_fluid_ticks = ticks.clone();
+            }
+            if let Some(extensions) = &region_patch.ritematica {
+                region.extensions = extensions.clone();
+            }
+        }
+
+        self.regions.extend(
+            patch
+                .added
+                .iter()
+                .map(|(name, region)| (name.clone(), region.clone())),
+        );
+
+        self.update_metadata();
+
+        Ok(())
+    }
+}
+
+/// Collects the changes between two regions of the same size.
+fn diff_region(old: &Region, new: &Region) -> RegionPatch {
+    let mut patch = RegionPatch::default();
+    let mut palette_indices: HashMap<&BlockState, i32> = HashMap::new();
+
+    for ((position, old_block), (_, new_block)) in old.iter_blocks().zip(new.iter_blocks()) {
+        if old_block == new_block {
+            continue;
+        }
+
+        let index = *palette_indices.entry(new_block).or_insert_with(|| {
+            patch.palette.push(new_block.clone());
+            patch.palette.len() as i32 - 1
+        });
+
+        let Coordinates { x, y, z } = position;
+        patch.blocks.extend([x, y, z, index]);
+    }
+
+    let changed = |old: &Vec<Value>, new: &Vec<Value>| (old != new).then(|| new.clone());
+
+    patch.entities = (old.entities != new.entities).then(|| new.entities.clone());
+    patch.tile_entities = changed(&old.tile_entities, &new.tile_entities);
+    patch.pending_block_ticks = changed(&old.pending_block_ticks, &new.pending_block_ticks);
+    patch.pending_fluid_ticks = changed(&old.pending_fluid_ticks, &new.pending_fluid_ticks);
+    patch.ritematica = (old.extensions != new.extensions).then(|| new.extensions.clone());
+
+    patch
+}
+
+fn invalid_data(message: String) -> crate::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn diff_and_apply() {
+        let path = std::env::temp_dir().join("ritematica_diff_and_apply.litpatch");
+
+        let old = LitematicaFile::read("test.litematic").unwrap();
+        let mut new = LitematicaFile::read("test.litematic").unwrap();
+
+        assert!(old.diff(&new).is_empty());
+
+        let region = new.get_region_mut("test").unwrap();
+        region.set_block((0, 0, 0), BlockStateBuilder::new("diamond_block").build());
+        region.set_block((3, 2, 1), BlockStateBuilder::new("diamond_block").build());
+
+        let extra = Region::filled_with_air(
+            Coordinates { x: 40, y: 0, z: 0 },
+            Coordinates { x: 2, y: 2, z: 2 },
+        );
+        new.get_regions_mut().insert("extra".to_string(), extra);
+
+        let patch = old.diff(&new);
+        assert_eq!(patch.changed_blocks(), 2);
+        assert!(patch.matches_base(&old));
+
+        patch.write(&path).unwrap();
+        let patch = SchematicPatch::read(&path).unwrap();
+
+        let mut copy = LitematicaFile::read("test.litematic").unwrap();
+        copy.apply_patch(&patch).unwrap();
+        new.update_metadata();
+
+        assert_eq!(copy.compute_checksum(), new.compute_checksum());
+        assert_eq!(copy.metadata.region_count, 2);
+    }
+
+    #[test]
+    fn apply_to_missing_region() {
+        let old = LitematicaFile::read("test.litematic").unwrap();
+        let mut new = LitematicaFile::read("test.litematic").unwrap();
+
+        new.get_region_mut("test")
+            .unwrap()
+            .set_block((0, 0, 0), BlockStateBuilder::new("diamond_block").build());
+
+        let patch = old.diff(&new);
+
+        let mut other = LitematicaFile::read("test.litematic").unwrap();
+        other.rename_region("test", "renamed");
+
+        assert!(other.apply_patch(&patch).is_err());
+        assert!(other.get_region("renamed").is_some());
+    }
+    #[test]
+    fn apply_corrupt_patch() {
+        let old = LitematicaFile::read("test.litematic").unwrap();
+        let mut new = LitematicaFile::read("test.litematic").unwrap();
+
+        new.get_region_mut("test")
+            .unwrap()
+            .set_block((0, 0, 0), BlockStateBuilder::new("diamond_block").build());
+
+        let patch = old.diff(&new);
+        let checksum = old.compute_checksum();
+
+        let mut invalid_index = patch.clone();
+        invalid_index.changed.get_mut("test").unwrap().blocks[3] = 99;
+
+        let mut changed_and_removed = patch.clone();
+        changed_and_removed.removed.push("test".to_string());
+
+        let mut corrupt_region = patch.clone();
+        let mut region = Region::new((4, 4, 4));
+        region.block_states.truncate(1);
+        corrupt_region.added.insert("corrupt".to_string(), region);
+
+        for patch in [invalid_index, changed_and_removed, corrupt_region] {
+            let mut copy = LitematicaFile::read("test.litematic").unwrap();
+
+            assert!(copy.apply_patch(&patch).is_err());
+            assert_eq!(copy.compute_checksum(), checksum);
+        }
+    }
+}
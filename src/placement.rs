@@ -0,0 +1,932 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "fs")]
+use crate::error::Result;
+use crate::region::{read_palette_index, set_value_position, value_position};
+use crate::structure::{BlockState, Coordinates, Entity, LitematicaFile, Region};
+use crate::trace;
+use crate::vector::Vec3;
+
+const DIRECTIONS: [&str; 4] = ["north", "east", "south", "west"];
+
+/// The `Facing` bytes of item frames for the horizontal `DIRECTIONS`, in the same order.
+const FACING_BYTES: [i8; 4] = [2, 5, 3, 4];
+
+/// A rotation around the y axis, named like in Litematica and Minecraft.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Rotation {
+    #[default]
+    #[serde(rename = "NONE")]
+    None,
+
+    #[serde(rename = "CLOCKWISE_90")]
+    Clockwise90,
+
+    #[serde(rename = "CLOCKWISE_180")]
+    Clockwise180,
+
+    #[serde(rename = "COUNTERCLOCKWISE_90")]
+    Counterclockwise90,
+}
+
+impl Rotation {
+    pub(crate) fn quarter_turns(self) -> usize {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::Counterclockwise90 => 3,
+        }
+    }
+}
+
+/// A mirror along one horizontal axis, named like in Litematica and Minecraft.
+///
+/// `LeftRight` flips the z axis (north and south), `FrontBack` flips the x axis (east and west).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mirror {
+    #[default]
+    #[serde(rename = "NONE")]
+    None,
+
+    #[serde(rename = "LEFT_RIGHT")]
+    LeftRight,
+
+    #[serde(rename = "FRONT_BACK")]
+    FrontBack,
+}
+
+/// The placement of a single region inside of a `Placement`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubRegionPlacement {
+    /// The position of the region relative to the placement origin, before the placement itself is rotated or mirrored.
+    #[serde(with = "coordinates_array")]
+    pub pos: Coordinates,
+
+    #[serde(default)]
+    pub rotation: Rotation,
+
+    #[serde(default)]
+    pub mirror: Mirror,
+
+    /// Disabled regions are removed by `LitematicaFile::apply_placement()`.
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub ignore_entities: bool,
+
+    // fields that are only relevant to Litematica itself, kept so that they survive reading and writing
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SubRegionPlacement {
+    /// Creates an enabled `SubRegionPlacement` at `pos` without rotation or mirror.
+    pub fn new(pos: impl Into<Coordinates>) -> Self {
+        Self {
+            pos: pos.into(),
+            enabled: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// A schematic placement as saved by Litematica into its `.json` placement files.
+///
+/// A placement describes where a schematic is pasted into the world and how it and its regions are rotated and mirrored.
+/// Fields that are only relevant to Litematica itself, like the color of the bounding box, are kept as they are.
+///
+/// # Examples
+/// ```
+/// use ritematica::{LitematicaFile, Placement};
+///
+/// let placement = Placement::read("placement.json").unwrap();
+/// let mut file = LitematicaFile::read("test.litematic").unwrap();
+///
+/// file.apply_placement(&placement);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+    pub name: String,
+
+    /// The path of the schematic file the placement belongs to.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schematic: Option<PathBuf>,
+
+    /// The position in the world where the origin of the schematic is placed.
+    #[serde(with = "coordinates_array")]
+    pub origin: Coordinates,
+
+    #[serde(default)]
+    pub rotation: Rotation,
+
+    #[serde(default)]
+    pub mirror: Mirror,
+
+    #[serde(default)]
+    pub ignore_entities: bool,
+
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+
+    /// The placements of the individual regions, keyed by region name. Regions without an entry are placed unchanged.
+    #[serde(rename = "placements")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(with = "sub_region_list")]
+    pub sub_regions: BTreeMap<String, SubRegionPlacement>,
+
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Placement {
+    /// Creates an enabled `Placement` at `origin` without rotation, mirror or region placements.
+    pub fn new(name: impl Into<String>, origin: impl Into<Coordinates>) -> Self {
+        Self {
+            name: name.into(),
+            origin: origin.into(),
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// Reads a `Placement` from a Litematica placement `.json` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid placement.
+    #[cfg(feature = "fs")]
+    pub fn read(path: impl AsRef<Path>) -> Result<Placement> {
+        let file = File::open(path)?;
+        let buf_reader = BufReader::new(file);
+
+        Ok(serde_json::from_reader(buf_reader)?)
+    }
+
+    /// Writes the `Placement` as a `.json` file that can be loaded by Litematica.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or serialized.
+    #[cfg(feature = "fs")]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let buf_writer = BufWriter::new(file);
+
+        serde_json::to_writer_pretty(buf_writer, self)?;
+
+        Ok(())
+    }
+}
+
+impl LitematicaFile {
+    /// Bakes the rotation, mirror and region placements of a `Placement` into the file.
+    ///
+    /// Every region is first moved, mirrored and rotated according to its `SubRegionPlacement`, and then the whole
+    /// schematic is mirrored and rotated around its origin. Block states are transformed along with their positions,
+    /// e.g. `facing`, `axis`, `rotation`, rail shapes and the `north`, `east`, `south` and `west` connections.
+    /// Disabled regions are removed, and entities are dropped if the placement or region ignores them.
+    ///
+    /// The `origin` of the placement is not baked in, since positions in a file are always relative to the schematic origin.
+    /// The metadata is updated to match the new regions.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{LitematicaFile, Placement, Rotation};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// let mut placement = Placement::new("test", (0, 0, 0));
+    /// placement.rotation = Rotation::Clockwise90;
+    ///
+    /// file.apply_placement(&placement);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(placement = %placement.name, regions = self.regions.len(), elapsed_ms))
+    )]
+    pub fn apply_placement(&mut self, placement: &Placement) {
+        let _timer = trace::Timer::start();
+
+        let global = Step {
+            mirror: placement.mirror,
+            rotation: placement.rotation,
+            from: Coordinates { x: 0, y: 0, z: 0 },
+            to: Coordinates { x: 0, y: 0, z: 0 },
+        };
+
+        for (name, region) in std::mem::take(&mut self.regions) {
+            let sub_region = placement.sub_regions.get(&name);
+
+            if sub_region.is_some_and(|sub_region| !sub_region.enabled) {
+                continue;
+            }
+
+            let local = Step {
+                mirror: sub_region.map_or(Mirror::None, |sub_region| sub_region.mirror),
+                rotation: sub_region.map_or(Rotation::None, |sub_region| sub_region.rotation),
+                from: region.position,
+                to: sub_region.map_or(region.position, |sub_region| sub_region.pos),
+            };
+
+            let keep_entities = !placement.ignore_entities
+                && !sub_region.is_some_and(|sub_region| sub_region.ignore_entities);
+
+            self.regions.insert(
+                name,
+                transform_region(&region, &[local, global], keep_entities),
+            );
+        }
+
+        self.update_metadata();
+    }
+}
+
+/// A single mirror and rotation around `from`, followed by a move to `to`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Step {
+    pub(crate) mirror: Mirror,
+    pub(crate) rotation: Rotation,
+    pub(crate) from: Coordinates,
+    pub(crate) to: Coordinates,
+}
+
+impl Step {
+    fn apply(&self, [x, y, z]: [f64; 3]) -> [f64; 3] {
+        let mut x = x - self.from.x as f64;
+        let mut z = z - self.from.z as f64;
+
+        match self.mirror {
+            Mirror::None => {}
+            Mirror::LeftRight => z = -z,
+            Mirror::FrontBack => x = -x,
+        }
+
+        let (x, z) = match self.rotation {
+            Rotation::None => (x, z),
+            Rotation::Clockwise90 => (-z, x),
+            Rotation::Clockwise180 => (-x, -z),
+            Rotation::Counterclockwise90 => (z, -x),
+        };
+
+        [
+            x + self.to.x as f64,
+            y - self.from.y as f64 + self.to.y as f64,
+            z + self.to.z as f64,
+        ]
+    }
+
+    /// Applies the mirror and rotation to the yaw of an entity, in degrees, where `0` faces south and `90` faces west.
+    fn apply_yaw(&self, yaw: f32) -> f32 {
+        if self.mirror == Mirror::None && self.rotation == Rotation::None {
+            return yaw;
+        }
+
+        let mirrored = match self.mirror {
+            Mirror::None => yaw,
+            Mirror::LeftRight => 180.0 - yaw,
+            Mirror::FrontBack => -yaw,
+        };
+
+        wrap_degrees(mirrored + 90.0 * self.rotation.quarter_turns() as f32)
+    }
+
+    fn apply_direction(&self, direction: usize) -> usize {
+        let mirrored = match self.mirror {
+            Mirror::LeftRight if direction.is_multiple_of(2) => (direction + 2) % 4,
+            Mirror::FrontBack if !direction.is_multiple_of(2) => (direction + 2) % 4,
+            _ => direction,
+        };
+
+        (mirrored + self.rotation.quarter_turns()) % 4
+    }
+}
+
+fn apply_steps(steps: &[Step], position: Coordinates) -> Coordinates {
+    let [x, y, z] = steps.iter().fold(
+        [position.x as f64, position.y as f64, position.z as f64],
+        |position, step| step.apply(position),
+    );
+
+    Coordinates {
+        x: x as i32,
+        y: y as i32,
+        z: z as i32,
+    }
+}
+
+/// Transforms an entity position, which is continuous, so that it stays at the same place inside of its transformed block.
+fn apply_steps_continuous(steps: &[Step], [x, y, z]: [f64; 3]) -> [f64; 3] {
+    let [x, y, z] = steps
+        .iter()
+        .fold([x - 0.5, y - 0.5, z - 0.5], |position, step| {
+            step.apply(position)
+        });
+
+    [x + 0.5, y + 0.5, z + 0.5]
+}
+
+/// Wraps an angle in degrees into `-180..180`, like Minecraft does for entity rotations.
+fn wrap_degrees(degrees: f32) -> f32 {
+    let wrapped = degrees % 360.0;
+
+    if wrapped >= 180.0 {
+        wrapped - 360.0
+    } else if wrapped < -180.0 {
+        wrapped + 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// Returns the `Entity` facing the way it should after applying all `steps`. Only the yaw and the `Facing` of item
+/// frames change, the pitch is kept since mirrors and rotations around the y axis don't tilt entities.
+fn transform_entity_rotation(entity: &mut Entity, steps: &[Step]) {
+    for step in steps {
+        entity.rotation.yaw = step.apply_yaw(entity.rotation.yaw);
+
+        // up and down, and unknown values, stay as they are
+        if let Some(direction) = entity
+            .facing
+            .and_then(|facing| FACING_BYTES.iter().position(|byte| *byte == facing))
+        {
+            entity.facing = Some(FACING_BYTES[step.apply_direction(direction)]);
+        }
+    }
+}
+
+fn direction_index(direction: &str) -> Option<usize> {
+    DIRECTIONS.iter().position(|d| *d == direction)
+}
+
+/// Returns the `BlockState` after mirroring and then rotating it.
+pub(crate) fn mirror_and_rotate(
+    block: &BlockState,
+    mirror: Mirror,
+    rotation: Rotation,
+) -> BlockState {
+    let step = Step {
+        mirror,
+        rotation,
+        from: Coordinates::default(),
+        to: Coordinates::default(),
+    };
+
+    transform_block(block, &step)
+}
+
+/// Returns the `BlockState` after applying the mirror and rotation of `step` to it.
+fn transform_block(block: &BlockState, step: &Step) -> BlockState {
+    let turns = step.rotation.quarter_turns();
+    let mirrored = step.mirror != Mirror::None;
+
+    let transform_direction =
+        |direction: &str| direction_index(direction).map(|i| DIRECTIONS[step.apply_direction(i)]);
+
+    let properties = block
+        .properties
+        .iter()
+        .map(|(key, value)| {
+            let new_value = match key.as_str() {
+                "facing" => transform_direction(value).map(str::to_string),
+                "axis" if !turns.is_multiple_of(2) => match value.as_str() {
+                    "x" => Some("z".to_string()),
+                    "z" => Some("x".to_string()),
+                    _ => None,
+                },
+                "rotation" => value.parse::<usize>().ok().map(|rotation| {
+                    let rotation = rotation % 16;
+                    let rotation = match step.mirror {
+                        Mirror::None => rotation,
+                        Mirror::LeftRight => (24 - rotation) % 16,
+                        Mirror::FrontBack => (16 - rotation) % 16,
+                    };
+
+                    ((rotation + turns * 4) % 16).to_string()
+                }),
+                "shape" => transform_shape(value, mirrored, &transform_direction),
+                // door hinges and the halves of double chests swap sides in a mirror
+                "hinge" | "type" if mirrored => match value.as_str() {
+                    "left" => Some("right".to_string()),
+                    "right" => Some("left".to_string()),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            let key = transform_direction(key).map_or_else(|| key.clone(), str::to_string);
+
+            (key, new_value.unwrap_or_else(|| value.clone()))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    BlockState::from_parts(block.name.clone(), properties)
+}
+
+/// Transforms the `shape` of rails and stairs, or returns `None` if it does not change.
+fn transform_shape(
+    shape: &str,
+    mirrored: bool,
+    transform_direction: &impl Fn(&str) -> Option<&'static str>,
+) -> Option<String> {
+    if let Some(side) = shape
+        .strip_suffix("_left")
+        .or_else(|| shape.strip_suffix("_right"))
+    {
+        return mirrored.then(|| {
+            let flipped = if shape.ends_with("_left") {
+                "right"
+            } else {
+                "left"
+            };
+
+            format!("{}_{}", side, flipped)
+        });
+    }
+
+    if let Some(direction) = shape.strip_prefix("ascending_") {
+        return Some(format!("ascending_{}", transform_direction(direction)?));
+    }
+
+    let (first, second) = shape.split_once('_')?;
+    let first = transform_direction(first)?;
+    let second = transform_direction(second)?;
+
+    // rail shapes always name north or south first
+    let shape = match (first, second) {
+        ("north", "south") | ("south", "north") => "north_south".to_string(),
+        ("east", "west") | ("west", "east") => "east_west".to_string(),
+        ("east" | "west", _) => format!("{}_{}", second, first),
+        _ => format!("{}_{}", first, second),
+    };
+
+    Some(shape)
+}
+
+/// Returns a copy of `region` with all `steps` applied to its blocks, entities, tile entities, ticks, annotations, groups and anchors.
+///
+/// The returned region always has a positive size.
+pub(crate) fn transform_region(region: &Region, steps: &[Step], keep_entities: bool) -> Region {
+    let min = region.min_corner();
+    let size = Coordinates {
+        x: region.size.x.abs(),
+        y: region.size.y.abs(),
+        z: region.size.z.abs(),
+    };
+
+    let first = apply_steps(steps, min);
+    let last = apply_steps(
+        steps,
+        Coordinates {
+            x: min.x + size.x - 1,
+            y: min.y + size.y - 1,
+            z: min.z + size.z - 1,
+        },
+    );
+
+    let new_min = Coordinates {
+        x: first.x.min(last.x),
+        y: first.y.min(last.y),
+        z: first.z.min(last.z),
+    };
+    let new_size = Coordinates {
+        x: (first.x - last.x).abs() + 1,
+        y: (first.y - last.y).abs() + 1,
+        z: (first.z - last.z).abs() + 1,
+    };
+
+    let mut transformed = Region::filled_with_air(new_min, new_size);
+
+    // converts a position inside of `region` into a position inside of `transformed`
+    let transform_position = |local: Coordinates| {
+        let absolute = apply_steps(
+            steps,
+            Coordinates {
+                x: min.x + local.x,
+                y: min.y + local.y,
+                z: min.z + local.z,
+            },
+        );
+
+        Coordinates {
+            x: absolute.x - new_min.x,
+            y: absolute.y - new_min.y,
+            z: absolute.z - new_min.z,
+        }
+    };
+
+    // every step maps different block states to different block states, so the palette stays free of duplicates
+    let palette = region
+        .block_state_palette
+        .iter()
+        .map(|block| {
+            steps
+                .iter()
+                .fold(block.clone(), |block, step| transform_block(&block, step))
+        })
+        .collect::<Vec<_>>();
+
+    let required_bits = Region::calc_required_bits(&palette);
+    let bitmask = (1 << required_bits) - 1;
+    let words = (region.calc_volume() * required_bits).div_ceil(64);
+
+    transformed.block_states = vec![0; words as usize];
+
+    let mut block_index = 0;
+
+    for y in 0..size.y {
+        for z in 0..size.z {
+            for x in 0..size.x {
+                let palette_index =
+                    read_palette_index(&region.block_states, block_index, required_bits, bitmask);
+                let target = transformed.get_3d_index(transform_position(Coordinates { x, y, z }));
+
+                Region::set_block_index(
+                    &mut transformed.block_states,
+                    target,
+                    palette_index,
+                    required_bits,
+                    bitmask,
+                );
+
+                block_index += 1;
+            }
+        }
+    }
+
+    transformed.block_state_palette = palette;
+
+    if keep_entities {
+        for entity in &region.entities {
+            let position = apply_steps_continuous(steps, (entity.pos + min.into()).into());
+
+            let mut entity = entity.clone();
+            entity.pos = Vec3::from(position) - new_min.into();
+            transform_entity_rotation(&mut entity, steps);
+            transformed.entities.push(entity);
+        }
+    }
+
+    let transform_values = |values: &[nbt::Value]| {
+        values
+            .iter()
+            .map(|value| {
+                let mut value = value.clone();
+
+                if let Some(position) = value_position(&value) {
+                    set_value_position(&mut value, transform_position(position));
+                }
+
+                value
+            })
+            .collect::<Vec<_>>()
+    };
+
+    transformed.tile_entities = transform_values(&region.tile_entities);
+    transformed.pending_block_ticks = transform_values(&region.pending_block_ticks);
+    transformed.pending_fluid_ticks = transform_values(&region.pending_fluid_ticks);
+
+    for (position, data) in region.annotations().iter() {
+        transformed
+            .extensions
+            .annotations
+            .insert(transform_position(position), data.clone());
+    }
+
+    for name in region.group_names() {
+        let positions = region
+            .get_group(name)
+            .expect("group exists")
+            .positions()
+            .iter()
+            .map(|position| transform_position(*position))
+            .collect::<Vec<_>>();
+
+        transformed.create_group(name, positions);
+    }
+
+    for (name, position) in region.anchors() {
+        transformed.set_anchor(name, transform_position(position));
+    }
+
+    transformed
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+// Litematica stores positions as `[x, y, z]` arrays
+mod coordinates_array {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(
+        coordinates: &Coordinates,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        [coordinates.x, coordinates.y, coordinates.z].serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Coordinates, D::Error> {
+        let [x, y, z] = <[i32; 3]>::deserialize(deserializer)?;
+
+        Ok(Coordinates { x, y, z })
+    }
+}
+
+// Litematica stores region placements as a list of `{ "name": ..., "placement": { ... } }` objects
+mod sub_region_list {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Entry<T> {
+        name: String,
+        placement: T,
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        sub_regions: &BTreeMap<String, SubRegionPlacement>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        sub_regions
+            .iter()
+            .map(|(name, placement)| Entry {
+                name: name.clone(),
+                placement,
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<BTreeMap<String, SubRegionPlacement>, D::Error> {
+        let entries = Vec::<Entry<SubRegionPlacement>>::deserialize(deserializer)?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| (entry.name, entry.placement))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::vector::Rotation2;
+
+    use super::*;
+
+    #[test]
+    fn placement_round_trip() {
+        let path = std::env::temp_dir().join("ritematica_placement_round_trip.json");
+
+        let json = r#"{
+            "name": "test",
+            "schematic": "/home/user/.minecraft/schematics/test.litematic",
+            "origin": [10, 64, -20],
+            "rotation": "CLOCKWISE_90",
+            "mirror": "NONE",
+            "ignore_entities": false,
+            "enabled": true,
+            "locked": true,
+            "placements": [
+                {
+                    "name": "test",
+                    "placement": { "pos": [1, 2, 3], "name": "test", "rotation": "NONE", "mirror": "LEFT_RIGHT", "enabled": false }
+                }
+            ]
+        }"#;
+        std::fs::write(&path, json).unwrap();
+
+        let placement = Placement::read(&path).unwrap();
+
+        assert_eq!(
+            placement.origin,
+            Coordinates {
+                x: 10,
+                y: 64,
+                z: -20
+            }
+        );
+        assert_eq!(placement.rotation, Rotation::Clockwise90);
+        assert_eq!(placement.sub_regions["test"].mirror, Mirror::LeftRight);
+        assert!(!placement.sub_regions["test"].enabled);
+
+        placement.write(&path).unwrap();
+        let written = Placement::read(&path).unwrap();
+
+        assert_eq!(written, placement);
+        assert_eq!(written.other["locked"], serde_json::Value::Bool(true));
+    }
+
+    #[test]
+    fn transform_block_state() {
+        let step = Step {
+            mirror: Mirror::None,
+            rotation: Rotation::Clockwise90,
+            from: Coordinates { x: 0, y: 0, z: 0 },
+            to: Coordinates { x: 0, y: 0, z: 0 },
+        };
+
+        let repeater = BlockStateBuilder::new("repeater")
+            .properties([("facing", "north")])
+            .build();
+        let rail = BlockStateBuilder::new("rail")
+            .properties([("shape", "north_east")])
+            .build();
+        let wire = BlockStateBuilder::new("redstone_wire")
+            .properties([("north", "side"), ("east", "none")])
+            .build();
+
+        assert_eq!(
+            transform_block(&repeater, &step).properties["facing"],
+            "east"
+        );
+        assert_eq!(
+            transform_block(&rail, &step).properties["shape"],
+            "south_east"
+        );
+        assert_eq!(transform_block(&wire, &step).properties["east"], "side");
+        assert_eq!(transform_block(&wire, &step).properties["south"], "none");
+
+        let door = BlockStateBuilder::new("oak_door")
+            .properties([("facing", "north"), ("hinge", "left")])
+            .build();
+        let chest = BlockStateBuilder::new("chest")
+            .properties([("facing", "north"), ("type", "right")])
+            .build();
+        let slab = BlockStateBuilder::new("oak_slab")
+            .properties([("type", "top")])
+            .build();
+        let sign = BlockStateBuilder::new("oak_sign")
+            .properties([("rotation", "20")])
+            .build();
+
+        assert_eq!(transform_block(&door, &step).properties["hinge"], "left");
+        assert_eq!(transform_block(&sign, &step).properties["rotation"], "8");
+
+        let mirror = Step {
+            mirror: Mirror::LeftRight,
+            rotation: Rotation::None,
+            ..step
+        };
+
+        assert_eq!(transform_block(&door, &mirror).properties["hinge"], "right");
+        assert_eq!(transform_block(&chest, &mirror).properties["type"], "left");
+        assert_eq!(transform_block(&slab, &mirror).properties["type"], "top");
+        assert_eq!(transform_block(&sign, &mirror).properties["rotation"], "4");
+    }
+
+    #[test]
+    fn transform_entity_rotations() {
+        let mut item_frame = Entity {
+            rotation: Rotation2::new(180.0, 0.0),
+            fire: -1,
+            pos: Vec3::new(0.5, 0.5, 0.5),
+            motion: Vec3::new(0.0, 0.0, 0.0),
+            air: 300,
+            fall_distance: 0.0,
+            on_ground: false,
+            id: "minecraft:item_frame".to_string(),
+            portal_cooldown: 0,
+            uuid: vec![1, 2, 3, 4],
+            invulnerable: false,
+            facing: Some(2),
+        };
+
+        let step = |mirror, rotation| Step {
+            mirror,
+            rotation,
+            from: Coordinates::default(),
+            to: Coordinates::default(),
+        };
+
+        // facing north, turned clockwise to face east
+        transform_entity_rotation(
+            &mut item_frame,
+            &[step(Mirror::None, Rotation::Clockwise90)],
+        );
+        assert_eq!(item_frame.rotation.yaw, -90.0);
+        assert_eq!(item_frame.facing, Some(5));
+
+        transform_entity_rotation(&mut item_frame, &[step(Mirror::FrontBack, Rotation::None)]);
+        assert_eq!(item_frame.rotation.yaw, 90.0);
+        assert_eq!(item_frame.facing, Some(4));
+
+        item_frame.facing = Some(1);
+        transform_entity_rotation(
+            &mut item_frame,
+            &[step(Mirror::LeftRight, Rotation::Clockwise180)],
+        );
+        assert_eq!(item_frame.facing, Some(1));
+
+        // the direction an entity looks in is transformed like positions are
+        for mirror in [Mirror::None, Mirror::LeftRight, Mirror::FrontBack] {
+            for rotation in [
+                Rotation::None,
+                Rotation::Clockwise90,
+                Rotation::Clockwise180,
+                Rotation::Counterclockwise90,
+            ] {
+                let step = step(mirror, rotation);
+
+                for yaw in [0.0, 30.0, -135.0, 179.0] {
+                    let direction = Rotation2::new(yaw, 20.0).direction();
+                    let [x, y, z] = step.apply([direction.x, direction.y, direction.z]);
+
+                    let turned_yaw = step.apply_yaw(yaw);
+                    let turned = Rotation2::new(turned_yaw, 20.0).direction();
+
+                    assert!((turned - Vec3::new(x, y, z)).length() < 1e-5);
+                    assert!((-180.0..180.0).contains(&turned_yaw));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_placement_rotates_blocks() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let original = file.get_region("test").unwrap();
+        let size = original.size;
+        let corner = original.get_block((0, 4, 0)).clone();
+
+        let mut placement = Placement::new("test", (0, 0, 0));
+        placement.rotation = Rotation::Clockwise90;
+        file.apply_placement(&placement);
+
+        let rotated = file.get_region("test").unwrap();
+        let step = Step {
+            mirror: Mirror::None,
+            rotation: Rotation::Clockwise90,
+            from: Coordinates { x: 0, y: 0, z: 0 },
+            to: Coordinates { x: 0, y: 0, z: 0 },
+        };
+
+        assert_eq!(rotated.size.x, size.z.abs());
+        assert_eq!(rotated.size.z, size.x.abs());
+        assert_eq!(
+            rotated.get_block((size.z.abs() - 1, 4, 0)),
+            &transform_block(&corner, &step)
+        );
+    }
+
+    #[test]
+    fn apply_placement_full_turn() {
+        let original = LitematicaFile::read("test.litematic").unwrap();
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+
+        let mut placement = Placement::new("test", (0, 0, 0));
+        placement.rotation = Rotation::Clockwise90;
+        placement.mirror = Mirror::FrontBack;
+
+        // mirroring and rotating twice restores the original
+        for _ in 0..2 {
+            file.apply_placement(&placement);
+            placement.mirror = Mirror::None;
+            placement.rotation = Rotation::Counterclockwise90;
+            file.apply_placement(&placement);
+            placement.mirror = Mirror::FrontBack;
+            placement.rotation = Rotation::Clockwise90;
+        }
+
+        let original = original.get_region("test").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        assert_eq!(region.min_corner(), original.min_corner());
+
+        for (position, block) in original.iter_blocks() {
+            assert_eq!(region.get_block(position), block);
+        }
+    }
+
+    #[test]
+    fn apply_placement_removes_disabled_regions() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+
+        let mut placement = Placement::new("test", (0, 0, 0));
+        let mut sub_region = SubRegionPlacement::new((0, 0, 0));
+        sub_region.enabled = false;
+        placement.sub_regions.insert("test".to_string(), sub_region);
+
+        file.apply_placement(&placement);
+
+        assert!(file.get_regions().is_empty());
+        assert_eq!(file.metadata.region_count, 0);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::structure::LitematicaFile;
+
+/// A record of where a schematic comes from, stored in the file so that it survives round-trips through this crate.
+///
+/// Litematica itself ignores it, so it is lost when the schematic is saved again by the mod.
+///
+/// # Examples
+/// ```
+/// use ritematica::{LitematicaFile, Provenance};
+///
+/// let mut file = LitematicaFile::read("test.litematic").unwrap();
+///
+/// file.set_provenance(Provenance {
+///     source_url: Some("https://example.com/farms/42".to_string()),
+///     license: Some("CC-BY-4.0".to_string()),
+///     ..Default::default()
+/// });
+/// file.chain_checksum();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Provenance {
+    /// The program that created or last processed the schematic.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooling: Option<String>,
+
+    /// Where the schematic was downloaded from or first published.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+
+    /// The license of the schematic, e.g. an SPDX identifier.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(serialize_with = "nbt::i64_array")]
+    checksum_chain: Vec<i64>,
+}
+
+impl Provenance {
+    /// Returns the recorded checksums of earlier versions of the schematic, oldest first.
+    pub fn checksum_chain(&self) -> impl Iterator<Item = u32> + '_ {
+        self.checksum_chain.iter().map(|checksum| *checksum as u32)
+    }
+
+    /// Appends a checksum to the chain, see `LitematicaFile::compute_checksum()`.
+    pub fn push_checksum(&mut self, checksum: u32) {
+        self.checksum_chain.push(checksum as i64);
+    }
+}
+
+impl LitematicaFile {
+    /// Returns the `Provenance` stored in the file, if there is one.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.extensions.provenance.as_ref()
+    }
+
+    /// Returns a mutable reference to the `Provenance` of the file, creating an empty one if there is none.
+    pub fn provenance_mut(&mut self) -> &mut Provenance {
+        self.extensions
+            .provenance
+            .get_or_insert_with(Default::default)
+    }
+
+    /// Replaces the `Provenance` of the file.
+    pub fn set_provenance(&mut self, provenance: Provenance) {
+        self.extensions.provenance = Some(provenance);
+    }
+
+    /// Removes the `Provenance` from the file and returns it, if there was one.
+    pub fn remove_provenance(&mut self) -> Option<Provenance> {
+        self.extensions.provenance.take()
+    }
+
+    /// Appends the checksum of the current contents to the checksum chain of the `Provenance` and returns it.
+    ///
+    /// Calling this before every modification records the history of the schematic, so an archive can tell whether
+    /// an upload is a derivative of a known schematic. The checksum chain itself is not part of the checksum.
+    pub fn chain_checksum(&mut self) -> u32 {
+        let checksum = self.compute_checksum();
+        self.provenance_mut().push_checksum(checksum);
+
+        checksum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provenance_round_trip() {
+        let path = std::env::temp_dir().join("ritematica_provenance_round_trip.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        assert!(file.provenance().is_none());
+
+        file.set_provenance(Provenance {
+            tooling: Some("ritematica".to_string()),
+            license: Some("CC0-1.0".to_string()),
+            ..Default::default()
+        });
+        let checksum = file.chain_checksum();
+        file.write(&path).unwrap();
+
+        let file = LitematicaFile::read(&path).unwrap();
+        let provenance = file.provenance().unwrap();
+
+        assert_eq!(provenance.tooling.as_deref(), Some("ritematica"));
+        assert_eq!(provenance.license.as_deref(), Some("CC0-1.0"));
+        assert_eq!(provenance.source_url, None);
+        assert_eq!(
+            provenance.checksum_chain().collect::<Vec<_>>(),
+            vec![checksum]
+        );
+        assert_eq!(file.compute_checksum(), checksum);
+    }
+}
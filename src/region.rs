@@ -1,11 +1,72 @@
+use std::collections::HashMap;
+use std::ops::Index;
+
+use nbt::Value;
+
 use crate::{
-    block::BlockStatePattern,
-    structure::{BlockState, Coordinates, Region},
+    block::{BlockStateBuilder, BlockStatePattern},
+    error::{RegionError, Result},
+    iter::coordinates_of,
+    structure::{BlockState, Coordinates, Entity, Region},
 };
 
-const BIT_TO_LONG_SHIFT: u8 = 6; //log2(64)
+pub(crate) const BIT_TO_LONG_SHIFT: u8 = 6; //log2(64)
 
 impl Region {
+    /// Creates a region of the given size at the schematic origin that only contains `minecraft:air`.
+    ///
+    /// The palette starts with air at index 0, and the packed block data is allocated for the whole volume. Use
+    /// `RegionBuilder` to set the position or declare the palette up front.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The size of the region. Negative components extend the region in the negative direction, like in
+    ///   Litematica.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::BlockStateBuilder;
+    /// use ritematica::structure::Region;
+    ///
+    /// let mut region = Region::new((4, 4, 4));
+    /// region.set_block((1, 2, 3), BlockStateBuilder::new("stone").build());
+    ///
+    /// assert!(region.get_block((0, 0, 0)).is_air());
+    /// ```
+    pub fn new(size: impl Into<Coordinates>) -> Self {
+        Self::filled_with_air(Coordinates { x: 0, y: 0, z: 0 }, size.into())
+    }
+
+    /// Creates a region at `position` with the given `size` that only contains `minecraft:air`.
+    pub(crate) fn filled_with_air(position: Coordinates, size: Coordinates) -> Self {
+        let palette = vec![BlockStateBuilder::new("air").build()];
+
+        let volume = size.x.unsigned_abs() as u64
+            * size.y.unsigned_abs() as u64
+            * size.z.unsigned_abs() as u64;
+        let required_bits = Self::calc_required_bits(&palette);
+        let words = (volume * required_bits + 63) >> BIT_TO_LONG_SHIFT; // rounding up
+
+        Self {
+            position,
+            size,
+            entities: Vec::new(),
+            tile_entities: Vec::new(),
+            pending_block_ticks: Vec::new(),
+            pending_fluid_ticks: Vec::new(),
+            block_state_palette: palette,
+            block_states: vec![0; words as usize],
+            extensions: Default::default(),
+            reserved_palette: 0,
+        }
+    }
+
+    /// Returns the `BlockState` at the given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the message of `RegionError::OutOfBounds` if the position is outside of the region. See
+    /// `try_get_block()` and `get()` for checked versions.
     pub fn get_block(&self, position: impl Into<Coordinates>) -> &BlockState {
         let position = position.into();
         let block_index = self.get_3d_index(position);
@@ -19,6 +80,139 @@ impl Region {
         &self.block_state_palette[palette_index as usize]
     }
 
+    /// Returns the `BlockState` at the given position, or `None` if the position is outside of the region.
+    ///
+    /// Unlike `get_block()` and indexing, this never panics.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// assert_eq!(region.get((0, 0, 0)), Some(&region[(0, 0, 0)]));
+    /// assert_eq!(region.get((-1, 0, 0)), None);
+    /// ```
+    pub fn get(&self, position: impl Into<Coordinates>) -> Option<&BlockState> {
+        let position = position.into();
+
+        self.contains(position).then(|| self.get_block(position))
+    }
+
+    /// Returns the `BlockState` at the given position.
+    ///
+    /// Unlike `get_block()`, this never panics, so positions from user input can be passed in directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegionError::OutOfBounds` if the position is outside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// match region.try_get_block((100, 0, 0)) {
+    ///     Ok(block) => println!("{}", block),
+    ///     Err(error) => println!("{}", error),
+    /// }
+    /// ```
+    pub fn try_get_block(&self, position: impl Into<Coordinates>) -> Result<&BlockState> {
+        let position = position.into();
+        self.check_bounds(position)?;
+
+        Ok(self.get_block(position))
+    }
+
+    /// Sets the `BlockState` at the given position.
+    ///
+    /// Unlike `set_block()`, this never panics, so positions from user input can be passed in directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegionError::OutOfBounds` if the position is outside of the region. The region is left unchanged in
+    /// that case.
+    pub fn try_set_block(
+        &mut self,
+        position: impl Into<Coordinates>,
+        block: BlockState,
+    ) -> Result<()> {
+        let position = position.into();
+        self.check_bounds(position)?;
+
+        self.set_block(position, block);
+
+        Ok(())
+    }
+
+    /// Returns the palette entry used at the given position, which allows modifying every block using it.
+    ///
+    /// Unlike `get_block_mut()`, this never panics.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegionError::OutOfBounds` if the position is outside of the region.
+    pub fn try_get_block_mut(
+        &mut self,
+        position: impl Into<Coordinates>,
+    ) -> Result<&mut BlockState> {
+        let position = position.into();
+        self.check_bounds(position)?;
+
+        Ok(self.get_block_mut(position))
+    }
+
+    fn check_bounds(&self, position: Coordinates) -> Result<()> {
+        checked_index_3d(self.size, position)?;
+
+        Ok(())
+    }
+
+    /// Checks that the palette and the packed block states fit together and to the size of the region, so that
+    /// reading any block can't panic, and that all annotations, group positions and anchors are inside of the region.
+    ///
+    /// This is done for every region when a file is read, so it only needs to be called for regions that were
+    /// assembled by hand.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `RegionError` describing the first problem found.
+    pub fn validate(&self) -> std::result::Result<(), RegionError> {
+        validate_block_data(self.size, &self.block_state_palette, &self.block_states)?;
+
+        let extensions = &self.extensions;
+        let annotations = extensions.annotations.iter().map(|(position, _)| position);
+        let groups = extensions
+            .groups
+            .values()
+            .flat_map(|group| group.positions().iter().copied());
+        let anchors = extensions.anchors.values().copied();
+
+        for position in annotations.chain(groups).chain(anchors) {
+            checked_index_3d(self.size, position)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the given position, relative to `min_corner()`, lies inside of the region.
+    pub fn contains(&self, position: impl Into<Coordinates>) -> bool {
+        let position = position.into();
+
+        (0..self.size.x.abs()).contains(&position.x)
+            && (0..self.size.y.abs()).contains(&position.y)
+            && (0..self.size.z.abs()).contains(&position.z)
+    }
+
+    /// Returns the palette entry used at the given position, which allows modifying every block using it.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the message of `RegionError::OutOfBounds` if the position is outside of the region. See
+    /// `try_get_block_mut()` for a checked version.
     pub fn get_block_mut(&mut self, position: impl Into<Coordinates>) -> &mut BlockState {
         let position = position.into();
         let index = self.get_3d_index(position);
@@ -32,19 +226,47 @@ impl Region {
         &mut self.block_state_palette[palette_index as usize]
     }
 
+    /// Sets the `BlockState` at the given position, adding it to the palette if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the message of `RegionError::OutOfBounds` if the position is outside of the region. See
+    /// `try_set_block()` for a checked version.
     pub fn set_block(&mut self, position: impl Into<Coordinates>, block: BlockState) {
         let position = position.into();
         let index = self.get_3d_index(position);
 
+        let (palette_index, bits) = self.palette_index_or_insert(block);
+
+        Self::set_block_index(
+            &mut self.block_states,
+            index,
+            palette_index,
+            bits,
+            (1 << bits) - 1,
+        );
+    }
+
+    /// Returns the palette index of `block` and the bits per block, adding `block` to the palette and repacking the
+    /// block data if needed.
+    pub(crate) fn palette_index_or_insert(&mut self, block: BlockState) -> (u32, u64) {
         let mut bits = Self::calc_required_bits(&self.block_state_palette);
 
         let mut mask = (1 << bits) - 1;
 
-        let palette_index = self
-            .block_state_palette
+        let used = self.block_state_palette.len() - self.reserved_palette;
+
+        let palette_index = self.block_state_palette[..used]
             .iter()
             .position(|b| *b == block)
             .unwrap_or_else(|| {
+                if self.reserved_palette > 0 {
+                    self.block_state_palette[used] = block;
+                    self.reserved_palette -= 1;
+
+                    return used;
+                }
+
                 let index = self.block_state_palette.len();
 
                 // minimum size is 2 bits
@@ -62,60 +284,181 @@ impl Region {
                 index
             });
 
-        Self::set_block_index(
-            &mut self.block_states,
-            index,
-            palette_index as u32,
-            bits,
-            mask,
-        );
+        (palette_index as u32, bits)
     }
 
-    pub fn find_block_positions(
-        &self,
-        block_state: &impl BlockStatePattern,
-    ) -> impl Iterator<Item = Coordinates> {
-        let mut matching = Vec::new();
+    /// Makes room in the palette for `additional` new block states, so that placing them with `set_block()` doesn't
+    /// repack the whole block data midway through a bulk edit.
+    ///
+    /// The number of bits per block is grown once up front if needed. Since Litematica derives the bit width from the
+    /// palette size, this is done by adding `minecraft:air` placeholders to the end of the palette, which new block
+    /// states take over one by one. Placeholders that are still unused when the region is written are saved as
+    /// regular air entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `additional` - The number of distinct block states that are not in the palette yet and will be placed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::BlockStateBuilder;
+    /// use ritematica::structure::Region;
+    ///
+    /// let mut region = Region::new((64, 64, 64));
+    /// region.reserve_palette(16);
+    ///
+    /// for (index, color) in ["white", "orange", "magenta", "light_blue"].iter().enumerate() {
+    ///     let wool = BlockStateBuilder::new(format!("{}_wool", color)).build();
+    ///     region.set_block((index as i32, 0, 0), wool);
+    /// }
+    /// ```
+    pub fn reserve_palette(&mut self, additional: usize) {
+        if additional <= self.reserved_palette {
+            return;
+        }
+
+        let used = self.block_state_palette.len() - self.reserved_palette;
+        let bits = Self::calc_required_bits(&self.block_state_palette);
+        let new_bits = (used + additional)
+            .next_power_of_two()
+            .trailing_zeros()
+            .max(2) as u64;
 
-        for y in 0..self.size.y.abs() {
-            for z in 0..self.size.z.abs() {
-                for x in 0..self.size.x.abs() {
-                    let coords = Coordinates::from((x, y, z));
+        if new_bits <= bits {
+            return;
+        }
 
-                    let block = self.get_block(coords);
+        // the smallest palette that still needs the new bit width
+        let placeholders = (1 << (new_bits - 1)) + 1 - self.block_state_palette.len();
+        let air = BlockStateBuilder::new("air").build();
 
-                    if block_state.matches(block) {
-                        matching.push(coords);
-                    }
-                }
+        self.block_state_palette
+            .extend(std::iter::repeat_n(air, placeholders));
+        self.reserved_palette += placeholders;
+
+        self.resize_block_states(bits, (1 << bits) - 1, new_bits, (1 << new_bits) - 1);
+    }
+
+    /// Returns an iterator over the positions of all blocks that match `pattern`, in YZX order.
+    ///
+    /// The pattern is only checked once per palette entry, and the blocks are read while iterating, so taking the
+    /// first few matches of a huge region stops early. If no palette entry matches, the iterator is empty right away.
+    /// The matching palette entries are kept in a bit set on the stack, so nothing is allocated unless the palette has
+    /// more than 256 entries.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let beacon = BlockStateBuilder::new("beacon").build();
+    ///
+    /// if let Some(position) = region.find_block_positions(&beacon).next() {
+    ///     println!("Found a beacon at {:?}", position);
+    /// }
+    /// ```
+    pub fn find_block_positions(
+        &self,
+        pattern: &impl BlockStatePattern,
+    ) -> impl Iterator<Item = Coordinates> + '_ {
+        let mut matching = PaletteSet::new(self.block_state_palette.len());
+
+        for (index, block) in self.block_state_palette.iter().enumerate() {
+            if pattern.matches(block) {
+                matching.insert(index);
             }
         }
 
-        matching.into_iter()
+        let volume = if matching.is_empty() {
+            0
+        } else {
+            self.calc_volume()
+        };
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        (0..volume)
+            .filter(move |index| {
+                matching.contains(read_palette_index(
+                    &self.block_states,
+                    *index,
+                    required_bits,
+                    bitmask,
+                ) as usize)
+            })
+            .map(|index| coordinates_of(index, self.size))
     }
 
-    pub(crate) fn calc_required_bits(palette: &Vec<BlockState>) -> u64 {
-        palette.len().next_power_of_two().trailing_zeros().max(2) as u64
+    /// Returns the positions of the first `limit` blocks that match `pattern`, in YZX order.
+    ///
+    /// Like `find_block_positions()`, the scan stops as soon as `limit` blocks were found.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let hopper = BlockStateBuilder::new("hopper").properties([("enabled", "true"), ("facing", "down")]).build();
+    /// let first_ten = region.find_block_positions_limited(&hopper, 10);
+    /// ```
+    pub fn find_block_positions_limited(
+        &self,
+        pattern: &impl BlockStatePattern,
+        limit: usize,
+    ) -> Vec<Coordinates> {
+        self.find_block_positions(pattern).take(limit).collect()
     }
 
-    pub(crate) fn get_3d_index(&self, coords: impl Into<Coordinates>) -> u64 {
-        let coords = coords.into();
+    /// Checks whether any block matches `pattern`, stopping at the first match.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockState, LitematicaFile};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let is_command_block = |block: &BlockState| block.get_name().get_path().ends_with("command_block");
+    ///
+    /// if region.any_match(&is_command_block) {
+    ///     println!("The region contains command blocks");
+    /// }
+    /// ```
+    pub fn any_match(&self, pattern: &impl BlockStatePattern) -> bool {
+        self.find_block_positions(pattern).next().is_some()
+    }
 
-        // check that the coordinates are withoin the bounds of the region
-        assert!(coords.x >= 0 && coords.x < self.size.x.abs());
-        assert!(coords.y >= 0 && coords.y < self.size.y.abs());
-        assert!(coords.z >= 0 && coords.z < self.size.z.abs());
+    /// Returns the position of the corner of the region with the lowest coordinates, relative to the schematic origin.
+    ///
+    /// Litematica allows negative region sizes, in which case the region extends from its `position` into the negative direction.
+    /// Block coordinates inside of the region are always relative to this corner.
+    pub fn min_corner(&self) -> Coordinates {
+        let min = |position: i32, size: i32| {
+            if size < 0 {
+                position + size + 1
+            } else {
+                position
+            }
+        };
 
-        // convert the coordinates to unsigned integers
-        let x = coords.x as u64;
-        let y = coords.y as u64;
-        let z = coords.z as u64;
+        Coordinates {
+            x: min(self.position.x, self.size.x),
+            y: min(self.position.y, self.size.y),
+            z: min(self.position.z, self.size.z),
+        }
+    }
 
-        // calculate the linear index
-        let size_x = self.size.x.unsigned_abs() as u64;
-        let size_layer = size_x * self.size.z.unsigned_abs() as u64;
+    pub(crate) fn calc_required_bits(palette: &[BlockState]) -> u64 {
+        palette.len().next_power_of_two().trailing_zeros().max(2) as u64
+    }
 
-        y * size_layer + z * size_x + x
+    pub(crate) fn get_3d_index(&self, coords: impl Into<Coordinates>) -> u64 {
+        index_3d(self.size, coords.into())
     }
 
     pub(crate) fn get_palette_index(
@@ -124,23 +467,10 @@ impl Region {
         required_bits: u64,
         bitmask: u32,
     ) -> u32 {
-        let bit_index = block_index * required_bits;
-        let word_index = (bit_index >> BIT_TO_LONG_SHIFT) as usize;
-        let end_word_index =
-            (((block_index + 1) * required_bits - 1) >> BIT_TO_LONG_SHIFT) as usize;
-        let index_in_word = (bit_index ^ ((word_index as u64) << BIT_TO_LONG_SHIFT)) as u8;
-
-        if word_index == end_word_index {
-            (self.block_states[word_index] >> index_in_word) as u32 & bitmask
-        } else {
-            let first_bits = 64 - index_in_word; // 2
-
-            ((self.block_states[word_index] as u64 >> index_in_word) as u32 & bitmask)
-                | ((self.block_states[end_word_index] << first_bits) as u32 & bitmask)
-        }
+        read_palette_index(&self.block_states, block_index, required_bits, bitmask)
     }
 
-    fn set_block_index(
+    pub(crate) fn set_block_index(
         block_states: &mut [i64],
         block_index: u64,
         value: u32,
@@ -196,17 +526,419 @@ impl Region {
         self.block_states = new_blockstates;
     }
 
-    fn calc_volume(&self) -> u64 {
-        self.size.x.unsigned_abs() as u64
-            * self.size.y.unsigned_abs() as u64
-            * self.size.z.unsigned_abs() as u64
+    /// Replaces every palette entry for which `replace` returns a new `BlockState`, merging entries that become equal.
+    ///
+    /// Returns the number of replaced palette entries.
+    pub(crate) fn replace_palette(
+        &mut self,
+        mut replace: impl FnMut(&BlockState) -> Option<BlockState>,
+    ) -> usize {
+        let mut replaced = 0;
+        let mut palette: Vec<BlockState> = Vec::with_capacity(self.block_state_palette.len());
+        let mut palette_indices = HashMap::with_capacity(self.block_state_palette.len());
+        let mut remap = Vec::with_capacity(self.block_state_palette.len());
+
+        for block in &self.block_state_palette {
+            let block = match replace(block) {
+                Some(new_block) => {
+                    replaced += 1;
+                    new_block
+                }
+                None => block.clone(),
+            };
+
+            let index = *palette_indices.entry(block.clone()).or_insert_with(|| {
+                palette.push(block);
+                palette.len() - 1
+            });
+
+            remap.push(index as u32);
+        }
+
+        if palette.len() != self.block_state_palette.len() {
+            let old_bits = Self::calc_required_bits(&self.block_state_palette);
+            let old_mask = (1 << old_bits) - 1;
+            let new_bits = Self::calc_required_bits(&palette);
+            let new_mask = (1 << new_bits) - 1;
+
+            let volume = self.calc_volume();
+            let mut block_states = vec![0; (volume * new_bits).div_ceil(64) as usize];
+
+            for i in 0..volume {
+                let old_index = self.get_palette_index(i, old_bits, old_mask);
+
+                Self::set_block_index(
+                    &mut block_states,
+                    i,
+                    remap[old_index as usize],
+                    new_bits,
+                    new_mask,
+                );
+            }
+
+            self.block_states = block_states;
+        }
+
+        self.block_state_palette = palette;
+        // reserved placeholders are air and got merged with the other air entries
+        self.reserved_palette = 0;
+
+        replaced
+    }
+
+    /// # Panics
+    ///
+    /// Panics with the message of `RegionError::SizeTooLarge` if the volume overflows, which `validate()` rules out
+    /// for regions read from files.
+    pub(crate) fn calc_volume(&self) -> u64 {
+        checked_volume(self.size).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+/// Returns the number of blocks in a box of the given `size`, or an error if it overflows.
+pub(crate) fn checked_volume(size: Coordinates) -> std::result::Result<u64, RegionError> {
+    (size.x.unsigned_abs() as u64)
+        .checked_mul(size.y.unsigned_abs() as u64)
+        .and_then(|layer| layer.checked_mul(size.z.unsigned_abs() as u64))
+        .ok_or(RegionError::SizeTooLarge { size })
+}
+
+/// Runs the checks of `Region::validate` on loose block data, e.g. for a deserialized `RegionSnapshot`.
+pub(crate) fn validate_block_data(
+    size: Coordinates,
+    palette: &[BlockState],
+    block_states: &[i64],
+) -> std::result::Result<(), RegionError> {
+    if palette.is_empty() {
+        return Err(RegionError::EmptyPalette);
+    }
+
+    let required_bits = Region::calc_required_bits(palette);
+    let volume = checked_volume(size)?;
+    let expected = volume
+        .checked_mul(required_bits)
+        .and_then(|bits| usize::try_from(bits.div_ceil(64)).ok())
+        .ok_or(RegionError::SizeTooLarge { size })?;
+
+    if block_states.len() < expected {
+        return Err(RegionError::BlockStatesLength {
+            expected,
+            actual: block_states.len(),
+        });
+    }
+
+    // every index that fits into the bit width is valid if the palette is full
+    if palette.len() == 1 << required_bits {
+        return Ok(());
+    }
+
+    let bitmask = (1 << required_bits) - 1;
+
+    for block_index in 0..volume {
+        let palette_index = read_palette_index(block_states, block_index, required_bits, bitmask);
+
+        if palette_index as usize >= palette.len() {
+            return Err(RegionError::InvalidPaletteIndex {
+                block_index,
+                palette_index,
+                palette_len: palette.len(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// A builder for creating a `Region` filled with a single block, e.g. for generating schematics or test fixtures.
+///
+/// # Examples
+/// ```
+/// use ritematica::BlockStateBuilder;
+/// use ritematica::region::RegionBuilder;
+///
+/// let stone = BlockStateBuilder::new("stone").build();
+/// let glass = BlockStateBuilder::new("glass").build();
+///
+/// let mut region = RegionBuilder::new((16, 16, 16))
+///     .position((0, 64, 0))
+///     .fill(stone)
+///     .with_palette([glass.clone()])
+///     .build();
+///
+/// region.set_block((0, 0, 0), glass);
+/// ```
+#[derive(Debug)]
+pub struct RegionBuilder {
+    position: Coordinates,
+    size: Coordinates,
+    palette: Vec<BlockState>,
+    fill: Option<BlockState>,
+    entities: Vec<Entity>,
+}
+
+impl RegionBuilder {
+    /// Creates a new `RegionBuilder` for a region of the given size at the schematic origin.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The size of the region. Negative components extend the region from `position` in the negative
+    ///   direction, like in Litematica.
+    pub fn new(size: impl Into<Coordinates>) -> Self {
+        Self {
+            position: Coordinates { x: 0, y: 0, z: 0 },
+            size: size.into(),
+            palette: vec![BlockStateBuilder::new("air").build()],
+            fill: None,
+            entities: Vec::new(),
+        }
+    }
+
+    /// Sets the position of the region relative to the schematic origin.
+    pub fn position(mut self, position: impl Into<Coordinates>) -> Self {
+        self.position = position.into();
+        self
+    }
+
+    /// Declares the `BlockState`s that will be placed up front, so the block data is allocated with enough bits per
+    /// block for all of them.
+    ///
+    /// Without this, `Region::set_block()` repacks the whole block data every time the palette outgrows the current
+    /// bit width, which gets expensive for large generated regions. Blocks that are not declared can still be placed.
+    /// `minecraft:air` always stays the first palette entry, and duplicates are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `states` - The `BlockState`s that will be used in the region.
+    pub fn with_palette(mut self, states: impl IntoIterator<Item = BlockState>) -> Self {
+        for state in states {
+            if !self.palette.contains(&state) {
+                self.palette.push(state);
+            }
+        }
+
+        self
+    }
+
+    /// Sets the block that the whole region is filled with. Defaults to `minecraft:air`.
+    ///
+    /// The block is added to the palette after air, which always stays the first entry.
+    pub fn fill(mut self, block: BlockState) -> Self {
+        if !self.palette.contains(&block) {
+            self.palette.push(block.clone());
+        }
+
+        self.fill = Some(block);
+        self
+    }
+
+    /// Adds `entities` to the region. Their positions are relative to the region origin.
+    pub fn entities(mut self, entities: impl IntoIterator<Item = Entity>) -> Self {
+        self.entities.extend(entities);
+        self
+    }
+
+    /// Builds the `Region` with every block set to the fill block.
+    pub fn build(self) -> Region {
+        let mut region = Region::filled_with_air(self.position, self.size);
+
+        let required_bits = Region::calc_required_bits(&self.palette);
+        let bitmask = (1 << required_bits) - 1;
+        let volume = region.calc_volume();
+
+        region.block_states = vec![0; (volume * required_bits).div_ceil(64) as usize];
+
+        let fill_index = self
+            .fill
+            .and_then(|fill| self.palette.iter().position(|block| *block == fill))
+            .unwrap_or(0);
+
+        if fill_index != 0 {
+            for index in 0..volume {
+                Region::set_block_index(
+                    &mut region.block_states,
+                    index,
+                    fill_index as u32,
+                    required_bits,
+                    bitmask,
+                );
+            }
+        }
+
+        region.block_state_palette = self.palette;
+        region.entities = self.entities;
+
+        region
+    }
+}
+
+/// Indexes the blocks of a region like `Region::get_block()`.
+///
+/// # Panics
+///
+/// Panics if the position is outside of the region, see `Region::get()` for a checked version.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+/// let region = file.get_region("test").unwrap();
+///
+/// let block = &region[(2, 4, 2)];
+/// ```
+impl Index<Coordinates> for Region {
+    type Output = BlockState;
+
+    fn index(&self, position: Coordinates) -> &BlockState {
+        self.get_block(position)
+    }
+}
+
+impl Index<(i32, i32, i32)> for Region {
+    type Output = BlockState;
+
+    fn index(&self, position: (i32, i32, i32)) -> &BlockState {
+        self.get_block(position)
+    }
+}
+
+/// A set of palette indices, stored inline for palettes with up to 256 entries.
+enum PaletteSet {
+    Inline([u64; 4]),
+    Heap(Vec<u64>),
+}
+
+impl PaletteSet {
+    fn new(palette_len: usize) -> Self {
+        if palette_len <= 256 {
+            Self::Inline([0; 4])
+        } else {
+            Self::Heap(vec![0; palette_len.div_ceil(64)])
+        }
+    }
+
+    fn words(&self) -> &[u64] {
+        match self {
+            Self::Inline(words) => words,
+            Self::Heap(words) => words,
+        }
+    }
+
+    fn insert(&mut self, index: usize) {
+        let words = match self {
+            Self::Inline(words) => &mut words[..],
+            Self::Heap(words) => &mut words[..],
+        };
+
+        words[index >> BIT_TO_LONG_SHIFT] |= 1 << (index & 63);
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.words()
+            .get(index >> BIT_TO_LONG_SHIFT)
+            .is_some_and(|word| word & (1 << (index & 63)) != 0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words().iter().all(|word| *word == 0)
+    }
+}
+
+/// Converts `coords` into the linear (YZX) index of a block inside a box of the given `size`.
+///
+/// # Panics
+///
+/// Panics if the coordinates are outside of the region, see `checked_index_3d()`.
+pub(crate) fn index_3d(size: Coordinates, coords: Coordinates) -> u64 {
+    checked_index_3d(size, coords).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Returns the linear index of the given coordinates in a region of the given size, or an error if they are outside of
+/// the region.
+pub(crate) fn checked_index_3d(
+    size: Coordinates,
+    coords: Coordinates,
+) -> std::result::Result<u64, RegionError> {
+    if !(0..size.x.abs()).contains(&coords.x)
+        || !(0..size.y.abs()).contains(&coords.y)
+        || !(0..size.z.abs()).contains(&coords.z)
+    {
+        return Err(RegionError::OutOfBounds { coords, size });
+    }
+
+    // convert the coordinates to unsigned integers
+    let x = coords.x as u64;
+    let y = coords.y as u64;
+    let z = coords.z as u64;
+
+    // calculate the linear index
+    let size_x = size.x.unsigned_abs() as u64;
+    let size_layer = size_x * size.z.unsigned_abs() as u64;
+
+    Ok(y * size_layer + z * size_x + x)
+}
+
+/// Reads the palette index stored for `block_index` from a packed `block_states` array.
+pub(crate) fn read_palette_index(
+    block_states: &[i64],
+    block_index: u64,
+    required_bits: u64,
+    bitmask: u32,
+) -> u32 {
+    let bit_index = block_index * required_bits;
+    let word_index = (bit_index >> BIT_TO_LONG_SHIFT) as usize;
+    let end_word_index = (((block_index + 1) * required_bits - 1) >> BIT_TO_LONG_SHIFT) as usize;
+    let index_in_word = (bit_index ^ ((word_index as u64) << BIT_TO_LONG_SHIFT)) as u8;
+
+    if word_index == end_word_index {
+        (block_states[word_index] >> index_in_word) as u32 & bitmask
+    } else {
+        let first_bits = 64 - index_in_word; // 2
+
+        ((block_states[word_index] as u64 >> index_in_word) as u32 & bitmask)
+            | ((block_states[end_word_index] << first_bits) as u32 & bitmask)
+    }
+}
+
+/// Reads the `x`, `y` and `z` fields of a tile entity or scheduled tick compound.
+pub(crate) fn value_position(value: &Value) -> Option<Coordinates> {
+    let Value::Compound(compound) = value else {
+        return None;
+    };
+
+    let coordinate = |key: &str| match compound.get(key) {
+        Some(Value::Int(value)) => Some(*value),
+        _ => None,
+    };
+
+    Some(Coordinates {
+        x: coordinate("x")?,
+        y: coordinate("y")?,
+        z: coordinate("z")?,
+    })
+}
+
+/// Returns the entry with the given key if `value` is a compound.
+pub(crate) fn value_get<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Compound(compound) => compound.get(key),
+        _ => None,
+    }
+}
+
+/// Overwrites the `x`, `y` and `z` fields of a tile entity or scheduled tick compound.
+pub(crate) fn set_value_position(value: &mut Value, position: Coordinates) {
+    if let Value::Compound(compound) = value {
+        compound.insert("x".to_string(), Value::Int(position.x));
+        compound.insert("y".to_string(), Value::Int(position.y));
+        compound.insert("z".to_string(), Value::Int(position.z));
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{resource_location::ResourceLocation, structure::LitematicaFile};
-    use std::collections::HashMap;
+    use crate::{error::Error, resource_location::ResourceLocation, structure::LitematicaFile};
+    use std::collections::BTreeMap;
 
     use super::*;
 
@@ -220,6 +952,134 @@ mod tests {
         assert_eq!(region.get_3d_index((0, 8, 0)), 31 * 29 * 8);
     }
 
+    #[test]
+    fn reserve_palette() {
+        let mut region = Region::new((16, 4, 16));
+        let stone = BlockStateBuilder::new("stone").build();
+        region.set_block((0, 0, 0), stone.clone());
+
+        region.reserve_palette(20);
+
+        assert_eq!(Region::calc_required_bits(&region.block_state_palette), 5);
+        assert_eq!(region.block_state_palette.len(), 17);
+        assert_eq!(region.get_block((0, 0, 0)), &stone);
+
+        let words = region.block_states.len();
+        let lights = (0..16)
+            .map(|level| {
+                BlockStateBuilder::new("light")
+                    .properties([("level", level.to_string())])
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        for (x, light) in lights.iter().enumerate() {
+            region.set_block((x as i32, 1, 0), light.clone());
+        }
+
+        assert_eq!(region.block_states.len(), words);
+        assert_eq!(region.reserved_palette, 0);
+        assert_eq!(region.block_state_palette.len(), 18);
+
+        for (x, light) in lights.iter().enumerate() {
+            assert_eq!(region.get_block((x as i32, 1, 0)), light);
+        }
+
+        // placeholders are air, but are still taken over when air is placed in a region without air
+        let mut region = Region::new((4, 1, 1));
+        region.set_block((0, 0, 0), stone.clone());
+        region.replace_palette(|block| block.is_air().then(|| stone.clone()));
+        region.reserve_palette(8);
+        region.set_block((1, 0, 0), BlockStateBuilder::new("air").build());
+        region.set_block((2, 0, 0), BlockStateBuilder::new("dirt").build());
+
+        assert!(region.get_block((1, 0, 0)).is_air());
+        assert_eq!(region.get_block((2, 0, 0)).to_string(), "minecraft:dirt");
+        assert_eq!(region.get_block((3, 0, 0)), &stone);
+    }
+
+    #[test]
+    fn new_region() {
+        let mut region = Region::new((3, 5, -7));
+
+        assert_eq!(region.position, Coordinates { x: 0, y: 0, z: 0 });
+        assert_eq!(region.calc_volume(), 105);
+        assert_eq!(region.block_states.len(), 4); // 105 blocks * 2 bits
+        assert_eq!(region.block_state_palette[0].to_string(), "minecraft:air");
+
+        let stone = BlockStateBuilder::new("stone").build();
+        region.set_block((2, 4, 6), stone.clone());
+
+        assert_eq!(region.get_block((2, 4, 6)), &stone);
+        assert_eq!(
+            region
+                .iter_blocks()
+                .filter(|(_, block)| !block.is_air())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn builder_with_palette() {
+        let states = (0..20)
+            .map(|level| {
+                BlockStateBuilder::new("light")
+                    .properties([("level", (level % 16).to_string())])
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        let mut region = RegionBuilder::new((8, -4, 8))
+            .position((1, 2, 3))
+            .with_palette(states.clone())
+            .build();
+
+        // air and the 16 distinct light levels
+        assert_eq!(region.block_state_palette.len(), 17);
+        assert_eq!(Region::calc_required_bits(&region.block_state_palette), 5);
+        assert_eq!(region.position, Coordinates { x: 1, y: 2, z: 3 });
+        assert!(region.iter_blocks().all(|(_, block)| block.is_air()));
+
+        let words = region.block_states.len();
+
+        for (index, (position, _)) in region.clone().iter_blocks().enumerate() {
+            region.set_block(position, states[index % 16].clone());
+        }
+
+        assert_eq!(region.block_states.len(), words);
+        assert_eq!(region.block_state_palette.len(), 17);
+        assert_eq!(region.get_block((3, 0, 0)), &states[3]);
+    }
+
+    #[test]
+    fn builder_with_fill() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let entity = LitematicaFile::read("test.litematic")
+            .unwrap()
+            .get_region("test")
+            .unwrap()
+            .entities
+            .clone();
+
+        let region = RegionBuilder::new((5, 3, 7))
+            .position((-2, 0, 4))
+            .fill(stone.clone())
+            .entities(entity.clone())
+            .build();
+
+        assert_eq!(region.block_state_palette[0].to_string(), "minecraft:air");
+        assert!(region.iter_blocks().all(|(_, block)| *block == stone));
+        assert_eq!(region.entities, entity);
+
+        let air = RegionBuilder::new((2, 2, 2))
+            .fill(BlockStateBuilder::new("air").build())
+            .build();
+
+        assert_eq!(air.block_state_palette.len(), 1);
+        assert!(air.iter_blocks().all(|(_, block)| block.is_air()));
+    }
+
     #[test]
     fn get_palette_index() {
         let litematic = LitematicaFile::read("test.litematic").unwrap();
@@ -247,8 +1107,8 @@ mod tests {
             ResourceLocation::minecraft("powered_rail")
         );
         assert_eq!(
-            region.block_state_palette[palette_index as usize].properties,
-            HashMap::from([
+            *region.block_state_palette[palette_index as usize].properties,
+            BTreeMap::from([
                 ("shape".to_string(), "north_south".to_string()),
                 ("powered".to_string(), "true".to_string()),
                 ("waterlogged".to_string(), "false".to_string())
@@ -256,6 +1116,195 @@ mod tests {
         );
     }
 
+    #[test]
+    fn index_and_get() {
+        let litematic = LitematicaFile::read("test.litematic").unwrap();
+        let region = litematic.get_region("test").unwrap(); // region size: 31x9x29
+
+        assert_eq!(
+            region[(2, 4, 2)].name,
+            ResourceLocation::minecraft("powered_rail")
+        );
+        assert_eq!(
+            &region[Coordinates::from((2, 4, 2))],
+            region.get_block((2, 4, 2))
+        );
+        assert_eq!(region.get((2, 4, 2)), Some(&region[(2, 4, 2)]));
+        assert_eq!(region.get((31, 0, 0)), None);
+        assert_eq!(region.get((0, -1, 0)), None);
+    }
+
+    #[test]
+    fn try_get_and_set_block() {
+        let mut region = Region::new((2, 3, -4));
+        let stone = BlockStateBuilder::new("stone").build();
+
+        assert!(region.try_get_block((1, 2, 3)).unwrap().is_air());
+        region.try_set_block((1, 2, 3), stone.clone()).unwrap();
+        assert_eq!(region.try_get_block((1, 2, 3)).unwrap(), &stone);
+
+        for position in [(2, 0, 0), (0, -1, 0), (0, 0, 4)] {
+            assert!(matches!(
+                region.try_get_block(position),
+                Err(Error::Region(RegionError::OutOfBounds { size, .. })) if size == region.size
+            ));
+            assert!(region.try_set_block(position, stone.clone()).is_err());
+            assert!(region.try_get_block_mut(position).is_err());
+        }
+
+        region
+            .try_get_block_mut((1, 2, 3))
+            .unwrap()
+            .set_name("granite");
+        assert_eq!(region.get_block((1, 2, 3)).get_name().get_path(), "granite");
+
+        let error = region.try_get_block((0, 0, 4)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Region error: Position Coordinates { x: 0, y: 0, z: 4 } is outside of a region of size \
+             Coordinates { x: 2, y: 3, z: -4 }"
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+        assert_eq!(region.validate(), Ok(()));
+
+        let mut region = Region::new((4, 4, 4));
+        region.set_block((0, 0, 0), BlockStateBuilder::new("stone").build());
+        assert_eq!(region.validate(), Ok(()));
+
+        // index 3 with only air and stone in the palette
+        let mut corrupt = region.clone();
+        Region::set_block_index(&mut corrupt.block_states, 5, 3, 2, 0b11);
+        assert_eq!(
+            corrupt.validate(),
+            Err(RegionError::InvalidPaletteIndex {
+                block_index: 5,
+                palette_index: 3,
+                palette_len: 2,
+            })
+        );
+
+        // corrupt files are rejected when reading instead of panicking later
+        let mut corrupt_file = LitematicaFile::read("test.litematic").unwrap();
+        corrupt_file.regions.insert("test".to_string(), corrupt);
+        assert!(matches!(
+            LitematicaFile::from_bytes(&corrupt_file.to_bytes().unwrap()),
+            Err(Error::Region(RegionError::InvalidPaletteIndex { .. }))
+        ));
+
+        let mut truncated = region.clone();
+        truncated.block_states.pop();
+        assert_eq!(
+            truncated.validate(),
+            Err(RegionError::BlockStatesLength {
+                expected: 2,
+                actual: 1,
+            })
+        );
+
+        let mut empty = region.clone();
+        empty.block_state_palette.clear();
+        assert_eq!(empty.validate(), Err(RegionError::EmptyPalette));
+
+        // extension positions are read from the file and never went through `set_anchor()` and co.
+        let mut stray_anchor = region.clone();
+        stray_anchor
+            .extensions
+            .anchors
+            .insert("stray".to_string(), (4, 0, 0).into());
+        assert_eq!(
+            stray_anchor.validate(),
+            Err(RegionError::OutOfBounds {
+                coords: (4, 0, 0).into(),
+                size: region.size,
+            })
+        );
+
+        let mut larger = Region::new((8, 8, 8));
+        larger.create_group("stray", [(5, 5, 5)]);
+        let mut stray_group = region.clone();
+        stray_group.extensions.groups = larger.extensions.groups;
+        assert!(matches!(
+            stray_group.validate(),
+            Err(RegionError::OutOfBounds { .. })
+        ));
+
+        // a volume of 2^64 would wrap to 0 and accept the empty block states
+        let mut huge = region;
+        huge.size = (1 << 21, 1 << 21, 1 << 22).into();
+        huge.block_states.clear();
+        assert_eq!(
+            huge.validate(),
+            Err(RegionError::SizeTooLarge { size: huge.size })
+        );
+
+        let mut huge_file = LitematicaFile::read("test.litematic").unwrap();
+        huge_file.regions.insert("test".to_string(), huge);
+        assert!(matches!(
+            LitematicaFile::from_bytes(&huge_file.to_bytes().unwrap()),
+            Err(Error::Region(RegionError::SizeTooLarge { .. }))
+        ));
+    }
+
+    #[test]
+    fn find_block_positions_limited() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let not_air = |block: &BlockState| !block.is_air();
+        let all = region
+            .iter_blocks()
+            .filter(|(_, block)| not_air(block))
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            region.find_block_positions(&not_air).collect::<Vec<_>>(),
+            all
+        );
+        assert_eq!(region.find_block_positions(&not_air).next(), Some(all[0]));
+        assert_eq!(region.find_block_positions_limited(&not_air, 5), all[..5]);
+        assert_eq!(
+            region.find_block_positions_limited(&not_air, usize::MAX),
+            all
+        );
+        assert!(region.find_block_positions_limited(&not_air, 0).is_empty());
+        assert!(region.any_match(&not_air));
+
+        let barrier = BlockStateBuilder::new("barrier").build();
+        assert!(!region.any_match(&barrier));
+        assert!(region.find_block_positions_limited(&barrier, 10).is_empty());
+    }
+
+    #[test]
+    fn find_block_positions_large_palette() {
+        let mut region = Region::new((300, 1, 1));
+
+        for x in 0..300 {
+            let block = BlockStateBuilder::new("note_block")
+                .properties([("note", x.to_string())])
+                .build();
+            region.set_block((x, 0, 0), block);
+        }
+
+        let last = |block: &BlockState| {
+            block
+                .get_properties()
+                .get("note")
+                .is_some_and(|n| n == "299")
+        };
+
+        assert!(region.block_state_palette.len() > 256);
+        assert_eq!(
+            region.find_block_positions(&last).collect::<Vec<_>>(),
+            vec![Coordinates { x: 299, y: 0, z: 0 }]
+        );
+    }
+
     #[test]
     fn idk_how_this_works() {
         let litematic = LitematicaFile::read("test.litematic").unwrap();
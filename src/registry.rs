@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::error::{Result, ValidationError};
+use crate::resource_location::ResourceLocation;
+use crate::structure::BlockState;
+
+/// The data version of the bundled block table (Minecraft 1.21), which is also the data version written to new files.
+pub const BUILTIN_DATA_VERSION: i32 = crate::file::DEFAULT_DATA_VERSION;
+
+/// The data versions that share the block table of `BUILTIN_DATA_VERSION` (Minecraft 1.21 and 1.21.1).
+pub const BUILTIN_DATA_VERSIONS: RangeInclusive<i32> = BUILTIN_DATA_VERSION..=3955;
+
+static BUILTIN: OnceLock<Registry> = OnceLock::new();
+
+/// The allowed properties of a single block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct BlockSchema {
+    #[serde(default)]
+    properties: BTreeMap<String, Vec<String>>,
+}
+
+impl BlockSchema {
+    /// Returns an iterator over the names of all properties of the block, sorted by name.
+    pub fn property_names(&self) -> impl Iterator<Item = &str> {
+        self.properties.keys().map(|s| s.as_str())
+    }
+
+    /// Returns the allowed values of the given `property`, or `None` if the block does not have this property.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The name of the property, e.g. `facing`.
+    pub fn allowed_values(&self, property: &str) -> Option<&[String]> {
+        self.properties
+            .get(property)
+            .map(|values| values.as_slice())
+    }
+
+    /// Checks whether `value` is an allowed value of `property`.
+    pub fn is_valid(&self, property: &str, value: &str) -> bool {
+        self.allowed_values(property)
+            .is_some_and(|values| values.iter().any(|v| v == value))
+    }
+}
+
+/// A table of block property schemas for a specific Minecraft data version.
+///
+/// The crate bundles the table of every block in Minecraft 1.21 (see `Registry::builtin()`).
+/// Tables for other versions can be loaded from the `blocks.json` report generated by the Minecraft server
+/// (`java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports`).
+#[derive(Debug, Clone)]
+pub struct Registry {
+    data_version: i32,
+    blocks: HashMap<ResourceLocation, BlockSchema>,
+    complete: bool,
+}
+
+impl Registry {
+    /// Returns the bundled `Registry` for Minecraft 1.21.
+    ///
+    /// The table contains every vanilla block of that version. Use `Registry::for_data_version()` to make sure the
+    /// table matches the version of a schematic, or `Registry::from_reports()` for other versions.
+    pub fn builtin() -> &'static Registry {
+        BUILTIN.get_or_init(|| {
+            Self::from_reports(
+                include_str!("../data/blocks.json").as_bytes(),
+                BUILTIN_DATA_VERSION,
+            )
+            .expect("Bundled block table is invalid")
+        })
+    }
+
+    /// Returns the bundled `Registry` if it matches the given data version, or `None` if the blocks of that version
+    /// are not bundled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_version` - The data version of the Minecraft version, e.g. `LitematicaFile::minecraft_data_version`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::registry::Registry;
+    ///
+    /// assert!(Registry::for_data_version(3953).is_some());
+    /// assert!(Registry::for_data_version(3465).is_none());
+    /// ```
+    pub fn for_data_version(data_version: i32) -> Option<&'static Registry> {
+        BUILTIN_DATA_VERSIONS
+            .contains(&data_version)
+            .then(Self::builtin)
+    }
+
+    /// Loads a `Registry` from a `blocks.json` report generated by the Minecraft server.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A reader over the JSON report.
+    /// * `data_version` - The data version of the Minecraft version the report was generated with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be read or parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    /// use ritematica::registry::Registry;
+    ///
+    /// let registry = Registry::from_reports(File::open("reports/blocks.json").unwrap(), 4189).unwrap();
+    /// ```
+    pub fn from_reports(reader: impl Read, data_version: i32) -> Result<Self> {
+        let blocks: HashMap<String, BlockSchema> = serde_json::from_reader(reader)?;
+
+        let blocks = blocks
+            .into_iter()
+            .filter_map(|(name, schema)| Some((ResourceLocation::parse(name).ok()?, schema)))
+            .collect();
+
+        Ok(Self {
+            data_version,
+            blocks,
+            complete: true,
+        })
+    }
+
+    /// Marks this registry as partial, e.g. because it was built from a hand-written subset of a report.
+    ///
+    /// Blocks missing from a partial registry are not checked by `Registry::validate()` instead of being reported
+    /// as unknown.
+    pub fn partial(mut self) -> Self {
+        self.complete = false;
+        self
+    }
+
+    /// Returns `false` if this registry was marked as partial, see `Registry::partial()`.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Returns the data version this registry was created for.
+    pub fn data_version(&self) -> i32 {
+        self.data_version
+    }
+
+    /// Returns the `BlockSchema` of the given block, or `None` if the block is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The name of the block as a `ResourceLocation` or a `String` in the format `namespace:name`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::registry::Registry;
+    ///
+    /// let schema = Registry::builtin().properties_of("minecraft:piston").unwrap();
+    ///
+    /// assert!(schema.is_valid("facing", "up"));
+    /// ```
+    pub fn properties_of(&self, block: impl Into<ResourceLocation>) -> Option<&BlockSchema> {
+        self.blocks.get(&block.into())
+    }
+
+    /// Validates the name and properties of a `BlockState` against this registry.
+    ///
+    /// Properties that are not set are not an error, since Minecraft uses their default values. Blocks missing from
+    /// a partial registry are not checked at all.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ValidationError` if the block is unknown, has an unknown property or a property has an invalid value.
+    pub fn validate(&self, block_state: &BlockState) -> std::result::Result<(), ValidationError> {
+        let Some(schema) = self.blocks.get(&block_state.name) else {
+            return match self.complete {
+                true => Err(ValidationError::UnknownBlock(block_state.name.clone())),
+                false => Ok(()),
+            };
+        };
+
+        for (property, value) in block_state.properties.iter() {
+            let Some(values) = schema.allowed_values(property) else {
+                return Err(ValidationError::UnknownProperty {
+                    block: block_state.name.clone(),
+                    property: property.clone(),
+                });
+            };
+
+            if !values.contains(value) {
+                return Err(ValidationError::InvalidValue {
+                    block: block_state.name.clone(),
+                    property: property.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the `BlockSchema` of the given block from the bundled `Registry`, or `None` if the block is unknown or
+/// the blocks of `data_version` are not bundled (see `Registry::for_data_version()`).
+///
+/// # Arguments
+///
+/// * `block` - The name of the block as a `ResourceLocation` or a `String` in the format `namespace:name`.
+/// * `data_version` - The data version the block is looked up for.
+///
+/// # Examples
+/// ```
+/// use ritematica::registry;
+///
+/// let schema = registry::properties_of("minecraft:piston", 3953).unwrap();
+/// let names = schema.property_names().collect::<Vec<_>>();
+///
+/// assert_eq!(names, vec!["extended", "facing"]);
+/// ```
+pub fn properties_of(
+    block: impl Into<ResourceLocation>,
+    data_version: i32,
+) -> Option<&'static BlockSchema> {
+    Registry::for_data_version(data_version)?.properties_of(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn properties_of() {
+        let schema = super::properties_of("minecraft:piston", BUILTIN_DATA_VERSION).unwrap();
+
+        assert_eq!(
+            schema.property_names().collect::<Vec<_>>(),
+            vec!["extended", "facing"]
+        );
+        assert_eq!(
+            schema.allowed_values("extended"),
+            Some(&["true".to_string(), "false".to_string()][..])
+        );
+        assert!(super::properties_of("create:mechanical_drill", BUILTIN_DATA_VERSION).is_none());
+        assert!(super::properties_of("minecraft:piston", 3465).is_none());
+
+        for block in [
+            "blue_wool",
+            "oak_fence",
+            "short_grass",
+            "crafter",
+            "copper_bulb",
+        ] {
+            assert!(super::properties_of(block, 3955).is_some(), "{block}");
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let registry = Registry::builtin();
+
+        let valid = BlockStateBuilder::new("repeater")
+            .properties([("delay", "4"), ("facing", "east")])
+            .build();
+        assert_eq!(registry.validate(&valid), Ok(()));
+
+        let invalid_value = BlockStateBuilder::new("repeater")
+            .properties([("delay", "5")])
+            .build();
+        assert!(matches!(
+            registry.validate(&invalid_value),
+            Err(ValidationError::InvalidValue { .. })
+        ));
+
+        let unknown_property = BlockStateBuilder::new("stone")
+            .properties([("facing", "up")])
+            .build();
+        assert!(matches!(
+            registry.validate(&unknown_property),
+            Err(ValidationError::UnknownProperty { .. })
+        ));
+    }
+
+    #[test]
+    fn from_reports() {
+        let json = r#"{
+            "minecraft:lever": {
+                "properties": { "powered": ["true", "false"] },
+                "states": [{ "id": 1, "properties": { "powered": "true" } }]
+            }
+        }"#;
+
+        let registry = Registry::from_reports(json.as_bytes(), 3700).unwrap();
+
+        assert_eq!(registry.data_version(), 3700);
+        assert!(registry
+            .properties_of("lever")
+            .unwrap()
+            .is_valid("powered", "false"));
+
+        let lever = BlockStateBuilder::new("lever")
+            .properties([("powered", "maybe")])
+            .build();
+        let stone = BlockStateBuilder::new("stone").build();
+
+        assert!(matches!(
+            registry.validate(&stone),
+            Err(ValidationError::UnknownBlock(_))
+        ));
+
+        let registry = registry.partial();
+
+        assert!(!registry.is_complete());
+        assert_eq!(registry.validate(&stone), Ok(()));
+        assert!(matches!(
+            registry.validate(&lever),
+            Err(ValidationError::InvalidValue { .. })
+        ));
+    }
+}
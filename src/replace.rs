@@ -0,0 +1,324 @@
+use crate::block::BlockStatePattern;
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+/// The blocks a replacement would change, created by `Region::replace_dry_run()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplacePreview {
+    /// The positions of the blocks that would change, relative to the region origin, in YZX order.
+    pub positions: Vec<Coordinates>,
+}
+
+impl ReplacePreview {
+    /// Returns the number of blocks that would change.
+    pub fn count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Checks whether the replacement would change nothing.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl Region {
+    /// Replaces every block that matches `pattern` with `block`.
+    ///
+    /// Only the palette is rewritten, so this is fast even for huge regions. Returns the number of blocks that changed,
+    /// which doesn't include matching blocks that already were `block`. Use `replace_dry_run()` to preview the change.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockState, BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// let is_glass = |block: &BlockState| block.get_name().get_path().ends_with("stained_glass");
+    /// let replaced = region.replace(&is_glass, BlockStateBuilder::new("glass").build());
+    /// ```
+    pub fn replace(&mut self, pattern: &impl BlockStatePattern, block: BlockState) -> usize {
+        let changed = self.changing_palette_indices(pattern, &block);
+
+        if !changed.contains(&true) {
+            return 0;
+        }
+
+        let count = self.count_palette_indices(&changed);
+
+        self.replace_palette(|state| {
+            (pattern.matches(state) && *state != block).then(|| block.clone())
+        });
+
+        count
+    }
+
+    /// Returns the blocks that `replace()` would change, without modifying the region, e.g. to show a preview before
+    /// committing a large edit.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// let stone = BlockStateBuilder::new("stone").build();
+    /// let dirt = BlockStateBuilder::new("dirt").build();
+    ///
+    /// let preview = region.replace_dry_run(&stone, &dirt);
+    ///
+    /// if preview.count() < 1000 {
+    ///     assert_eq!(region.replace(&stone, dirt), preview.count());
+    /// }
+    /// ```
+    pub fn replace_dry_run(
+        &self,
+        pattern: &impl BlockStatePattern,
+        block: &BlockState,
+    ) -> ReplacePreview {
+        let changed = self.changing_palette_indices(pattern, block);
+
+        if !changed.contains(&true) {
+            return ReplacePreview::default();
+        }
+
+        let positions = self
+            .iter_blocks()
+            .filter(|(_, state)| pattern.matches(state) && *state != block)
+            .map(|(position, _)| position)
+            .collect();
+
+        ReplacePreview { positions }
+    }
+
+    /// Sets the listed properties on every block that matches `pattern` and already has them, leaving all other
+    /// properties and blocks as they are.
+    ///
+    /// Properties a block doesn't have are not added, so a broad pattern can't create invalid block states.
+    /// Returns the number of blocks that changed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockState, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// // de-power every redstone component before sharing the schematic
+    /// let any_block = |_: &BlockState| true;
+    /// region.update_properties(&any_block, [("powered", "false"), ("power", "0")]);
+    /// ```
+    pub fn update_properties<K, V>(
+        &mut self,
+        pattern: &impl BlockStatePattern,
+        properties: impl IntoIterator<Item = (K, V)>,
+    ) -> usize
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let properties = properties
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect::<Vec<(String, String)>>();
+
+        let update = |state: &BlockState| {
+            if !pattern.matches(state) {
+                return None;
+            }
+
+            let current = state.get_properties();
+            let changes = properties
+                .iter()
+                .filter(|(key, value)| current.get(key).is_some_and(|current| current != value))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            (!changes.is_empty()).then(|| {
+                let mut state = state.clone();
+                state.add_properties(changes);
+                state
+            })
+        };
+
+        let changed = self
+            .block_state_palette
+            .iter()
+            .map(|state| update(state).is_some())
+            .collect::<Vec<_>>();
+
+        if !changed.contains(&true) {
+            return 0;
+        }
+
+        let count = self.count_palette_indices(&changed);
+        self.replace_palette(update);
+
+        count
+    }
+
+    /// Returns for every palette entry whether replacing it would change it.
+    fn changing_palette_indices(
+        &self,
+        pattern: &impl BlockStatePattern,
+        block: &BlockState,
+    ) -> Vec<bool> {
+        self.block_state_palette
+            .iter()
+            .map(|state| pattern.matches(state) && state != block)
+            .collect()
+    }
+
+    /// Counts the blocks whose palette index is marked in `marked`.
+    fn count_palette_indices(&self, marked: &[bool]) -> usize {
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        (0..self.calc_volume())
+            .filter(|index| marked[self.get_palette_index(*index, required_bits, bitmask) as usize])
+            .count()
+    }
+}
+
+impl LitematicaFile {
+    /// Replaces every block that matches `pattern` with `block` in all regions, see `Region::replace()`.
+    ///
+    /// Returns the total number of blocks that changed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockStateBuilder, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// let stone = BlockStateBuilder::new("stone").build();
+    /// let replaced = file.replace(&stone, BlockStateBuilder::new("deepslate").build());
+    ///
+    /// println!("Replaced {} blocks", replaced);
+    /// ```
+    pub fn replace(&mut self, pattern: &impl BlockStatePattern, block: BlockState) -> usize {
+        self.regions
+            .values_mut()
+            .map(|region| region.replace(pattern, block.clone()))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn replace_dry_run() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let stone = BlockStateBuilder::new("stone").build();
+        let dirt = BlockStateBuilder::new("dirt").build();
+
+        region.set_block((0, 0, 0), stone.clone());
+        region.set_block((1, 0, 0), stone.clone());
+        region.set_block((2, 0, 0), dirt.clone());
+
+        let is_stone_or_dirt =
+            |block: &BlockState| matches!(block.get_name().get_path(), "stone" | "dirt");
+        let before = region.clone();
+
+        let preview = region.replace_dry_run(&is_stone_or_dirt, &dirt);
+
+        assert!(preview
+            .positions
+            .contains(&Coordinates { x: 0, y: 0, z: 0 }));
+        assert!(preview
+            .positions
+            .contains(&Coordinates { x: 1, y: 0, z: 0 }));
+        assert!(!preview
+            .positions
+            .contains(&Coordinates { x: 2, y: 0, z: 0 }));
+        assert!(region
+            .iter_blocks()
+            .zip(before.iter_blocks())
+            .all(|(a, b)| a == b));
+
+        assert_eq!(
+            region.replace(&is_stone_or_dirt, dirt.clone()),
+            preview.count()
+        );
+
+        for position in &preview.positions {
+            assert_eq!(region.get_block(*position), &dirt);
+        }
+
+        assert!(region.replace_dry_run(&stone, &dirt).is_empty());
+        assert_eq!(region.replace(&stone, dirt), 0);
+    }
+
+    #[test]
+    fn replace_in_file() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let deepslate = BlockStateBuilder::new("deepslate").build();
+
+        let mut first = Region::new((4, 1, 1));
+        first.fill((0, 0, 0), (2, 0, 0), stone.clone());
+        let mut second = Region::new((2, 2, 2));
+        second.fill((0, 0, 0), (1, 1, 1), stone.clone());
+
+        let mut file = crate::file::LitematicaFileBuilder::new("Test")
+            .region("first", first)
+            .region("second", second)
+            .build();
+
+        assert_eq!(file.replace(&stone, deepslate.clone()), 11);
+        assert_eq!(file.replace(&stone, deepslate.clone()), 0);
+
+        let second = file.get_region("second").unwrap();
+        assert!(second.iter_blocks().all(|(_, block)| *block == deepslate));
+        assert!(file
+            .get_region("first")
+            .unwrap()
+            .get_block((3, 0, 0))
+            .is_air());
+    }
+
+    #[test]
+    fn update_properties() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 4, y: 1, z: 1 },
+        );
+
+        let lever = |powered: &str| {
+            BlockStateBuilder::new("lever")
+                .properties([("face", "floor"), ("facing", "north"), ("powered", powered)])
+                .build()
+        };
+        let stone = BlockStateBuilder::new("stone").build();
+
+        region.set_block((0, 0, 0), lever("true"));
+        region.set_block((1, 0, 0), lever("true"));
+        region.set_block((2, 0, 0), lever("false"));
+        region.set_block((3, 0, 0), stone.clone());
+
+        let any_lever = |block: &BlockState| block.get_name().get_path() == "lever";
+
+        assert_eq!(
+            region.update_properties(&any_lever, [("powered", "false"), ("facing", "north")]),
+            2
+        );
+        assert_eq!(region.get_block((0, 0, 0)), &lever("false"));
+        assert_eq!(region.get_block((2, 0, 0)), &lever("false"));
+        assert_eq!(region.get_block((3, 0, 0)), &stone);
+
+        assert_eq!(
+            region.update_properties(&any_lever, [("powered", "false")]),
+            0
+        );
+        assert_eq!(
+            region.update_properties(&lever("false"), [("powered", "true")]),
+            3
+        );
+        assert_eq!(region.get_block((1, 0, 0)), &lever("true"));
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::{fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize};
@@ -16,8 +18,46 @@ use crate::error::ParseError;
 /// assert_eq!(resource_location.get_path(), "mechanical_drill");
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ResourceLocation {
-    pub(crate) namespace: String,
-    pub(crate) path: String,
+    pub(crate) namespace: Arc<str>,
+    pub(crate) path: Arc<str>,
+}
+
+/// Options that control how `ResourceLocation::parse_with_options()` and `BlockState::parse_with_options()` read names,
+/// e.g. for tools around a single mod, where most names are written without a namespace.
+///
+/// # Examples
+/// ```
+/// use ritematica::resource_location::{ParseOptions, ResourceLocation};
+///
+/// let mut options = ParseOptions {
+///     default_namespace: "create".to_string(),
+///     ..Default::default()
+/// };
+/// options.namespace_remaps.insert("createaddition".to_string(), "create".to_string());
+///
+/// let drill = ResourceLocation::parse_with_options("mechanical_drill", &options).unwrap();
+/// assert_eq!(drill.to_string(), "create:mechanical_drill");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// The namespace of names without one. Defaults to `minecraft`.
+    pub default_namespace: String,
+
+    /// Namespaces that are replaced after parsing, e.g. of a mod that was merged into another one.
+    pub namespace_remaps: HashMap<String, String>,
+
+    /// Names that are replaced after parsing and remapping the namespace, e.g. of renamed blocks.
+    pub remaps: HashMap<ResourceLocation, ResourceLocation>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            default_namespace: "minecraft".to_string(),
+            namespace_remaps: HashMap::new(),
+            remaps: HashMap::new(),
+        }
+    }
 }
 
 impl ResourceLocation {
@@ -78,7 +118,10 @@ impl ResourceLocation {
         );
         assert!(Self::is_valid_path(&path), "Invalid path {}", path);
 
-        Self { namespace, path }
+        Self {
+            namespace: namespace.into(),
+            path: path.into(),
+        }
     }
 
     /// Creates a new `ResourceLocation` with the "minecraft" namespace and the given path.
@@ -129,7 +172,35 @@ impl ResourceLocation {
     /// assert!(ResourceLocation::parse("invalid@namespace:stone").is_err());
     /// ```
     pub fn parse(resource: impl AsRef<str>) -> Result<Self, ParseError> {
-        let resource = resource.as_ref();
+        Self::parse_with_namespace(resource.as_ref(), "minecraft")
+    }
+
+    /// Parses a string representation of a `ResourceLocation` using the given `ParseOptions`.
+    ///
+    /// Names without a namespace get the default namespace of the options, and the remaps of the options are applied
+    /// to the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the input string or the default namespace is not a valid `ResourceLocation`.
+    pub fn parse_with_options(
+        resource: impl AsRef<str>,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError> {
+        if !Self::is_valid_namespace(&options.default_namespace) {
+            return Err(ParseError);
+        }
+
+        let mut parsed = Self::parse_with_namespace(resource.as_ref(), &options.default_namespace)?;
+
+        if let Some(namespace) = options.namespace_remaps.get(&*parsed.namespace) {
+            parsed.namespace = namespace.as_str().into();
+        }
+
+        Ok(options.remaps.get(&parsed).cloned().unwrap_or(parsed))
+    }
+
+    fn parse_with_namespace(resource: &str, default_namespace: &str) -> Result<Self, ParseError> {
         let mut split = resource.splitn(2, ':');
 
         let first = split.next().ok_or(ParseError)?;
@@ -144,8 +215,8 @@ impl ResourceLocation {
             }
 
             Ok(Self {
-                namespace: first.to_string(),
-                path: second.to_string(),
+                namespace: first.into(),
+                path: second.into(),
             })
         } else {
             if !Self::is_valid_path(first) {
@@ -153,8 +224,8 @@ impl ResourceLocation {
             }
 
             Ok(Self {
-                namespace: "minecraft".to_string(),
-                path: first.to_string(),
+                namespace: default_namespace.into(),
+                path: first.into(),
             })
         }
     }
@@ -252,6 +323,35 @@ mod tests {
         assert_eq!(resource_location.get_path(), "andesite");
     }
 
+    #[test]
+    fn parse_resource_location_with_options() {
+        let mut options = ParseOptions {
+            default_namespace: "create".to_string(),
+            ..Default::default()
+        };
+        options
+            .namespace_remaps
+            .insert("create_old".to_string(), "create".to_string());
+        options.remaps.insert(
+            ResourceLocation::new("create", "cogwheel_old"),
+            ResourceLocation::new("create", "cogwheel"),
+        );
+
+        let parse = |input| {
+            ResourceLocation::parse_with_options(input, &options)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(parse("shaft"), "create:shaft");
+        assert_eq!(parse("minecraft:stone"), "minecraft:stone");
+        assert_eq!(parse("create_old:belt"), "create:belt");
+        assert_eq!(parse("create_old:cogwheel_old"), "create:cogwheel");
+
+        options.default_namespace = "invalid!".to_string();
+        assert!(ResourceLocation::parse_with_options("shaft", &options).is_err());
+    }
+
     #[test]
     fn parse_resource_location_invalid_namespace() {
         let result = ResourceLocation::parse("invalid!namespace:resource_path");
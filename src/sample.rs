@@ -0,0 +1,153 @@
+use crate::iter::coordinates_of;
+use crate::region::read_palette_index;
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// How many positions are probed per requested block, so rare palette entries still get a chance to be sampled.
+const PROBES_PER_BLOCK: u64 = 4;
+
+impl Region {
+    /// Returns up to `max_blocks` representative non-air blocks, e.g. for a lightweight preview of an enormous region.
+    ///
+    /// Only evenly spaced positions are probed instead of the whole region. The sample is stratified by palette entry:
+    /// every block state that was hit gets its share of the sample, taken round-robin, so a few rare blocks are not
+    /// drowned out by the main building material. The blocks are returned in YZX order, and the same region always
+    /// gives the same sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_blocks` - The maximum number of blocks in the sample.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// for (position, block) in region.sample(100) {
+    ///     println!("{:?}: {}", position, block);
+    /// }
+    /// ```
+    pub fn sample(&self, max_blocks: usize) -> Vec<(Coordinates, &BlockState)> {
+        let volume = self.calc_volume();
+
+        if max_blocks == 0 || volume == 0 {
+            return Vec::new();
+        }
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let probes = (max_blocks as u64 * PROBES_PER_BLOCK).min(volume);
+        let mut buckets = vec![Vec::new(); self.block_state_palette.len()];
+
+        for probe in 0..probes {
+            // spreads the probes over the whole region, including both ends
+            let index = probe * volume / probes;
+            let palette_index =
+                read_palette_index(&self.block_states, index, required_bits, bitmask);
+
+            if !self.block_state_palette[palette_index as usize].is_air() {
+                buckets[palette_index as usize].push(index);
+            }
+        }
+
+        // hands out the sample one block per palette entry at a time, until it is full or every hit was used
+        let mut quotas = vec![0; buckets.len()];
+        let mut remaining = max_blocks;
+
+        while remaining > 0 {
+            let before = remaining;
+
+            for (quota, bucket) in quotas.iter_mut().zip(&buckets) {
+                if remaining > 0 && *quota < bucket.len() {
+                    *quota += 1;
+                    remaining -= 1;
+                }
+            }
+
+            if remaining == before {
+                break;
+            }
+        }
+
+        // takes the blocks of every palette entry evenly spread out instead of from the start
+        let mut sampled = buckets
+            .iter()
+            .zip(quotas)
+            .enumerate()
+            .flat_map(|(palette_index, (bucket, quota))| {
+                (0..quota).map(move |pick| (bucket[pick * bucket.len() / quota], palette_index))
+            })
+            .collect::<Vec<_>>();
+
+        sampled.sort_unstable();
+
+        sampled
+            .into_iter()
+            .map(|(index, palette_index)| {
+                (
+                    coordinates_of(index, self.size),
+                    &self.block_state_palette[palette_index],
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::block::BlockStateBuilder;
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let sample = region.sample(50);
+
+        assert!(sample.len() <= 50);
+        assert!(!sample.is_empty());
+        assert_eq!(sample, region.sample(50));
+        assert!(region.sample(0).is_empty());
+
+        for (position, block) in &sample {
+            assert!(!block.is_air());
+            assert_eq!(region.get_block(*position), *block);
+        }
+
+        let positions = sample
+            .iter()
+            .map(|(position, _)| *position)
+            .collect::<HashSet<_>>();
+        assert_eq!(positions.len(), sample.len());
+    }
+
+    #[test]
+    fn sample_is_stratified() {
+        let mut region = Region::new((64, 1, 64));
+        let stone = BlockStateBuilder::new("stone").build();
+        let gold = BlockStateBuilder::new("gold_block").build();
+
+        for x in 0..64 {
+            for z in 0..64 {
+                region.set_block((x, 0, z), stone.clone());
+            }
+        }
+        for x in 0..64 {
+            region.set_block((x, 0, 0), gold.clone());
+        }
+
+        let sample = region.sample(10);
+        let gold_count = sample.iter().filter(|(_, block)| **block == gold).count();
+
+        assert_eq!(sample.len(), 10);
+        assert!(gold_count >= 1);
+        assert!(gold_count <= 5);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+/// A high-level view of a `LitematicaFile` as a single grid of blocks, without regions.
+///
+/// Coordinates are relative to the corner of the enclosing box with the lowest coordinates,
+/// so `(0, 0, 0)` is always the first block of the schematic regardless of how its regions are laid out.
+///
+/// Reads and writes are forwarded to the region containing the position. Writes into gaps between regions
+/// create a new region that covers as much of the gap as possible without overlapping other regions.
+///
+/// # Examples
+/// ```
+/// use ritematica::{BlockStateBuilder, LitematicaFile, Schematic};
+///
+/// let mut schematic = Schematic::new(LitematicaFile::read("test.litematic").unwrap());
+///
+/// schematic.set_block((0, 0, 0), BlockStateBuilder::new("stone").build());
+/// assert_eq!(schematic.get_block((0, 0, 0)).unwrap().to_string(), "minecraft:stone");
+/// ```
+#[derive(Debug)]
+pub struct Schematic {
+    file: LitematicaFile,
+    origin: Coordinates,
+    size: Coordinates,
+}
+
+impl Schematic {
+    /// Creates a new `Schematic` from a `LitematicaFile`.
+    ///
+    /// The enclosing box is computed from the regions of the file. If the file does not contain any regions,
+    /// the `enclosing_size` of the metadata is used instead.
+    pub fn new(file: LitematicaFile) -> Self {
+        let (origin, size) = if file.regions.is_empty() {
+            let size = file.metadata.enclosing_size;
+            let size = Coordinates {
+                x: size.x.abs(),
+                y: size.y.abs(),
+                z: size.z.abs(),
+            };
+
+            (Coordinates { x: 0, y: 0, z: 0 }, size)
+        } else {
+            let (min, max) = file.enclosing_box();
+            let size = Coordinates {
+                x: max.x - min.x,
+                y: max.y - min.y,
+                z: max.z - min.z,
+            };
+
+            (min, size)
+        };
+
+        Self { file, origin, size }
+    }
+
+    /// Returns the size of the enclosing box of the schematic.
+    pub fn size(&self) -> Coordinates {
+        self.size
+    }
+
+    /// Returns a reference to the underlying `LitematicaFile`.
+    pub fn file(&self) -> &LitematicaFile {
+        &self.file
+    }
+
+    /// Consumes the `Schematic` and returns the underlying `LitematicaFile`, e.g. to write it to disk.
+    pub fn into_inner(self) -> LitematicaFile {
+        self.file
+    }
+
+    /// Checks whether the given position lies inside of the enclosing box.
+    pub fn contains(&self, position: impl Into<Coordinates>) -> bool {
+        let position = position.into();
+
+        (0..self.size.x).contains(&position.x)
+            && (0..self.size.y).contains(&position.y)
+            && (0..self.size.z).contains(&position.z)
+    }
+
+    /// Returns the `BlockState` at the given position.
+    ///
+    /// Returns `None` if the position is outside of the enclosing box or not covered by any region. Where regions
+    /// overlap, the block of the first region by name is returned, like `LitematicaFile::region_at()` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position relative to the enclosing box.
+    pub fn get_block(&self, position: impl Into<Coordinates>) -> Option<&BlockState> {
+        let position = position.into();
+
+        if !self.contains(position) {
+            return None;
+        }
+
+        let absolute = self.to_absolute(position);
+        let (_, region) = self.region_containing(absolute)?;
+
+        local_position(region, absolute).map(|local| region.get_block(local))
+    }
+
+    /// Sets the `BlockState` at the given position.
+    ///
+    /// Writes into the same region `get_block()` reads from. If no region contains the position, a new region filled
+    /// with air is created around it. The region count, total volume and total block count of the metadata are kept
+    /// up to date.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position relative to the enclosing box.
+    /// * `block` - The new `BlockState`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the enclosing box.
+    pub fn set_block(&mut self, position: impl Into<Coordinates>, block: BlockState) {
+        let position = position.into();
+
+        assert!(
+            self.contains(position),
+            "Position {:?} is outside of the schematic",
+            position
+        );
+
+        let absolute = self.to_absolute(position);
+
+        let name = match self.region_containing(absolute) {
+            Some((name, _)) => name.clone(),
+            None => self.create_gap_region(absolute),
+        };
+
+        let region = self.file.regions.get_mut(&name).expect("region exists");
+        let local = local_position(region, absolute).expect("region contains position");
+
+        let was_air = region.get_block(local).is_air();
+        let is_air = block.is_air();
+
+        region.set_block(local, block);
+
+        self.file.metadata.total_blocks += was_air as i32 - is_air as i32;
+    }
+
+    fn to_absolute(&self, position: Coordinates) -> Coordinates {
+        Coordinates {
+            x: self.origin.x + position.x,
+            y: self.origin.y + position.y,
+            z: self.origin.z + position.z,
+        }
+    }
+
+    /// Returns the first region by name that contains `absolute`.
+    fn region_containing(&self, absolute: Coordinates) -> Option<(&String, &Region)> {
+        self.file
+            .regions
+            .iter()
+            .filter(|(_, region)| local_position(region, absolute).is_some())
+            .min_by_key(|(name, _)| *name)
+    }
+
+    /// Creates a region containing `absolute` that grows into every direction until it would overlap another region
+    /// or leave the enclosing box.
+    fn create_gap_region(&mut self, absolute: Coordinates) -> String {
+        let enclosing_max = self.to_absolute(Coordinates {
+            x: self.size.x - 1,
+            y: self.size.y - 1,
+            z: self.size.z - 1,
+        });
+
+        let boxes = self
+            .file
+            .regions
+            .values()
+            .map(region_box)
+            .collect::<Vec<_>>();
+        let is_free = |min: Coordinates, max: Coordinates| {
+            boxes.iter().all(|(region_min, region_max)| {
+                max.x < region_min.x
+                    || min.x > region_max.x
+                    || max.y < region_min.y
+                    || min.y > region_max.y
+                    || max.z < region_min.z
+                    || min.z > region_max.z
+            })
+        };
+
+        let mut min = absolute;
+        let mut max = absolute;
+
+        loop {
+            let mut grown = false;
+
+            for axis in 0..3 {
+                let mut candidate_min = min;
+                let mut candidate_max = max;
+
+                let (candidate_min_value, enclosing_min_value) = match axis {
+                    0 => (&mut candidate_min.x, self.origin.x),
+                    1 => (&mut candidate_min.y, self.origin.y),
+                    _ => (&mut candidate_min.z, self.origin.z),
+                };
+
+                if *candidate_min_value > enclosing_min_value {
+                    *candidate_min_value -= 1;
+
+                    if is_free(candidate_min, max) {
+                        min = candidate_min;
+                        grown = true;
+                    }
+                }
+
+                let (candidate_max_value, enclosing_max_value) = match axis {
+                    0 => (&mut candidate_max.x, enclosing_max.x),
+                    1 => (&mut candidate_max.y, enclosing_max.y),
+                    _ => (&mut candidate_max.z, enclosing_max.z),
+                };
+
+                if *candidate_max_value < enclosing_max_value {
+                    *candidate_max_value += 1;
+
+                    if is_free(min, candidate_max) {
+                        max = candidate_max;
+                        grown = true;
+                    }
+                }
+            }
+
+            if !grown {
+                break;
+            }
+        }
+
+        let size = Coordinates {
+            x: max.x - min.x + 1,
+            y: max.y - min.y + 1,
+            z: max.z - min.z + 1,
+        };
+
+        let mut index = self.file.regions.len() + 1;
+        let name = loop {
+            let name = format!("Region {}", index);
+
+            if !self.file.regions.contains_key(&name) {
+                break name;
+            }
+
+            index += 1;
+        };
+
+        self.file
+            .regions
+            .insert(name.clone(), Region::filled_with_air(min, size));
+
+        self.file.metadata.region_count = self.file.regions.len() as i32;
+        self.file.metadata.total_volume += size.x * size.y * size.z;
+
+        name
+    }
+}
+
+impl From<LitematicaFile> for Schematic {
+    fn from(file: LitematicaFile) -> Self {
+        Self::new(file)
+    }
+}
+
+/// Returns the inclusive minimum and maximum corner of a region relative to the schematic origin.
+fn region_box(region: &Region) -> (Coordinates, Coordinates) {
+    let min = region.min_corner();
+    let max = Coordinates {
+        x: min.x + region.size.x.abs() - 1,
+        y: min.y + region.size.y.abs() - 1,
+        z: min.z + region.size.z.abs() - 1,
+    };
+
+    (min, max)
+}
+
+/// Converts a position relative to the schematic origin into a position inside of `region`,
+/// or `None` if the region does not contain it.
+fn local_position(region: &Region, absolute: Coordinates) -> Option<Coordinates> {
+    let (min, max) = region_box(region);
+
+    let inside = (min.x..=max.x).contains(&absolute.x)
+        && (min.y..=max.y).contains(&absolute.y)
+        && (min.z..=max.z).contains(&absolute.z);
+
+    inside.then(|| Coordinates {
+        x: absolute.x - min.x,
+        y: absolute.y - min.y,
+        z: absolute.z - min.z,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn get_block_matches_region() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let expected = file
+            .get_region("test")
+            .unwrap()
+            .get_block((2, 4, 2))
+            .clone();
+
+        let schematic = Schematic::new(file);
+
+        assert_eq!(schematic.get_block((2, 4, 2)), Some(&expected));
+        assert_eq!(schematic.get_block((-1, 0, 0)), None);
+    }
+
+    #[test]
+    fn set_block_in_gap_creates_region() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+        let min = region.min_corner();
+        let width = region.size.x.abs();
+
+        // a second region five blocks away from the first one along the x axis
+        let far = Coordinates {
+            x: min.x + width + 5,
+            y: min.y,
+            z: min.z,
+        };
+        file.regions.insert(
+            "far".to_string(),
+            Region::filled_with_air(far, Coordinates { x: 1, y: 1, z: 1 }),
+        );
+
+        let mut schematic = Schematic::new(file);
+        assert_eq!(schematic.size().x, width + 6);
+        assert_eq!(schematic.get_block((width + 1, 0, 0)), None);
+
+        let stone = BlockStateBuilder::new("stone").build();
+        schematic.set_block((width + 1, 0, 0), stone.clone());
+
+        assert_eq!(schematic.get_block((width + 1, 0, 0)), Some(&stone));
+        assert_eq!(schematic.file().get_regions().len(), 3);
+
+        // the created region fills the gap between the two regions without overlapping them
+        let created = schematic.file().get_region("Region 3").unwrap();
+        assert_eq!(created.min_corner().x, min.x + width);
+        assert_eq!(created.size.x, 5);
+    }
+
+    #[test]
+    fn overlapping_regions_use_first_name() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+        let expected = region.get_block((0, 0, 0)).clone();
+
+        // "a" comes before "test" and covers its first block
+        let overlap =
+            Region::filled_with_air(region.min_corner(), Coordinates { x: 1, y: 1, z: 1 });
+        file.regions.insert("a".to_string(), overlap);
+
+        let mut schematic = Schematic::new(file);
+        assert!(schematic.get_block((0, 0, 0)).unwrap().is_air());
+        assert_eq!(
+            schematic
+                .file()
+                .region_at((0, 0, 0))
+                .unwrap()
+                .1
+                .get_block((0, 0, 0)),
+            schematic.get_block((0, 0, 0)).unwrap()
+        );
+
+        let blocks = schematic.file().metadata.total_blocks;
+        let stone = BlockStateBuilder::new("stone").build();
+
+        schematic.set_block((0, 0, 0), stone.clone());
+        assert_eq!(schematic.get_block((0, 0, 0)), Some(&stone));
+        assert_eq!(
+            schematic
+                .file()
+                .get_region("test")
+                .unwrap()
+                .get_block((0, 0, 0)),
+            &expected
+        );
+        assert_eq!(schematic.file().metadata.total_blocks, blocks + 1);
+
+        schematic.set_block((0, 0, 0), BlockStateBuilder::new("air").build());
+        assert_eq!(schematic.file().metadata.total_blocks, blocks);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::fill::split_mix;
+use crate::iter::coordinates_of;
+use crate::region::{index_3d, read_palette_index};
+use crate::structure::{BlockState, Coordinates, Region};
+use crate::trace;
+
+/// Options that control how `Region::similarity_with_options()` compares two regions.
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityOptions {
+    /// Skips positions that are air in both regions, so that large empty areas don't make different builds look similar.
+    pub ignore_air: bool,
+}
+
+impl Region {
+    /// Returns the fraction of positions in which the two regions have the same `BlockState`, between `0.0` and `1.0`.
+    ///
+    /// The regions are compared over the union of their bounding boxes, aligned at their corners with the lowest coordinates.
+    /// Positions outside of one of the regions count as air. Air is compared like any other block, see
+    /// `similarity_with_options()` to ignore it.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let plan = LitematicaFile::read("plan.litematic").unwrap();
+    /// let build = LitematicaFile::read("build.litematic").unwrap();
+    ///
+    /// let progress = build.get_region("farm").unwrap().similarity(plan.get_region("farm").unwrap());
+    /// println!("{:.0}% done", progress * 100.0);
+    /// ```
+    pub fn similarity(&self, other: &Region) -> f64 {
+        self.similarity_with_options(other, &SimilarityOptions::default())
+    }
+
+    /// Returns the fraction of positions in which the two regions have the same `BlockState`, using the given `SimilarityOptions`.
+    ///
+    /// Returns `1.0` if no position is compared, e.g. if both regions only contain air and air is ignored.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(volume = self.calc_volume(), other_volume = other.calc_volume(), elapsed_ms))
+    )]
+    pub fn similarity_with_options(&self, other: &Region, options: &SimilarityOptions) -> f64 {
+        let _timer = trace::Timer::start();
+
+        let size = Coordinates {
+            x: self.size.x.abs().max(other.size.x.abs()),
+            y: self.size.y.abs().max(other.size.y.abs()),
+            z: self.size.z.abs().max(other.size.z.abs()),
+        };
+
+        let mut compared = 0u64;
+        let mut matching = 0u64;
+
+        for y in 0..size.y {
+            for z in 0..size.z {
+                for x in 0..size.x {
+                    let position = Coordinates { x, y, z };
+
+                    let first = block_or_air(self, position);
+                    let second = block_or_air(other, position);
+
+                    if options.ignore_air && is_air(first) && is_air(second) {
+                        continue;
+                    }
+
+                    compared += 1;
+
+                    if first == second || (is_air(first) && is_air(second)) {
+                        matching += 1;
+                    }
+                }
+            }
+        }
+
+        if compared == 0 {
+            1.0
+        } else {
+            matching as f64 / compared as f64
+        }
+    }
+
+    /// Compares the blocks of the two regions at `samples` pseudo-random positions, as a quick sanity check for
+    /// regions that are too large to compare completely, e.g. in tests.
+    ///
+    /// The regions must have the same absolute size and are aligned at their corners with the lowest coordinates.
+    /// The positions only depend on the `seed`, so a failing check can be reproduced. If `samples` is at least the
+    /// volume of the region, every position is compared and the result is exact.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let expected = LitematicaFile::read("expected.litematic").unwrap();
+    /// let generated = LitematicaFile::read("generated.litematic").unwrap();
+    ///
+    /// assert!(generated
+    ///     .get_region("city")
+    ///     .unwrap()
+    ///     .sample_equal(expected.get_region("city").unwrap(), 10_000, 42));
+    /// ```
+    pub fn sample_equal(&self, other: &Region, samples: usize, seed: u64) -> bool {
+        let size = Coordinates {
+            x: self.size.x.abs(),
+            y: self.size.y.abs(),
+            z: self.size.z.abs(),
+        };
+
+        let same_size = size.x == other.size.x.abs()
+            && size.y == other.size.y.abs()
+            && size.z == other.size.z.abs();
+
+        if !same_size {
+            return false;
+        }
+
+        let volume = self.calc_volume();
+
+        let equal_at = |index: u64| {
+            let position = coordinates_of(index, size);
+            block_or_air(self, position) == block_or_air(other, position)
+        };
+
+        if samples as u64 >= volume {
+            (0..volume).all(equal_at)
+        } else {
+            (0..samples as u64)
+                .map(|sample| split_mix(seed ^ split_mix(sample)) % volume)
+                .all(equal_at)
+        }
+    }
+}
+
+/// Returns the `BlockState` at `position`, or `None` if the position is outside of the region.
+fn block_or_air(region: &Region, position: Coordinates) -> Option<&BlockState> {
+    let inside = position.x < region.size.x.abs()
+        && position.y < region.size.y.abs()
+        && position.z < region.size.z.abs();
+
+    inside.then(|| {
+        let required_bits = Region::calc_required_bits(&region.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+        let block_index = index_3d(region.size, position);
+
+        let palette_index =
+            read_palette_index(&region.block_states, block_index, required_bits, bitmask);
+
+        &region.block_state_palette[palette_index as usize]
+    })
+}
+
+fn is_air(block: Option<&BlockState>) -> bool {
+    block.is_none_or(BlockState::is_air)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn similarity_with_and_without_air() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let dirt = BlockStateBuilder::new("dirt").build();
+
+        let mut first = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 4, y: 1, z: 1 },
+        );
+        let mut second = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 2, y: 1, z: 1 },
+        );
+
+        first.set_block((0, 0, 0), stone.clone());
+        first.set_block((1, 0, 0), stone.clone());
+        second.set_block((0, 0, 0), stone);
+        second.set_block((1, 0, 0), dirt);
+
+        // two air positions outside of `second` match, one stone matches, one block differs
+        assert_eq!(first.similarity(&second), 0.75);
+
+        let options = SimilarityOptions { ignore_air: true };
+        assert_eq!(first.similarity_with_options(&second, &options), 0.5);
+
+        assert_eq!(first.similarity(&first), 1.0);
+    }
+
+    #[test]
+    fn sample_equal() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let mut first = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 8, y: 8, z: 8 },
+        );
+
+        for (position, _) in first.clone().iter_blocks() {
+            if (position.x + position.y + position.z) % 2 == 0 {
+                first.set_block(position, stone.clone());
+            }
+        }
+
+        // only the absolute size has to match
+        let mut second = first.clone();
+        second.size = Coordinates { x: -8, y: 8, z: 8 };
+        assert!(first.sample_equal(&second, 100, 1));
+
+        let mut changed = first.clone();
+        changed.set_block((3, 4, 5), BlockStateBuilder::new("dirt").build());
+        assert!(!first.sample_equal(&changed, 512, 1));
+
+        let smaller = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 8, y: 8, z: 7 },
+        );
+        assert!(!first.sample_equal(&smaller, 100, 1));
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::RegionError;
+use crate::region::{index_3d, read_palette_index, validate_block_data};
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// A compact, serializable copy of the block data of a `Region`.
+///
+/// A snapshot only contains the `size`, the block state `palette` and the packed palette indices,
+/// which makes it cheap to send over the wire (e.g. as JSON) to a client that only needs to look up blocks.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+/// let snapshot = file.get_region("test").unwrap().snapshot();
+///
+/// let json = serde_json::to_string(&snapshot).unwrap();
+/// let snapshot: RegionSnapshot = serde_json::from_str(&json).unwrap();
+///
+/// assert_eq!(snapshot.get_block((0, 0, 0)).get_name().to_string(), "minecraft:air");
+/// ```
+///
+/// Deserializing a snapshot runs the same checks as `Region::validate`, so a snapshot with missing block
+/// data or out of range palette indices is rejected instead of panicking in `get_block`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", try_from = "RawSnapshot")]
+pub struct RegionSnapshot {
+    pub(crate) size: Coordinates,
+    pub(crate) palette: Vec<BlockState>,
+    pub(crate) block_states: Vec<i64>,
+}
+
+/// The unchecked serialized form of a `RegionSnapshot`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RawSnapshot {
+    size: Coordinates,
+    palette: Vec<BlockState>,
+    block_states: Vec<i64>,
+}
+
+impl TryFrom<RawSnapshot> for RegionSnapshot {
+    type Error = RegionError;
+
+    fn try_from(raw: RawSnapshot) -> Result<Self, Self::Error> {
+        validate_block_data(raw.size, &raw.palette, &raw.block_states)?;
+
+        Ok(Self {
+            size: raw.size,
+            palette: raw.palette,
+            block_states: raw.block_states,
+        })
+    }
+}
+
+impl RegionSnapshot {
+    /// Returns the size of the snapshotted region.
+    pub fn get_size(&self) -> Coordinates {
+        self.size
+    }
+
+    /// Returns the block state palette of the snapshot as a slice.
+    pub fn get_palette(&self) -> &[BlockState] {
+        &self.palette
+    }
+
+    /// Returns a reference to the `BlockState` at the given `position`.
+    ///
+    /// Uses the same lookup as `Region::get_block`, so the result is identical to querying the original region.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position of the block relative to the region origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the snapshot.
+    pub fn get_block(&self, position: impl Into<Coordinates>) -> &BlockState {
+        let block_index = index_3d(self.size, position.into());
+
+        let required_bits = Region::calc_required_bits(&self.palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let palette_index =
+            read_palette_index(&self.block_states, block_index, required_bits, bitmask);
+
+        &self.palette[palette_index as usize]
+    }
+}
+
+impl Region {
+    /// Creates a `RegionSnapshot` containing the size, palette and packed block data of this region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    /// let snapshot = region.snapshot();
+    ///
+    /// assert_eq!(snapshot.get_block((2, 4, 2)), region.get_block((2, 4, 2)));
+    /// ```
+    pub fn snapshot(&self) -> RegionSnapshot {
+        RegionSnapshot {
+            size: self.size,
+            palette: self.block_state_palette.clone(),
+            block_states: self.block_states.clone(),
+        }
+    }
+}
+
+impl From<&Region> for RegionSnapshot {
+    fn from(region: &Region) -> Self {
+        region.snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn snapshot_matches_region() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+        let snapshot = region.snapshot();
+
+        assert_eq!(snapshot.get_size(), region.size);
+        assert_eq!(snapshot.get_block((0, 2, 0)), region.get_block((0, 2, 0)));
+        assert_eq!(snapshot.get_block((2, 4, 2)), region.get_block((2, 4, 2)));
+    }
+
+    #[test]
+    fn snapshot_json_round_trip() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let snapshot = file.get_region("test").unwrap().snapshot();
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: RegionSnapshot = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, snapshot);
+        assert_eq!(
+            parsed.get_block((2, 4, 2)).get_name().to_string(),
+            "minecraft:powered_rail"
+        );
+    }
+
+    #[test]
+    fn reject_corrupt_snapshot() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let snapshot = file.get_region("test").unwrap().snapshot();
+
+        let mut json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+        json["BlockStates"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<RegionSnapshot>(json).is_err());
+
+        let mut json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+        json["Palette"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<RegionSnapshot>(json).is_err());
+
+        let mut json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+        json["BlockStates"] = serde_json::json!(vec![-1i64; snapshot.block_states.len()]);
+        assert!(serde_json::from_value::<RegionSnapshot>(json).is_err());
+
+        // the volume overflows instead of wrapping to 0
+        let mut json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+        json["Size"] = serde_json::json!({ "x": 1 << 21, "y": 1 << 21, "z": 1 << 22 });
+        json["BlockStates"] = serde_json::json!([]);
+        let err = serde_json::from_value::<RegionSnapshot>(json).unwrap_err();
+        assert!(err.to_string().contains("too large"));
+    }
+}
@@ -0,0 +1,764 @@
+use crate::analysis::update_order::Direction;
+use crate::block::BlockStatePattern;
+use crate::region::read_palette_index;
+use crate::structure::{BlockState, Coordinates, Region};
+use crate::vector::Vec3;
+
+/// The most positions a node of a `SpatialIndex` holds before it is split into octants.
+const LEAF_CAPACITY: usize = 16;
+
+/// A block hit by a ray, e.g. by `Region::raycast()` or `SpatialIndex::raycast()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// The position of the block relative to the region origin.
+    pub position: Coordinates,
+
+    /// The distance from the origin of the ray to the point where it enters the block.
+    pub distance: f64,
+
+    /// The face of the block the ray enters through, e.g. `Direction::Up` for a ray coming from above.
+    pub face: Direction,
+}
+
+/// An octree over the positions of the blocks of a region that match a pattern, created by `Region::spatial_index()`.
+///
+/// It answers nearest neighbor, radius and raycast queries without scanning the whole region, e.g. for viewers and
+/// bots. Points are in the coordinates of the region, where the block at `(x, y, z)` spans from `(x, y, z)` to
+/// `(x + 1, y + 1, z + 1)`. The index doesn't change with the region, so it has to be built again after editing it.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    root: Node,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    min: Coordinates,
+    /// The edge length of the cube covered by the node, always a power of two.
+    size: i32,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Leaf(Vec<Coordinates>),
+    Branch(Vec<Node>),
+}
+
+impl SpatialIndex {
+    /// Returns the position of the matching block whose center is closest to `point`, or `None` if there is none.
+    ///
+    /// Of several equally close blocks, any one may be returned.
+    pub fn nearest(&self, point: impl Into<Vec3>) -> Option<Coordinates> {
+        let point = point.into();
+        let mut best = None;
+
+        self.root.nearest(point, &mut best);
+
+        best.map(|(position, _)| position)
+    }
+
+    /// Returns the positions of all matching blocks whose centers are at most `radius` away from `point`, in YZX order.
+    pub fn within_radius(&self, point: impl Into<Vec3>, radius: f64) -> Vec<Coordinates> {
+        let point = point.into();
+        let mut found = Vec::new();
+
+        self.root.within_radius(point, radius * radius, &mut found);
+
+        found.sort_unstable_by_key(|position| (position.y, position.z, position.x));
+        found
+    }
+
+    /// Casts a ray and returns the first matching block it enters, or `None` if it doesn't hit one within
+    /// `max_distance`.
+    ///
+    /// A ray that starts inside of a matching block hits it at distance 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The start of the ray.
+    /// * `direction` - The direction of the ray, which doesn't have to be normalized.
+    /// * `max_distance` - The maximum distance from `origin` to the hit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `direction` has a length of zero.
+    pub fn raycast(
+        &self,
+        origin: impl Into<Vec3>,
+        direction: impl Into<Vec3>,
+        max_distance: f64,
+    ) -> Option<RayHit> {
+        let origin = origin.into();
+        let direction = normalize(direction.into());
+
+        let mut best = None;
+        self.root
+            .raycast(origin, direction, max_distance, &mut best);
+
+        best
+    }
+
+    /// Returns the number of positions in the index.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no block matched the pattern.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Node {
+    fn build(min: Coordinates, size: i32, positions: Vec<Coordinates>) -> Node {
+        if positions.len() <= LEAF_CAPACITY || size == 1 {
+            return Node {
+                min,
+                size,
+                kind: NodeKind::Leaf(positions),
+            };
+        }
+
+        let half = size / 2;
+        let mut octants = vec![Vec::new(); 8];
+
+        for position in positions {
+            let octant = (position.x >= min.x + half) as usize
+                | ((position.y >= min.y + half) as usize) << 1
+                | ((position.z >= min.z + half) as usize) << 2;
+
+            octants[octant].push(position);
+        }
+
+        let children = octants
+            .into_iter()
+            .enumerate()
+            .filter(|(_, positions)| !positions.is_empty())
+            .map(|(octant, positions)| {
+                let min = Coordinates {
+                    x: min.x + half * (octant & 1) as i32,
+                    y: min.y + half * (octant >> 1 & 1) as i32,
+                    z: min.z + half * (octant >> 2 & 1) as i32,
+                };
+
+                Node::build(min, half, positions)
+            })
+            .collect();
+
+        Node {
+            min,
+            size,
+            kind: NodeKind::Branch(children),
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let min = Vec3::from(self.min);
+        let size = self.size as f64;
+
+        (min, min + Vec3::new(size, size, size))
+    }
+
+    /// Returns the squared distance from `point` to the closest point of the cube covered by the node.
+    fn distance_squared(&self, point: Vec3) -> f64 {
+        let (min, max) = self.bounds();
+        let axis = |value: f64, min: f64, max: f64| (min - value).max(value - max).max(0.0);
+
+        let (x, y, z) = (
+            axis(point.x, min.x, max.x),
+            axis(point.y, min.y, max.y),
+            axis(point.z, min.z, max.z),
+        );
+
+        x * x + y * y + z * z
+    }
+
+    fn nearest(&self, point: Vec3, best: &mut Option<(Coordinates, f64)>) {
+        if best.is_some_and(|(_, distance)| self.distance_squared(point) >= distance) {
+            return;
+        }
+
+        match &self.kind {
+            NodeKind::Leaf(positions) => {
+                for position in positions {
+                    let distance = center_distance_squared(*position, point);
+
+                    if best.is_none_or(|(_, best)| distance < best) {
+                        *best = Some((*position, distance));
+                    }
+                }
+            }
+            NodeKind::Branch(children) => {
+                let mut children = children
+                    .iter()
+                    .map(|child| (child.distance_squared(point), child))
+                    .collect::<Vec<_>>();
+                children.sort_unstable_by(|(a, _), (b, _)| a.total_cmp(b));
+
+                for (_, child) in children {
+                    child.nearest(point, best);
+                }
+            }
+        }
+    }
+
+    fn within_radius(&self, point: Vec3, radius_squared: f64, found: &mut Vec<Coordinates>) {
+        if self.distance_squared(point) > radius_squared {
+            return;
+        }
+
+        match &self.kind {
+            NodeKind::Leaf(positions) => {
+                found.extend(positions.iter().filter(|position| {
+                    center_distance_squared(**position, point) <= radius_squared
+                }))
+            }
+            NodeKind::Branch(children) => {
+                for child in children {
+                    child.within_radius(point, radius_squared, found);
+                }
+            }
+        }
+    }
+
+    fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f64, best: &mut Option<RayHit>) {
+        let (min, max) = self.bounds();
+
+        let Some((distance, _)) = ray_box(origin, direction, min, max) else {
+            return;
+        };
+
+        if distance > max_distance || best.is_some_and(|best| distance >= best.distance) {
+            return;
+        }
+
+        match &self.kind {
+            NodeKind::Leaf(positions) => {
+                for position in positions {
+                    let min = Vec3::from(*position);
+
+                    if let Some((distance, face)) =
+                        ray_box(origin, direction, min, min + Vec3::new(1.0, 1.0, 1.0))
+                    {
+                        if distance <= max_distance
+                            && best.is_none_or(|best| distance < best.distance)
+                        {
+                            *best = Some(RayHit {
+                                position: *position,
+                                distance,
+                                face,
+                            });
+                        }
+                    }
+                }
+            }
+            NodeKind::Branch(children) => {
+                let mut children = children
+                    .iter()
+                    .filter_map(|child| {
+                        let (min, max) = child.bounds();
+                        Some((ray_box(origin, direction, min, max)?.0, child))
+                    })
+                    .collect::<Vec<_>>();
+                children.sort_unstable_by(|(a, _), (b, _)| a.total_cmp(b));
+
+                for (_, child) in children {
+                    child.raycast(origin, direction, max_distance, best);
+                }
+            }
+        }
+    }
+}
+
+impl Region {
+    /// Builds a `SpatialIndex` over the positions of the blocks that match `pattern`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockState, LitematicaFile};
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let is_hopper = |block: &BlockState| block.get_name().get_path() == "hopper";
+    /// let hoppers = region.spatial_index(&is_hopper);
+    ///
+    /// let closest = hoppers.nearest([15.5, 4.0, 14.5]);
+    /// let nearby = hoppers.within_radius([15.5, 4.0, 14.5], 8.0);
+    /// let looked_at = hoppers.raycast([0.5, 10.0, 0.5], [1.0, -0.5, 1.0], 64.0);
+    /// ```
+    pub fn spatial_index(&self, pattern: &impl BlockStatePattern) -> SpatialIndex {
+        let positions = self.find_block_positions(pattern).collect::<Vec<_>>();
+        let len = positions.len();
+
+        let extent = self
+            .size
+            .x
+            .abs()
+            .max(self.size.y.abs())
+            .max(self.size.z.abs())
+            .max(1) as u32;
+
+        SpatialIndex {
+            root: Node::build(
+                Coordinates::default(),
+                extent.next_power_of_two() as i32,
+                positions,
+            ),
+            len,
+        }
+    }
+
+    /// Casts a ray through the region and returns the first non-air block it enters, e.g. for click-to-select in
+    /// viewers. Returns `None` if it doesn't hit one within `max_distance`.
+    ///
+    /// The ray steps through the blocks one by one, so nothing has to be built first, unlike for
+    /// `SpatialIndex::raycast()`. The origin may be outside of the region. A ray that starts inside of a non-air block
+    /// hits it at distance 0. A region with a size of 0 is never hit.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The start of the ray, relative to `min_corner()` like the block positions of the region, so for
+    ///   regions with negative sizes it is not the region `position`.
+    /// * `direction` - The direction of the ray, which doesn't have to be normalized.
+    /// * `max_distance` - The maximum distance from `origin` to the hit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `direction` has a length of zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::vector::Rotation2;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let camera = [15.5, 20.0, -10.0];
+    /// let looking = Rotation2::new(0.0, 45.0).direction();
+    ///
+    /// if let Some(hit) = region.raycast(camera, looking, 128.0) {
+    ///     println!("Selected {} through its {} face", region.get_block(hit.position), hit.face.name());
+    /// }
+    /// ```
+    pub fn raycast(
+        &self,
+        origin: impl Into<Vec3>,
+        direction: impl Into<Vec3>,
+        max_distance: f64,
+    ) -> Option<RayHit> {
+        let is_solid = |block: &BlockState| !block.is_air();
+
+        self.raycast_matching(origin, direction, max_distance, &is_solid)
+    }
+
+    /// Casts a ray through the region and returns the first block it enters that matches `pattern`, see `raycast()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `direction` has a length of zero.
+    pub fn raycast_matching(
+        &self,
+        origin: impl Into<Vec3>,
+        direction: impl Into<Vec3>,
+        max_distance: f64,
+        pattern: &impl BlockStatePattern,
+    ) -> Option<RayHit> {
+        let origin = origin.into();
+        let direction = normalize(direction.into());
+
+        let matching = self
+            .block_state_palette
+            .iter()
+            .map(|block| pattern.matches(block))
+            .collect::<Vec<_>>();
+
+        if self.calc_volume() == 0 || !matching.contains(&true) {
+            return None;
+        }
+
+        let size = [self.size.x.abs(), self.size.y.abs(), self.size.z.abs()];
+        let (start, region_face) = ray_box(
+            origin,
+            direction,
+            Vec3::default(),
+            Vec3::new(size[0] as f64, size[1] as f64, size[2] as f64),
+        )?;
+
+        if start > max_distance {
+            return None;
+        }
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let entry = origin + direction * start;
+        let entry = [entry.x, entry.y, entry.z];
+        let direction = [direction.x, direction.y, direction.z];
+        let faces = [
+            (Direction::West, Direction::East),
+            (Direction::Down, Direction::Up),
+            (Direction::North, Direction::South),
+        ];
+
+        // the block containing the entry point, which can be just outside of the region on its far faces
+        let mut voxel = [0; 3];
+        let mut next = [f64::INFINITY; 3];
+        let mut delta = [f64::INFINITY; 3];
+
+        for axis in 0..3 {
+            voxel[axis] = (entry[axis].floor() as i32).clamp(0, size[axis] - 1);
+
+            if direction[axis] != 0.0 {
+                let boundary = voxel[axis] + (direction[axis] > 0.0) as i32;
+
+                delta[axis] = 1.0 / direction[axis].abs();
+                next[axis] = start + (boundary as f64 - entry[axis]) / direction[axis];
+            }
+        }
+
+        let to_vec = |voxel: [i32; 3]| Vec3::new(voxel[0] as f64, voxel[1] as f64, voxel[2] as f64);
+        let face = ray_box(
+            origin,
+            Vec3::new(direction[0], direction[1], direction[2]),
+            to_vec(voxel),
+            to_vec(voxel) + Vec3::new(1.0, 1.0, 1.0),
+        )
+        .map_or(region_face, |(_, face)| face);
+
+        let mut hit = RayHit {
+            position: Coordinates::default(),
+            distance: start,
+            face,
+        };
+
+        loop {
+            hit.position = Coordinates {
+                x: voxel[0],
+                y: voxel[1],
+                z: voxel[2],
+            };
+
+            let palette_index = read_palette_index(
+                &self.block_states,
+                self.get_3d_index(hit.position),
+                required_bits,
+                bitmask,
+            );
+
+            if matching[palette_index as usize] {
+                return Some(hit);
+            }
+
+            let axis = (0..3)
+                .min_by(|a, b| next[*a].total_cmp(&next[*b]))
+                .expect("there are three axes");
+
+            if next[axis] > max_distance {
+                return None;
+            }
+
+            let step = if direction[axis] > 0.0 { 1 } else { -1 };
+            voxel[axis] += step;
+
+            if !(0..size[axis]).contains(&voxel[axis]) {
+                return None;
+            }
+
+            hit.distance = next[axis];
+            hit.face = if step > 0 {
+                faces[axis].0
+            } else {
+                faces[axis].1
+            };
+            next[axis] += delta[axis];
+        }
+    }
+}
+
+/// Intersects a ray with a box, returning the distance along the ray at which it enters the box and the face of the
+/// box it enters through. Rays starting inside of the box enter it at distance 0.
+pub(crate) fn ray_box(
+    origin: Vec3,
+    direction: Vec3,
+    min: Vec3,
+    max: Vec3,
+) -> Option<(f64, Direction)> {
+    let axes = [
+        (
+            origin.x,
+            direction.x,
+            min.x,
+            max.x,
+            Direction::West,
+            Direction::East,
+        ),
+        (
+            origin.y,
+            direction.y,
+            min.y,
+            max.y,
+            Direction::Down,
+            Direction::Up,
+        ),
+        (
+            origin.z,
+            direction.z,
+            min.z,
+            max.z,
+            Direction::North,
+            Direction::South,
+        ),
+    ];
+
+    let mut enter = f64::NEG_INFINITY;
+    let mut exit = f64::INFINITY;
+    let mut face = Direction::Down;
+
+    for (origin, direction, min, max, low_face, high_face) in axes {
+        if direction == 0.0 {
+            if origin < min || origin > max {
+                return None;
+            }
+
+            continue;
+        }
+
+        let (near, far, near_face) = if direction > 0.0 {
+            (
+                (min - origin) / direction,
+                (max - origin) / direction,
+                low_face,
+            )
+        } else {
+            (
+                (max - origin) / direction,
+                (min - origin) / direction,
+                high_face,
+            )
+        };
+
+        if near > enter {
+            enter = near;
+            face = near_face;
+        }
+        exit = exit.min(far);
+    }
+
+    (enter <= exit && exit >= 0.0).then(|| (enter.max(0.0), face))
+}
+
+pub(crate) fn normalize(direction: Vec3) -> Vec3 {
+    let length = direction.length();
+
+    assert!(length > 0.0, "The direction of a ray must not be zero");
+
+    direction * (1.0 / length)
+}
+
+fn center_distance_squared(position: Coordinates, point: Vec3) -> f64 {
+    let offset = Vec3::from(position) + Vec3::new(0.5, 0.5, 0.5) - point;
+
+    offset.x * offset.x + offset.y * offset.y + offset.z * offset.z
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::{BlockState, LitematicaFile};
+
+    use super::*;
+
+    #[test]
+    fn nearest_and_within_radius() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let is_solid = |block: &BlockState| !block.is_air();
+        let index = region.spatial_index(&is_solid);
+        let positions = region.find_block_positions(&is_solid).collect::<Vec<_>>();
+
+        assert_eq!(index.len(), positions.len());
+
+        for point in [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(15.2, 4.7, 14.1),
+            Vec3::new(-20.0, 30.0, 50.0),
+        ] {
+            let nearest = index.nearest(point).unwrap();
+            let closest = positions
+                .iter()
+                .map(|p| center_distance_squared(*p, point))
+                .fold(f64::INFINITY, f64::min);
+
+            assert_eq!(center_distance_squared(nearest, point), closest);
+
+            let expected = positions
+                .iter()
+                .filter(|p| center_distance_squared(**p, point) <= 36.0)
+                .copied()
+                .collect::<Vec<_>>();
+
+            assert_eq!(index.within_radius(point, 6.0), expected);
+        }
+
+        let stone = BlockStateBuilder::new("stone").build();
+        let empty = region.spatial_index(&stone);
+
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest([0.0, 0.0, 0.0]), None);
+        assert!(empty
+            .raycast([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 100.0)
+            .is_none());
+    }
+
+    #[test]
+    fn region_raycast() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let glass = BlockStateBuilder::new("glass").build();
+
+        let mut region = Region::new((40, 40, 40));
+        for x in 0..40 {
+            region.set_block((x, 0, x), stone.clone());
+        }
+        region.set_block((10, 20, 10), glass.clone());
+
+        let hit = region
+            .raycast([10.5, 50.0, 10.5], [0.0, -1.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(
+            hit.position,
+            Coordinates {
+                x: 10,
+                y: 20,
+                z: 10
+            }
+        );
+        assert_eq!(hit.distance, 29.0);
+        assert_eq!(hit.face, Direction::Up);
+
+        let hit = region
+            .raycast_matching([10.5, 50.0, 10.5], [0.0, -1.0, 0.0], 100.0, &stone)
+            .unwrap();
+        assert_eq!(hit.position, Coordinates { x: 10, y: 0, z: 10 });
+
+        assert!(region
+            .raycast([10.5, 50.0, 10.5], [0.0, -1.0, 0.0], 28.0)
+            .is_none());
+        assert!(region
+            .raycast([10.5, 50.0, 10.5], [0.0, 1.0, 0.0], 100.0)
+            .is_none());
+
+        // a ray entering the region through its far side
+        let hit = region
+            .raycast([45.0, 0.5, 30.5], [-1.0, 0.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(hit.position, Coordinates { x: 30, y: 0, z: 30 });
+        assert_eq!(hit.face, Direction::East);
+        assert_eq!(hit.distance, 14.0);
+
+        let hit = region
+            .raycast([3.5, 0.5, 3.5], [1.0, 0.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(hit.position, Coordinates { x: 3, y: 0, z: 3 });
+        assert_eq!(hit.distance, 0.0);
+
+        // the same hits as the spatial index for random rays
+        let solid = region.spatial_index(&|block: &BlockState| !block.is_air());
+
+        for i in 0..200 {
+            let angle = i as f64 * 0.37;
+            let origin = Vec3::new(
+                20.0 + 30.0 * angle.cos(),
+                10.0 + (i % 30) as f64,
+                20.0 + 30.0 * angle.sin(),
+            );
+            let direction = Vec3::new(20.5, (i % 7) as f64, 20.5) - origin;
+
+            let expected = solid.raycast(origin, direction, 80.0);
+            let hit = region.raycast(origin, direction, 80.0);
+
+            assert_eq!(
+                hit.map(|hit| hit.position),
+                expected.map(|hit| hit.position)
+            );
+
+            if let (Some(hit), Some(expected)) = (hit, expected) {
+                assert!((hit.distance - expected.distance).abs() < 1e-9);
+                assert_eq!(hit.face, expected.face);
+            }
+        }
+    }
+
+    #[test]
+    fn region_raycast_edge_sizes() {
+        let air = BlockStateBuilder::new("air").build();
+        let stone = BlockStateBuilder::new("stone").build();
+
+        let empty = Region::new((0, 4, 4));
+        assert!(empty
+            .raycast_matching([-1.0, 0.5, 0.5], [1.0, 0.0, 0.0], 100.0, &air)
+            .is_none());
+
+        // the origin is relative to the min corner, which is the far end of a region with a negative size
+        let mut region = Region::new((-4, 1, 1));
+        region.set_block((0, 0, 0), stone);
+        assert_eq!(region.min_corner().x, -3);
+
+        let hit = region
+            .raycast([-1.0, 0.5, 0.5], [1.0, 0.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(hit.position, Coordinates { x: 0, y: 0, z: 0 });
+        assert_eq!(hit.distance, 1.0);
+    }
+
+    #[test]
+    fn raycast() {
+        let stone = BlockStateBuilder::new("stone").build();
+
+        let mut region = Region::new((40, 40, 40));
+        for x in 0..40 {
+            region.set_block((x, 0, x), stone.clone());
+        }
+        region.set_block((10, 20, 10), stone.clone());
+
+        let index = region.spatial_index(&stone);
+
+        let hit = index
+            .raycast([10.5, 30.0, 10.5], [0.0, -1.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(
+            hit.position,
+            Coordinates {
+                x: 10,
+                y: 20,
+                z: 10
+            }
+        );
+        assert_eq!(hit.distance, 9.0);
+        assert_eq!(hit.face, Direction::Up);
+
+        // too short to reach the block
+        assert!(index
+            .raycast([10.5, 30.0, 10.5], [0.0, -1.0, 0.0], 8.0)
+            .is_none());
+
+        let hit = index
+            .raycast([-5.0, 0.5, 30.5], [1.0, 0.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(hit.position, Coordinates { x: 30, y: 0, z: 30 });
+        assert_eq!(hit.face, Direction::West);
+
+        let hit = index
+            .raycast([3.5, 0.5, 3.5], [1.0, 0.0, 0.0], 100.0)
+            .unwrap();
+        assert_eq!(hit.position, Coordinates { x: 3, y: 0, z: 3 });
+        assert_eq!(hit.distance, 0.0);
+
+        assert!(index
+            .raycast([0.5, 5.0, 0.5], [0.0, 1.0, 0.0], 100.0)
+            .is_none());
+    }
+}
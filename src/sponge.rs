@@ -0,0 +1,775 @@
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{self, Read, Write};
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use nbt::Value;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::region::{read_palette_index, value_position};
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+/// A Sponge schematic (`.schem`) of version 2 or 3, the format used by WorldEdit and many other tools.
+///
+/// A Sponge schematic holds a single box of blocks, stored here as a `Region` whose `position` is the `Offset` of the
+/// schematic, i.e. the position of its minimum corner relative to the origin it was copied from. This is the same
+/// meaning the position of a region has in a `Litematica` file, so regions can be moved between the formats as they are.
+///
+/// Block entities are converted between the `Pos` and `Id` fields of Sponge and the `x`, `y`, `z` and `id` fields of
+/// `Litematica`. Entities, biomes and scheduled ticks are not converted.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::sponge::SpongeSchematic;
+///
+/// let schem = SpongeSchematic::read("castle.schem").unwrap();
+/// LitematicaFile::from_sponge(schem, "castle").write("castle.litematic").unwrap();
+///
+/// let file = LitematicaFile::read("test.litematic").unwrap();
+/// file.to_sponge(3).unwrap().write("test.schem").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpongeSchematic {
+    /// The version of the Sponge format, `2` or `3`.
+    pub version: i32,
+
+    /// The data version of the Minecraft version the schematic was created with.
+    pub data_version: i32,
+
+    /// The blocks and block entities of the schematic, positioned at its offset.
+    pub region: Region,
+}
+
+impl SpongeSchematic {
+    /// Creates a new `SpongeSchematic` from a copy of `region`, to be written in the given version of the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is not `2` or `3`, or if the region is larger than 65535 blocks along any axis.
+    pub fn from_region(region: &Region, version: i32, data_version: i32) -> Result<Self> {
+        check_version(version)?;
+        dimensions(region)?;
+
+        Ok(Self {
+            version,
+            data_version,
+            region: region.clone(),
+        })
+    }
+
+    /// Reads a gzip-compressed Sponge schematic of version 2 or 3.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid schematic of a supported version.
+    #[cfg(feature = "fs")]
+    pub fn read(path: impl AsRef<Path>) -> Result<SpongeSchematic> {
+        let file = File::open(path)?;
+        Self::read_from(BufReader::new(file))
+    }
+
+    /// Reads a gzip-compressed Sponge schematic of version 2 or 3 from a reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be read or is not a valid schematic of a supported version.
+    pub fn read_from(reader: impl Read) -> Result<SpongeSchematic> {
+        let mut bytes = Vec::new();
+        GzDecoder::new(reader).read_to_end(&mut bytes)?;
+
+        // version 3 nests everything in a `Schematic` compound, version 2 stores it in the root
+        let probe: Probe = nbt::from_reader(&bytes[..])?;
+
+        let (version, data_version, size, offset, blocks) = if probe.schematic.is_some() {
+            let root: ReadRootV3 = nbt::from_reader(&bytes[..])?;
+            let schematic = root.schematic;
+            let blocks = schematic.blocks.unwrap_or_default();
+
+            let block_entities = blocks
+                .block_entities
+                .into_iter()
+                .map(|mut block_entity| {
+                    block_entity
+                        .data
+                        .insert("Pos".to_string(), Value::IntArray(block_entity.pos));
+                    block_entity
+                        .data
+                        .insert("Id".to_string(), Value::String(block_entity.id));
+                    block_entity.data
+                })
+                .collect();
+
+            (
+                schematic.version,
+                schematic.data_version,
+                [schematic.width, schematic.height, schematic.length],
+                schematic.offset,
+                ReadBlocks {
+                    palette: blocks.palette,
+                    data: blocks.data,
+                    block_entities,
+                },
+            )
+        } else {
+            let schematic: ReadV2 = nbt::from_reader(&bytes[..])?;
+
+            (
+                schematic.version,
+                schematic.data_version,
+                [schematic.width, schematic.height, schematic.length],
+                schematic.offset,
+                ReadBlocks {
+                    palette: schematic.palette,
+                    data: schematic.block_data,
+                    block_entities: schematic.block_entities,
+                },
+            )
+        };
+
+        check_version(version)?;
+
+        let [width, height, length] = size.map(|size| size as u16 as i32);
+        let position = match offset[..] {
+            [x, y, z] => Coordinates { x, y, z },
+            [] => Coordinates::default(),
+            _ => return Err(invalid_data("invalid offset")),
+        };
+
+        let region = read_region(
+            position,
+            Coordinates {
+                x: width,
+                y: height,
+                z: length,
+            },
+            blocks,
+        )?;
+
+        Ok(SpongeSchematic {
+            version,
+            data_version,
+            region,
+        })
+    }
+
+    /// Writes the schematic as a gzip-compressed Sponge schematic in its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created, the version is not supported or the region is too large.
+    #[cfg(feature = "fs")]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        let mut buf_writer = BufWriter::new(file);
+
+        self.write_to(&mut buf_writer)?;
+        buf_writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the schematic as a gzip-compressed Sponge schematic in its version to a writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be written, the version is not supported or the region is too large.
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<()> {
+        write_sponge(writer, &self.region, self.version, self.data_version)
+    }
+}
+
+impl LitematicaFile {
+    /// Reads a Sponge schematic (`.schem`) of version 2 or 3 and converts it into a `Litematica` file with a single
+    /// region, named after the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid schematic.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read_schem("castle.schem").unwrap();
+    /// file.write("castle.litematic").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn read_schem(path: impl AsRef<Path>) -> Result<LitematicaFile> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Self::from_sponge(SpongeSchematic::read(path)?, name))
+    }
+
+    /// Converts a `SpongeSchematic` into a `Litematica` file with a single region called `name`.
+    pub fn from_sponge(schematic: SpongeSchematic, name: impl Into<String>) -> LitematicaFile {
+        LitematicaFile::from_region(name, schematic.region, schematic.data_version)
+    }
+
+    /// Converts the file into a `SpongeSchematic` of the given version.
+    ///
+    /// Since a Sponge schematic only holds a single box of blocks, all regions are combined into their enclosing box.
+    /// Where regions overlap, the first non-air block by region name is used. Entities are not converted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is not `2` or `3`, or if the enclosing box is larger than 65535 blocks along
+    /// any axis.
+    pub fn to_sponge(&self, version: i32) -> Result<SpongeSchematic> {
+        check_version(version)?;
+
+        let region = self.combined_region();
+        dimensions(&region)?;
+
+        Ok(SpongeSchematic {
+            version,
+            data_version: self.minecraft_data_version,
+            region,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct Probe {
+    #[serde(rename = "Schematic")]
+    #[serde(default)]
+    schematic: Option<IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReadV2 {
+    version: i32,
+    data_version: i32,
+    width: i16,
+    height: i16,
+    length: i16,
+
+    #[serde(default)]
+    offset: Vec<i32>,
+
+    palette: HashMap<String, i32>,
+    block_data: Vec<i8>,
+
+    #[serde(default)]
+    block_entities: Vec<HashMap<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct ReadRootV3 {
+    #[serde(rename = "Schematic")]
+    schematic: ReadV3,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReadV3 {
+    version: i32,
+    data_version: i32,
+    width: i16,
+    height: i16,
+    length: i16,
+
+    #[serde(default)]
+    offset: Vec<i32>,
+
+    #[serde(default)]
+    blocks: Option<ReadBlocksV3>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReadBlocksV3 {
+    palette: HashMap<String, i32>,
+    data: Vec<i8>,
+
+    #[serde(default)]
+    block_entities: Vec<ReadBlockEntityV3>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReadBlockEntityV3 {
+    pos: Vec<i32>,
+    id: String,
+
+    #[serde(default)]
+    data: HashMap<String, Value>,
+}
+
+/// The block data shared by both versions, with block entities in the flattened version 2 layout.
+struct ReadBlocks {
+    palette: HashMap<String, i32>,
+    data: Vec<i8>,
+    block_entities: Vec<HashMap<String, Value>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct WriteV2 {
+    version: i32,
+    data_version: i32,
+    width: i16,
+    height: i16,
+    length: i16,
+
+    #[serde(serialize_with = "nbt::i32_array")]
+    offset: Vec<i32>,
+
+    palette_max: i32,
+    palette: HashMap<String, i32>,
+
+    #[serde(serialize_with = "nbt::i8_array")]
+    block_data: Vec<i8>,
+
+    block_entities: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct WriteRootV3 {
+    #[serde(rename = "Schematic")]
+    schematic: WriteV3,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct WriteV3 {
+    version: i32,
+    data_version: i32,
+    width: i16,
+    height: i16,
+    length: i16,
+
+    #[serde(serialize_with = "nbt::i32_array")]
+    offset: Vec<i32>,
+
+    blocks: WriteBlocksV3,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct WriteBlocksV3 {
+    palette: HashMap<String, i32>,
+
+    #[serde(serialize_with = "nbt::i8_array")]
+    data: Vec<i8>,
+
+    block_entities: Vec<Value>,
+}
+
+/// Writes a single region as a gzip-compressed Sponge schematic of the given version.
+pub(crate) fn write_sponge(
+    writer: &mut impl Write,
+    region: &Region,
+    version: i32,
+    data_version: i32,
+) -> Result<()> {
+    check_version(version)?;
+
+    let [width, height, length] = dimensions(region)?;
+
+    // maps litematica palette indices to sponge palette ids, merging duplicate entries
+    let mut palette = HashMap::new();
+    let mut palette_ids = Vec::with_capacity(region.block_state_palette.len());
+
+    for block_state in &region.block_state_palette {
+        let next_id = palette.len() as i32;
+        let id = *palette.entry(block_state.canonical()).or_insert(next_id);
+        palette_ids.push(id);
+    }
+
+    let required_bits = Region::calc_required_bits(&region.block_state_palette);
+    let bitmask = (1 << required_bits) - 1;
+    let volume = region.calc_volume();
+
+    let mut block_data = Vec::with_capacity(volume as usize);
+
+    // sponge uses the same YZX order as litematica, so the indices can be copied directly
+    for block_index in 0..volume {
+        let palette_index =
+            read_palette_index(&region.block_states, block_index, required_bits, bitmask);
+        write_varint(&mut block_data, palette_ids[palette_index as usize] as u32);
+    }
+
+    let block_entities = region
+        .tile_entities
+        .iter()
+        .filter_map(to_sponge_block_entity);
+
+    let palette = palette
+        .into_iter()
+        .map(|(block_state, id)| (block_state.to_string(), id))
+        .collect::<HashMap<_, _>>();
+
+    let min_corner = region.min_corner();
+    let offset = vec![min_corner.x, min_corner.y, min_corner.z];
+
+    if version == 2 {
+        let schematic = WriteV2 {
+            version,
+            data_version,
+            width,
+            height,
+            length,
+            offset,
+            palette_max: palette.len() as i32,
+            palette,
+            block_data,
+            block_entities: block_entities.map(Value::Compound).collect(),
+        };
+
+        nbt::to_gzip_writer(writer, &schematic, Some("Schematic"))?;
+    } else {
+        let block_entities = block_entities
+            .map(|mut block_entity| {
+                let pos = block_entity.remove("Pos").expect("position was added");
+                let id = block_entity.remove("Id");
+
+                let mut compound = HashMap::from([("Pos".to_string(), pos)]);
+                compound.extend(id.map(|id| ("Id".to_string(), id)));
+                compound.insert("Data".to_string(), Value::Compound(block_entity));
+
+                Value::Compound(compound)
+            })
+            .collect();
+
+        let schematic = WriteRootV3 {
+            schematic: WriteV3 {
+                version,
+                data_version,
+                width,
+                height,
+                length,
+                offset,
+                blocks: WriteBlocksV3 {
+                    palette,
+                    data: block_data,
+                    block_entities,
+                },
+            },
+        };
+
+        nbt::to_gzip_writer(writer, &schematic, Some(""))?;
+    }
+
+    Ok(())
+}
+
+/// Decodes the palette, block data and block entities of a schematic into a region.
+fn read_region(position: Coordinates, size: Coordinates, blocks: ReadBlocks) -> Result<Region> {
+    let mut palette: BTreeMap<i32, BlockState> = BTreeMap::new();
+
+    for (block_state, id) in blocks.palette {
+        let block_state = BlockState::parse(&block_state)
+            .map_err(|_| invalid_data(&format!("invalid block state {}", block_state)))?;
+        palette.insert(id, block_state);
+    }
+
+    let volume = size.x as u64 * size.y as u64 * size.z as u64;
+
+    // every block takes at least one varint byte, so this rejects bogus sizes before allocating the region
+    if (blocks.data.len() as u64) < volume {
+        return Err(invalid_data("block data is shorter than the schematic"));
+    }
+
+    let mut region = Region::filled_with_air(position, size);
+
+    let mut data = blocks.data.iter().map(|byte| *byte as u8);
+    let mut index = 0;
+
+    while index < volume {
+        let id = read_varint(&mut data)?;
+        let block_state = palette
+            .get(&(id as i32))
+            .ok_or_else(|| invalid_data("block data refers to a missing palette entry"))?;
+
+        if !block_state.is_air() {
+            let y = index / (size.x as u64 * size.z as u64);
+            let z = index / size.x as u64 % size.z as u64;
+            let x = index % size.x as u64;
+
+            region.set_block((x as i32, y as i32, z as i32), block_state.clone());
+        }
+
+        index += 1;
+    }
+
+    if data.next().is_some() {
+        return Err(invalid_data("block data is longer than the schematic"));
+    }
+
+    region.tile_entities = blocks
+        .block_entities
+        .into_iter()
+        .filter_map(from_sponge_block_entity)
+        .filter(|tile_entity| value_position(tile_entity).is_some_and(|pos| region.contains(pos)))
+        .collect();
+
+    Ok(region)
+}
+
+/// Converts a litematica tile entity with `x`, `y` and `z` fields into a sponge block entity with `Pos` and `Id` fields.
+fn to_sponge_block_entity(tile_entity: &Value) -> Option<HashMap<String, Value>> {
+    let Value::Compound(compound) = tile_entity else {
+        return None;
+    };
+
+    let mut compound = compound.clone();
+
+    let mut coordinate = |key: &str| match compound.remove(key) {
+        Some(Value::Int(value)) => Some(value),
+        _ => None,
+    };
+
+    let pos = vec![coordinate("x")?, coordinate("y")?, coordinate("z")?];
+    compound.insert("Pos".to_string(), Value::IntArray(pos));
+
+    if let Some(id) = compound.remove("id") {
+        compound.insert("Id".to_string(), id);
+    }
+
+    Some(compound)
+}
+
+/// Converts a sponge block entity with `Pos` and `Id` fields into a litematica tile entity with `x`, `y`, `z` and `id`
+/// fields.
+fn from_sponge_block_entity(mut block_entity: HashMap<String, Value>) -> Option<Value> {
+    // int arrays of small numbers may be read as byte arrays
+    let pos = match block_entity.remove("Pos")? {
+        Value::IntArray(pos) => pos,
+        Value::ByteArray(pos) => pos.into_iter().map(i32::from).collect(),
+        Value::List(pos) => pos
+            .into_iter()
+            .map(|value| match value {
+                Value::Int(value) => Some(value),
+                Value::Byte(value) => Some(value as i32),
+                _ => None,
+            })
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+
+    let [x, y, z] = pos[..] else {
+        return None;
+    };
+
+    block_entity.insert("x".to_string(), Value::Int(x));
+    block_entity.insert("y".to_string(), Value::Int(y));
+    block_entity.insert("z".to_string(), Value::Int(z));
+
+    if let Some(id) = block_entity.remove("Id") {
+        block_entity.insert("id".to_string(), id);
+    }
+
+    Some(Value::Compound(block_entity))
+}
+
+/// Returns the size of the region as the `Width`, `Height` and `Length` of a schematic.
+fn dimensions(region: &Region) -> Result<[i16; 3]> {
+    let dimension = |size: i32| {
+        u16::try_from(size.unsigned_abs())
+            .map(|size| size as i16)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Region is too large for a .schem file",
+                )
+            })
+    };
+
+    Ok([
+        dimension(region.size.x)?,
+        dimension(region.size.y)?,
+        dimension(region.size.z)?,
+    ])
+}
+
+fn check_version(version: i32) -> Result<()> {
+    if version == 2 || version == 3 {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported Sponge schematic version {}", version),
+        )
+        .into())
+    }
+}
+
+pub(crate) fn write_varint(out: &mut Vec<i8>, mut value: u32) {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value != 0 {
+            byte |= 0x80;
+        }
+
+        out.push(byte as i8);
+
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Result<u32> {
+    let mut value = 0;
+
+    for shift in (0..35).step_by(7) {
+        let byte = bytes
+            .next()
+            .ok_or_else(|| invalid_data("block data is shorter than the schematic"))?;
+
+        value |= ((byte & 0x7f) as u32) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(invalid_data("varint is too long"))
+}
+
+fn invalid_data(message: &str) -> crate::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn varint() {
+        let mut out = Vec::new();
+        write_varint(&mut out, 1);
+        write_varint(&mut out, 300);
+
+        assert_eq!(out, vec![1, 0xac_u8 as i8, 0x02]);
+
+        let mut bytes = out.iter().map(|byte| *byte as u8);
+        assert_eq!(read_varint(&mut bytes).unwrap(), 1);
+        assert_eq!(read_varint(&mut bytes).unwrap(), 300);
+        assert!(read_varint(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn sponge_round_trip() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.set_block((1, 1, 1), BlockStateBuilder::new("chest").build());
+        region.tile_entities.push(Value::Compound(HashMap::from([
+            ("x".to_string(), Value::Int(1)),
+            ("y".to_string(), Value::Int(1)),
+            ("z".to_string(), Value::Int(1)),
+            (
+                "id".to_string(),
+                Value::String("minecraft:chest".to_string()),
+            ),
+            ("Lock".to_string(), Value::String("key".to_string())),
+        ])));
+
+        let region = file.get_region("test").unwrap();
+
+        for version in [2, 3] {
+            let mut bytes = Vec::new();
+            file.to_sponge(version)
+                .unwrap()
+                .write_to(&mut bytes)
+                .unwrap();
+
+            let schematic = SpongeSchematic::read_from(&bytes[..]).unwrap();
+            assert_eq!(schematic.version, version);
+            assert_eq!(schematic.data_version, file.minecraft_data_version);
+
+            let read = LitematicaFile::from_sponge(schematic, "test");
+            let read_region = read.get_region("test").unwrap();
+
+            assert_eq!(read_region.position, region.min_corner());
+            assert!(region
+                .iter_blocks()
+                .all(|(position, block)| read_region.get_block(position) == block));
+
+            let chest = read_region
+                .tile_entities
+                .iter()
+                .find(|tile_entity| value_position(tile_entity) == Some((1, 1, 1).into()))
+                .unwrap();
+            let Value::Compound(chest) = chest else {
+                panic!("tile entity is not a compound");
+            };
+
+            assert_eq!(
+                chest.get("id"),
+                Some(&Value::String("minecraft:chest".to_string()))
+            );
+            assert_eq!(chest.get("Lock"), Some(&Value::String("key".to_string())));
+        }
+
+        assert!(file.to_sponge(1).is_err());
+    }
+
+    #[test]
+    fn reject_short_block_data() {
+        // a size of -1 is read as 65535 and must not allocate a region of 65535^3 blocks
+        let schematic = WriteV2 {
+            version: 2,
+            data_version: 3953,
+            width: -1,
+            height: -1,
+            length: -1,
+            offset: vec![0, 0, 0],
+            palette_max: 1,
+            palette: HashMap::from([("minecraft:air".to_string(), 0)]),
+            block_data: vec![0],
+            block_entities: Vec::new(),
+        };
+
+        let mut bytes = Vec::new();
+        nbt::to_gzip_writer(&mut bytes, &schematic, Some("Schematic")).unwrap();
+
+        assert!(SpongeSchematic::read_from(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn combine_regions() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+        let corner = region.min_corner();
+
+        let mut extra = Region::filled_with_air(
+            Coordinates {
+                x: corner.x - 2,
+                y: corner.y,
+                z: corner.z,
+            },
+            Coordinates { x: 1, y: 1, z: 1 },
+        );
+        extra.set_block((0, 0, 0), BlockStateBuilder::new("gold_block").build());
+        file.get_regions_mut().insert("extra".to_string(), extra);
+
+        let schematic = file.to_sponge(2).unwrap();
+        let (min, max) = file.enclosing_box();
+
+        assert_eq!(schematic.region.position, min);
+        assert_eq!(schematic.region.size.x, max.x - min.x);
+        assert_eq!(
+            schematic.region.get_block((0, 0, 0)).to_string(),
+            "minecraft:gold_block"
+        );
+    }
+}
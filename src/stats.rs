@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::analysis::fluids::{self, Fluid};
+use crate::csv::escape_field;
+use crate::error::Result;
+use crate::structure::{Coordinates, LitematicaFile};
+
+/// The output format of `LitematicaFile::stats_report()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// One row per region, material, fluid bucket and total, with the columns `section,name,x,y,z,count`.
+    Csv,
+
+    /// A single pretty-printed JSON object.
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    name: String,
+    author: String,
+    enclosing_size: [i32; 3],
+    total_volume: u64,
+    total_blocks: u64,
+    regions: Vec<RegionReport>,
+    materials: Vec<MaterialReport>,
+    fluids: Vec<FluidReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct RegionReport {
+    name: String,
+    position: [i32; 3],
+    size: [i32; 3],
+    volume: u64,
+    blocks: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct MaterialReport {
+    block: String,
+    count: u64,
+}
+
+/// The buckets needed for the fluids of the file, which are not part of the material list.
+#[derive(Debug, Serialize)]
+struct FluidReport {
+    bucket: String,
+    sources: u64,
+    waterlogged: u64,
+    count: u64,
+}
+
+impl LitematicaFile {
+    /// Creates a machine-readable report of the block counts, region sizes and the material list of the file,
+    /// e.g. for spreadsheets or indexing an archive of schematics.
+    ///
+    /// Air is not counted. Water and lava are listed in a separate fluid section as the buckets needed for their source
+    /// blocks and for waterlogged blocks, while flowing fluids are only counted as blocks of their region. Regions are
+    /// sorted by name, materials by descending count and then by name, so the same file always produces the same report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be serialized.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::stats::Format;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// std::fs::write("test.csv", file.stats_report(Format::Csv).unwrap()).unwrap();
+    /// ```
+    pub fn stats_report(&self, format: Format) -> Result<String> {
+        let report = self.report();
+
+        match format {
+            Format::Json => Ok(serde_json::to_string_pretty(&report)?),
+            Format::Csv => Ok(report.to_csv()),
+        }
+    }
+
+    /// Creates a short human-readable summary of the file, e.g. for posting in a chat.
+    ///
+    /// The summary contains the name and author, the enclosing size as `WxHxL`, the volume and block count, the `limit`
+    /// most used materials and the Minecraft version,
+    /// or its data version if the release is unknown. Materials from the `minecraft` namespace are shown without it.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// println!("{}", file.summary_text(5));
+    /// ```
+    pub fn summary_text(&self, limit: usize) -> String {
+        let report = self.report();
+
+        let name = match report.name.as_str() {
+            "" => "Unnamed schematic",
+            name => name,
+        };
+
+        let mut summary = match report.author.as_str() {
+            "" => format!("{}\n", name),
+            author => format!("{} by {}\n", name, author),
+        };
+
+        let [x, y, z] = report.enclosing_size;
+        summary += &format!(
+            "Size: {}x{}x{} ({} volume, {} blocks in {} {})\n",
+            x,
+            y,
+            z,
+            report.total_volume,
+            report.total_blocks,
+            report.regions.len(),
+            if report.regions.len() == 1 {
+                "region"
+            } else {
+                "regions"
+            }
+        );
+
+        if limit > 0 && !report.materials.is_empty() {
+            let mut materials = report
+                .materials
+                .iter()
+                .take(limit)
+                .map(|material| {
+                    let block = material
+                        .block
+                        .strip_prefix("minecraft:")
+                        .unwrap_or(&material.block);
+
+                    format!("{}x {}", material.count, block)
+                })
+                .collect::<Vec<_>>();
+
+            if report.materials.len() > limit {
+                materials.push(format!("{} more", report.materials.len() - limit));
+            }
+
+            summary += &format!("Materials: {}\n", materials.join(", "));
+        }
+
+        summary += &match self.minecraft_version_name() {
+            Some(version) => format!("Minecraft: {}", version),
+            None => format!("Minecraft: data version {}", self.minecraft_data_version),
+        };
+
+        summary
+    }
+
+    fn report(&self) -> Report {
+        let mut names = self.regions.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut materials: HashMap<String, u64> = HashMap::new();
+        let mut buckets: HashMap<Fluid, (u64, u64)> = HashMap::new();
+        let mut regions = Vec::with_capacity(names.len());
+
+        for name in names {
+            let region = &self.regions[name];
+            let mut blocks = 0;
+
+            for (_, block) in region.iter_blocks() {
+                if block.is_air() {
+                    continue;
+                }
+
+                blocks += 1;
+
+                let Some(fluid) = fluids::fluid(block) else {
+                    *materials.entry(block.get_name().to_string()).or_default() += 1;
+                    continue;
+                };
+
+                let (sources, waterlogged) = buckets.entry(fluid).or_default();
+
+                if matches!(block.get_name().get_path(), "water" | "lava") {
+                    let level = block.get_properties().get("level").map(String::as_str);
+
+                    if level.is_none_or(|level| level == "0") {
+                        *sources += 1;
+                    }
+                } else {
+                    *waterlogged += 1;
+                    *materials.entry(block.get_name().to_string()).or_default() += 1;
+                }
+            }
+
+            regions.push(RegionReport {
+                name: name.clone(),
+                position: to_array(region.position),
+                size: to_array(region.size),
+                volume: region.calc_volume(),
+                blocks,
+            });
+        }
+
+        let mut materials = materials
+            .into_iter()
+            .map(|(block, count)| MaterialReport { block, count })
+            .collect::<Vec<_>>();
+        materials.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.block.cmp(&b.block)));
+
+        let mut fluids = buckets
+            .into_iter()
+            .filter(|(_, (sources, waterlogged))| sources + waterlogged > 0)
+            .map(|(fluid, (sources, waterlogged))| FluidReport {
+                bucket: bucket(fluid).to_string(),
+                sources,
+                waterlogged,
+                count: sources + waterlogged,
+            })
+            .collect::<Vec<_>>();
+        fluids.sort_by(|a, b| a.bucket.cmp(&b.bucket));
+
+        Report {
+            name: self.metadata.name.clone(),
+            author: self.metadata.author.clone(),
+            enclosing_size: to_array(self.metadata.enclosing_size),
+            total_volume: regions.iter().map(|region| region.volume).sum(),
+            total_blocks: regions.iter().map(|region| region.blocks).sum(),
+            regions,
+            materials,
+            fluids,
+        }
+    }
+}
+
+impl Report {
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("section,name,x,y,z,count\n");
+
+        let [x, y, z] = self.enclosing_size;
+        csv += &format!(
+            "total,{},{},{},{},{}\n",
+            escape_csv(&self.name),
+            x,
+            y,
+            z,
+            self.total_blocks
+        );
+
+        for region in &self.regions {
+            let [x, y, z] = region.size;
+            csv += &format!(
+                "region,{},{},{},{},{}\n",
+                escape_csv(&region.name),
+                x,
+                y,
+                z,
+                region.blocks
+            );
+        }
+
+        for material in &self.materials {
+            csv += &format!(
+                "material,{},,,,{}\n",
+                escape_csv(&material.block),
+                material.count
+            );
+        }
+
+        for fluid in &self.fluids {
+            csv += &format!("fluid,{},,,,{}\n", escape_csv(&fluid.bucket), fluid.count);
+        }
+
+        csv
+    }
+}
+
+fn bucket(fluid: Fluid) -> &'static str {
+    match fluid {
+        Fluid::Water => "minecraft:water_bucket",
+        Fluid::Lava => "minecraft:lava_bucket",
+    }
+}
+
+fn to_array(coordinates: Coordinates) -> [i32; 3] {
+    [coordinates.x, coordinates.y, coordinates.z]
+}
+
+fn escape_csv(field: &str) -> String {
+    escape_field(field, ',')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn summary_text() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.metadata.name = "Sorter".to_string();
+        file.metadata.author = "someone".to_string();
+
+        let summary = file.summary_text(2);
+        let lines = summary.lines().collect::<Vec<_>>();
+        let size = file.metadata.enclosing_size;
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "Sorter by someone");
+        assert!(lines[1].starts_with(&format!("Size: {}x{}x{} (", size.x, size.y, size.z)));
+        assert!(lines[1].ends_with("in 1 region)"));
+        assert!(lines[2].starts_with("Materials: "));
+        assert!(!lines[2].contains("minecraft:"));
+        assert!(lines[3].starts_with("Minecraft: "));
+
+        file.set_minecraft_version("1.20.1").unwrap();
+        assert!(file.summary_text(2).ends_with("\nMinecraft: 1.20.1"));
+
+        file.metadata.author.clear();
+        let summary = file.summary_text(0);
+        assert!(summary.starts_with("Sorter\nSize: "));
+        assert!(!summary.contains("Materials"));
+    }
+
+    #[test]
+    fn json_report() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let report = file.stats_report(Format::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+        let region = file.get_region("test").unwrap();
+        let materials = json["materials"].as_array().unwrap();
+        let counted: u64 = materials
+            .iter()
+            .map(|material| material["count"].as_u64().unwrap())
+            .sum();
+
+        assert_eq!(json["regions"][0]["name"], "test");
+        assert_eq!(json["regions"][0]["volume"], region.calc_volume());
+        assert_eq!(json["total_blocks"], counted);
+        assert!(materials.iter().all(|m| m["block"] != "minecraft:air"));
+    }
+
+    #[test]
+    fn csv_report() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let report = file.stats_report(Format::Csv).unwrap();
+        let mut lines = report.lines();
+
+        assert_eq!(lines.next(), Some("section,name,x,y,z,count"));
+        assert!(lines.next().unwrap().starts_with("total,"));
+        assert!(lines.next().unwrap().starts_with("region,test,"));
+        assert!(lines.all(|line| line.starts_with("material,minecraft:")));
+    }
+
+    #[test]
+    fn fluid_report() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        let water = |level: &str| {
+            BlockStateBuilder::new("water")
+                .properties([("level", level)])
+                .build()
+        };
+        let slab = BlockStateBuilder::new("stone_slab")
+            .properties([("type", "bottom"), ("waterlogged", "true")])
+            .build();
+
+        region.set_block((0, 0, 0), water("0"));
+        region.set_block((1, 0, 0), water("0"));
+        region.set_block((2, 0, 0), water("3"));
+        region.set_block((3, 0, 0), slab);
+        region.set_block((0, 1, 0), BlockStateBuilder::new("lava").build());
+
+        let report = file.stats_report(Format::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+        let fluids = json["fluids"].as_array().unwrap();
+
+        assert_eq!(fluids.len(), 2);
+        assert_eq!(fluids[0]["bucket"], "minecraft:lava_bucket");
+        assert_eq!(fluids[0]["count"], 1);
+        assert_eq!(fluids[1]["bucket"], "minecraft:water_bucket");
+        assert_eq!(fluids[1]["sources"], 2);
+        assert_eq!(fluids[1]["waterlogged"], 1);
+        assert_eq!(fluids[1]["count"], 3);
+
+        let materials = json["materials"].as_array().unwrap();
+        assert!(materials.iter().all(|m| m["block"] != "minecraft:water"));
+        assert!(materials
+            .iter()
+            .any(|m| m["block"] == "minecraft:stone_slab"));
+
+        let csv = file.stats_report(Format::Csv).unwrap();
+        assert!(csv.ends_with("fluid,minecraft:water_bucket,,,,3\n"));
+    }
+
+    #[test]
+    fn escape_csv_fields() {
+        assert_eq!(escape_csv("plain"), "plain");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
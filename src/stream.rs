@@ -0,0 +1,883 @@
+use std::collections::HashMap;
+use std::io::{self, BufReader, Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::convert::RegionSink;
+use crate::error::Result;
+use crate::file::LITEMATICA_VERSION;
+use crate::structure::{BlockState, Coordinates, Metadata, Region};
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+// guards against stack overflows caused by maliciously nested files
+const MAX_DEPTH: usize = 512;
+
+/// The top-level values of a `Litematica` file that are read while streaming its regions.
+///
+/// Every field is an `Option`, because a corrupt or truncated file might not contain all of them.
+#[derive(Debug, Default)]
+pub struct Header {
+    pub metadata: Option<Metadata>,
+    pub minecraft_data_version: Option<i32>,
+    pub version: Option<i32>,
+}
+
+/// Reads the top-level values of a gzip-compressed `Litematica` file, skipping over all region data.
+///
+/// The regions are never held in memory, so this is cheap even for very large files.
+///
+/// # Arguments
+///
+/// * `reader` - A reader over the gzip-compressed file.
+///
+/// # Errors
+///
+/// Returns an error if the data cannot be read or is not valid NBT.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+/// use ritematica::stream;
+///
+/// let header = stream::read_header(File::open("test.litematic").unwrap()).unwrap();
+///
+/// assert!(header.minecraft_data_version.is_some());
+/// ```
+pub fn read_header(reader: impl Read) -> Result<Header> {
+    scan(reader, None)
+}
+
+/// Reads a gzip-compressed `Litematica` file and passes its regions to `on_region` one at a time.
+///
+/// Only a single region is decoded at any time, so the memory usage is bounded by the largest region instead of the whole file.
+///
+/// # Arguments
+///
+/// * `reader` - A reader over the gzip-compressed file.
+/// * `on_region` - A closure that is called with the name and data of every region. Returning an error stops the stream.
+///
+/// # Errors
+///
+/// Returns an error if the data cannot be read, is not valid NBT or if `on_region` returns an error.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+/// use ritematica::stream;
+///
+/// stream::for_each_region(File::open("test.litematic").unwrap(), |name, region| {
+///     println!("{}: {:?}", name, region.size);
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+pub fn for_each_region(
+    reader: impl Read,
+    mut on_region: impl FnMut(String, Region) -> Result<()>,
+) -> Result<Header> {
+    scan(reader, Some(&mut on_region))
+}
+
+type RegionCallback<'a> = &'a mut dyn FnMut(String, Region) -> Result<()>;
+
+fn scan(reader: impl Read, mut on_region: Option<RegionCallback>) -> Result<Header> {
+    let mut reader = TagReader {
+        inner: BufReader::new(GzDecoder::new(reader)),
+    };
+
+    if reader.read_u8()? != TAG_COMPOUND {
+        return Err(invalid_data("root tag is not a compound"));
+    }
+
+    reader.read_string()?;
+
+    let mut header = Header::default();
+
+    loop {
+        let tag = reader.read_u8()?;
+
+        if tag == TAG_END {
+            break;
+        }
+
+        let name = reader.read_string()?;
+
+        match (name.as_str(), tag) {
+            ("Metadata", TAG_COMPOUND) => {
+                header.metadata = Some(reader.read_compound()?);
+            }
+            ("MinecraftDataVersion", TAG_INT) => {
+                header.minecraft_data_version = Some(reader.read_i32()?);
+            }
+            ("Version", TAG_INT) => {
+                header.version = Some(reader.read_i32()?);
+            }
+            ("Regions", TAG_COMPOUND) => match on_region.as_mut() {
+                Some(on_region) => loop {
+                    let region_tag = reader.read_u8()?;
+
+                    if region_tag == TAG_END {
+                        break;
+                    }
+
+                    let region_name = reader.read_string()?;
+
+                    if region_tag == TAG_COMPOUND {
+                        let region: Region = reader.read_compound()?;
+                        region.validate()?;
+
+                        on_region(region_name, region)?;
+                    } else {
+                        reader.copy_payload(region_tag, None, 0)?;
+                    }
+                },
+                None => reader.copy_payload(tag, None, 0)?,
+            },
+            _ => reader.copy_payload(tag, None, 0)?,
+        }
+    }
+
+    Ok(header)
+}
+
+struct TagReader<R> {
+    inner: R,
+}
+
+impl<R: Read> TagReader<R> {
+    /// Reads the payload of a compound tag and deserializes it into `T`.
+    fn read_compound<T: serde::de::DeserializeOwned>(&mut self) -> Result<T> {
+        // an unnamed root compound, followed by the captured payload
+        let mut buf = vec![TAG_COMPOUND, 0, 0];
+        self.copy_payload(TAG_COMPOUND, Some(&mut buf), 0)?;
+
+        Ok(nbt::from_reader(&buf[..])?)
+    }
+
+    /// Reads the payload of a tag of type `tag`, appending the raw bytes to `out` if it is `Some`.
+    fn copy_payload(&mut self, tag: u8, mut out: Option<&mut Vec<u8>>, depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("NBT nested too deeply"));
+        }
+
+        match tag {
+            TAG_BYTE => self.copy_bytes(1, out),
+            TAG_SHORT => self.copy_bytes(2, out),
+            TAG_INT | TAG_FLOAT => self.copy_bytes(4, out),
+            TAG_LONG | TAG_DOUBLE => self.copy_bytes(8, out),
+            TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+                let len = self.copy_i32(out.as_deref_mut())?;
+                let width = match tag {
+                    TAG_BYTE_ARRAY => 1,
+                    TAG_INT_ARRAY => 4,
+                    _ => 8,
+                };
+
+                self.copy_bytes(len as u64 * width, out)
+            }
+            TAG_STRING => {
+                let mut len = [0; 2];
+                self.inner.read_exact(&mut len)?;
+
+                if let Some(out) = out.as_deref_mut() {
+                    out.extend_from_slice(&len);
+                }
+
+                self.copy_bytes(u16::from_be_bytes(len) as u64, out)
+            }
+            TAG_LIST => {
+                let element_tag = self.read_u8()?;
+
+                if let Some(out) = out.as_deref_mut() {
+                    out.push(element_tag);
+                }
+
+                let len = self.copy_i32(out.as_deref_mut())?;
+
+                for _ in 0..len {
+                    self.copy_payload(element_tag, out.as_deref_mut(), depth + 1)?;
+                }
+
+                Ok(())
+            }
+            TAG_COMPOUND => loop {
+                let child_tag = self.read_u8()?;
+
+                if let Some(out) = out.as_deref_mut() {
+                    out.push(child_tag);
+                }
+
+                if child_tag == TAG_END {
+                    return Ok(());
+                }
+
+                self.copy_payload(TAG_STRING, out.as_deref_mut(), depth + 1)?;
+                self.copy_payload(child_tag, out.as_deref_mut(), depth + 1)?;
+            },
+            _ => Err(invalid_data(&format!("invalid tag type {}", tag))),
+        }
+    }
+
+    /// Reads a non-negative length prefix, appending the raw bytes to `out` if it is `Some`.
+    fn copy_i32(&mut self, out: Option<&mut Vec<u8>>) -> Result<u32> {
+        let value = self.read_i32()?;
+
+        if let Some(out) = out {
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+
+        u32::try_from(value).map_err(|_| invalid_data("negative length"))
+    }
+
+    fn copy_bytes(&mut self, len: u64, out: Option<&mut Vec<u8>>) -> Result<()> {
+        let mut take = (&mut self.inner).take(len);
+
+        let copied = match out {
+            Some(out) => take.read_to_end(out)? as u64,
+            None => io::copy(&mut take, &mut io::sink())?,
+        };
+
+        if copied != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok(())
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        let mut buf = [0; 1];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(buf[0])
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        let mut buf = [0; 4];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(i32::from_be_bytes(buf))
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let mut len = [0; 2];
+        self.inner.read_exact(&mut len)?;
+
+        let mut buf = vec![0; u16::from_be_bytes(len) as usize];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+}
+
+/// Writes a gzip-compressed `Litematica` file one region at a time, the counterpart of `for_each_region()`.
+///
+/// Every region is serialized straight into the compressor when it is passed to `write_region()`, so only the region
+/// being written and a small compression buffer are held in memory, no matter how large the whole file is.
+/// Combined with `for_each_region()` or `convert::convert_file()`, this converts or filters files far larger than
+/// the available memory.
+///
+/// The file is only complete after `finish()` has been called. Dropping the writer earlier leaves a truncated file.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+/// use std::io::BufWriter;
+/// use ritematica::stream::{self, RegionWriter};
+///
+/// let header = stream::read_header(File::open("test.litematic").unwrap()).unwrap();
+/// let output = BufWriter::new(File::create("copy.litematic").unwrap());
+/// let mut writer = RegionWriter::new(output, &header.metadata.unwrap(), header.minecraft_data_version.unwrap()).unwrap();
+///
+/// stream::for_each_region(File::open("test.litematic").unwrap(), |name, region| {
+///     writer.write_region(&name, &region)?;
+///     println!("{} regions, {} bytes written", writer.regions_written(), writer.bytes_written());
+///     Ok(())
+/// })
+/// .unwrap();
+///
+/// writer.finish().unwrap();
+/// ```
+pub struct RegionWriter<W: Write> {
+    encoder: GzEncoder<CountingWriter<W>>,
+    regions_written: usize,
+}
+
+impl<W: Write> RegionWriter<W> {
+    /// Creates a new `RegionWriter` and writes the top-level values of the file.
+    ///
+    /// The metadata is written before any region, so its block counts and sizes must already describe all regions
+    /// that will be written, e.g. by taking them from the `Header` of the source file.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer the gzip-compressed file is written to. It is not buffered, so wrapping it in a `BufWriter` is recommended.
+    /// * `metadata` - The metadata of the file.
+    /// * `minecraft_data_version` - The Minecraft data version of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn new(writer: W, metadata: &Metadata, minecraft_data_version: i32) -> Result<Self> {
+        let mut encoder = GzEncoder::new(
+            CountingWriter {
+                inner: writer,
+                count: 0,
+            },
+            Compression::default(),
+        );
+
+        write_tag_header(&mut encoder, TAG_COMPOUND, "")?;
+        nbt::to_writer(&mut encoder, metadata, Some("Metadata"))?;
+
+        write_tag_header(&mut encoder, TAG_INT, "MinecraftDataVersion")?;
+        encoder.write_all(&minecraft_data_version.to_be_bytes())?;
+
+        write_tag_header(&mut encoder, TAG_INT, "Version")?;
+        encoder.write_all(&LITEMATICA_VERSION.to_be_bytes())?;
+
+        write_tag_header(&mut encoder, TAG_COMPOUND, "Regions")?;
+
+        Ok(Self {
+            encoder,
+            regions_written: 0,
+        })
+    }
+
+    /// Serializes a single region into the file. Region names must be unique.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the region cannot be serialized or writing fails.
+    pub fn write_region(&mut self, name: &str, region: &Region) -> Result<()> {
+        nbt::to_writer(&mut self.encoder, region, Some(name))?;
+        self.regions_written += 1;
+
+        Ok(())
+    }
+
+    /// Starts a region whose blocks are passed in one by one in YZX order, e.g. by a generator, instead of as a whole
+    /// `Region`.
+    ///
+    /// The blocks are packed and written as they come in, so the region is never held in memory and can be larger
+    /// than it. Since the bits per block depend on the palette, it has to be known up front. The region has no
+    /// entities, tile entities or scheduled ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the region. Region names must be unique.
+    /// * `position` - The position of the region relative to the schematic origin.
+    /// * `size` - The size of the region.
+    /// * `palette` - Every `BlockState` the region will contain, which should start with `minecraft:air` like in
+    ///   Litematica.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the palette is empty or writing fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    /// use std::io::BufWriter;
+    /// use ritematica::BlockStateBuilder;
+    /// use ritematica::file::MetadataBuilder;
+    /// use ritematica::stream::RegionWriter;
+    ///
+    /// let palette = [BlockStateBuilder::new("air").build(), BlockStateBuilder::new("stone").build()];
+    /// let metadata = MetadataBuilder::new("Terrain").build();
+    ///
+    /// let output = BufWriter::new(File::create("terrain.litematic").unwrap());
+    /// let mut writer = RegionWriter::new(output, &metadata, 3953).unwrap();
+    /// let mut blocks = writer.begin_region("terrain", (0, 0, 0), (1024, 256, 1024), &palette).unwrap();
+    ///
+    /// for y in 0..256 {
+    ///     for _ in 0..1024 * 1024 {
+    ///         blocks.push(if y < 64 { 1 } else { 0 }).unwrap();
+    ///     }
+    /// }
+    ///
+    /// blocks.finish().unwrap();
+    /// writer.finish().unwrap();
+    /// ```
+    pub fn begin_region(
+        &mut self,
+        name: &str,
+        position: impl Into<Coordinates>,
+        size: impl Into<Coordinates>,
+        palette: &[BlockState],
+    ) -> Result<BlockStream<'_, W>> {
+        if palette.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "The palette is empty").into());
+        }
+
+        let position = position.into();
+        let size = size.into();
+
+        let encoder = &mut self.encoder;
+
+        write_tag_header(encoder, TAG_COMPOUND, name)?;
+
+        write_coordinates(encoder, "Position", position)?;
+        write_coordinates(encoder, "Size", size)?;
+
+        for list in [
+            "Entities",
+            "TileEntities",
+            "PendingBlockTicks",
+            "PendingFluidTicks",
+        ] {
+            write_tag_header(encoder, TAG_LIST, list)?;
+            encoder.write_all(&[TAG_COMPOUND])?;
+            encoder.write_all(&0i32.to_be_bytes())?;
+        }
+
+        write_tag_header(encoder, TAG_LIST, "BlockStatePalette")?;
+        encoder.write_all(&[TAG_COMPOUND])?;
+        encoder.write_all(&(palette.len() as i32).to_be_bytes())?;
+
+        for block in palette {
+            write_tag_header(encoder, TAG_STRING, "Name")?;
+            write_string(encoder, &block.get_name().to_string())?;
+
+            if !block.get_properties().is_empty() {
+                write_tag_header(encoder, TAG_COMPOUND, "Properties")?;
+
+                for (key, value) in block.get_properties() {
+                    write_tag_header(encoder, TAG_STRING, key)?;
+                    write_string(encoder, value)?;
+                }
+
+                encoder.write_all(&[TAG_END])?;
+            }
+
+            encoder.write_all(&[TAG_END])?;
+        }
+
+        let volume = size.x.unsigned_abs() as u64
+            * size.y.unsigned_abs() as u64
+            * size.z.unsigned_abs() as u64;
+        let required_bits = Region::calc_required_bits(palette);
+        let longs = (volume * required_bits).div_ceil(64);
+
+        write_tag_header(encoder, TAG_LONG_ARRAY, "BlockStates")?;
+        encoder.write_all(&(longs as i32).to_be_bytes())?;
+
+        Ok(BlockStream {
+            writer: self,
+            indices: palette
+                .iter()
+                .enumerate()
+                .map(|(index, block)| (block.clone(), index as u32))
+                .collect(),
+            palette_len: palette.len() as u32,
+            required_bits: required_bits as u32,
+            volume,
+            pushed: 0,
+            current: 0,
+            filled: 0,
+        })
+    }
+
+    /// Returns the number of regions written so far.
+    pub fn regions_written(&self) -> usize {
+        self.regions_written
+    }
+
+    /// Returns the number of compressed bytes passed to the underlying writer so far.
+    ///
+    /// The compressor holds back some data until it is flushed, so this lags slightly behind the serialized regions.
+    pub fn bytes_written(&self) -> u64 {
+        self.encoder.get_ref().count
+    }
+
+    /// Closes the file, flushes the compressor and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn finish(mut self) -> Result<W> {
+        // ends the regions and the root compound
+        self.encoder.write_all(&[TAG_END, TAG_END])?;
+
+        let mut writer = self.encoder.finish()?.inner;
+        writer.flush()?;
+
+        Ok(writer)
+    }
+}
+
+/// The blocks of a region being streamed into a `RegionWriter`, created by `RegionWriter::begin_region()`.
+///
+/// The region is only complete after `finish()` has been called. Dropping it earlier leaves a corrupt file.
+pub struct BlockStream<'a, W: Write> {
+    writer: &'a mut RegionWriter<W>,
+    indices: HashMap<BlockState, u32>,
+    palette_len: u32,
+    required_bits: u32,
+    volume: u64,
+    pushed: u64,
+    /// The long being packed, and how many of its bits are used.
+    current: u64,
+    filled: u32,
+}
+
+impl<W: Write> BlockStream<'_, W> {
+    /// Adds the next block in YZX order, as an index into the palette passed to `RegionWriter::begin_region()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is not in the palette, the region is already full or writing fails.
+    pub fn push(&mut self, palette_index: u32) -> Result<()> {
+        if palette_index >= self.palette_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Palette index {} is out of bounds for a palette of {} entries",
+                    palette_index, self.palette_len
+                ),
+            )
+            .into());
+        }
+
+        if self.pushed == self.volume {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("The region already contains all {} blocks", self.volume),
+            )
+            .into());
+        }
+
+        let value = palette_index as u64;
+        self.current |= value << self.filled;
+        self.filled += self.required_bits;
+        self.pushed += 1;
+
+        // values are packed across the boundaries of longs, like in Litematica
+        if self.filled >= 64 {
+            self.write_current()?;
+
+            self.filled -= 64;
+            self.current = if self.filled > 0 {
+                value >> (self.required_bits - self.filled)
+            } else {
+                0
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Adds the next block in YZX order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block is not in the palette, the region is already full or writing fails.
+    pub fn push_block(&mut self, block: &BlockState) -> Result<()> {
+        let palette_index = *self.indices.get(block).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not in the palette", block),
+            )
+        })?;
+
+        self.push(palette_index)
+    }
+
+    /// Returns the number of blocks added so far.
+    pub fn blocks_pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    /// Writes the last partially filled long and closes the region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer blocks than the volume of the region were added, or writing fails.
+    pub fn finish(mut self) -> Result<()> {
+        if self.pushed != self.volume {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Only {} of {} blocks were added to the region",
+                    self.pushed, self.volume
+                ),
+            )
+            .into());
+        }
+
+        if self.filled > 0 {
+            self.write_current()?;
+        }
+
+        self.writer.encoder.write_all(&[TAG_END])?;
+        self.writer.regions_written += 1;
+
+        Ok(())
+    }
+
+    fn write_current(&mut self) -> Result<()> {
+        self.writer.encoder.write_all(&self.current.to_be_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> RegionSink for RegionWriter<W> {
+    fn write_region(&mut self, name: &str, region: &Region, _header: &Header) -> Result<()> {
+        RegionWriter::write_region(self, name, region)
+    }
+}
+
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn write_tag_header(writer: &mut impl Write, tag: u8, name: &str) -> Result<()> {
+    writer.write_all(&[tag])?;
+    writer.write_all(&(name.len() as u16).to_be_bytes())?;
+    writer.write_all(name.as_bytes())?;
+
+    Ok(())
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> Result<()> {
+    writer.write_all(&(value.len() as u16).to_be_bytes())?;
+    writer.write_all(value.as_bytes())?;
+
+    Ok(())
+}
+
+fn write_coordinates(writer: &mut impl Write, name: &str, coordinates: Coordinates) -> Result<()> {
+    write_tag_header(writer, TAG_COMPOUND, name)?;
+
+    for (axis, value) in [
+        ("x", coordinates.x),
+        ("y", coordinates.y),
+        ("z", coordinates.z),
+    ] {
+        write_tag_header(writer, TAG_INT, axis)?;
+        writer.write_all(&value.to_be_bytes())?;
+    }
+
+    writer.write_all(&[TAG_END])?;
+
+    Ok(())
+}
+
+fn invalid_data(message: &str) -> crate::error::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn read_header() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let header = super::read_header(File::open("test.litematic").unwrap()).unwrap();
+
+        assert_eq!(
+            header.minecraft_data_version,
+            Some(file.minecraft_data_version)
+        );
+        assert_eq!(header.version, Some(file.version));
+        assert_eq!(header.metadata.unwrap().name, file.metadata.name);
+    }
+
+    #[test]
+    fn for_each_region() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let expected = file.get_region("test").unwrap();
+
+        let mut names = Vec::new();
+
+        super::for_each_region(File::open("test.litematic").unwrap(), |name, region| {
+            assert_eq!(region.size, expected.size);
+            assert_eq!(region.get_block((2, 4, 2)), expected.get_block((2, 4, 2)));
+
+            names.push(name);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(names, vec!["test".to_string()]);
+    }
+
+    #[test]
+    fn write_regions_one_at_a_time() {
+        let header = super::read_header(File::open("test.litematic").unwrap()).unwrap();
+        let mut writer = RegionWriter::new(
+            Vec::new(),
+            header.metadata.as_ref().unwrap(),
+            header.minecraft_data_version.unwrap(),
+        )
+        .unwrap();
+
+        super::for_each_region(File::open("test.litematic").unwrap(), |name, region| {
+            writer.write_region(&name, &region)
+        })
+        .unwrap();
+
+        assert_eq!(writer.regions_written(), 1);
+
+        let bytes = writer.finish().unwrap();
+        let file: LitematicaFile = nbt::from_gzip_reader(&bytes[..]).unwrap();
+        let expected = LitematicaFile::read("test.litematic").unwrap();
+
+        assert_eq!(file.minecraft_data_version, expected.minecraft_data_version);
+        assert_eq!(file.metadata.name, expected.metadata.name);
+        assert_eq!(
+            file.get_region("test").unwrap().iter_blocks().count(),
+            expected.get_region("test").unwrap().iter_blocks().count()
+        );
+        assert_eq!(
+            file.get_region("test").unwrap().get_block((2, 4, 2)),
+            expected.get_region("test").unwrap().get_block((2, 4, 2))
+        );
+    }
+
+    #[test]
+    fn stream_blocks() {
+        let expected = LitematicaFile::read("test.litematic").unwrap();
+        let region = expected.get_region("test").unwrap();
+
+        let mut writer = RegionWriter::new(
+            Vec::new(),
+            &expected.metadata,
+            expected.minecraft_data_version,
+        )
+        .unwrap();
+
+        let mut blocks = writer
+            .begin_region(
+                "streamed",
+                region.position,
+                region.size,
+                &region.block_state_palette,
+            )
+            .unwrap();
+
+        for (_, block) in region.iter_blocks() {
+            blocks.push_block(block).unwrap();
+        }
+
+        assert!(blocks.push(0).is_err());
+        assert_eq!(blocks.blocks_pushed(), region.calc_volume());
+        blocks.finish().unwrap();
+
+        // a palette of 5 entries takes 3 bits per block, so values cross the boundaries of longs
+        let palette = ["air", "stone", "dirt", "sand", "glass"]
+            .map(|name| crate::block::BlockStateBuilder::new(name).build());
+        let mut blocks = writer
+            .begin_region("generated", (0, 0, 0), (5, 3, -7), &palette)
+            .unwrap();
+
+        assert!(blocks.push(5).is_err());
+        for index in 0..5 * 3 * 7 {
+            blocks.push(index % 5).unwrap();
+        }
+        blocks.finish().unwrap();
+
+        let incomplete = writer
+            .begin_region("incomplete", (0, 0, 0), (2, 2, 2), &palette)
+            .unwrap();
+        assert!(incomplete.finish().is_err());
+
+        let file = LitematicaFile::from_bytes(&writer.finish().unwrap());
+        assert!(file.is_err());
+
+        let mut writer = RegionWriter::new(
+            Vec::new(),
+            &expected.metadata,
+            expected.minecraft_data_version,
+        )
+        .unwrap();
+
+        let mut blocks = writer
+            .begin_region("generated", (0, 0, 0), (5, 3, -7), &palette)
+            .unwrap();
+        for index in 0..5 * 3 * 7 {
+            blocks.push(index % 5).unwrap();
+        }
+        blocks.finish().unwrap();
+
+        let mut blocks = writer
+            .begin_region(
+                "streamed",
+                region.position,
+                region.size,
+                &region.block_state_palette,
+            )
+            .unwrap();
+        for (_, block) in region.iter_blocks() {
+            blocks.push_block(block).unwrap();
+        }
+        blocks.finish().unwrap();
+
+        assert_eq!(writer.regions_written(), 2);
+
+        let file = LitematicaFile::from_bytes(&writer.finish().unwrap()).unwrap();
+
+        let streamed = file.get_region("streamed").unwrap();
+        assert_eq!(streamed.block_states, region.block_states);
+        assert!(streamed.entities.is_empty());
+
+        let generated = file.get_region("generated").unwrap();
+        assert_eq!(generated.size, Coordinates { x: 5, y: 3, z: -7 });
+        for (index, (_, block)) in generated.iter_blocks().enumerate() {
+            assert_eq!(block, &palette[index % 5]);
+        }
+    }
+
+    #[test]
+    fn truncated_input() {
+        let bytes = std::fs::read("test.litematic").unwrap();
+        let mut decoded = Vec::new();
+        GzDecoder::new(&bytes[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+
+        decoded.truncate(decoded.len() / 2);
+
+        let mut encoded = Vec::new();
+        let mut encoder =
+            flate2::write::GzEncoder::new(&mut encoded, flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &decoded).unwrap();
+        encoder.finish().unwrap();
+
+        assert!(super::for_each_region(&encoded[..], |_, _| Ok(())).is_err());
+    }
+}
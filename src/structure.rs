@@ -1,9 +1,14 @@
 use nbt::Value;
 use serde::{Deserialize, Serialize};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
+use crate::annotation::Annotations;
+use crate::group::BlockGroup;
+use crate::provenance::Provenance;
 use crate::resource_location::ResourceLocation;
+use crate::vector::{Rotation2, Vec3};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -12,9 +17,37 @@ pub struct LitematicaFile {
     pub minecraft_data_version: i32,
     pub version: i32,
     pub(crate) regions: HashMap<String, Region>,
+
+    #[serde(rename = "Ritematica")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Extensions::is_empty")]
+    pub(crate) extensions: Extensions,
+
+    /// The names of the regions in the order set by `sort_regions_by()`. Regions that are missing come last, by name.
+    #[serde(skip)]
+    pub(crate) region_order: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Additional data written by this crate into its own compound, which is ignored by Litematica itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Extensions {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) checksum: Option<i64>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) provenance: Option<Provenance>,
+}
+
+impl Extensions {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.checksum.is_none() && self.provenance.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Region {
     pub position: Coordinates,
@@ -27,19 +60,69 @@ pub struct Region {
 
     #[serde(serialize_with = "nbt::i64_array")]
     pub(crate) block_states: Vec<i64>,
+
+    #[serde(rename = "Ritematica")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "RegionExtensions::is_empty")]
+    pub(crate) extensions: RegionExtensions,
+
+    /// The number of placeholder entries at the end of the palette reserved by `reserve_palette()`.
+    #[serde(skip)]
+    pub(crate) reserved_palette: usize,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+/// Additional per-region data written by this crate into its own compound, which is ignored by Litematica itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
+pub struct RegionExtensions {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Annotations::is_empty")]
+    pub(crate) annotations: Annotations,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) groups: BTreeMap<String, BlockGroup>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) anchors: BTreeMap<String, Coordinates>,
+}
+
+impl RegionExtensions {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.annotations.is_empty() && self.groups.is_empty() && self.anchors.is_empty()
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase", from = "BlockStateData")]
 pub struct BlockState {
     pub(crate) name: ResourceLocation,
 
+    /// Shared between clones, so palette entries interned by a `StateRegistry` don't use memory of their own.
+    #[serde(skip_serializing_if = "has_no_properties")]
+    pub(crate) properties: Arc<BTreeMap<String, String>>,
+
+    /// The canonical string of the block state, kept up to date on every mutation.
+    #[serde(skip_serializing)]
+    pub(crate) canonical: Arc<str>,
+}
+
+fn has_no_properties(properties: &Arc<BTreeMap<String, String>>) -> bool {
+    properties.is_empty()
+}
+
+/// The serialized fields of a `BlockState`, which is deserialized through this to compute its canonical string.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct BlockStateData {
+    pub(crate) name: ResourceLocation,
+
     #[serde(default)]
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub(crate) properties: HashMap<String, String>,
+    pub(crate) properties: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Metadata {
     pub author: String,
@@ -53,19 +136,19 @@ pub struct Metadata {
     pub time_created: i64,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Entity {
     #[serde(rename = "Rotation")]
-    pub rotation: Vec<f64>,
+    pub rotation: Rotation2,
 
     #[serde(rename = "Fire")]
     pub fire: i16,
 
     #[serde(rename = "Pos")]
-    pub pos: Vec<f64>,
+    pub pos: Vec3,
 
     #[serde(rename = "Motion")]
-    pub motion: Vec<f64>,
+    pub motion: Vec3,
 
     #[serde(rename = "Air")]
     pub air: i16,
@@ -86,9 +169,16 @@ pub struct Entity {
 
     #[serde(rename = "Invulnerable")]
     pub invulnerable: bool,
+
+    /// The direction item frames hang on, as `0` for down, `1` for up, `2` for north, `3` for south, `4` for west and
+    /// `5` for east.
+    #[serde(rename = "Facing")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facing: Option<i8>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Coordinates {
     pub x: i32,
     pub y: i32,
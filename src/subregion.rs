@@ -0,0 +1,800 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::io;
+
+use crate::block::{BlockStateBuilder, BlockStatePattern};
+use crate::error::Result;
+use crate::region::{
+    checked_index_3d, index_3d, read_palette_index, set_value_position, value_position,
+};
+use crate::structure::{Coordinates, LitematicaFile, Region};
+use crate::trace;
+
+impl Region {
+    /// Copies a box of blocks out of the region into a new `Region`.
+    ///
+    /// The new region only contains the palette entries that are actually used. Entities, tile entities,
+    /// scheduled ticks, annotations, groups and anchors inside of the box are copied along with their positions adjusted.
+    /// The `position` of the new region is the position of the box relative to the schematic origin, and its size is always positive.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The corner of the box with the lowest coordinates, relative to the region origin.
+    /// * `size` - The size of the box. All components must be positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box is empty or not completely inside of the region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let part = region.extract((2, 4, 2), (3, 1, 3));
+    ///
+    /// assert_eq!(part.get_block((0, 0, 0)), region.get_block((2, 4, 2)));
+    /// ```
+    pub fn extract(&self, min: impl Into<Coordinates>, size: impl Into<Coordinates>) -> Region {
+        let min = min.into();
+        let size = size.into();
+
+        assert!(
+            size.x > 0 && size.y > 0 && size.z > 0,
+            "Size {:?} must be positive",
+            size
+        );
+
+        let max = Coordinates {
+            x: min.x + size.x - 1,
+            y: min.y + size.y - 1,
+            z: min.z + size.z - 1,
+        };
+
+        // check that both corners are inside of the region
+        index_3d(self.size, min);
+        index_3d(self.size, max);
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let mut remap: Vec<Option<u32>> = vec![None; self.block_state_palette.len()];
+        let mut palette = Vec::new();
+        let mut indices = Vec::with_capacity((size.x * size.y * size.z) as usize);
+
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    let block_index = index_3d(self.size, Coordinates { x, y, z });
+                    let old_index =
+                        read_palette_index(&self.block_states, block_index, required_bits, bitmask)
+                            as usize;
+
+                    let new_index = *remap[old_index].get_or_insert_with(|| {
+                        palette.push(self.block_state_palette[old_index].clone());
+                        (palette.len() - 1) as u32
+                    });
+
+                    indices.push(new_index);
+                }
+            }
+        }
+
+        let corner = self.min_corner();
+        let mut region = Region::filled_with_air(
+            Coordinates {
+                x: corner.x + min.x,
+                y: corner.y + min.y,
+                z: corner.z + min.z,
+            },
+            size,
+        );
+
+        let new_bits = Self::calc_required_bits(&palette);
+        let new_bitmask = (1 << new_bits) - 1;
+        let words = (indices.len() as u64 * new_bits).div_ceil(64);
+
+        region.block_states = vec![0; words as usize];
+
+        for (block_index, palette_index) in indices.into_iter().enumerate() {
+            Self::set_block_index(
+                &mut region.block_states,
+                block_index as u64,
+                palette_index,
+                new_bits,
+                new_bitmask,
+            );
+        }
+
+        region.block_state_palette = palette;
+
+        let inside = |position: Coordinates| {
+            (min.x..=max.x).contains(&position.x)
+                && (min.y..=max.y).contains(&position.y)
+                && (min.z..=max.z).contains(&position.z)
+        };
+
+        let shift = |position: Coordinates| Coordinates {
+            x: position.x - min.x,
+            y: position.y - min.y,
+            z: position.z - min.z,
+        };
+
+        for entity in &self.entities {
+            if inside(entity.pos.block_position()) {
+                let mut entity = entity.clone();
+                entity.pos -= min.into();
+                region.entities.push(entity);
+            }
+        }
+
+        let copy_positioned = |values: &[nbt::Value]| {
+            values
+                .iter()
+                .filter_map(|value| {
+                    let position = value_position(value).filter(|p| inside(*p))?;
+
+                    let mut value = value.clone();
+                    set_value_position(&mut value, shift(position));
+                    Some(value)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        region.tile_entities = copy_positioned(&self.tile_entities);
+        region.pending_block_ticks = copy_positioned(&self.pending_block_ticks);
+        region.pending_fluid_ticks = copy_positioned(&self.pending_fluid_ticks);
+
+        for (position, data) in self.annotations().iter() {
+            if inside(position) {
+                region
+                    .extensions
+                    .annotations
+                    .insert(shift(position), data.clone());
+            }
+        }
+
+        for name in self.group_names() {
+            let positions = self
+                .get_group(name)
+                .expect("group exists")
+                .positions()
+                .iter()
+                .filter(|position| inside(**position))
+                .map(|position| shift(*position))
+                .collect::<Vec<_>>();
+
+            if !positions.is_empty() {
+                region.create_group(name, positions);
+            }
+        }
+
+        for (name, position) in self.anchors() {
+            if inside(position) {
+                region.set_anchor(name, shift(position));
+            }
+        }
+
+        region
+    }
+
+    /// Shrinks the region to the bounding box of all blocks matching `pattern`, e.g. to drop the scaffolding around a machine.
+    ///
+    /// Blocks outside of the new box are removed along with their entities, tile entities, annotations and group positions,
+    /// like with `extract()`. Returns `false` and leaves the region unchanged if no block matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A `BlockStatePattern` matching the blocks that should be kept inside of the box.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockState, LitematicaFile};
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region_mut("test").unwrap();
+    ///
+    /// let machine = |block: &BlockState| {
+    ///     !block.is_air() && block.get_name().get_path() != "white_wool"
+    /// };
+    ///
+    /// region.crop_to(&machine);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(volume = self.calc_volume(), elapsed_ms))
+    )]
+    pub fn crop_to(&mut self, pattern: &impl BlockStatePattern) -> bool {
+        let _timer = trace::Timer::start();
+
+        let Some((min, max)) = self
+            .iter_blocks()
+            .filter(|(_, block)| pattern.matches(block))
+            .map(|(position, _)| (position, position))
+            .reduce(|(min, max), (position, _)| {
+                (
+                    Coordinates {
+                        x: min.x.min(position.x),
+                        y: min.y.min(position.y),
+                        z: min.z.min(position.z),
+                    },
+                    Coordinates {
+                        x: max.x.max(position.x),
+                        y: max.y.max(position.y),
+                        z: max.z.max(position.z),
+                    },
+                )
+            })
+        else {
+            return false;
+        };
+
+        let size = Coordinates {
+            x: max.x - min.x + 1,
+            y: max.y - min.y + 1,
+            z: max.z - min.z + 1,
+        };
+
+        *self = self.extract(min, size);
+
+        true
+    }
+
+    /// Splits the region into a grid of adjacent regions that are each at most `max_dims` large.
+    ///
+    /// The parts are returned in YZX order together with a deterministic name of the form `x_y_z`,
+    /// where `x`, `y` and `z` are the indices of the part in the grid, e.g. `0_0_0`, `1_0_0`, ...
+    /// Like with `extract()`, the parts keep the entities, tile entities, annotations, groups and anchors inside of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_dims` - The maximum size of every part, e.g. `(48, 48, 48)` for structure blocks. All components must be positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a component of `max_dims` is not positive.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap(); // 31x9x29
+    ///
+    /// let parts = region.split_max_size((16, 16, 16));
+    ///
+    /// assert_eq!(parts.len(), 4);
+    /// assert_eq!(parts[0].0, "0_0_0");
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(volume = self.calc_volume(), parts, elapsed_ms))
+    )]
+    pub fn split_max_size(&self, max_dims: impl Into<Coordinates>) -> Vec<(String, Region)> {
+        let _timer = trace::Timer::start();
+
+        let max_dims = max_dims.into();
+
+        assert!(
+            max_dims.x > 0 && max_dims.y > 0 && max_dims.z > 0,
+            "Maximum dimensions {:?} must be positive",
+            max_dims
+        );
+
+        let size = Coordinates {
+            x: self.size.x.abs(),
+            y: self.size.y.abs(),
+            z: self.size.z.abs(),
+        };
+
+        let mut parts = Vec::new();
+
+        for (iy, y) in (0..size.y).step_by(max_dims.y as usize).enumerate() {
+            for (iz, z) in (0..size.z).step_by(max_dims.z as usize).enumerate() {
+                for (ix, x) in (0..size.x).step_by(max_dims.x as usize).enumerate() {
+                    let part_size = Coordinates {
+                        x: max_dims.x.min(size.x - x),
+                        y: max_dims.y.min(size.y - y),
+                        z: max_dims.z.min(size.z - z),
+                    };
+
+                    let part = self.extract(Coordinates { x, y, z }, part_size);
+                    parts.push((format!("{}_{}_{}", ix, iy, iz), part));
+                }
+            }
+        }
+
+        trace::record("parts", parts.len() as u64);
+
+        parts
+    }
+
+    /// Splits the region into one region per phase, e.g. structure blocks first, redstone second and decoration third,
+    /// so that a project can be pasted or built in stages.
+    ///
+    /// Every block belongs to the first phase whose pattern it matches, and blocks that match no phase are dropped.
+    /// All parts have the position and size of the region and are filled with air everywhere else. Tile entities, scheduled
+    /// ticks, annotations, group positions and anchors go to the part that contains their block and are dropped if they are
+    /// outside of the region, while entities are all placed in the last part.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::{BlockState, LitematicaFile};
+    /// use ritematica::block::BlockStatePattern;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let redstone = |block: &BlockState| block.get_name().get_path().contains("redstone");
+    /// let rest = |block: &BlockState| !block.is_air();
+    ///
+    /// let phases = region.split_by_pattern(&[&redstone, &rest]);
+    ///
+    /// assert_eq!(phases.len(), 2);
+    /// ```
+    pub fn split_by_pattern(&self, phases: &[&dyn BlockStatePattern]) -> Vec<Region> {
+        let phase_of_palette = self
+            .block_state_palette
+            .iter()
+            .map(|block| phases.iter().position(|phase| phase.matches(block)))
+            .collect::<Vec<_>>();
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        // positions outside of the region, e.g. from a corrupt file, belong to no phase
+        let phase_at = |position: Coordinates| {
+            let index = checked_index_3d(self.size, position).ok()?;
+            let palette_index =
+                read_palette_index(&self.block_states, index, required_bits, bitmask);
+
+            phase_of_palette[palette_index as usize]
+        };
+
+        let mut parts = phases
+            .iter()
+            .map(|_| Region::filled_with_air(self.position, self.size))
+            .collect::<Vec<_>>();
+
+        for (position, block) in self.iter_blocks() {
+            if let Some(phase) = phase_at(position) {
+                parts[phase].set_block(position, block.clone());
+            }
+        }
+
+        let owner = |value: &nbt::Value| value_position(value).and_then(&phase_at);
+
+        for value in &self.tile_entities {
+            if let Some(phase) = owner(value) {
+                parts[phase].tile_entities.push(value.clone());
+            }
+        }
+        for value in &self.pending_block_ticks {
+            if let Some(phase) = owner(value) {
+                parts[phase].pending_block_ticks.push(value.clone());
+            }
+        }
+        for value in &self.pending_fluid_ticks {
+            if let Some(phase) = owner(value) {
+                parts[phase].pending_fluid_ticks.push(value.clone());
+            }
+        }
+
+        for (position, data) in self.annotations().iter() {
+            if let Some(phase) = phase_at(position) {
+                parts[phase]
+                    .extensions
+                    .annotations
+                    .insert(position, data.clone());
+            }
+        }
+
+        for name in self.group_names() {
+            let positions = self.get_group(name).expect("group exists").positions();
+
+            for (phase, part) in parts.iter_mut().enumerate() {
+                let inside = positions
+                    .iter()
+                    .filter(|position| phase_at(**position) == Some(phase))
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                if !inside.is_empty() {
+                    part.create_group(name, inside);
+                }
+            }
+        }
+
+        for (name, position) in self.anchors() {
+            if let Some(phase) = phase_at(position) {
+                parts[phase].set_anchor(name, position);
+            }
+        }
+
+        if let Some(last) = parts.last_mut() {
+            last.entities = self.entities.clone();
+        }
+
+        parts
+    }
+}
+
+impl LitematicaFile {
+    /// Splits every region into a grid of tiles that are each at most `region_size` large, e.g. for paste plugins that
+    /// throttle their work by pasting one tile per tick.
+    ///
+    /// The tiles are returned in the order they should be pasted in: the regions by name, and the tiles of every region
+    /// from the bottom layer up, in YZX order. They are named `region_x_y_z` after their region and their indices in its
+    /// grid, see `Region::split_max_size()`. Every tile keeps its place in the schematic.
+    ///
+    /// # Arguments
+    ///
+    /// * `region_size` - The maximum size of every tile, e.g. `(64, 64, 64)`. All components must be positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a component of `region_size` is not positive.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// for (name, tile) in file.tile((64, 64, 64)) {
+    ///     println!("Pasting {} at {:?}", name, tile.position);
+    /// }
+    /// ```
+    pub fn tile(&self, region_size: impl Into<Coordinates>) -> Vec<(String, Region)> {
+        let region_size = region_size.into();
+
+        let mut names = self.regions.keys().collect::<Vec<_>>();
+        names.sort();
+
+        names
+            .into_iter()
+            .flat_map(|name| {
+                self.regions[name]
+                    .split_max_size(region_size)
+                    .into_iter()
+                    .map(move |(index, tile)| (format!("{}_{}", name, index), tile))
+            })
+            .collect()
+    }
+
+    /// Moves a box of blocks out of an existing region into a new region of the file, e.g. to split a monolithic
+    /// schematic into logical modules.
+    ///
+    /// The new region is created with `Region::extract()`, so it keeps its place in the schematic. The box is then
+    /// replaced with air in the source region, and the entities, tile entities, scheduled ticks, annotations, group
+    /// positions and anchors inside of it are removed there. Groups left without positions are removed. The metadata
+    /// is updated afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `source_region` - The name of the region the blocks are taken from.
+    /// * `bounds` - The inclusive corners of the box relative to the origin of the source region.
+    /// * `new_name` - The name of the new region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no region named `source_region` or if a region named `new_name` already exists.
+    /// The file is left unchanged in that case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the box is not completely inside of the source region.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// file.carve_region("test", ((0, 0, 0), (9, 8, 9)), "storage").unwrap();
+    ///
+    /// assert_eq!(file.metadata.region_count, 2);
+    /// ```
+    pub fn carve_region<Q>(
+        &mut self,
+        source_region: &Q,
+        bounds: (impl Into<Coordinates>, impl Into<Coordinates>),
+        new_name: impl Into<String>,
+    ) -> Result<()>
+    where
+        String: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let new_name = new_name.into();
+
+        if self.regions.contains_key::<String>(&new_name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("A region named {} already exists", new_name),
+            )
+            .into());
+        }
+
+        let source = self.regions.get_mut(source_region).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The source region does not exist",
+            )
+        })?;
+
+        let positions = source.box_positions(bounds).collect::<Vec<_>>();
+        // the positions are in YZX order, so the corners come first and last
+        let min = positions[0];
+        let max = positions[positions.len() - 1];
+
+        let size = Coordinates {
+            x: max.x - min.x + 1,
+            y: max.y - min.y + 1,
+            z: max.z - min.z + 1,
+        };
+        let carved = source.extract(min, size);
+
+        let inside = |position: Coordinates| {
+            (min.x..=max.x).contains(&position.x)
+                && (min.y..=max.y).contains(&position.y)
+                && (min.z..=max.z).contains(&position.z)
+        };
+        let outside = |value: &nbt::Value| !value_position(value).is_some_and(inside);
+
+        let air = BlockStateBuilder::new("air").build();
+        for position in positions {
+            source.set_block(position, air.clone());
+        }
+
+        source
+            .entities
+            .retain(|entity| !inside(entity.pos.block_position()));
+        source.tile_entities.retain(outside);
+        source.pending_block_ticks.retain(outside);
+        source.pending_fluid_ticks.retain(outside);
+
+        let extensions = &mut source.extensions;
+
+        extensions
+            .annotations
+            .retain(|position, _| !inside(position));
+        extensions.anchors.retain(|_, position| !inside(*position));
+
+        for group in extensions.groups.values_mut() {
+            group.retain(|position| !inside(*position));
+        }
+        extensions.groups.retain(|_, group| !group.is_empty());
+
+        self.regions.insert(new_name, carved);
+        self.update_metadata();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nbt::Value;
+
+    use crate::structure::{BlockState, LitematicaFile};
+
+    use super::*;
+
+    #[test]
+    fn tile() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        file.carve_region("test", ((0, 0, 0), (3, 3, 3)), "a_corner")
+            .unwrap();
+
+        let tiles = file.tile((16, 4, 16));
+        let names = tiles
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+
+        // the test region is 31x9x29
+        assert_eq!(names.len(), 1 + 2 * 3 * 2);
+        assert_eq!(
+            names[..4],
+            ["a_corner_0_0_0", "test_0_0_0", "test_1_0_0", "test_0_0_1"]
+        );
+        assert_eq!(names[names.len() - 1], "test_1_2_1");
+
+        let region = file.get_region("test").unwrap();
+        let (_, last) = &tiles[tiles.len() - 1];
+
+        assert_eq!(last.size, Coordinates { x: 15, y: 1, z: 13 });
+        assert_eq!(last.get_block((0, 0, 0)), region.get_block((16, 8, 16)));
+        assert!(file
+            .tile((8, 8, 8))
+            .iter()
+            .all(|(_, tile)| tile.calc_volume() <= 512));
+    }
+
+    #[test]
+    fn extract() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let part = region.extract((1, 3, 1), (4, 2, 5));
+
+        assert_eq!(part.size, Coordinates { x: 4, y: 2, z: 5 });
+        assert!(part.block_state_palette.len() <= region.block_state_palette.len());
+
+        for (position, block) in part.iter_blocks() {
+            let source = Coordinates {
+                x: position.x + 1,
+                y: position.y + 3,
+                z: position.z + 1,
+            };
+
+            assert_eq!(block, region.get_block(source));
+        }
+    }
+
+    #[test]
+    fn extract_keeps_annotations() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+
+        region.annotate((2, 4, 2), Value::String("rail".to_string()));
+        region.annotate((0, 0, 0), Value::String("corner".to_string()));
+
+        let part = region.extract((2, 4, 2), (1, 1, 1));
+
+        assert_eq!(part.annotations().len(), 1);
+        assert_eq!(
+            part.annotations().get((0, 0, 0)),
+            Some(&Value::String("rail".to_string()))
+        );
+    }
+
+    #[test]
+    fn split_by_pattern() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+        let rail = region.get_block((2, 4, 2)).clone();
+
+        region.set_anchor("rail", (2, 4, 2));
+
+        // out of bounds positions are dropped instead of panicking
+        let mut outside = region.tile_entities[0].clone();
+        set_value_position(&mut outside, (0, 100, 0).into());
+        region.tile_entities.push(outside.clone());
+
+        let is_rail = |block: &BlockState| block == &rail;
+        let solid = |block: &BlockState| !block.is_air();
+
+        let phases = region.split_by_pattern(&[&is_rail, &solid]);
+
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[0].get_block((2, 4, 2)), &rail);
+        assert_eq!(phases[0].get_anchor("rail"), Some((2, 4, 2).into()));
+        assert!(phases[1].get_anchor("rail").is_none());
+        assert!(phases[1].get_block((2, 4, 2)).is_air());
+        assert!(phases
+            .iter()
+            .all(|phase| !phase.tile_entities.contains(&outside)));
+
+        for (position, block) in region.iter_blocks() {
+            let expected = if block == &rail { 0 } else { 1 };
+
+            if !block.is_air() {
+                assert_eq!(phases[expected].get_block(position), block);
+                assert!(phases[1 - expected].get_block(position).is_air());
+            }
+        }
+    }
+
+    #[test]
+    fn crop_to_ignores_scaffolding() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region_mut("test").unwrap();
+        let corner = region.min_corner();
+        let rail = region.get_block((2, 4, 2)).clone();
+
+        assert!(region.crop_to(&rail));
+
+        assert_eq!(region.get_block((0, 0, 0)), &rail);
+        assert_eq!(region.min_corner().y, corner.y + 4);
+
+        let stone = |block: &BlockState| block.get_name().get_path() == "stone";
+        assert!(!region.crop_to(&stone));
+    }
+
+    #[test]
+    fn split_max_size() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap(); // 31x9x29
+
+        let parts = region.split_max_size((16, 8, 29));
+
+        assert_eq!(parts.len(), 4);
+        assert_eq!(
+            parts
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["0_0_0", "1_0_0", "0_1_0", "1_1_0"]
+        );
+
+        let volume: u64 = parts.iter().map(|(_, part)| part.calc_volume()).sum();
+        assert_eq!(volume, region.calc_volume());
+
+        let (_, last) = &parts[3];
+        assert_eq!(last.size, Coordinates { x: 15, y: 1, z: 29 });
+        assert_eq!(last.get_block((0, 0, 0)), region.get_block((16, 8, 0)));
+    }
+
+    #[test]
+    fn carve_region() {
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let original = file.get_region("test").unwrap().clone();
+        let blocks = file.metadata.total_blocks;
+
+        assert!(file
+            .carve_region("missing", ((0, 0, 0), (1, 1, 1)), "part")
+            .is_err());
+        assert!(file
+            .carve_region("test", ((0, 0, 0), (1, 1, 1)), "test")
+            .is_err());
+
+        let region = file.get_region_mut("test").unwrap();
+        region.annotate((2, 1, 2), Value::String("inside".to_string()));
+        region.annotate((8, 1, 2), Value::String("outside".to_string()));
+        region.set_anchor("inside", (3, 0, 3));
+        region.set_anchor("outside", (0, 0, 0));
+        region.create_group("carved", [(1, 0, 1), (5, 2, 4)]);
+        region.create_group("split", [(1, 0, 1), (6, 0, 1)]);
+
+        file.carve_region("test", ((5, 2, 4), (1, 0, 1)), "part")
+            .unwrap();
+
+        let source = file.get_region("test").unwrap();
+        let part = file.get_region("part").unwrap();
+
+        assert_eq!(part.size, Coordinates { x: 5, y: 3, z: 4 });
+        assert_eq!(file.metadata.region_count, 2);
+        assert_eq!(file.metadata.total_blocks, blocks);
+
+        for (position, block) in part.iter_blocks() {
+            let position = Coordinates {
+                x: position.x + 1,
+                y: position.y,
+                z: position.z + 1,
+            };
+
+            assert_eq!(block, original.get_block(position));
+            assert!(source.get_block(position).is_air());
+        }
+
+        assert!(source
+            .tile_entities
+            .iter()
+            .filter_map(value_position)
+            .all(|position| !(1..=5).contains(&position.x)
+                || !(0..=2).contains(&position.y)
+                || !(1..=4).contains(&position.z)));
+
+        assert_eq!(source.annotations().len(), 1);
+        assert!(source.annotations().get((8, 1, 2)).is_some());
+        assert_eq!(part.annotations().len(), 1);
+
+        assert_eq!(
+            source.anchors().collect::<Vec<_>>(),
+            vec![("outside", (0, 0, 0).into())]
+        );
+        assert_eq!(part.get_anchor("inside"), Some((2, 0, 2).into()));
+
+        assert_eq!(source.group_names().collect::<Vec<_>>(), vec!["split"]);
+        assert_eq!(
+            source.get_group("split").unwrap().positions(),
+            &[(6, 0, 1).into()]
+        );
+        assert_eq!(
+            part.group_names().collect::<Vec<_>>(),
+            vec!["carved", "split"]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::placement::{mirror_and_rotate, Mirror, Rotation};
+use crate::region::{index_3d, read_palette_index};
+use crate::structure::{Coordinates, Region};
+use crate::trace;
+
+/// The mirror planes and rotations that map the non-air content of a `Region` onto itself.
+///
+/// Created by `Region::detect_symmetry()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symmetry {
+    /// The mirrors that leave the content unchanged. `Mirror::FrontBack` means that the content is symmetric along the x axis,
+    /// `Mirror::LeftRight` that it is symmetric along the z axis.
+    pub mirrors: Vec<Mirror>,
+
+    /// The rotations around the y axis that leave the content unchanged, not including `Rotation::None`.
+    pub rotations: Vec<Rotation>,
+}
+
+impl Symmetry {
+    /// Checks whether the content has any mirror plane or rotational symmetry.
+    pub fn is_symmetric(&self) -> bool {
+        !self.mirrors.is_empty() || !self.rotations.is_empty()
+    }
+}
+
+impl Region {
+    /// Detects the horizontal mirror planes and the rotational symmetry of the non-air content of the region.
+    ///
+    /// Only the bounding box of the non-air blocks is considered, so symmetric content that is not centered in the region
+    /// is still detected. Block states have to match after being mirrored or rotated, e.g. a repeater facing east on one side
+    /// needs a repeater facing west on the other side for a `Mirror::FrontBack` symmetry.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::placement::Mirror;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// if region.detect_symmetry().mirrors.contains(&Mirror::FrontBack) {
+    ///     println!("Only half of the structure needs to be built by hand");
+    /// }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(volume = self.calc_volume(), elapsed_ms))
+    )]
+    pub fn detect_symmetry(&self) -> Symmetry {
+        let _timer = trace::Timer::start();
+
+        let required_bits = Self::calc_required_bits(&self.block_state_palette);
+        let bitmask = (1 << required_bits) - 1;
+
+        let palette_index = |position: Coordinates| {
+            let block_index = index_3d(self.size, position);
+
+            read_palette_index(&self.block_states, block_index, required_bits, bitmask) as usize
+        };
+
+        let Some((min, max)) = self
+            .iter_blocks()
+            .filter(|(_, block)| !block.is_air())
+            .map(|(position, _)| (position, position))
+            .reduce(|(min, max), (position, _)| {
+                (
+                    Coordinates {
+                        x: min.x.min(position.x),
+                        y: min.y.min(position.y),
+                        z: min.z.min(position.z),
+                    },
+                    Coordinates {
+                        x: max.x.max(position.x),
+                        y: max.y.max(position.y),
+                        z: max.z.max(position.z),
+                    },
+                )
+            })
+        else {
+            // there is no content, which is symmetric in every way
+            return Symmetry {
+                mirrors: vec![Mirror::LeftRight, Mirror::FrontBack],
+                rotations: vec![
+                    Rotation::Clockwise90,
+                    Rotation::Clockwise180,
+                    Rotation::Counterclockwise90,
+                ],
+            };
+        };
+
+        let size = Coordinates {
+            x: max.x - min.x + 1,
+            y: max.y - min.y + 1,
+            z: max.z - min.z + 1,
+        };
+
+        // checks that every block, transformed and moved by `map`, matches the block at its new position
+        let is_symmetric =
+            |mirror: Mirror, rotation: Rotation, map: &dyn Fn(i32, i32) -> (i32, i32)| {
+                let transformed = self
+                    .block_state_palette
+                    .iter()
+                    .map(|block| mirror_and_rotate(block, mirror, rotation))
+                    .collect::<Vec<_>>();
+
+                (0..size.y).all(|y| {
+                    (0..size.z).all(|z| {
+                        (0..size.x).all(|x| {
+                            let (target_x, target_z) = map(x, z);
+
+                            let source = palette_index(Coordinates {
+                                x: min.x + x,
+                                y: min.y + y,
+                                z: min.z + z,
+                            });
+                            let target = palette_index(Coordinates {
+                                x: min.x + target_x,
+                                y: min.y + y,
+                                z: min.z + target_z,
+                            });
+
+                            transformed[source] == self.block_state_palette[target]
+                        })
+                    })
+                })
+            };
+
+        let mut symmetry = Symmetry::default();
+
+        if is_symmetric(Mirror::LeftRight, Rotation::None, &|x, z| {
+            (x, size.z - 1 - z)
+        }) {
+            symmetry.mirrors.push(Mirror::LeftRight);
+        }
+
+        if is_symmetric(Mirror::FrontBack, Rotation::None, &|x, z| {
+            (size.x - 1 - x, z)
+        }) {
+            symmetry.mirrors.push(Mirror::FrontBack);
+        }
+
+        // quarter turns only map the bounding box onto itself if it is square
+        let square = size.x == size.z;
+
+        if square
+            && is_symmetric(Mirror::None, Rotation::Clockwise90, &|x, z| {
+                (size.z - 1 - z, x)
+            })
+        {
+            symmetry.rotations.push(Rotation::Clockwise90);
+        }
+
+        if is_symmetric(Mirror::None, Rotation::Clockwise180, &|x, z| {
+            (size.x - 1 - x, size.z - 1 - z)
+        }) {
+            symmetry.rotations.push(Rotation::Clockwise180);
+        }
+
+        if square
+            && is_symmetric(Mirror::None, Rotation::Counterclockwise90, &|x, z| {
+                (z, size.x - 1 - x)
+            })
+        {
+            symmetry.rotations.push(Rotation::Counterclockwise90);
+        }
+
+        symmetry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+
+    use super::*;
+
+    #[test]
+    fn mirrored_line() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let repeater = BlockStateBuilder::new("repeater")
+            .properties([("facing", "east")])
+            .build();
+
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 5, y: 2, z: 5 },
+        );
+        region.set_block((1, 1, 2), stone.clone());
+        region.set_block((2, 1, 2), repeater);
+        region.set_block((3, 1, 2), stone);
+
+        let symmetry = region.detect_symmetry();
+
+        // the repeater breaks the symmetry along the x axis, but not along the z axis
+        assert_eq!(symmetry.mirrors, vec![Mirror::LeftRight]);
+        assert!(symmetry.rotations.is_empty());
+    }
+
+    #[test]
+    fn rotational_symmetry() {
+        let stone = BlockStateBuilder::new("stone").build();
+
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 3, y: 1, z: 3 },
+        );
+
+        for position in [(0, 0, 0), (2, 0, 0), (0, 0, 2), (2, 0, 2)] {
+            region.set_block(position, stone.clone());
+        }
+
+        let symmetry = region.detect_symmetry();
+
+        assert_eq!(symmetry.mirrors.len(), 2);
+        assert_eq!(
+            symmetry.rotations,
+            vec![
+                Rotation::Clockwise90,
+                Rotation::Clockwise180,
+                Rotation::Counterclockwise90
+            ]
+        );
+    }
+}
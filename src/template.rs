@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashSet};
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::error::Result;
+use crate::region::{set_value_position, value_position};
+#[cfg(feature = "fs")]
+use crate::structure::LitematicaFile;
+use crate::structure::{Coordinates, Region};
+use crate::transform::Transform;
+
+/// A collection of small named templates, e.g. doors, pillars or lamps, that generator programs assemble builds from.
+///
+/// # Examples
+/// ```
+/// use ritematica::LitematicaFile;
+/// use ritematica::placement::Rotation;
+/// use ritematica::structure::Region;
+/// use ritematica::template::TemplateLibrary;
+/// use ritematica::transform::Transform;
+///
+/// let library = TemplateLibrary::load("templates").unwrap();
+/// let mut region = Region::new((64, 16, 64));
+///
+/// for x in (0..64).step_by(8) {
+///     library.insert(&mut region, "pillar", (x, 0, 0), &Transform::identity()).unwrap();
+///     library.insert(&mut region, "pillar", (x, 0, 63), &Transform::rotation(Rotation::Clockwise180)).unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TemplateLibrary {
+    templates: BTreeMap<String, Region>,
+}
+
+impl TemplateLibrary {
+    /// Creates an empty `TemplateLibrary`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every `.litematic` file directly inside of `directory` as a template named after the file without its
+    /// extension, e.g. `door` for `door.litematic`.
+    ///
+    /// Files with several regions are combined into a single template, where the first non-air block by region name
+    /// wins. Entities of those files are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or one of the files cannot be read.
+    #[cfg(feature = "fs")]
+    pub fn load(directory: impl AsRef<Path>) -> Result<Self> {
+        let mut library = Self::new();
+
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+
+            if !path.is_file()
+                || path
+                    .extension()
+                    .is_none_or(|extension| extension != "litematic")
+            {
+                continue;
+            }
+
+            let Some(name) = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+            else {
+                continue;
+            };
+
+            let file = LitematicaFile::read(&path)?;
+            let template = if file.regions.len() == 1 {
+                file.regions
+                    .into_values()
+                    .next()
+                    .expect("file has one region")
+            } else {
+                file.combined_region()
+            };
+
+            library.add(name, template);
+        }
+
+        Ok(library)
+    }
+
+    /// Adds a template to the library. A template with the same name is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the template.
+    /// * `template` - The `Region` of the template. Its `position` is the offset from the point it is inserted at.
+    pub fn add(&mut self, name: impl Into<String>, template: Region) {
+        self.templates.insert(name.into(), template);
+    }
+
+    /// Returns the template with the given name.
+    pub fn get(&self, name: &str) -> Option<&Region> {
+        self.templates.get(name)
+    }
+
+    /// Returns the names of all templates in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Inserts a template into `region`, after applying `transform` to it.
+    ///
+    /// The origin of the template is placed at `position`. Only the non-air blocks of the template are placed, so
+    /// templates can be layered, and they replace the blocks and tile entities that were there before. Tile entities,
+    /// scheduled ticks and entities of the template are copied along. Everything outside of `region` is cut off.
+    /// Returns the number of blocks that were placed.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The `Region` the template is inserted into.
+    /// * `name` - The name of the template.
+    /// * `position` - The position of the template origin, relative to the origin of `region`.
+    /// * `transform` - The `Transform` applied to the template before inserting, e.g. a rotation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no template with the given name. The region is left unchanged in that case.
+    pub fn insert(
+        &self,
+        region: &mut Region,
+        name: &str,
+        position: impl Into<Coordinates>,
+        transform: &Transform,
+    ) -> Result<usize> {
+        let template = self.get(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown template {}", name),
+            )
+        })?;
+
+        let template = template.transformed(transform);
+        let position = position.into();
+        let corner = template.min_corner();
+        let offset = Coordinates {
+            x: position.x + corner.x,
+            y: position.y + corner.y,
+            z: position.z + corner.z,
+        };
+
+        let shift = |local: Coordinates| Coordinates {
+            x: local.x + offset.x,
+            y: local.y + offset.y,
+            z: local.z + offset.z,
+        };
+
+        let mut placed = HashSet::new();
+
+        for (local, block) in template.iter_blocks() {
+            let target = shift(local);
+
+            if !block.is_air() && region.contains(target) {
+                region.set_block(target, block.clone());
+                placed.insert(target);
+            }
+        }
+
+        let replaced =
+            |value: &nbt::Value| value_position(value).is_some_and(|p| placed.contains(&p));
+
+        region.tile_entities.retain(|value| !replaced(value));
+        region.pending_block_ticks.retain(|value| !replaced(value));
+        region.pending_fluid_ticks.retain(|value| !replaced(value));
+
+        let copy_positioned = |values: &[nbt::Value]| {
+            values
+                .iter()
+                .filter_map(|value| {
+                    let target = shift(value_position(value)?);
+
+                    placed.contains(&target).then(|| {
+                        let mut value = value.clone();
+                        set_value_position(&mut value, target);
+                        value
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        region
+            .tile_entities
+            .extend(copy_positioned(&template.tile_entities));
+        region
+            .pending_block_ticks
+            .extend(copy_positioned(&template.pending_block_ticks));
+        region
+            .pending_fluid_ticks
+            .extend(copy_positioned(&template.pending_fluid_ticks));
+
+        for entity in &template.entities {
+            let mut entity = entity.clone();
+            entity.pos += offset.into();
+
+            if region.contains(entity.pos.block_position()) {
+                region.entities.push(entity);
+            }
+        }
+
+        Ok(placed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::placement::Rotation;
+
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let glass = BlockStateBuilder::new("glass").build();
+
+        // an L shape with a gap, so air in the template is visible
+        let mut pillar = Region::new((2, 2, 1));
+        pillar.set_block((0, 0, 0), stone.clone());
+        pillar.set_block((1, 0, 0), stone.clone());
+        pillar.set_block((0, 1, 0), stone.clone());
+
+        let mut library = TemplateLibrary::new();
+        library.add("pillar", pillar);
+
+        let mut region = Region::new((6, 4, 6));
+        region.set_block((1, 1, 0), glass.clone());
+
+        assert!(library
+            .insert(&mut region, "door", (0, 0, 0), &Transform::identity())
+            .is_err());
+
+        assert_eq!(
+            library
+                .insert(&mut region, "pillar", (0, 0, 0), &Transform::identity())
+                .unwrap(),
+            3
+        );
+        assert_eq!(region.get_block((1, 0, 0)), &stone);
+        assert_eq!(region.get_block((1, 1, 0)), &glass);
+
+        let rotation = Transform::rotation(Rotation::Clockwise90);
+        library
+            .insert(&mut region, "pillar", (4, 0, 3), &rotation)
+            .unwrap();
+
+        // (1, 0, 0) rotated clockwise ends up at (0, 0, 1)
+        assert_eq!(region.get_block((4, 0, 3)), &stone);
+        assert_eq!(region.get_block((4, 0, 4)), &stone);
+        assert!(region.get_block((5, 0, 3)).is_air());
+
+        // blocks outside of the region are cut off
+        assert_eq!(
+            library
+                .insert(&mut region, "pillar", (5, 3, 5), &Transform::identity())
+                .unwrap(),
+            1
+        );
+
+        assert_eq!(library.names().collect::<Vec<_>>(), ["pillar"]);
+    }
+
+    #[test]
+    fn load() {
+        let directory = std::env::temp_dir().join("ritematica_template_library");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        fs::copy("test.litematic", directory.join("machine.litematic")).unwrap();
+        fs::write(directory.join("notes.txt"), "not a template").unwrap();
+
+        let library = TemplateLibrary::load(&directory).unwrap();
+        let file = LitematicaFile::read("test.litematic").unwrap();
+
+        assert_eq!(library.names().collect::<Vec<_>>(), ["machine"]);
+        assert_eq!(
+            library.get("machine").unwrap().size,
+            file.get_region("test").unwrap().size
+        );
+    }
+}
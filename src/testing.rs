@@ -0,0 +1,417 @@
+//! Helpers for building small schematics with known contents in unit tests, so downstream crates don't have to ship
+//! binary `.litematic` fixtures.
+//!
+//! Everything here is deterministic: the same arguments always produce the same file, down to the timestamps.
+
+#[cfg(feature = "fs")]
+use std::fmt::Debug;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::block::BlockStateBuilder;
+use crate::file::LitematicaFileBuilder;
+use crate::structure::{BlockState, Coordinates, LitematicaFile, Region};
+
+/// Options for `assert_round_trip_with_options()`.
+#[derive(Debug, Clone, Default)]
+pub struct RoundTripOptions {
+    /// Compares entities, tile entities and pending ticks regardless of their order, for writers that reorder them.
+    pub ignore_list_order: bool,
+
+    /// Requires the block state palette and the packed block states to be identical, instead of only every position
+    /// resolving to the same block.
+    pub strict_palette: bool,
+}
+
+/// Creates a region of the given size at the schematic origin, with the block at every position chosen by `pattern`.
+///
+/// # Arguments
+///
+/// * `size` - The size of the region. All components must be positive.
+/// * `pattern` - Returns the `BlockState` for a position relative to the region origin, e.g. `checkerboard()`.
+///
+/// # Panics
+///
+/// Panics if a component of `size` is not positive.
+///
+/// # Examples
+/// ```
+/// use ritematica::BlockStateBuilder;
+/// use ritematica::testing::{layers, make_region};
+///
+/// let stone = BlockStateBuilder::new("stone").build();
+/// let dirt = BlockStateBuilder::new("dirt").build();
+///
+/// let region = make_region((4, 2, 4), layers([stone.clone(), dirt]));
+///
+/// assert_eq!(region.get_block((3, 0, 3)), &stone);
+/// ```
+pub fn make_region(
+    size: impl Into<Coordinates>,
+    pattern: impl Fn(Coordinates) -> BlockState,
+) -> Region {
+    let size = size.into();
+
+    assert!(
+        size.x > 0 && size.y > 0 && size.z > 0,
+        "Size {:?} must be positive",
+        size
+    );
+
+    let mut region = Region::new(size);
+
+    for y in 0..size.y {
+        for z in 0..size.z {
+            for x in 0..size.x {
+                let position = Coordinates { x, y, z };
+                let block = pattern(position);
+
+                if !block.is_air() {
+                    region.set_block(position, block);
+                }
+            }
+        }
+    }
+
+    region
+}
+
+/// Creates a file named `Test` by `ritematica` containing the given regions, with the metadata computed from them.
+///
+/// The creation and modification times are set to 0 and the data version to the one of 1.21, so the file is the
+/// same on every run.
+///
+/// # Examples
+/// ```
+/// use ritematica::BlockStateBuilder;
+/// use ritematica::testing::{make_file, make_region, solid};
+///
+/// let stone = BlockStateBuilder::new("stone").build();
+/// let file = make_file([("base", make_region((8, 1, 8), solid(stone)))]);
+///
+/// assert_eq!(file.metadata.total_blocks, 64);
+/// ```
+pub fn make_file<N: Into<String>>(
+    regions: impl IntoIterator<Item = (N, Region)>,
+) -> LitematicaFile {
+    let mut builder = LitematicaFileBuilder::new("Test").author("ritematica");
+
+    for (name, region) in regions {
+        builder = builder.region(name, region);
+    }
+
+    let mut file = builder.build();
+    file.metadata.time_created = 0;
+    file.metadata.time_modified = 0;
+
+    file
+}
+
+/// Reads the file at `path`, writes it, reads it back, and asserts that nothing changed on the way.
+///
+/// The metadata, versions and every region are compared structurally, so a palette that was reordered is fine as
+/// long as every position still resolves to the same block. See `assert_round_trip_with_options()` to configure this.
+///
+/// # Panics
+///
+/// Panics if the file cannot be read or written, or if the file that was read back differs from the original.
+///
+/// # Examples
+/// ```
+/// use ritematica::testing::assert_round_trip;
+///
+/// for fixture in ["fixtures/farm.litematic", "fixtures/castle.litematic"] {
+///     assert_round_trip(fixture);
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn assert_round_trip(path: impl AsRef<Path>) {
+    assert_round_trip_with_options(path, &RoundTripOptions::default())
+}
+
+/// Reads the file at `path`, writes it, reads it back, and asserts that nothing changed on the way, using the given
+/// `RoundTripOptions`.
+///
+/// # Panics
+///
+/// Panics if the file cannot be read or written, or if the file that was read back differs from the original.
+#[cfg(feature = "fs")]
+pub fn assert_round_trip_with_options(path: impl AsRef<Path>, options: &RoundTripOptions) {
+    let path = path.as_ref();
+
+    let original = LitematicaFile::read(path)
+        .unwrap_or_else(|error| panic!("Failed to read {}: {}", path.display(), error));
+
+    let bytes = original
+        .to_bytes()
+        .unwrap_or_else(|error| panic!("Failed to write {}: {}", path.display(), error));
+
+    let written = LitematicaFile::from_bytes(&bytes)
+        .unwrap_or_else(|error| panic!("Failed to read back {}: {}", path.display(), error));
+
+    assert_eq!(
+        written.metadata,
+        original.metadata,
+        "Metadata of {} changed",
+        path.display()
+    );
+    assert_eq!(
+        written.minecraft_data_version,
+        original.minecraft_data_version,
+        "Data version of {} changed",
+        path.display()
+    );
+    assert_eq!(
+        written.version,
+        original.version,
+        "Version of {} changed",
+        path.display()
+    );
+    assert_eq!(
+        written.extensions,
+        original.extensions,
+        "Extensions of {} changed",
+        path.display()
+    );
+
+    let mut names = original.regions.keys().collect::<Vec<_>>();
+    let mut written_names = written.regions.keys().collect::<Vec<_>>();
+    names.sort();
+    written_names.sort();
+
+    assert_eq!(
+        written_names,
+        names,
+        "Regions of {} changed",
+        path.display()
+    );
+
+    for name in names {
+        let context = format!("region {} of {}", name, path.display());
+        assert_regions_eq(
+            &written.regions[name],
+            &original.regions[name],
+            options,
+            &context,
+        );
+    }
+}
+
+#[cfg(feature = "fs")]
+fn assert_regions_eq(
+    written: &Region,
+    original: &Region,
+    options: &RoundTripOptions,
+    context: &str,
+) {
+    assert_eq!(
+        written.position, original.position,
+        "Position of {} changed",
+        context
+    );
+    assert_eq!(written.size, original.size, "Size of {} changed", context);
+    assert_eq!(
+        written.extensions, original.extensions,
+        "Extensions of {} changed",
+        context
+    );
+
+    if options.strict_palette {
+        assert_eq!(
+            written.block_state_palette, original.block_state_palette,
+            "Palette of {} changed",
+            context
+        );
+        assert_eq!(
+            written.block_states, original.block_states,
+            "Block states of {} changed",
+            context
+        );
+    } else if let Some(((position, block), (_, original_block))) = written
+        .iter_blocks()
+        .zip(original.iter_blocks())
+        .find(|((_, a), (_, b))| a != b)
+    {
+        panic!(
+            "Block at {:?} of {} changed from {} to {}",
+            position, context, original_block, block
+        );
+    }
+
+    let lists = [
+        (
+            "Entities",
+            compare_lists(&written.entities, &original.entities, options),
+        ),
+        (
+            "Tile entities",
+            compare_lists(&written.tile_entities, &original.tile_entities, options),
+        ),
+        (
+            "Pending block ticks",
+            compare_lists(
+                &written.pending_block_ticks,
+                &original.pending_block_ticks,
+                options,
+            ),
+        ),
+        (
+            "Pending fluid ticks",
+            compare_lists(
+                &written.pending_fluid_ticks,
+                &original.pending_fluid_ticks,
+                options,
+            ),
+        ),
+    ];
+
+    for (list, difference) in lists {
+        if let Some(difference) = difference {
+            panic!("{} of {} changed: {}", list, context, difference);
+        }
+    }
+}
+
+/// Compares two lists, in order or as multisets, and describes the first difference.
+#[cfg(feature = "fs")]
+fn compare_lists<T: PartialEq + Debug>(
+    written: &[T],
+    original: &[T],
+    options: &RoundTripOptions,
+) -> Option<String> {
+    if written.len() != original.len() {
+        return Some(format!(
+            "{} entries instead of {}",
+            written.len(),
+            original.len()
+        ));
+    }
+
+    if !options.ignore_list_order {
+        return written
+            .iter()
+            .zip(original)
+            .position(|(a, b)| a != b)
+            .map(|index| format!("{:?} instead of {:?}", written[index], original[index]));
+    }
+
+    let mut unmatched = written.iter().collect::<Vec<_>>();
+
+    for entry in original {
+        match unmatched.iter().position(|candidate| *candidate == entry) {
+            Some(index) => {
+                unmatched.swap_remove(index);
+            }
+            None => return Some(format!("{:?} is missing", entry)),
+        }
+    }
+
+    None
+}
+
+/// Returns a pattern that places `block` everywhere.
+pub fn solid(block: BlockState) -> impl Fn(Coordinates) -> BlockState {
+    move |_| block.clone()
+}
+
+/// Returns a pattern that alternates between `first` and `second` in every direction, starting with `first` at the
+/// region origin.
+pub fn checkerboard(first: BlockState, second: BlockState) -> impl Fn(Coordinates) -> BlockState {
+    move |position| {
+        if (position.x + position.y + position.z) % 2 == 0 {
+            first.clone()
+        } else {
+            second.clone()
+        }
+    }
+}
+
+/// Returns a pattern that fills every layer along the y axis with the next block of `blocks`, starting over when they
+/// run out.
+///
+/// # Panics
+///
+/// Panics if `blocks` is empty.
+pub fn layers(blocks: impl IntoIterator<Item = BlockState>) -> impl Fn(Coordinates) -> BlockState {
+    let blocks = blocks.into_iter().collect::<Vec<_>>();
+
+    assert!(!blocks.is_empty(), "Layers need at least one block");
+
+    move |position| blocks[position.y as usize % blocks.len()].clone()
+}
+
+/// Returns a pattern that gives every position its own block state, e.g. to check that positions are not mixed up.
+///
+/// The blocks are `minecraft:target` with the position in the `x`, `y` and `z` properties.
+pub fn unique() -> impl Fn(Coordinates) -> BlockState {
+    |position| {
+        BlockStateBuilder::new("target")
+            .properties([
+                ("x", position.x.to_string()),
+                ("y", position.y.to_string()),
+                ("z", position.z.to_string()),
+            ])
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures() {
+        let stone = BlockStateBuilder::new("stone").build();
+        let air = BlockStateBuilder::new("air").build();
+
+        let region = make_region((3, 3, 3), checkerboard(stone.clone(), air));
+
+        assert_eq!(region.get_block((0, 0, 0)), &stone);
+        assert!(region.get_block((1, 0, 0)).is_air());
+        assert_eq!(region.get_block((1, 1, 0)), &stone);
+
+        let unique_region = make_region((2, 3, 4), unique());
+        for (position, block) in unique_region.iter_blocks() {
+            assert_eq!(block, &unique()(position));
+        }
+
+        let file = make_file([("a", region.clone()), ("b", unique_region)]);
+        let again = make_file([("a", region), ("b", make_region((2, 3, 4), unique()))]);
+
+        assert_eq!(file.metadata.total_blocks, 14 + 24);
+        assert_eq!(file.metadata.time_created, 0);
+        assert_eq!(file.compute_checksum(), again.compute_checksum());
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip("test.litematic");
+
+        let options = RoundTripOptions {
+            ignore_list_order: true,
+            strict_palette: true,
+        };
+        assert_round_trip_with_options("test.litematic", &options);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to read")]
+    fn round_trip_missing_file() {
+        assert_round_trip("missing.litematic");
+    }
+
+    #[test]
+    fn compare_unordered_lists() {
+        let ordered = RoundTripOptions::default();
+        let unordered = RoundTripOptions {
+            ignore_list_order: true,
+            ..Default::default()
+        };
+
+        assert!(compare_lists(&[1, 2, 2], &[1, 2, 2], &ordered).is_none());
+        assert!(compare_lists(&[2, 1, 2], &[1, 2, 2], &ordered).is_some());
+        assert!(compare_lists(&[2, 1, 2], &[1, 2, 2], &unordered).is_none());
+        assert!(compare_lists(&[1, 1, 2], &[1, 2, 2], &unordered).is_some());
+        assert!(compare_lists(&[1, 2], &[1, 2, 2], &unordered).is_some());
+    }
+}
@@ -0,0 +1,37 @@
+//! Helpers for the optional `tracing` instrumentation, which compile to nothing without the `tracing` feature.
+//!
+//! Instrumented functions declare their fields with `#[cfg_attr(feature = "tracing", tracing::instrument(..))]`
+//! and fill them in with `record()` and `Timer`.
+
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// Records the time since its creation in the `elapsed_ms` field of the current span when it is dropped.
+pub(crate) struct Timer {
+    #[cfg(feature = "tracing")]
+    start: Instant,
+}
+
+impl Timer {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+        tracing::Span::current().record("elapsed_ms", elapsed_ms);
+    }
+}
+
+/// Records `value` in the declared `field` of the current span.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record(field: &'static str, value: u64) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record(field, value);
+}
@@ -0,0 +1,395 @@
+use crate::placement::{transform_region, Mirror, Rotation, Step};
+use crate::structure::{Coordinates, Region};
+
+/// The horizontal part of every combination of a mirror and a rotation, as `[xx, xz, zx, zz]` matrices in the order
+/// `(mirror, rotation)`. `Mirror::LeftRight` is not needed, since it is the same as `FrontBack` rotated by 180 degrees.
+const ORIENTATIONS: [(Mirror, Rotation, [i32; 4]); 8] = [
+    (Mirror::None, Rotation::None, [1, 0, 0, 1]),
+    (Mirror::None, Rotation::Clockwise90, [0, -1, 1, 0]),
+    (Mirror::None, Rotation::Clockwise180, [-1, 0, 0, -1]),
+    (Mirror::None, Rotation::Counterclockwise90, [0, 1, -1, 0]),
+    (Mirror::FrontBack, Rotation::None, [-1, 0, 0, 1]),
+    (Mirror::FrontBack, Rotation::Clockwise90, [0, -1, -1, 0]),
+    (Mirror::FrontBack, Rotation::Clockwise180, [1, 0, 0, -1]),
+    (
+        Mirror::FrontBack,
+        Rotation::Counterclockwise90,
+        [0, 1, 1, 0],
+    ),
+];
+
+/// A geometric transformation of block positions: a scale, followed by a mirror, a rotation around the y axis and a
+/// translation.
+///
+/// Transforms are composed with `then()`, so a whole pipeline of operations can be built up front and applied to a
+/// region at once with `Region::transformed()`. Rotations and mirrors work like in Litematica, around the schematic
+/// origin.
+///
+/// # Examples
+/// ```
+/// use ritematica::placement::Rotation;
+/// use ritematica::structure::Coordinates;
+/// use ritematica::transform::Transform;
+///
+/// let transform = Transform::rotation(Rotation::Clockwise90).then(&Transform::translation((10, 0, 0)));
+///
+/// assert_eq!(transform.apply((1, 0, 0)), Coordinates { x: 10, y: 0, z: 1 });
+/// assert_eq!(transform.inverse().unwrap().apply((10, 0, 1)), Coordinates { x: 1, y: 0, z: 0 });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+    /// The horizontal rotation and mirror as `[xx, xz, zx, zz]`.
+    matrix: [i32; 4],
+    scale: i32,
+    translation: Coordinates,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    /// Creates a `Transform` that doesn't change anything.
+    pub fn identity() -> Self {
+        Self {
+            matrix: [1, 0, 0, 1],
+            scale: 1,
+            translation: Coordinates::default(),
+        }
+    }
+
+    /// Creates a `Transform` that moves positions by `offset`.
+    pub fn translation(offset: impl Into<Coordinates>) -> Self {
+        Self {
+            translation: offset.into(),
+            ..Self::identity()
+        }
+    }
+
+    /// Creates a `Transform` that rotates positions around the y axis through the origin.
+    pub fn rotation(rotation: Rotation) -> Self {
+        Self::orientation(Mirror::None, rotation)
+    }
+
+    /// Creates a `Transform` that mirrors positions through the origin.
+    pub fn mirror(mirror: Mirror) -> Self {
+        Self::orientation(mirror, Rotation::None)
+    }
+
+    /// Creates a `Transform` that scales every block up to a cube of `factor` blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not positive.
+    pub fn scale(factor: i32) -> Self {
+        assert!(factor > 0, "Scale {} must be positive", factor);
+
+        Self {
+            scale: factor,
+            ..Self::identity()
+        }
+    }
+
+    /// Returns the `Transform` that applies this transform first and `next` afterwards.
+    pub fn then(&self, next: &Transform) -> Transform {
+        let [a, b, c, d] = next.matrix;
+        let [e, f, g, h] = self.matrix;
+
+        Transform {
+            matrix: [a * e + b * g, a * f + b * h, c * e + d * g, c * f + d * h],
+            scale: self.scale * next.scale,
+            translation: next.apply(self.translation),
+        }
+    }
+
+    /// Returns the `Transform` that undoes this transform, or `None` if it scales, since shrinking loses blocks.
+    pub fn inverse(&self) -> Option<Transform> {
+        if self.scale != 1 {
+            return None;
+        }
+
+        // the matrices of rotations and mirrors are orthogonal, so their inverse is their transpose
+        let [a, b, c, d] = self.matrix;
+        let linear = Transform {
+            matrix: [a, c, b, d],
+            ..Self::identity()
+        };
+
+        let translation = linear.apply(self.translation);
+
+        Some(Transform {
+            translation: Coordinates {
+                x: -translation.x,
+                y: -translation.y,
+                z: -translation.z,
+            },
+            ..linear
+        })
+    }
+
+    /// Applies the transform to a position. With a scale, this is the position of the scaled copy of the block that
+    /// is closest to the origin before mirroring and rotating.
+    pub fn apply(&self, position: impl Into<Coordinates>) -> Coordinates {
+        let position = position.into();
+        let [a, b, c, d] = self.matrix;
+        let (x, y, z) = (
+            position.x * self.scale,
+            position.y * self.scale,
+            position.z * self.scale,
+        );
+
+        add(
+            Coordinates {
+                x: a * x + b * z,
+                y,
+                z: c * x + d * z,
+            },
+            self.translation,
+        )
+    }
+
+    /// Returns the mirror and rotation that make up the horizontal part of the transform.
+    pub fn orientation_parts(&self) -> (Mirror, Rotation) {
+        ORIENTATIONS
+            .iter()
+            .find(|(_, _, matrix)| *matrix == self.matrix)
+            .map(|(mirror, rotation, _)| (*mirror, *rotation))
+            .expect("only rotations and mirrors are composed")
+    }
+
+    /// Returns the scale factor of the transform.
+    pub fn scale_factor(&self) -> i32 {
+        self.scale
+    }
+
+    /// Returns the translation of the transform, which is applied last.
+    pub fn translation_part(&self) -> Coordinates {
+        self.translation
+    }
+
+    fn orientation(mirror: Mirror, rotation: Rotation) -> Self {
+        // mirroring left and right is the same as mirroring front and back and turning around
+        let (mirror, rotation) = match mirror {
+            Mirror::LeftRight => (
+                Mirror::FrontBack,
+                match rotation {
+                    Rotation::None => Rotation::Clockwise180,
+                    Rotation::Clockwise90 => Rotation::Counterclockwise90,
+                    Rotation::Clockwise180 => Rotation::None,
+                    Rotation::Counterclockwise90 => Rotation::Clockwise90,
+                },
+            ),
+            mirror => (mirror, rotation),
+        };
+
+        let matrix = ORIENTATIONS
+            .iter()
+            .find(|(m, r, _)| *m == mirror && *r == rotation)
+            .map(|(_, _, matrix)| *matrix)
+            .expect("every orientation is listed");
+
+        Self {
+            matrix,
+            ..Self::identity()
+        }
+    }
+}
+
+impl Region {
+    /// Returns a copy of the region with the `Transform` applied to its position, blocks, entities, tile entities,
+    /// scheduled ticks, annotations, groups and anchors.
+    ///
+    /// Block states are rotated and mirrored like with `LitematicaFile::apply_placement()`. When scaling, every block,
+    /// tile entity, annotation and group position is copied into all blocks of its cube, while anchors stay at the
+    /// copy closest to the region origin. The returned region always has a positive size.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::placement::Rotation;
+    /// use ritematica::transform::Transform;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let big = region.transformed(&Transform::scale(2).then(&Transform::rotation(Rotation::Clockwise90)));
+    /// ```
+    pub fn transformed(&self, transform: &Transform) -> Region {
+        let (mirror, rotation) = transform.orientation_parts();
+
+        let step = Step {
+            mirror,
+            rotation,
+            from: Coordinates::default(),
+            to: Coordinates::default(),
+        };
+
+        // scaling commutes with mirrors and rotations, so it can be done after them
+        let mut region = transform_region(self, &[step], true);
+
+        if transform.scale != 1 {
+            region = scale_region(&region, transform.scale);
+        }
+
+        region.position = add(region.position, transform.translation);
+        region
+    }
+}
+
+/// Returns a copy of `region`, which must have a positive size, with every block scaled up to a cube of `scale` blocks.
+fn scale_region(region: &Region, scale: i32) -> Region {
+    let times = |position: Coordinates| Coordinates {
+        x: position.x * scale,
+        y: position.y * scale,
+        z: position.z * scale,
+    };
+
+    let cube = |position: Coordinates| {
+        let min = times(position);
+
+        (0..scale).flat_map(move |y| {
+            (0..scale).flat_map(move |z| (0..scale).map(move |x| add(min, Coordinates { x, y, z })))
+        })
+    };
+
+    let mut scaled = Region::filled_with_air(times(region.position), times(region.size));
+
+    for (position, block) in region.iter_blocks() {
+        if !block.is_air() {
+            for target in cube(position) {
+                scaled.set_block(target, block.clone());
+            }
+        }
+    }
+
+    for entity in &region.entities {
+        let mut entity = entity.clone();
+        entity.pos = entity.pos * scale as f64;
+        scaled.entities.push(entity);
+    }
+
+    let copy_values = |values: &[nbt::Value]| {
+        values
+            .iter()
+            .flat_map(|value| {
+                let position = crate::region::value_position(value);
+
+                position.into_iter().flat_map(cube).map(|target| {
+                    let mut value = value.clone();
+                    crate::region::set_value_position(&mut value, target);
+                    value
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    scaled.tile_entities = copy_values(&region.tile_entities);
+    scaled.pending_block_ticks = copy_values(&region.pending_block_ticks);
+    scaled.pending_fluid_ticks = copy_values(&region.pending_fluid_ticks);
+
+    for (position, data) in region.annotations().iter() {
+        for target in cube(position) {
+            scaled.extensions.annotations.insert(target, data.clone());
+        }
+    }
+
+    for name in region.group_names() {
+        let positions = region
+            .get_group(name)
+            .expect("group exists")
+            .positions()
+            .iter()
+            .flat_map(|position| cube(*position))
+            .collect::<Vec<_>>();
+
+        scaled.create_group(name, positions);
+    }
+
+    for (name, position) in region.anchors() {
+        scaled.set_anchor(name, times(position));
+    }
+
+    scaled
+}
+
+fn add(a: Coordinates, b: Coordinates) -> Coordinates {
+    Coordinates {
+        x: a.x + b.x,
+        y: a.y + b.y,
+        z: a.z + b.z,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn compose_and_invert() {
+        let transform = Transform::scale(1)
+            .then(&Transform::mirror(Mirror::LeftRight))
+            .then(&Transform::rotation(Rotation::Clockwise90))
+            .then(&Transform::translation((3, -2, 7)));
+
+        let inverse = transform.inverse().unwrap();
+
+        for position in [(0, 0, 0), (1, 2, 3), (-4, 5, -6)] {
+            let position = Coordinates::from(position);
+
+            assert_eq!(inverse.apply(transform.apply(position)), position);
+            assert_eq!(transform.then(&inverse).apply(position), position);
+        }
+
+        let turned = Transform::rotation(Rotation::Clockwise90)
+            .then(&Transform::rotation(Rotation::Clockwise90));
+        assert_eq!(turned, Transform::rotation(Rotation::Clockwise180));
+        assert_eq!(
+            Transform::mirror(Mirror::LeftRight).orientation_parts(),
+            (Mirror::FrontBack, Rotation::Clockwise180)
+        );
+
+        assert!(Transform::scale(2).inverse().is_none());
+    }
+
+    #[test]
+    fn transformed_region() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+
+        let rotate =
+            Transform::rotation(Rotation::Clockwise90).then(&Transform::translation((5, 0, 0)));
+        let back = region
+            .transformed(&rotate)
+            .transformed(&rotate.inverse().unwrap());
+
+        assert_eq!(back.min_corner(), region.min_corner());
+        assert!(region
+            .iter_blocks()
+            .all(|(position, block)| back.get_block(position) == block));
+
+        let mut small = Region::filled_with_air(
+            Coordinates { x: 1, y: 0, z: 0 },
+            Coordinates { x: 2, y: 1, z: 1 },
+        );
+        let stone = BlockStateBuilder::new("stone").build();
+        small.set_block((1, 0, 0), stone.clone());
+        small.set_anchor("stone", (1, 0, 0));
+
+        let big = small.transformed(&Transform::scale(3));
+
+        assert_eq!(big.position, Coordinates { x: 3, y: 0, z: 0 });
+        assert_eq!(big.size, Coordinates { x: 6, y: 3, z: 3 });
+        assert_eq!(big.get_block((3, 0, 0)), &stone);
+        assert_eq!(big.get_block((5, 2, 2)), &stone);
+        assert!(big.get_block((2, 2, 2)).is_air());
+        assert_eq!(
+            big.get_anchor("stone"),
+            Some(Coordinates { x: 3, y: 0, z: 0 })
+        );
+    }
+}
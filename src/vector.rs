@@ -0,0 +1,249 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::structure::Coordinates;
+
+/// A position or velocity with floating point components, e.g. the `Pos` and `Motion` of an `Entity`.
+///
+/// Stored as a list of three doubles, like Minecraft does.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vec3 {
+    /// Creates a new `Vec3` from its components.
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the position of the block containing this point.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::structure::Coordinates;
+    /// use ritematica::vector::Vec3;
+    ///
+    /// assert_eq!(Vec3::new(1.5, 64.0, -0.5).block_position(), Coordinates { x: 1, y: 64, z: -1 });
+    /// ```
+    pub fn block_position(self) -> Coordinates {
+        Coordinates {
+            x: self.x.floor() as i32,
+            y: self.y.floor() as i32,
+            z: self.z.floor() as i32,
+        }
+    }
+
+    /// Returns the length of the vector.
+    pub fn length(self) -> f64 {
+        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, factor: f64) -> Vec3 {
+        Vec3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, other: Vec3) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, other: Vec3) {
+        *self = *self - other;
+    }
+}
+
+impl From<[f64; 3]> for Vec3 {
+    fn from([x, y, z]: [f64; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<Vec3> for [f64; 3] {
+    fn from(vector: Vec3) -> Self {
+        [vector.x, vector.y, vector.z]
+    }
+}
+
+impl From<Coordinates> for Vec3 {
+    fn from(coordinates: Coordinates) -> Self {
+        Self {
+            x: coordinates.x as f64,
+            y: coordinates.y as f64,
+            z: coordinates.z as f64,
+        }
+    }
+}
+
+// serialized as a slice, because serde treats arrays as tuples, which NBT cannot represent
+impl Serialize for Vec3 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        [self.x, self.y, self.z][..].serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vec3 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let list = Vec::<f64>::deserialize(deserializer)?;
+
+        match list[..] {
+            [x, y, z] => Ok(Self { x, y, z }),
+            _ => Err(D::Error::invalid_length(list.len(), &"a list of 3 numbers")),
+        }
+    }
+}
+
+/// The rotation of an `Entity` in degrees, e.g. its `Rotation`.
+///
+/// Stored as a list of two floats, like Minecraft does.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rotation2 {
+    /// The rotation around the y axis, where `0` faces south and `90` faces west.
+    pub yaw: f32,
+
+    /// The vertical rotation, where `-90` faces up and `90` faces down.
+    pub pitch: f32,
+}
+
+impl Rotation2 {
+    /// Creates a new `Rotation2` from the yaw and pitch in degrees.
+    pub fn new(yaw: f32, pitch: f32) -> Self {
+        Self { yaw, pitch }
+    }
+
+    /// Returns the unit vector the rotation is looking at.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::vector::Rotation2;
+    ///
+    /// let south = Rotation2::new(0.0, 0.0).direction();
+    ///
+    /// assert!((south.z - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn direction(self) -> Vec3 {
+        let yaw = (self.yaw as f64).to_radians();
+        let pitch = (self.pitch as f64).to_radians();
+
+        Vec3::new(
+            -yaw.sin() * pitch.cos(),
+            -pitch.sin(),
+            yaw.cos() * pitch.cos(),
+        )
+    }
+}
+
+impl From<[f32; 2]> for Rotation2 {
+    fn from([yaw, pitch]: [f32; 2]) -> Self {
+        Self { yaw, pitch }
+    }
+}
+
+impl From<Rotation2> for [f32; 2] {
+    fn from(rotation: Rotation2) -> Self {
+        [rotation.yaw, rotation.pitch]
+    }
+}
+
+impl Serialize for Rotation2 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        [self.yaw, self.pitch][..].serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rotation2 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let list = Vec::<f32>::deserialize(deserializer)?;
+
+        match list[..] {
+            [yaw, pitch] => Ok(Self { yaw, pitch }),
+            _ => Err(D::Error::invalid_length(list.len(), &"a list of 2 numbers")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::{Entity, LitematicaFile};
+
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let mut position = Vec3::new(1.0, 2.0, 3.0);
+        position += Vec3::from([0.5, 0.5, 0.5]) * 2.0;
+
+        assert_eq!(position, Vec3::new(2.0, 3.0, 4.0));
+        assert_eq!(-position - position, Vec3::new(-4.0, -6.0, -8.0));
+        assert_eq!(
+            Vec3::new(-0.5, 1.0, 2.9).block_position(),
+            Coordinates { x: -1, y: 1, z: 2 }
+        );
+        assert_eq!(Vec3::new(3.0, 0.0, 4.0).length(), 5.0);
+    }
+
+    #[test]
+    fn entities_round_trip() {
+        let path = std::env::temp_dir().join("ritematica_entity_vectors_round_trip.litematic");
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let entity = Entity {
+            rotation: Rotation2::new(90.0, -12.5),
+            fire: -1,
+            pos: Vec3::new(2.5, 4.0, 2.5),
+            motion: Vec3::new(0.0, -0.08, 0.0),
+            air: 300,
+            fall_distance: 0.0,
+            on_ground: true,
+            id: "minecraft:armor_stand".to_string(),
+            portal_cooldown: 0,
+            uuid: vec![1, 2, 3, 4],
+            invulnerable: false,
+            facing: None,
+        };
+        file.get_region_mut("test")
+            .unwrap()
+            .entities
+            .push(entity.clone());
+
+        file.write(&path).unwrap();
+
+        let file = LitematicaFile::read(&path).unwrap();
+        assert_eq!(file.get_region("test").unwrap().entities, vec![entity]);
+    }
+}
@@ -0,0 +1,154 @@
+use std::io;
+
+use crate::error::Result;
+use crate::structure::LitematicaFile;
+
+/// The data versions of all Minecraft releases Litematica supports, oldest first.
+const VERSIONS: [(i32, &str); 46] = [
+    (1139, "1.12"),
+    (1241, "1.12.1"),
+    (1343, "1.12.2"),
+    (1519, "1.13"),
+    (1628, "1.13.1"),
+    (1631, "1.13.2"),
+    (1952, "1.14"),
+    (1957, "1.14.1"),
+    (1963, "1.14.2"),
+    (1968, "1.14.3"),
+    (1976, "1.14.4"),
+    (2225, "1.15"),
+    (2227, "1.15.1"),
+    (2230, "1.15.2"),
+    (2566, "1.16"),
+    (2567, "1.16.1"),
+    (2578, "1.16.2"),
+    (2580, "1.16.3"),
+    (2584, "1.16.4"),
+    (2586, "1.16.5"),
+    (2724, "1.17"),
+    (2730, "1.17.1"),
+    (2860, "1.18"),
+    (2865, "1.18.1"),
+    (2975, "1.18.2"),
+    (3105, "1.19"),
+    (3117, "1.19.1"),
+    (3120, "1.19.2"),
+    (3218, "1.19.3"),
+    (3337, "1.19.4"),
+    (3463, "1.20"),
+    (3465, "1.20.1"),
+    (3578, "1.20.2"),
+    (3698, "1.20.3"),
+    (3700, "1.20.4"),
+    (3837, "1.20.5"),
+    (3839, "1.20.6"),
+    (3953, "1.21"),
+    (3955, "1.21.1"),
+    (4080, "1.21.2"),
+    (4082, "1.21.3"),
+    (4189, "1.21.4"),
+    (4325, "1.21.5"),
+    (4435, "1.21.6"),
+    (4438, "1.21.7"),
+    (4440, "1.21.8"),
+];
+
+/// Returns the name of the Minecraft release with the given data version, e.g. `"1.20.1"` for `3465`.
+///
+/// Returns `None` for snapshots and versions that are not in the bundled table.
+///
+/// # Examples
+/// ```
+/// use ritematica::version::version_name;
+///
+/// assert_eq!(version_name(3465), Some("1.20.1"));
+/// assert_eq!(version_name(3464), None);
+/// ```
+pub fn version_name(data_version: i32) -> Option<&'static str> {
+    VERSIONS
+        .iter()
+        .find(|(version, _)| *version == data_version)
+        .map(|(_, name)| *name)
+}
+
+/// Returns the data version of the Minecraft release with the given name, e.g. `3465` for `"1.20.1"`.
+///
+/// Returns `None` for snapshots and versions that are not in the bundled table.
+pub fn data_version(name: &str) -> Option<i32> {
+    VERSIONS
+        .iter()
+        .find(|(_, version)| *version == name)
+        .map(|(data_version, _)| *data_version)
+}
+
+impl LitematicaFile {
+    /// Returns the name of the Minecraft release the file was created with, based on `minecraft_data_version`.
+    ///
+    /// Returns `None` for snapshots and versions that are not in the bundled table, see `version::version_name()`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    ///
+    /// println!("made in {}", file.minecraft_version_name().unwrap_or("an unknown version"));
+    /// ```
+    pub fn minecraft_version_name(&self) -> Option<&'static str> {
+        version_name(self.minecraft_data_version)
+    }
+
+    /// Sets `minecraft_data_version` to the data version of the Minecraft release with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release is not in the bundled table. The file is left unchanged in that case.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let mut file = LitematicaFile::read("test.litematic").unwrap();
+    /// file.set_minecraft_version("1.20.1").unwrap();
+    ///
+    /// assert_eq!(file.minecraft_data_version, 3465);
+    /// ```
+    pub fn set_minecraft_version(&mut self, name: &str) -> Result<()> {
+        self.minecraft_data_version = data_version(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown Minecraft version {}", name),
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_names() {
+        assert_eq!(version_name(2586), Some("1.16.5"));
+        assert_eq!(data_version("1.20.1"), Some(3465));
+        assert_eq!(data_version("23w31a"), None);
+
+        // the table is sorted and every entry maps both ways
+        assert!(VERSIONS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        for (version, name) in VERSIONS {
+            assert_eq!(data_version(name), Some(version));
+            assert_eq!(version_name(version), Some(name));
+        }
+
+        let mut file = LitematicaFile::read("test.litematic").unwrap();
+        let original = file.minecraft_data_version;
+
+        assert!(file.set_minecraft_version("0.30").is_err());
+        assert_eq!(file.minecraft_data_version, original);
+
+        file.set_minecraft_version("1.19.4").unwrap();
+        assert_eq!(file.minecraft_version_name(), Some("1.19.4"));
+    }
+}
@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
+
+use crate::error::Result;
+use crate::structure::{BlockState, Coordinates, Region};
+
+/// Anything blocks can be read from by world coordinates, e.g. a world save, a server or a region placed in the world.
+///
+/// Implementing this for a world backend makes it usable with `Region::verify_against()` and `Region::from_source()`.
+pub trait BlockSource {
+    /// Returns the block at the given world position, or `None` if it is unknown, e.g. because its chunk isn't loaded.
+    fn block_at(&self, position: Coordinates) -> Option<Cow<'_, BlockState>>;
+}
+
+/// Anything blocks can be written to by world coordinates, e.g. a world save or a connection to a server.
+///
+/// Implementing this for a world backend makes it usable with `Region::paste_into()`.
+pub trait BlockSink {
+    /// Sets the block at the given world position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block cannot be set, e.g. because the position is outside of the world.
+    fn set_block_at(&mut self, position: Coordinates, block: BlockState) -> Result<()>;
+}
+
+impl BlockSource for HashMap<Coordinates, BlockState> {
+    fn block_at(&self, position: Coordinates) -> Option<Cow<'_, BlockState>> {
+        self.get(&position).map(Cow::Borrowed)
+    }
+}
+
+impl BlockSink for HashMap<Coordinates, BlockState> {
+    fn set_block_at(&mut self, position: Coordinates, block: BlockState) -> Result<()> {
+        self.insert(position, block);
+        Ok(())
+    }
+}
+
+/// A `Region` placed in the world with its schematic origin at `origin`. Created by `Region::placed_at()`.
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedRegion<'a> {
+    region: &'a Region,
+    origin: Coordinates,
+}
+
+/// A mutable `Region` placed in the world with its schematic origin at `origin`. Created by `Region::placed_at_mut()`.
+#[derive(Debug)]
+pub struct PlacedRegionMut<'a> {
+    region: &'a mut Region,
+    origin: Coordinates,
+}
+
+impl BlockSource for PlacedRegion<'_> {
+    fn block_at(&self, position: Coordinates) -> Option<Cow<'_, BlockState>> {
+        self.region
+            .get(to_local(self.region, self.origin, position))
+            .map(Cow::Borrowed)
+    }
+}
+
+impl BlockSource for PlacedRegionMut<'_> {
+    fn block_at(&self, position: Coordinates) -> Option<Cow<'_, BlockState>> {
+        self.region
+            .get(to_local(self.region, self.origin, position))
+            .map(Cow::Borrowed)
+    }
+}
+
+impl BlockSink for PlacedRegionMut<'_> {
+    fn set_block_at(&mut self, position: Coordinates, block: BlockState) -> Result<()> {
+        let local = to_local(self.region, self.origin, position);
+
+        if !self.region.contains(local) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Position {:?} is outside of the region", position),
+            )
+            .into());
+        }
+
+        self.region.set_block(local, block);
+        Ok(())
+    }
+}
+
+/// A position where the world differs from a region, found by `Region::verify_against()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The world position of the block.
+    pub position: Coordinates,
+
+    /// The block in the region.
+    pub expected: BlockState,
+
+    /// The block in the world, or `None` if it is unknown.
+    pub found: Option<BlockState>,
+}
+
+impl Region {
+    /// Places the region in the world with its schematic origin at `origin`, so it can be read by world coordinates.
+    ///
+    /// # Examples
+    /// ```
+    /// use ritematica::LitematicaFile;
+    /// use ritematica::world::BlockSource;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let region = file.get_region("test").unwrap();
+    ///
+    /// let placed = region.placed_at((100, 64, -20));
+    /// println!("{:?}", placed.block_at((102, 68, -18).into()));
+    /// ```
+    pub fn placed_at(&self, origin: impl Into<Coordinates>) -> PlacedRegion<'_> {
+        PlacedRegion {
+            region: self,
+            origin: origin.into(),
+        }
+    }
+
+    /// Places the region in the world with its schematic origin at `origin`, so it can be read and written by world coordinates.
+    pub fn placed_at_mut(&mut self, origin: impl Into<Coordinates>) -> PlacedRegionMut<'_> {
+        PlacedRegionMut {
+            region: self,
+            origin: origin.into(),
+        }
+    }
+
+    /// Compares the region with the world, when its schematic origin is placed at `origin`, and returns every position
+    /// where the blocks differ or the world block is unknown, in YZX order.
+    ///
+    /// All kinds of air are considered equal.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use ritematica::LitematicaFile;
+    ///
+    /// let file = LitematicaFile::read("test.litematic").unwrap();
+    /// let world = HashMap::new();
+    ///
+    /// let mismatches = file.get_region("test").unwrap().verify_against((0, 0, 0), &world);
+    /// ```
+    pub fn verify_against(
+        &self,
+        origin: impl Into<Coordinates>,
+        world: &impl BlockSource,
+    ) -> Vec<Mismatch> {
+        let origin = origin.into();
+        let min_corner = self.min_corner();
+
+        self.iter_blocks()
+            .filter_map(|(local, expected)| {
+                let position = to_world(min_corner, origin, local);
+                let found = world.block_at(position);
+
+                let matches = found.as_deref().is_some_and(|found| {
+                    found == expected || (found.is_air() && expected.is_air())
+                });
+
+                (!matches).then(|| Mismatch {
+                    position,
+                    expected: expected.clone(),
+                    found: found.map(Cow::into_owned),
+                })
+            })
+            .collect()
+    }
+
+    /// Writes every block of the region, including air, into the world, with its schematic origin placed at `origin`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by the sink. Blocks before it have already been written.
+    pub fn paste_into(
+        &self,
+        origin: impl Into<Coordinates>,
+        world: &mut impl BlockSink,
+    ) -> Result<()> {
+        let origin = origin.into();
+        let min_corner = self.min_corner();
+
+        for (local, block) in self.iter_blocks() {
+            world.set_block_at(to_world(min_corner, origin, local), block.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a region at the schematic origin from the blocks of the world between `min` and `min + size`,
+    /// e.g. to import a build from a world backend. Unknown blocks become air.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The world the blocks are read from.
+    /// * `min` - The world position of the corner with the lowest coordinates.
+    /// * `size` - The size of the area, which must be positive.
+    pub fn from_source(
+        world: &impl BlockSource,
+        min: impl Into<Coordinates>,
+        size: impl Into<Coordinates>,
+    ) -> Region {
+        let min = min.into();
+        let size = size.into();
+        let mut region = Region::filled_with_air(Coordinates::default(), size);
+
+        for y in 0..size.y {
+            for z in 0..size.z {
+                for x in 0..size.x {
+                    let local = Coordinates { x, y, z };
+                    let block = world.block_at(to_world(Coordinates::default(), min, local));
+
+                    if let Some(block) = block.filter(|block| !block.is_air()) {
+                        region.set_block(local, block.into_owned());
+                    }
+                }
+            }
+        }
+
+        region
+    }
+}
+
+fn to_world(min_corner: Coordinates, origin: Coordinates, local: Coordinates) -> Coordinates {
+    Coordinates {
+        x: origin.x + min_corner.x + local.x,
+        y: origin.y + min_corner.y + local.y,
+        z: origin.z + min_corner.z + local.z,
+    }
+}
+
+fn to_local(region: &Region, origin: Coordinates, position: Coordinates) -> Coordinates {
+    let min_corner = region.min_corner();
+
+    Coordinates {
+        x: position.x - origin.x - min_corner.x,
+        y: position.y - origin.y - min_corner.y,
+        z: position.z - origin.z - min_corner.z,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::BlockStateBuilder;
+    use crate::structure::LitematicaFile;
+
+    use super::*;
+
+    #[test]
+    fn paste_verify_and_import() {
+        let file = LitematicaFile::read("test.litematic").unwrap();
+        let region = file.get_region("test").unwrap();
+        let origin = Coordinates {
+            x: 100,
+            y: -10,
+            z: 7,
+        };
+
+        let mut world = HashMap::new();
+        region.paste_into(origin, &mut world).unwrap();
+
+        assert_eq!(world.len() as u64, region.calc_volume());
+        assert!(region.verify_against(origin, &world).is_empty());
+
+        let min_corner = region.min_corner();
+        let changed = to_world(min_corner, origin, Coordinates { x: 2, y: 4, z: 2 });
+        world.insert(changed, BlockStateBuilder::new("bedrock").build());
+
+        let mismatches = region.verify_against(origin, &world);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].position, changed);
+
+        let placed = region.placed_at(origin);
+        let imported = Region::from_source(
+            &placed,
+            to_world(min_corner, origin, Coordinates::default()),
+            Coordinates {
+                x: region.size.x.abs(),
+                y: region.size.y.abs(),
+                z: region.size.z.abs(),
+            },
+        );
+
+        assert!(region
+            .iter_blocks()
+            .all(|(position, block)| imported.get_block(position) == block));
+    }
+
+    #[test]
+    fn placed_region_sink() {
+        let mut region = Region::filled_with_air(
+            Coordinates { x: 0, y: 0, z: 0 },
+            Coordinates { x: 2, y: 2, z: 2 },
+        );
+        let stone = BlockStateBuilder::new("stone").build();
+
+        let mut placed = region.placed_at_mut((10, 0, 0));
+        placed
+            .set_block_at(Coordinates { x: 11, y: 1, z: 0 }, stone.clone())
+            .unwrap();
+        assert!(placed
+            .set_block_at(Coordinates { x: 0, y: 0, z: 0 }, stone.clone())
+            .is_err());
+
+        assert_eq!(region.get_block((1, 1, 0)), &stone);
+    }
+}